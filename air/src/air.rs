@@ -1,3 +1,5 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::ops::{Add, Mul, Sub};
 
 use p3_field::{Algebra, ExtensionField, Field, PrimeCharacteristicRing};
@@ -12,6 +14,26 @@ pub trait BaseAir<F>: Sync {
     fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// The number of consecutive rows, starting at the current one, that the main trace exposes
+    /// to constraints. The default of two gives access to the current and next rows.
+    fn window_size(&self) -> usize {
+        2
+    }
+
+    /// Columns which repeat with a fixed period and are known to the verifier, so need not be
+    /// committed. Each entry holds one period of a column, whose length must be a power of two no
+    /// larger than the trace height.
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// The largest degree of any constraint, counting the selectors `is_first_row`, `is_last_row`
+    /// and `is_transition` as degree one, if the AIR declares it. A declared degree must match
+    /// that of the constraints when they are evaluated symbolically.
+    fn max_constraint_degree(&self) -> Option<usize> {
+        None
+    }
 }
 
 ///  An AIR with 0 or more public values.
@@ -137,12 +159,59 @@ pub trait AirBuilderWithPublicValues: AirBuilder {
     type PublicVar: Into<Self::Expr> + Copy;
 
     fn public_values(&self) -> &[Self::PublicVar];
+
+    /// Assert that column `col` of the first row equals public value `index`.
+    fn assert_first_row_public(&mut self, col: usize, index: usize) {
+        let value = self.main().row_slice(0)[col];
+        let public = self.public_values()[index];
+        self.when_first_row().assert_eq(value, public);
+    }
+
+    /// Assert that column `col` of the last row equals public value `index`.
+    fn assert_last_row_public(&mut self, col: usize, index: usize) {
+        let value = self.main().row_slice(0)[col];
+        let public = self.public_values()[index];
+        self.when_last_row().assert_eq(value, public);
+    }
+}
+
+/// An `AirBuilder` which can single out any row of the trace, e.g. to expose a cell in the middle
+/// of the computation as a public value.
+pub trait RowAirBuilder: AirBuilder {
+    /// An expression which is nonzero on row `row` of the trace and zero on every other row.
+    ///
+    /// `row` is taken modulo the trace height. Like the other selectors, this may not be
+    /// normalized, so it should only be used to filter constraints.
+    fn is_row(&self, row: usize) -> Self::Expr;
+
+    /// Returns a sub-builder whose constraints are enforced only on row `row`.
+    fn when_row(&mut self, row: usize) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.is_row(row))
+    }
+
+    /// Assert that column `col` of row `row` equals public value `index`.
+    fn assert_row_public(&mut self, row: usize, col: usize, index: usize)
+    where
+        Self: AirBuilderWithPublicValues,
+    {
+        let value = self.main().row_slice(0)[col];
+        let public = self.public_values()[index];
+        self.when_row(row).assert_eq(value, public);
+    }
 }
 
 pub trait PairBuilder: AirBuilder {
     fn preprocessed(&self) -> Self::M;
 }
 
+/// An `AirBuilder` which exposes the values of the AIR's periodic columns on the current row.
+pub trait PeriodicAirBuilder: AirBuilder {
+    type PeriodicVar: Into<Self::Expr> + Copy;
+
+    /// The value of each column of `BaseAir::periodic_columns`, in order.
+    fn periodic_values(&self) -> &[Self::PeriodicVar];
+}
+
 pub trait ExtensionBuilder: AirBuilder {
     type EF: ExtensionField<Self::F>;
 
@@ -178,6 +247,48 @@ pub trait PermutationAirBuilder: ExtensionBuilder {
     fn permutation(&self) -> Self::MP;
 
     fn permutation_randomness(&self) -> &[Self::RandomVar];
+
+    /// Values the permutation argument exposes to the verifier, such as the final value of a
+    /// running sum which is checked against those of other AIRs.
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        &[]
+    }
+}
+
+/// An `AirBuilder` on which an AIR can declare interactions with buses shared between AIRs, e.g. to
+/// look up values held in another table.
+///
+/// Each row sends or receives a tuple of `fields` on bus `bus_id` with the given multiplicity. A set
+/// of traces is valid only if, on every bus, each tuple is sent and received equally often across
+/// all of them. That takes an argument spanning whole traces, such as LogUp, so builders which
+/// fold constraints row by row shouldn't implement this trait: an AIR declaring interactions must
+/// then be wrapped in an AIR which enforces them before it can be proven.
+pub trait InteractionAirBuilder: AirBuilder {
+    /// Send `fields` to bus `bus_id`, `multiplicity` times.
+    fn push_send<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>;
+
+    /// Receive `fields` from bus `bus_id`, `multiplicity` times.
+    ///
+    /// By default this sends `fields` with the multiplicity negated.
+    fn push_receive<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let multiplicity: Self::Expr = multiplicity.into();
+        self.push_send(bus_id, fields, -multiplicity);
+    }
 }
 
 #[derive(Debug)]
@@ -232,6 +343,20 @@ impl<AB: ExtensionBuilder> ExtensionBuilder for FilteredAirBuilder<'_, AB> {
     }
 }
 
+impl<AB: RowAirBuilder> RowAirBuilder for FilteredAirBuilder<'_, AB> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        self.inner.is_row(row)
+    }
+}
+
+impl<AB: PeriodicAirBuilder> PeriodicAirBuilder for FilteredAirBuilder<'_, AB> {
+    type PeriodicVar = AB::PeriodicVar;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.inner.periodic_values()
+    }
+}
+
 impl<AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder<'_, AB> {
     type MP = AB::MP;
 
@@ -244,4 +369,36 @@ impl<AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder<'_,
     fn permutation_randomness(&self) -> &[Self::RandomVar] {
         self.inner.permutation_randomness()
     }
+
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        self.inner.permutation_values()
+    }
+}
+
+impl<AB: InteractionAirBuilder> InteractionAirBuilder for FilteredAirBuilder<'_, AB> {
+    fn push_send<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let multiplicity = multiplicity.into() * self.condition();
+        self.inner.push_send(bus_id, fields, multiplicity);
+    }
+
+    fn push_receive<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let multiplicity = multiplicity.into() * self.condition();
+        self.inner.push_receive(bus_id, fields, multiplicity);
+    }
 }
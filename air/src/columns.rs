@@ -0,0 +1,101 @@
+/// Lets a row of a trace be viewed as a struct of named columns, so that constraints can refer to
+/// `local.foo` and `next.foo` rather than to column indices.
+///
+/// `$cols` must be a `#[repr(C)]` struct, generic over a single type `T`, whose fields are all `T`
+/// or (nested) arrays of `T`. This defines `$num_cols`, the number of columns in the struct, and
+/// implements `Borrow<$cols<T>>` and `BorrowMut<$cols<T>>` for `[T]`.
+///
+/// ```
+/// use core::borrow::Borrow;
+///
+/// use p3_air::{Air, AirBuilder, BaseAir, impl_columns};
+/// use p3_field::PrimeCharacteristicRing;
+/// use p3_matrix::Matrix;
+///
+/// #[repr(C)]
+/// pub struct CounterCols<T> {
+///     pub count: T,
+///     pub limbs: [T; 2],
+/// }
+///
+/// impl_columns!(CounterCols, NUM_COUNTER_COLS);
+///
+/// pub struct CounterAir;
+///
+/// impl<F> BaseAir<F> for CounterAir {
+///     fn width(&self) -> usize {
+///         NUM_COUNTER_COLS
+///     }
+/// }
+///
+/// impl<AB: AirBuilder> Air<AB> for CounterAir {
+///     fn eval(&self, builder: &mut AB) {
+///         let main = builder.main();
+///         let (local, next) = (main.row_slice(0), main.row_slice(1));
+///         let local: &CounterCols<AB::Var> = (*local).borrow();
+///         let next: &CounterCols<AB::Var> = (*next).borrow();
+///
+///         builder.when_first_row().assert_zero(local.count);
+///         builder
+///             .when_transition()
+///             .assert_eq(local.count + AB::Expr::ONE, next.count);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_columns {
+    ($cols:ident, $num_cols:ident) => {
+        pub const $num_cols: usize = ::core::mem::size_of::<$cols<u8>>();
+
+        impl<T> ::core::borrow::Borrow<$cols<T>> for [T] {
+            fn borrow(&self) -> &$cols<T> {
+                debug_assert_eq!(self.len(), $num_cols);
+                let (prefix, shorts, suffix) = unsafe { self.align_to::<$cols<T>>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &shorts[0]
+            }
+        }
+
+        impl<T> ::core::borrow::BorrowMut<$cols<T>> for [T] {
+            fn borrow_mut(&mut self) -> &mut $cols<T> {
+                debug_assert_eq!(self.len(), $num_cols);
+                let (prefix, shorts, suffix) = unsafe { self.align_to_mut::<$cols<T>>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &mut shorts[0]
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use core::borrow::{Borrow, BorrowMut};
+
+    #[repr(C)]
+    struct TestCols<T> {
+        a: T,
+        b: [T; 2],
+        c: [[T; 2]; 2],
+    }
+
+    impl_columns!(TestCols, NUM_TEST_COLS);
+
+    #[test]
+    fn test_named_columns() {
+        assert_eq!(NUM_TEST_COLS, 7);
+
+        let mut row: [u32; NUM_TEST_COLS] = core::array::from_fn(|i| i as u32);
+        let cols: &TestCols<u32> = row[..].borrow();
+        assert_eq!(cols.a, 0);
+        assert_eq!(cols.b, [1, 2]);
+        assert_eq!(cols.c, [[3, 4], [5, 6]]);
+
+        let cols: &mut TestCols<u32> = row[..].borrow_mut();
+        cols.c[1][0] = 10;
+        assert_eq!(row[5], 10);
+    }
+}
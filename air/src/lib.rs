@@ -5,8 +5,11 @@
 extern crate alloc;
 
 mod air;
+mod columns;
+mod sub_air;
 pub mod utils;
 mod virtual_column;
 
 pub use air::*;
+pub use sub_air::*;
 pub use virtual_column::*;
@@ -0,0 +1,118 @@
+use p3_matrix::Matrix;
+
+use crate::{Air, AirBuilder, BaseAir};
+
+/// A reusable group of constraints over a contiguous range of columns, from which larger AIRs can
+/// be composed without hand-written column offsets.
+///
+/// A sub-AIR only sees its own columns of the current and next rows. Its width and degree compose
+/// with those of the sub-AIRs around it, e.g. a `PairAir` is as wide as both of its parts together.
+pub trait BaseSubAir {
+    /// The number of columns this sub-AIR constrains.
+    fn width(&self) -> usize;
+
+    /// The largest degree of any constraint this sub-AIR asserts, counting the selectors
+    /// `is_first_row`, `is_last_row` and `is_transition` as degree one.
+    fn max_constraint_degree(&self) -> usize;
+}
+
+/// The constraints of a sub-AIR, for evaluation on the builder `AB`.
+pub trait SubAir<AB: AirBuilder>: BaseSubAir {
+    /// Assert this sub-AIR's constraints, where `local` and `next` hold exactly its `width`
+    /// columns of the current and next rows.
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]);
+}
+
+/// Two sub-AIRs side by side, with the columns of `second` following those of `first`.
+#[derive(Clone, Debug)]
+pub struct PairAir<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+impl<A, B> PairAir<A, B> {
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: BaseSubAir, B: BaseSubAir> BaseSubAir for PairAir<A, B> {
+    fn width(&self) -> usize {
+        self.first.width() + self.second.width()
+    }
+
+    fn max_constraint_degree(&self) -> usize {
+        self.first
+            .max_constraint_degree()
+            .max(self.second.max_constraint_degree())
+    }
+}
+
+impl<AB: AirBuilder, A: SubAir<AB>, B: SubAir<AB>> SubAir<AB> for PairAir<A, B> {
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]) {
+        let split = self.first.width();
+        self.first.eval(builder, &local[..split], &next[..split]);
+        self.second.eval(builder, &local[split..], &next[split..]);
+    }
+}
+
+/// A sub-AIR whose columns start `offset` columns in. The columns before them are left
+/// unconstrained, to be filled freely or constrained by the enclosing AIR.
+#[derive(Clone, Debug)]
+pub struct OffsetAir<A> {
+    pub inner: A,
+    pub offset: usize,
+}
+
+impl<A> OffsetAir<A> {
+    pub const fn new(inner: A, offset: usize) -> Self {
+        Self { inner, offset }
+    }
+}
+
+impl<A: BaseSubAir> BaseSubAir for OffsetAir<A> {
+    fn width(&self) -> usize {
+        self.offset + self.inner.width()
+    }
+
+    fn max_constraint_degree(&self) -> usize {
+        self.inner.max_constraint_degree()
+    }
+}
+
+impl<AB: AirBuilder, A: SubAir<AB>> SubAir<AB> for OffsetAir<A> {
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]) {
+        self.inner
+            .eval(builder, &local[self.offset..], &next[self.offset..]);
+    }
+}
+
+/// A complete AIR made of a single sub-AIR spanning the whole main trace.
+#[derive(Clone, Debug)]
+pub struct ComposedAir<A> {
+    pub inner: A,
+}
+
+impl<A> ComposedAir<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F, A: BaseSubAir + Sync> BaseAir<F> for ComposedAir<A> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+
+    fn max_constraint_degree(&self) -> Option<usize> {
+        Some(self.inner.max_constraint_degree())
+    }
+}
+
+impl<AB: AirBuilder, A: SubAir<AB> + Sync> Air<AB> for ComposedAir<A> {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        self.inner.eval(builder, &local, &next);
+    }
+}
@@ -220,6 +220,11 @@ mod tests {
 
     test_field_dft!(radix2dit, crate::BabyBear, p3_dft::Radix2Dit<_>);
     test_field_dft!(bowers, crate::BabyBear, p3_dft::Radix2Bowers);
+    test_field_dft!(
+        bowers_bitrev,
+        crate::BabyBear,
+        p3_dft::Radix2BowersBitReversed
+    );
     test_field_dft!(parallel, crate::BabyBear, p3_dft::Radix2DitParallel::<_>);
     test_field_dft!(
         recur_dft,
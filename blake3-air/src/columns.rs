@@ -1,5 +1,4 @@
-use core::borrow::{Borrow, BorrowMut};
-use core::mem::size_of;
+use p3_air::impl_columns;
 
 use crate::constants::U32_LIMBS;
 
@@ -93,26 +92,4 @@ pub(crate) struct QuarterRound<'a, T, U> {
     pub d_output: &'a [T; 32],
 }
 
-pub const NUM_BLAKE3_COLS: usize = size_of::<Blake3Cols<u8>>();
-
-impl<T> Borrow<Blake3Cols<T>> for [T] {
-    fn borrow(&self) -> &Blake3Cols<T> {
-        debug_assert_eq!(self.len(), NUM_BLAKE3_COLS);
-        let (prefix, shorts, suffix) = unsafe { self.align_to::<Blake3Cols<T>>() };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &shorts[0]
-    }
-}
-
-impl<T> BorrowMut<Blake3Cols<T>> for [T] {
-    fn borrow_mut(&mut self) -> &mut Blake3Cols<T> {
-        debug_assert_eq!(self.len(), NUM_BLAKE3_COLS);
-        let (prefix, shorts, suffix) = unsafe { self.align_to_mut::<Blake3Cols<T>>() };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &mut shorts[0]
-    }
-}
+impl_columns!(Blake3Cols, NUM_BLAKE3_COLS);
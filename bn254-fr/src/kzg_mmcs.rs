@@ -0,0 +1,311 @@
+//! An MMCS backed by KZG commitments over BN254, for proofs whose commitments a pairing-based
+//! SNARK over BN254 has to check.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use halo2curves::bn256::{Bn256, G1, G1Affine, G2, G2Affine};
+use halo2curves::group::{Curve, Group, GroupEncoding};
+use halo2curves::msm::msm_best;
+use halo2curves::pairing::Engine;
+use p3_challenger::{CanObserve, MultiField32Challenger};
+use p3_commit::Mmcs;
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{PrimeCharacteristicRing, PrimeField32, TwoAdicField};
+use p3_matrix::{Dimensions, Matrix};
+use p3_symmetric::{CryptographicHasher, CryptographicPermutation};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Bn254Fr, FFBn254Fr};
+
+/// The structured reference string of a KZG commitment: the powers `tau^i G_1` for `i` below the
+/// largest committable height, and `tau G_2`.
+#[derive(Clone, Debug)]
+pub struct KzgSrs {
+    g1_powers: Vec<G1Affine>,
+    tau_g2: G2Affine,
+}
+
+impl KzgSrs {
+    /// An SRS from the output of a trusted setup.
+    pub const fn new(g1_powers: Vec<G1Affine>, tau_g2: G2Affine) -> Self {
+        Self { g1_powers, tau_g2 }
+    }
+
+    /// An SRS for heights up to `max_height` from a `tau` sampled here, which anyone who learns
+    /// it can use to forge openings, so this is only fit for testing.
+    pub fn new_insecure<R: Rng>(max_height: usize, rng: &mut R) -> Self {
+        let tau: FFBn254Fr = rng.random::<Bn254Fr>().value;
+        let projective: Vec<G1> = (0..max_height)
+            .scan(G1::generator(), |power, _| {
+                let current = *power;
+                *power *= tau;
+                Some(current)
+            })
+            .collect();
+        let mut g1_powers = vec![G1Affine::default(); max_height];
+        G1::batch_normalize(&projective, &mut g1_powers);
+        Self::new(g1_powers, (G2::generator() * tau).to_affine())
+    }
+
+    /// The largest height of a matrix which can be committed to.
+    pub fn max_height(&self) -> usize {
+        self.g1_powers.len()
+    }
+
+    fn commit(&self, coeffs: &[Bn254Fr]) -> G1Affine {
+        let scalars = coeffs.iter().map(|c| c.value).collect::<Vec<_>>();
+        msm_best(&scalars, &self.g1_powers[..scalars.len()]).to_affine()
+    }
+}
+
+/// The commitment of a `KzgMmcs` to a batch of matrices: one KZG commitment per matrix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KzgCommitment(pub Vec<G1Affine>);
+
+/// Absorbs each point by its 32-byte compressed encoding, as 16 elements of `F` holding 16 bits
+/// each, so distinct points of the same batch size are observed as distinct element sequences.
+impl<F, P, const WIDTH: usize, const RATE: usize> CanObserve<KzgCommitment>
+    for MultiField32Challenger<F, Bn254Fr, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    P: CryptographicPermutation<[Bn254Fr; WIDTH]>,
+{
+    fn observe(&mut self, commitment: KzgCommitment) {
+        for point in commitment.0 {
+            for limb in point.to_bytes().as_ref().chunks_exact(2) {
+                self.observe(F::from_u16(u16::from_le_bytes([limb[0], limb[1]])));
+            }
+        }
+    }
+}
+
+/// The matrices committed to by a `KzgMmcs`, with the coefficients of the polynomial
+/// interpolating the row digests of each.
+pub struct KzgProverData<M> {
+    matrices: Vec<M>,
+    coeffs: Vec<Vec<Bn254Fr>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KzgMmcsError {
+    WrongBatchSize,
+    /// A matrix height isn't a power of two.
+    WrongHeight,
+    InvalidProof,
+}
+
+/// A vector commitment scheme in which each matrix is committed to by one KZG commitment, to the
+/// polynomial taking the digest of row `i` at the `i`th power of the generator of the subgroup of
+/// order the matrix height.
+///
+/// An opening holds one evaluation proof per matrix, so it is a constant size per matrix, and
+/// checking it takes two pairings per matrix.
+///
+/// Rows are hashed to `Bn254Fr` by `H`, e.g. a `MultiField32PaddingFreeSponge` over
+/// `Poseidon2Bn254`. Matrix heights must be powers of two; as with `MerkleTreeMmcs`, a matrix
+/// shorter than the tallest by a factor `2^k` is opened at `index >> k`.
+#[derive(Clone, Debug)]
+pub struct KzgMmcs<F, H> {
+    srs: Arc<KzgSrs>,
+    hash: H,
+    _phantom: PhantomData<F>,
+}
+
+impl<F, H> KzgMmcs<F, H> {
+    pub fn new(srs: KzgSrs, hash: H) -> Self {
+        Self {
+            srs: Arc::new(srs),
+            hash,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, H> Mmcs<F> for KzgMmcs<F, H>
+where
+    F: Clone + Send + Sync,
+    H: CryptographicHasher<F, [Bn254Fr; 1]> + Sync,
+{
+    type ProverData<M> = KzgProverData<M>;
+    type Commitment = KzgCommitment;
+    type Proof = Vec<G1Affine>;
+    type Error = KzgMmcsError;
+
+    fn commit<M: Matrix<F>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        let coeffs = inputs
+            .iter()
+            .map(|m| {
+                assert!(
+                    m.height().is_power_of_two(),
+                    "Matrix height must be a power of two."
+                );
+                assert!(
+                    m.height() <= self.srs.max_height(),
+                    "Matrix is too tall for the SRS."
+                );
+                let digests = m.rows().map(|row| self.hash.hash_iter(row)[0]).collect();
+                Radix2Dit::default().idft(digests)
+            })
+            .collect::<Vec<_>>();
+        let commit = KzgCommitment(coeffs.iter().map(|c| self.srs.commit(c)).collect());
+        (
+            commit,
+            KzgProverData {
+                matrices: inputs,
+                coeffs,
+            },
+        )
+    }
+
+    fn open_batch<M: Matrix<F>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<F>>, Self::Proof) {
+        let log_max_height = log2_ceil_usize(self.get_max_height(prover_data));
+        prover_data
+            .matrices
+            .iter()
+            .zip(&prover_data.coeffs)
+            .map(|(matrix, coeffs)| {
+                let log_height = log2_strict_usize(matrix.height());
+                let row = index >> (log_max_height - log_height);
+                let point = Bn254Fr::two_adic_generator(log_height).exp_u64(row as u64);
+                let opening = matrix.row(row).collect();
+                (opening, self.srs.commit(&divide_by_linear(coeffs, point)))
+            })
+            .unzip()
+    }
+
+    fn get_matrices<'a, M: Matrix<F>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        prover_data.matrices.iter().collect()
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<F>],
+        proof: &Self::Proof,
+    ) -> Result<(), Self::Error> {
+        if commit.0.len() != dimensions.len()
+            || opened_values.len() != dimensions.len()
+            || proof.len() != dimensions.len()
+        {
+            return Err(KzgMmcsError::WrongBatchSize);
+        }
+        if dimensions.iter().any(|d| !d.height.is_power_of_two()) {
+            return Err(KzgMmcsError::WrongHeight);
+        }
+        let log_max_height = log2_ceil_usize(dimensions.iter().map(|d| d.height).max().unwrap());
+
+        for (((c, dims), opening), quotient) in commit
+            .0
+            .iter()
+            .zip(dimensions)
+            .zip(opened_values)
+            .zip(proof)
+        {
+            let log_height = log2_strict_usize(dims.height);
+            let row = index >> (log_max_height - log_height);
+            let point = Bn254Fr::two_adic_generator(log_height).exp_u64(row as u64);
+            let eval = self.hash.hash_iter(opening.iter().cloned())[0];
+
+            // p(X) - p(z) = q(X) (X - z), checked at tau.
+            let lhs = (G1::from(*c) - G1::generator() * eval.value).to_affine();
+            let rhs = (G2::from(self.srs.tau_g2) - G2::generator() * point.value).to_affine();
+            if Bn256::pairing(&lhs, &G2::generator().to_affine()) != Bn256::pairing(quotient, &rhs)
+            {
+                return Err(KzgMmcsError::InvalidProof);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The coefficients of `(p(X) - p(z)) / (X - z)`, given those of `p`.
+fn divide_by_linear(coeffs: &[Bn254Fr], z: Bn254Fr) -> Vec<Bn254Fr> {
+    let mut quotient = vec![Bn254Fr::ZERO; coeffs.len().saturating_sub(1)];
+    let mut acc = Bn254Fr::ZERO;
+    for (q, &c) in quotient.iter_mut().zip(&coeffs[1..]).rev() {
+        acc = acc * z + c;
+        *q = acc;
+    }
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_symmetric::MultiField32PaddingFreeSponge;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::Poseidon2Bn254;
+
+    type F = BabyBear;
+    type Perm = Poseidon2Bn254<3>;
+    type MyHash = MultiField32PaddingFreeSponge<F, Bn254Fr, Perm, 3, 16, 1>;
+
+    #[test]
+    fn divide_by_linear_matches_evaluation() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let coeffs: Vec<Bn254Fr> = (0..5).map(|_| rng.random()).collect();
+        let (x, z): (Bn254Fr, Bn254Fr) = (rng.random(), rng.random());
+        let eval = |coeffs: &[Bn254Fr], x: Bn254Fr| {
+            coeffs
+                .iter()
+                .rev()
+                .fold(Bn254Fr::ZERO, |acc, &c| acc * x + c)
+        };
+        let quotient = divide_by_linear(&coeffs, z);
+        assert_eq!(
+            eval(&coeffs, x) - eval(&coeffs, z),
+            eval(&quotient, x) * (x - z)
+        );
+    }
+
+    #[test]
+    fn commit_open_verify() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng(8, 56, &mut rng);
+        let mmcs = KzgMmcs::new(
+            KzgSrs::new_insecure(16, &mut rng),
+            MyHash::new(perm).unwrap(),
+        );
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 16, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 4, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect::<Vec<_>>();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let (opened_values, proof) = mmcs.open_batch(13, &prover_data);
+        assert_eq!(
+            opened_values[1],
+            prover_data.matrices[1].row(3).collect::<Vec<_>>()
+        );
+        mmcs.verify_batch(&commit, &dims, 13, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered = opened_values.clone();
+        tampered[1][0] += F::ONE;
+        assert_eq!(
+            mmcs.verify_batch(&commit, &dims, 13, &tampered, &proof),
+            Err(KzgMmcsError::InvalidProof)
+        );
+        assert_eq!(
+            mmcs.verify_batch(&commit, &dims, 12, &opened_values, &proof),
+            Err(KzgMmcsError::InvalidProof)
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! The scalar field of the BN254 curve, defined as `F_r` where `r = 21888242871839275222246405745257275088548364400416034343698204186575808495617`.
 #![no_std]
 
+#[cfg(feature = "kzg")]
+mod kzg_mmcs;
 mod poseidon2;
 
 extern crate alloc;
@@ -15,6 +17,8 @@ use core::{fmt, stringify};
 pub use halo2curves::bn256::Fr as FFBn254Fr;
 use halo2curves::ff::{Field as FFField, PrimeField as FFPrimeField};
 use halo2curves::serde::SerdeObject;
+#[cfg(feature = "kzg")]
+pub use kzg_mmcs::*;
 use num_bigint::BigUint;
 use p3_field::integers::QuotientMap;
 use p3_field::{
@@ -36,6 +40,34 @@ impl Bn254Fr {
     pub(crate) const fn new(value: FFBn254Fr) -> Self {
         Self { value }
     }
+
+    /// The canonical little-endian encoding of the element, as used by halo2 and arkworks.
+    ///
+    /// This is the representation a Groth16 or PLONK circuit verifying a wrap proof should
+    /// expect public inputs in.
+    #[must_use]
+    pub fn to_bytes_le(&self) -> [u8; 32] {
+        self.value.to_bytes()
+    }
+
+    /// Decode a canonical little-endian encoding, returning `None` if it is not below the field
+    /// order.
+    #[must_use]
+    pub fn from_bytes_le(bytes: &[u8; 32]) -> Option<Self> {
+        Option::from(FFBn254Fr::from_bytes(bytes)).map(Self::new)
+    }
+}
+
+impl From<FFBn254Fr> for Bn254Fr {
+    fn from(value: FFBn254Fr) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Bn254Fr> for FFBn254Fr {
+    fn from(value: Bn254Fr) -> Self {
+        value.value
+    }
 }
 
 impl Serialize for Bn254Fr {
@@ -362,6 +394,22 @@ mod tests {
         assert_eq!(f_r_minus_2, f_r_minus_2_deserialized);
     }
 
+    #[test]
+    fn test_bytes_le_round_trip() {
+        let f = F::from_u64(0x0123_4567_89ab_cdef);
+        let bytes = f.to_bytes_le();
+        assert_eq!(BigUint::from_bytes_le(&bytes), f.as_canonical_biguint());
+        assert_eq!(F::from_bytes_le(&bytes), Some(f));
+        assert_eq!(FFBn254Fr::from(f), f.value);
+        assert_eq!(F::from(f.value), f);
+
+        // The order itself is not canonical.
+        let mut order = [0; 32];
+        let order_bytes = F::order().to_bytes_le();
+        order[..order_bytes.len()].copy_from_slice(&order_bytes);
+        assert_eq!(F::from_bytes_le(&order), None);
+    }
+
     const ZERO: Bn254Fr = Bn254Fr::ZERO;
     const ONE: Bn254Fr = Bn254Fr::ONE;
 
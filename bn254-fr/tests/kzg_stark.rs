@@ -0,0 +1,112 @@
+//! A STARK over BabyBear whose trace and FRI commitments are KZG commitments over BN254, with a
+//! transcript hashed over `Bn254Fr`.
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_bn254_fr::{Bn254Fr, KzgMmcs, KzgSrs, Poseidon2Bn254};
+use p3_challenger::MultiField32Challenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{PrimeCharacteristicRing, PrimeField64};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_symmetric::MultiField32PaddingFreeSponge;
+use p3_uni_stark::{StarkConfig, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// Two columns `(a, b)` stepping to `(b, a + b)`, starting at the first two public values and
+/// ending with `b` equal to the third.
+struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (local, next) = (&*local, &*next);
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local[0], a);
+        when_first_row.assert_eq(local[1], b);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1], next[0]);
+        when_transition.assert_eq(local[0] + local[1], next[1]);
+
+        builder.when_last_row().assert_eq(local[1], x);
+    }
+}
+
+fn generate_trace_rows<F: PrimeField64>(n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut a, mut b) = (F::ZERO, F::ONE);
+    for _ in 0..n {
+        values.extend([a, b]);
+        (a, b) = (b, a + b);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2Bn254<3>;
+type MyHash = MultiField32PaddingFreeSponge<Val, Bn254Fr, Perm, 3, 16, 1>;
+type ValMmcs = KzgMmcs<Val, MyHash>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = MultiField32Challenger<Val, Bn254Fr, Perm, 3, 2>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+#[test]
+fn prove_verify_with_kzg_commitments() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng(8, 56, &mut rng);
+    let val_mmcs = ValMmcs::new(
+        KzgSrs::new_insecure(1 << 8, &mut rng),
+        MyHash::new(perm.clone()).unwrap(),
+    );
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(1 << 3);
+    let pis = vec![Val::ZERO, Val::ONE, Val::from_u64(21)];
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        &mut Challenger::new(perm.clone()).unwrap(),
+        trace,
+        &pis,
+    );
+    verify(
+        &config,
+        &FibonacciAir,
+        &mut Challenger::new(perm.clone()).unwrap(),
+        &proof,
+        &pis,
+    )
+    .expect("verification failed");
+
+    let wrong_pis = vec![Val::ZERO, Val::ONE, Val::from_u64(22)];
+    verify(
+        &config,
+        &FibonacciAir,
+        &mut Challenger::new(perm).unwrap(),
+        &proof,
+        &wrong_pis,
+    )
+    .expect_err("verification with the wrong public values should fail");
+}
@@ -0,0 +1,69 @@
+//! A BabyBear MMCS and challenger hashing over `Bn254Fr`, as used by a final wrap proof whose
+//! transcript and commitments a Groth16 or PLONK circuit over BN254 can check natively.
+
+use p3_baby_bear::BabyBear;
+use p3_bn254_fr::{Bn254Fr, Poseidon2Bn254};
+use p3_challenger::{CanObserve, CanSample, MultiField32Challenger};
+use p3_commit::Mmcs;
+use p3_field::PrimeCharacteristicRing;
+use p3_matrix::Dimensions;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{MultiField32PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+type F = BabyBear;
+type Perm = Poseidon2Bn254<3>;
+type MyHash = MultiField32PaddingFreeSponge<F, Bn254Fr, Perm, 3, 16, 1>;
+type MyCompress = TruncatedPermutation<Perm, 2, 1, 3>;
+type ValMmcs = MerkleTreeMmcs<F, Bn254Fr, MyHash, MyCompress, 1>;
+type Challenger = MultiField32Challenger<F, Bn254Fr, Perm, 3, 2>;
+
+fn setup() -> (Perm, ValMmcs) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng(8, 56, &mut rng);
+    let mmcs = ValMmcs::new(
+        MyHash::new(perm.clone()).unwrap(),
+        MyCompress::new(perm.clone()),
+    );
+    (perm, mmcs)
+}
+
+#[test]
+fn commit_open_verify() {
+    let (_, mmcs) = setup();
+    let mut rng = SmallRng::seed_from_u64(2);
+    let mat = RowMajorMatrix::<F>::rand(&mut rng, 16, 20);
+    let dims = [Dimensions {
+        width: 20,
+        height: 16,
+    }];
+    let (commit, prover_data) = mmcs.commit_matrix(mat);
+
+    let (opened_values, proof) = mmcs.open_batch(5, &prover_data);
+    mmcs.verify_batch(&commit, &dims, 5, &opened_values, &proof)
+        .expect("expected verification to succeed");
+
+    let mut tampered = opened_values;
+    tampered[0][0] += F::ONE;
+    mmcs.verify_batch(&commit, &dims, 5, &tampered, &proof)
+        .expect_err("expected verification to fail");
+}
+
+#[test]
+fn challenger_binds_commitment() {
+    let (perm, mmcs) = setup();
+    let mut rng = SmallRng::seed_from_u64(3);
+    let (commit_a, _) = mmcs.commit_matrix(RowMajorMatrix::<F>::rand(&mut rng, 8, 4));
+    let (commit_b, _) = mmcs.commit_matrix(RowMajorMatrix::<F>::rand(&mut rng, 8, 4));
+
+    let sample = |commit| {
+        let mut challenger = Challenger::new(perm.clone()).unwrap();
+        challenger.observe(commit);
+        let x: F = challenger.sample();
+        x
+    };
+    assert_eq!(sample(commit_a), sample(commit_a));
+    assert_ne!(sample(commit_a), sample(commit_b));
+}
@@ -4,7 +4,7 @@ use alloc::vec::Vec;
 use p3_field::{BasedVectorSpace, Field, PrimeField64};
 use p3_symmetric::{CryptographicPermutation, Hash};
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{CanFork, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger};
 
 #[derive(Clone, Debug)]
 pub struct DuplexChallenger<F, P, const WIDTH: usize, const RATE: usize>
@@ -51,6 +51,51 @@ where
     }
 }
 
+/// The transcript state of a [`DuplexChallenger`].
+#[derive(Clone, Debug)]
+pub struct DuplexChallengerSnapshot<F, const WIDTH: usize> {
+    sponge_state: [F; WIDTH],
+    input_buffer: Vec<F>,
+    output_buffer: Vec<F>,
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanSnapshot
+    for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: Copy,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    type Snapshot = DuplexChallengerSnapshot<F, WIDTH>;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        DuplexChallengerSnapshot {
+            sponge_state: self.sponge_state,
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.sponge_state = snapshot.sponge_state;
+        self.input_buffer = snapshot.input_buffer;
+        self.output_buffer = snapshot.output_buffer;
+    }
+}
+
+/// Separates a fork by adding one to the last element of the capacity, which observations and
+/// samples never write, and absorbing any buffered input.
+impl<F, P, const WIDTH: usize, const RATE: usize> CanFork for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn separate_fork(&mut self) {
+        assert!(RATE < WIDTH, "Separating a fork needs a nonzero capacity.");
+        self.sponge_state[WIDTH - 1] += F::ONE;
+        self.duplexing();
+    }
+}
+
 impl<F, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
     for DuplexChallenger<F, P, WIDTH, RATE>
 where
@@ -243,4 +288,37 @@ mod tests {
         let witness = duplex_challenger.grind(too_many_bits);
         assert!(duplex_challenger.check_witness(too_many_bits, witness));
     }
+
+    #[test]
+    fn test_duplex_challenger_snapshot_and_fork() {
+        type Chal = DuplexChallenger<G, TestPermutation, WIDTH, RATE>;
+        let mut challenger = Chal::new(TestPermutation {});
+        (0..20).for_each(|element| challenger.observe(G::from_u8(element)));
+
+        let snapshot = challenger.snapshot();
+        let samples = <Chal as CanSample<G>>::sample_vec(&mut challenger, 20);
+        challenger.observe(G::ONE);
+        challenger.restore(snapshot);
+        assert_eq!(
+            <Chal as CanSample<G>>::sample_vec(&mut challenger, 20),
+            samples
+        );
+
+        let mut fork_a = challenger.fork([G::ONE; RATE]);
+        let mut fork_a_again = challenger.fork([G::ONE; RATE]);
+        let mut fork_b = challenger.fork([G::TWO; RATE]);
+        let samples_a = <Chal as CanSample<G>>::sample_vec(&mut fork_a, 8);
+        assert_eq!(
+            <Chal as CanSample<G>>::sample_vec(&mut fork_a_again, 8),
+            samples_a
+        );
+        assert_ne!(
+            <Chal as CanSample<G>>::sample_vec(&mut fork_b, 8),
+            samples_a
+        );
+
+        // Observing the label on the parent doesn't reach the state of the fork.
+        challenger.observe([G::ONE; RATE]);
+        assert_ne!(challenger.sponge_state, fork_a.sponge_state);
+    }
 }
@@ -0,0 +1,173 @@
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::{
+    CanFork, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger, GrindingChallenger,
+};
+
+/// A search for proof of work witnesses, which may run somewhere other than the prover, like on a
+/// GPU or across several machines.
+pub trait GrindingBackend<C: GrindingChallenger>: Clone + Sync {
+    /// Find a witness after which `challenger` samples `bits` zero bits, leaving `challenger` as
+    /// it is.
+    fn find_witness(&self, challenger: &C, bits: usize) -> C::Witness;
+}
+
+/// Searches for the smallest witness on the current thread, like `grind_sequential`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SequentialGrinding;
+
+impl<C: GrindingChallenger> GrindingBackend<C> for SequentialGrinding {
+    fn find_witness(&self, challenger: &C, bits: usize) -> C::Witness {
+        challenger.clone().grind_sequential(bits)
+    }
+}
+
+/// Searches for any witness on all threads of the rayon pool, or on the current thread without
+/// the `parallel` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParallelGrinding;
+
+impl<C: GrindingChallenger> GrindingBackend<C> for ParallelGrinding {
+    #[instrument(name = "grind for proof-of-work witness in parallel", skip_all)]
+    fn find_witness(&self, challenger: &C, bits: usize) -> C::Witness {
+        assert!(bits < (usize::BITS as usize));
+        (0..u64::MAX)
+            .into_par_iter()
+            .map(C::Witness::from_u64)
+            .find_any(|witness| challenger.clone().check_witness(bits, *witness))
+            .expect("failed to find witness")
+    }
+}
+
+/// A challenger which leaves the proof of work search of `grind` to `backend`, and checks the
+/// witness it finds like any other.
+///
+/// Everything else is forwarded to `inner`, so a prover can grind elsewhere just by wrapping its
+/// challenger.
+#[derive(Clone, Debug)]
+pub struct BackendGrindingChallenger<C, B> {
+    pub inner: C,
+    pub backend: B,
+}
+
+impl<C, B> BackendGrindingChallenger<C, B> {
+    pub const fn new(inner: C, backend: B) -> Self {
+        Self { inner, backend }
+    }
+}
+
+impl<C: CanObserve<T>, B, T> CanObserve<T> for BackendGrindingChallenger<C, B> {
+    fn observe(&mut self, value: T) {
+        self.inner.observe(value);
+    }
+
+    fn observe_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.inner.observe_slice(values);
+    }
+}
+
+impl<C: CanSample<T>, B, T> CanSample<T> for BackendGrindingChallenger<C, B> {
+    fn sample(&mut self) -> T {
+        self.inner.sample()
+    }
+}
+
+impl<C: CanSampleBits<T>, B, T> CanSampleBits<T> for BackendGrindingChallenger<C, B> {
+    fn sample_bits(&mut self, bits: usize) -> T {
+        self.inner.sample_bits(bits)
+    }
+}
+
+impl<C: FieldChallenger<F>, B: Sync, F: Field> FieldChallenger<F>
+    for BackendGrindingChallenger<C, B>
+{
+}
+
+impl<C: CanSnapshot, B: Clone> CanSnapshot for BackendGrindingChallenger<C, B> {
+    type Snapshot = C::Snapshot;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.inner.restore(snapshot);
+    }
+}
+
+impl<C: CanFork, B: Clone> CanFork for BackendGrindingChallenger<C, B> {
+    fn separate_fork(&mut self) {
+        self.inner.separate_fork();
+    }
+}
+
+impl<C, B> GrindingChallenger for BackendGrindingChallenger<C, B>
+where
+    C: GrindingChallenger,
+    B: GrindingBackend<C>,
+{
+    type Witness = C::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.backend.find_witness(&self.inner, bits);
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_symmetric::{CryptographicPermutation, Permutation};
+
+    use super::*;
+    use crate::DuplexChallenger;
+
+    /// Mixes every element into every other, enough for witnesses to look random.
+    #[derive(Clone)]
+    struct MixingPermutation;
+
+    impl Permutation<[BabyBear; 16]> for MixingPermutation {
+        fn permute_mut(&self, input: &mut [BabyBear; 16]) {
+            for _ in 0..4 {
+                let sum: BabyBear = input.iter().copied().sum();
+                for (i, x) in input.iter_mut().enumerate() {
+                    *x = x.cube() + sum + BabyBear::from_usize(i);
+                }
+            }
+        }
+    }
+
+    impl CryptographicPermutation<[BabyBear; 16]> for MixingPermutation {}
+
+    type Chal = DuplexChallenger<BabyBear, MixingPermutation, 16, 8>;
+
+    fn challenger() -> Chal {
+        let mut challenger = Chal::new(MixingPermutation);
+        challenger.observe(BabyBear::from_u8(7));
+        challenger
+    }
+
+    #[test]
+    fn test_grinding_backends() {
+        let bits = 6;
+        let smallest = challenger().grind_sequential(bits);
+
+        let mut sequential = BackendGrindingChallenger::new(challenger(), SequentialGrinding);
+        assert_eq!(sequential.grind(bits), smallest);
+
+        let mut parallel = BackendGrindingChallenger::new(challenger(), ParallelGrinding);
+        let witness = parallel.grind(bits);
+        assert!(challenger().check_witness(bits, witness));
+
+        // Grinding leaves the wrapped challenger where checking the witness would.
+        let mut expected = challenger();
+        assert!(expected.check_witness(bits, witness));
+        assert_eq!(parallel.sample_bits(20), expected.sample_bits(20));
+    }
+}
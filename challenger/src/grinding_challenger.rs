@@ -1,4 +1,4 @@
-use p3_field::{Field, PrimeField, PrimeField32, PrimeField64};
+use p3_field::{Field, PrimeCharacteristicRing, PrimeField, PrimeField32, PrimeField64};
 use p3_maybe_rayon::prelude::*;
 use p3_symmetric::CryptographicPermutation;
 use tracing::instrument;
@@ -12,6 +12,21 @@ pub trait GrindingChallenger:
 
     fn grind(&mut self, bits: usize) -> Self::Witness;
 
+    /// Like `grind`, but search for the witness sequentially from zero on the current thread.
+    ///
+    /// This always finds the smallest valid witness, so unlike a parallel search, the result
+    /// doesn't depend on how the search was scheduled.
+    #[instrument(name = "grind sequentially for proof-of-work witness", skip_all)]
+    fn grind_sequential(&mut self, bits: usize) -> Self::Witness {
+        assert!(bits < (usize::BITS as usize));
+        let witness = (0..)
+            .map(Self::Witness::from_u64)
+            .find(|witness| self.clone().check_witness(bits, *witness))
+            .expect("failed to find witness");
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+
     #[must_use]
     fn check_witness(&mut self, bits: usize, witness: Self::Witness) -> bool {
         self.observe(witness);
@@ -3,7 +3,7 @@ use alloc::vec::Vec;
 
 use p3_symmetric::CryptographicHasher;
 
-use crate::{CanObserve, CanSample};
+use crate::{CanObserve, CanSample, CanSnapshot};
 
 /// A generic challenger that uses a cryptographic hash function to generate challenges.
 #[derive(Clone, Debug)]
@@ -85,6 +85,33 @@ where
     }
 }
 
+/// The transcript state of a [`HashChallenger`].
+#[derive(Clone, Debug)]
+pub struct HashChallengerSnapshot<T> {
+    input_buffer: Vec<T>,
+    output_buffer: Vec<T>,
+}
+
+impl<T, H, const OUT_LEN: usize> CanSnapshot for HashChallenger<T, H, OUT_LEN>
+where
+    T: Clone,
+    H: CryptographicHasher<T, [T; OUT_LEN]>,
+{
+    type Snapshot = HashChallengerSnapshot<T>;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        HashChallengerSnapshot {
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.input_buffer = snapshot.input_buffer;
+        self.output_buffer = snapshot.output_buffer;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use p3_field::PrimeCharacteristicRing;
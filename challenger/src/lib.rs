@@ -5,19 +5,23 @@
 extern crate alloc;
 
 mod duplex_challenger;
+mod grinding_backend;
 mod grinding_challenger;
 mod hash_challenger;
 mod multi_field_challenger;
+mod safe_challenger;
 mod serializing_challenger;
 
 use alloc::vec::Vec;
 use core::array;
 
 pub use duplex_challenger::*;
+pub use grinding_backend::*;
 pub use grinding_challenger::*;
 pub use hash_challenger::*;
 pub use multi_field_challenger::*;
 use p3_field::{BasedVectorSpace, Field};
+pub use safe_challenger::*;
 pub use serializing_challenger::*;
 
 pub trait CanObserve<T> {
@@ -49,6 +53,46 @@ pub trait CanSampleBits<T> {
     fn sample_bits(&mut self, bits: usize) -> T;
 }
 
+/// A challenger whose transcript can be saved and rolled back to, e.g. to retry grinding with
+/// different parameters.
+pub trait CanSnapshot: Clone {
+    /// The transcript state of the challenger, without its hasher or permutation.
+    type Snapshot: Clone;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Return to the transcript state of `snapshot`, which must have been taken from this
+    /// challenger or one sharing its hasher or permutation.
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+/// A challenger whose transcript can be split into sub-transcripts derived from the current one,
+/// e.g. one per table of a multi-table proof.
+///
+/// Challengers built on a hash function rather than a sponge, like `HashChallenger`, keep no state
+/// outside what they hash, so they can't separate forks from ordinary transcripts and only
+/// implement `CanSnapshot`.
+pub trait CanFork: Clone {
+    /// Move to a state which no sequence of observations and samples reaches, so that what
+    /// follows never matches an ordinary transcript.
+    fn separate_fork(&mut self);
+
+    /// A sub-transcript which continues from the current one after separating it as a fork and
+    /// observing `label`.
+    ///
+    /// `self` is left as it was. Forks with distinct labels are independent of each other, and
+    /// no continuation of `self`, such as `self` observing `label`, reaches the state of a fork.
+    fn fork<L>(&self, label: L) -> Self
+    where
+        Self: CanObserve<L>,
+    {
+        let mut child = self.clone();
+        child.separate_fork();
+        child.observe(label);
+        child
+    }
+}
+
 pub trait FieldChallenger<F: Field>:
     CanObserve<F> + CanSample<F> + CanSampleBits<usize> + Sync
 {
@@ -5,7 +5,7 @@ use alloc::vec::Vec;
 use p3_field::{BasedVectorSpace, Field, PrimeField, PrimeField32, reduce_32, split_32};
 use p3_symmetric::{CryptographicPermutation, Hash};
 
-use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
+use crate::{CanFork, CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger};
 
 /// A challenger that operates natively on PF but produces challenges of F: PrimeField32.
 ///
@@ -75,6 +75,54 @@ where
     }
 }
 
+/// The transcript state of a [`MultiField32Challenger`].
+#[derive(Clone, Debug)]
+pub struct MultiField32ChallengerSnapshot<F, PF, const WIDTH: usize> {
+    sponge_state: [PF; WIDTH],
+    input_buffer: Vec<F>,
+    output_buffer: Vec<F>,
+}
+
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanSnapshot
+    for MultiField32Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: Field,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type Snapshot = MultiField32ChallengerSnapshot<F, PF, WIDTH>;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        MultiField32ChallengerSnapshot {
+            sponge_state: self.sponge_state,
+            input_buffer: self.input_buffer.clone(),
+            output_buffer: self.output_buffer.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.sponge_state = snapshot.sponge_state;
+        self.input_buffer = snapshot.input_buffer;
+        self.output_buffer = snapshot.output_buffer;
+    }
+}
+
+/// Separates a fork by adding one to the last element of the capacity, which observations and
+/// samples never write, and absorbing any buffered input.
+impl<F, PF, P, const WIDTH: usize, const RATE: usize> CanFork
+    for MultiField32Challenger<F, PF, P, WIDTH, RATE>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn separate_fork(&mut self) {
+        assert!(RATE < WIDTH, "Separating a fork needs a nonzero capacity.");
+        self.sponge_state[WIDTH - 1] += PF::ONE;
+        self.duplexing();
+    }
+}
+
 impl<F, PF, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
     for MultiField32Challenger<F, PF, P, WIDTH, RATE>
 where
@@ -0,0 +1,446 @@
+use alloc::vec::Vec;
+
+use p3_field::{BasedVectorSpace, Field, PrimeCharacteristicRing, PrimeField64};
+use p3_symmetric::{CryptographicPermutation, Hash};
+
+use crate::{
+    CanFork, CanObserve, CanSample, CanSampleBits, CanSnapshot, DuplexChallenger,
+    DuplexChallengerSnapshot, FieldChallenger, GrindingChallenger,
+};
+
+/// One call of a sponge IO pattern, absorbing or squeezing the given number of field elements.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpongeOp {
+    Absorb(usize),
+    Squeeze(usize),
+}
+
+/// The sequence of absorb and squeeze lengths of a transcript, as in the SAFE sponge API.
+///
+/// Consecutive calls of the same kind are merged, so absorbing 2 elements and then 3 is the same
+/// pattern as absorbing 5, and calls of length zero are dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IoPattern {
+    ops: Vec<SpongeOp>,
+}
+
+impl IoPattern {
+    pub const fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    #[must_use]
+    pub fn absorb(mut self, len: usize) -> Self {
+        match self.ops.last_mut() {
+            _ if len == 0 => {}
+            Some(SpongeOp::Absorb(n)) => *n += len,
+            _ => self.ops.push(SpongeOp::Absorb(len)),
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn squeeze(mut self, len: usize) -> Self {
+        match self.ops.last_mut() {
+            _ if len == 0 => {}
+            Some(SpongeOp::Squeeze(n)) => *n += len,
+            _ => self.ops.push(SpongeOp::Squeeze(len)),
+        }
+        self
+    }
+
+    pub fn ops(&self) -> &[SpongeOp] {
+        &self.ops
+    }
+
+    /// The pattern as field elements: its number of calls, then a kind and a length per call.
+    fn encode<F: PrimeCharacteristicRing>(&self) -> impl Iterator<Item = F> + '_ {
+        let calls = self.ops.iter().flat_map(|op| match *op {
+            SpongeOp::Absorb(len) => [F::ONE, F::from_usize(len)],
+            SpongeOp::Squeeze(len) => [F::TWO, F::from_usize(len)],
+        });
+        core::iter::once(F::from_usize(self.ops.len())).chain(calls)
+    }
+}
+
+/// Where a transcript departed from the IO pattern of its `SafeChallenger`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IoPatternError {
+    /// The transcript absorbed where the pattern squeezes, squeezed where it absorbs, or went on
+    /// past the end of the pattern, after `done` elements of call `call`.
+    Diverged { call: usize, done: usize },
+    /// The transcript ended after `done` elements of call `call`, before the end of the pattern.
+    Incomplete { call: usize, done: usize },
+}
+
+/// A duplex challenger which commits to the IO pattern of its transcript up front, following the
+/// SAFE sponge API.
+///
+/// The pattern is hashed into the capacity of the initial sponge state, so transcripts with
+/// different patterns draw unrelated challenges. Every element observed or sampled is checked
+/// against the pattern, and the first one which doesn't fit it is recorded; `check` reports it at
+/// any point, and `finish` also reports a transcript which stopped short. A verifier reading a
+/// proof of the wrong shape thus learns the call where its transcript diverged, rather than
+/// failing a later check of a silently wrong challenge.
+#[derive(Clone, Debug)]
+pub struct SafeChallenger<F, P, const WIDTH: usize, const RATE: usize>
+where
+    F: Clone,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    inner: DuplexChallenger<F, P, WIDTH, RATE>,
+    pattern: IoPattern,
+    /// The index of the current call in the pattern.
+    call: usize,
+    /// The number of elements of the current call done so far.
+    done: usize,
+    /// The first divergence from the pattern, after which the position is no longer tracked.
+    divergence: Option<IoPatternError>,
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    pub fn new(permutation: P, pattern: IoPattern) -> Self {
+        assert!(
+            RATE < WIDTH,
+            "The sponge needs a nonzero capacity to hold the tag."
+        );
+
+        let mut tag_sponge = DuplexChallenger::<F, P, WIDTH, RATE>::new(permutation.clone());
+        pattern
+            .encode::<F>()
+            .for_each(|value| tag_sponge.observe(value));
+        let tag: Vec<F> = tag_sponge.sample_vec(WIDTH - RATE);
+
+        let mut inner = DuplexChallenger::new(permutation);
+        inner.sponge_state[RATE..].copy_from_slice(&tag);
+        Self {
+            inner,
+            pattern,
+            call: 0,
+            done: 0,
+            divergence: None,
+        }
+    }
+
+    pub const fn pattern(&self) -> &IoPattern {
+        &self.pattern
+    }
+
+    /// Whether every call of the pattern has been made, without diverging from it.
+    pub fn is_complete(&self) -> bool {
+        self.divergence.is_none() && self.call == self.pattern.ops.len()
+    }
+
+    /// Check that the transcript has followed the pattern so far.
+    pub const fn check(&self) -> Result<(), IoPatternError> {
+        match self.divergence {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the transcript has followed the pattern to its end.
+    pub fn finish(&self) -> Result<(), IoPatternError> {
+        self.check()?;
+        if self.call != self.pattern.ops.len() {
+            return Err(IoPatternError::Incomplete {
+                call: self.call,
+                done: self.done,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that the next element of the pattern is absorbed or squeezed, as `absorb` says, and
+    /// move past it, or record the divergence if it isn't.
+    fn advance(&mut self, absorb: bool) {
+        if self.divergence.is_some() {
+            return;
+        }
+        match self.pattern.ops.get(self.call) {
+            Some(&SpongeOp::Absorb(len)) if absorb => self.step(len),
+            Some(&SpongeOp::Squeeze(len)) if !absorb => self.step(len),
+            _ => {
+                self.divergence = Some(IoPatternError::Diverged {
+                    call: self.call,
+                    done: self.done,
+                });
+            }
+        }
+    }
+
+    fn step(&mut self, len: usize) {
+        self.done += 1;
+        if self.done == len {
+            self.call += 1;
+            self.done = 0;
+        }
+    }
+}
+
+/// The transcript state of a [`SafeChallenger`], including its position in the IO pattern.
+#[derive(Clone, Debug)]
+pub struct SafeChallengerSnapshot<F, const WIDTH: usize> {
+    inner: DuplexChallengerSnapshot<F, WIDTH>,
+    call: usize,
+    done: usize,
+    divergence: Option<IoPatternError>,
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanSnapshot for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    type Snapshot = SafeChallengerSnapshot<F, WIDTH>;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        SafeChallengerSnapshot {
+            inner: self.inner.snapshot(),
+            call: self.call,
+            done: self.done,
+            divergence: self.divergence,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.inner.restore(snapshot.inner);
+        self.call = snapshot.call;
+        self.done = snapshot.done;
+        self.divergence = snapshot.divergence;
+    }
+}
+
+/// Separating a fork neither absorbs nor squeezes, so it doesn't advance the IO pattern.
+impl<F, P, const WIDTH: usize, const RATE: usize> CanFork for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn separate_fork(&mut self) {
+        self.inner.separate_fork();
+    }
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> FieldChallenger<F>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanObserve<F>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe(&mut self, value: F) {
+        self.advance(true);
+        self.inner.observe(value);
+    }
+}
+
+impl<F, P, const N: usize, const WIDTH: usize, const RATE: usize> CanObserve<[F; N]>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
+impl<F, P, const N: usize, const WIDTH: usize, const RATE: usize> CanObserve<Hash<F, F, N>>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe(&mut self, values: Hash<F, F, N>) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
+// for TrivialPcs
+impl<F, P, const WIDTH: usize, const RATE: usize> CanObserve<Vec<Vec<F>>>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn observe(&mut self, valuess: Vec<Vec<F>>) {
+        for values in valuess {
+            for value in values {
+                self.observe(value);
+            }
+        }
+    }
+}
+
+impl<F, EF, P, const WIDTH: usize, const RATE: usize> CanSample<EF>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: Field,
+    EF: BasedVectorSpace<F>,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn sample(&mut self) -> EF {
+        EF::from_basis_coefficients_fn(|_| {
+            self.advance(false);
+            CanSample::<F>::sample(&mut self.inner)
+        })
+    }
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> CanSampleBits<usize>
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        self.advance(false);
+        self.inner.sample_bits(bits)
+    }
+}
+
+impl<F, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
+    for SafeChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]>,
+{
+    type Witness = F;
+
+    /// Search for the witness on the underlying challenger, then check it here, which absorbs
+    /// the witness and squeezes one element of the pattern.
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.inner.clone().grind(bits);
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_symmetric::Permutation;
+
+    use super::*;
+
+    const WIDTH: usize = 16;
+    const RATE: usize = 8;
+
+    type F = BabyBear;
+    type Chal = SafeChallenger<F, TestPermutation, WIDTH, RATE>;
+
+    /// Scales each element by its position and adds the sum of the state, so that every element
+    /// depends on the capacity.
+    #[derive(Clone, Debug)]
+    struct TestPermutation;
+
+    impl Permutation<[F; WIDTH]> for TestPermutation {
+        fn permute_mut(&self, input: &mut [F; WIDTH]) {
+            let sum: F = input.iter().copied().sum();
+            input
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, x)| *x = *x * F::from_usize(i + 1) + sum);
+        }
+    }
+
+    impl CryptographicPermutation<[F; WIDTH]> for TestPermutation {}
+
+    fn run(challenger: &mut Chal) -> Vec<F> {
+        challenger.observe([F::ONE; 3]);
+        challenger.observe(F::TWO);
+        let a: F = challenger.sample();
+        challenger.observe(F::ONE);
+        let b: F = challenger.sample();
+        let c: F = challenger.sample();
+        vec![a, b, c]
+    }
+
+    #[test]
+    fn pattern_merges_calls() {
+        let pattern = IoPattern::new().absorb(3).absorb(1).squeeze(0).squeeze(1);
+        assert_eq!(pattern.ops(), &[SpongeOp::Absorb(4), SpongeOp::Squeeze(1)]);
+    }
+
+    #[test]
+    fn follows_pattern() {
+        let pattern = IoPattern::new().absorb(4).squeeze(1).absorb(1).squeeze(2);
+        let mut challenger = Chal::new(TestPermutation, pattern.clone());
+        let samples = run(&mut challenger);
+        assert!(challenger.is_complete());
+        assert_eq!(run(&mut Chal::new(TestPermutation, pattern)), samples);
+
+        // The same transcript under a pattern which admits it but differs draws other challenges.
+        let longer = IoPattern::new().absorb(4).squeeze(1).absorb(1).squeeze(3);
+        let mut challenger = Chal::new(TestPermutation, longer);
+        assert_ne!(run(&mut challenger), samples);
+        assert!(!challenger.is_complete());
+    }
+
+    #[test]
+    fn catches_divergence_at_first_mismatch() {
+        let pattern = IoPattern::new().absorb(5).squeeze(1);
+        let mut challenger = Chal::new(TestPermutation, pattern);
+        challenger.observe([F::ONE; 3]);
+        challenger.observe(F::TWO);
+        assert_eq!(challenger.check(), Ok(()));
+        // The pattern expects a fifth element to be absorbed before any squeeze.
+        let _: F = challenger.sample();
+        challenger.observe(F::ONE);
+        assert_eq!(
+            challenger.check(),
+            Err(IoPatternError::Diverged { call: 0, done: 4 })
+        );
+        assert!(!challenger.is_complete());
+    }
+
+    /// Read a proof of a commitment of four elements and then an answer per sampled point, as a
+    /// verifier would, and report whether its transcript followed the pattern.
+    fn read_proof(proof: &[F]) -> Result<(), IoPatternError> {
+        let pattern = IoPattern::new().absorb(4).squeeze(2).absorb(2);
+        let mut challenger = Chal::new(TestPermutation, pattern);
+        let (commitment, answers) = proof.split_at(proof.len().min(4));
+        commitment.iter().for_each(|&x| challenger.observe(x));
+        let _: [F; 2] = [challenger.sample(), challenger.sample()];
+        answers.iter().for_each(|&x| challenger.observe(x));
+        challenger.finish()
+    }
+
+    #[test]
+    fn rejects_wrong_shaped_proof() {
+        assert_eq!(read_proof(&[F::ONE; 6]), Ok(()));
+        // A commitment one element short: the verifier squeezes where it should absorb.
+        assert_eq!(
+            read_proof(&[F::ONE; 3]),
+            Err(IoPatternError::Diverged { call: 0, done: 3 })
+        );
+        // One answer too many runs past the end of the pattern.
+        assert_eq!(
+            read_proof(&[F::ONE; 7]),
+            Err(IoPatternError::Diverged { call: 3, done: 0 })
+        );
+        // One answer too few stops short of it.
+        assert_eq!(
+            read_proof(&[F::ONE; 5]),
+            Err(IoPatternError::Incomplete { call: 2, done: 1 })
+        );
+    }
+}
@@ -8,7 +8,8 @@ use p3_util::log2_ceil_u64;
 use tracing::instrument;
 
 use crate::{
-    CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger, HashChallenger,
+    CanObserve, CanSample, CanSampleBits, CanSnapshot, FieldChallenger, GrindingChallenger,
+    HashChallenger,
 };
 
 /// Given a challenger that can observe and sample bytes, produces a challenger that is able to
@@ -158,6 +159,18 @@ where
 {
 }
 
+impl<F: PrimeField32, Inner: CanSnapshot> CanSnapshot for SerializingChallenger32<F, Inner> {
+    type Snapshot = Inner::Snapshot;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.inner.restore(snapshot);
+    }
+}
+
 impl<F: PrimeField64, Inner: CanObserve<u8>> SerializingChallenger64<F, Inner> {
     pub const fn new(inner: Inner) -> Self {
         Self {
@@ -275,3 +288,15 @@ where
     Inner: CanSample<u8> + CanObserve<u8> + Clone + Send + Sync,
 {
 }
+
+impl<F: PrimeField64, Inner: CanSnapshot> CanSnapshot for SerializingChallenger64<F, Inner> {
+    type Snapshot = Inner::Snapshot;
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.inner.restore(snapshot);
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 
@@ -5,7 +6,9 @@ use itertools::{Itertools, iterate, izip};
 use p3_commit::PolynomialSpace;
 use p3_dft::{Butterfly, DifButterfly, DitButterfly, divide_by_height};
 use p3_field::extension::ComplexExtendable;
-use p3_field::{ExtensionField, Field, batch_multiplicative_inverse};
+use p3_field::{
+    Algebra, ExtensionField, Field, PackedFieldExtension, PackedValue, batch_multiplicative_inverse,
+};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_maybe_rayon::prelude::*;
@@ -14,14 +17,49 @@ use tracing::{debug_span, instrument};
 
 use crate::domain::CircleDomain;
 use crate::point::{Point, compute_lagrange_den_batched};
-use crate::{CfftPermutable, CfftView, cfft_permute_index, cfft_permute_slice};
-
+use crate::twiddles::{CfftTwiddles, LayerTwiddles};
+use crate::{
+    CfftPermutable, CfftView, CircleCfft, cfft_permute_index, cfft_permute_slice,
+    cfft_permute_slice_chunked_in_place,
+};
+
+/// Evaluations of a batch of polynomials over a circle domain, with rows in cfft order.
+///
+/// Natural order evaluations are held by [`NaturalOrderEvaluations`] instead, so the order of the
+/// rows is always known from the type and converting between the two is explicit.
 #[derive(Clone)]
 pub struct CircleEvaluations<F, M = RowMajorMatrix<F>> {
     pub(crate) domain: CircleDomain<F>,
     pub(crate) values: M,
 }
 
+/// Evaluations of a batch of polynomials over a circle domain, with rows in natural order, i.e.
+/// the order of `domain.points()`.
+#[derive(Clone)]
+pub struct NaturalOrderEvaluations<F, M = RowMajorMatrix<F>> {
+    domain: CircleDomain<F>,
+    values: M,
+}
+
+impl<F: Copy + Send + Sync, M: Matrix<F>> NaturalOrderEvaluations<F, M> {
+    pub fn new(domain: CircleDomain<F>, values: M) -> Self {
+        assert_eq!(1 << domain.log_n, values.height());
+        Self { domain, values }
+    }
+    pub const fn domain(&self) -> CircleDomain<F> {
+        self.domain
+    }
+    pub const fn values(&self) -> &M {
+        &self.values
+    }
+    pub fn into_values(self) -> M {
+        self.values
+    }
+    pub fn into_cfft_order(self) -> CircleEvaluations<F, CfftView<M>> {
+        CircleEvaluations::from_cfft_order(self.domain, self.values.cfft_perm_rows())
+    }
+}
+
 impl<F: Copy + Send + Sync, M: Matrix<F>> CircleEvaluations<F, M> {
     pub(crate) fn from_cfft_order(domain: CircleDomain<F>, values: M) -> Self {
         assert_eq!(1 << domain.log_n, values.height());
@@ -31,14 +69,25 @@ impl<F: Copy + Send + Sync, M: Matrix<F>> CircleEvaluations<F, M> {
         domain: CircleDomain<F>,
         values: M,
     ) -> CircleEvaluations<F, CfftView<M>> {
-        CircleEvaluations::from_cfft_order(domain, values.cfft_perm_rows())
+        NaturalOrderEvaluations::new(domain, values).into_cfft_order()
     }
     pub fn to_cfft_order(self) -> M {
         self.values
     }
+    #[deprecated(note = "use `into_natural_order`, whose type records the order of the rows")]
     pub fn to_natural_order(self) -> CfftView<M> {
         self.values.cfft_perm_rows()
     }
+    pub fn into_natural_order(self) -> NaturalOrderEvaluations<F, CfftView<M>> {
+        NaturalOrderEvaluations::new(self.domain, self.values.cfft_perm_rows())
+    }
+    pub const fn domain(&self) -> CircleDomain<F> {
+        self.domain
+    }
+    /// The evaluations, in cfft order.
+    pub const fn values(&self) -> &M {
+        &self.values
+    }
 }
 
 impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
@@ -47,43 +96,9 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
         let Self { domain, values } = self;
         let mut values = debug_span!("to_rmm").in_scope(|| values.to_row_major_matrix());
 
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| {
-                    batch_multiplicative_inverse(&ts)
-                        .into_iter()
-                        .map(|t| DifButterfly(t))
-                        .collect_vec()
-                })
-                .peekable()
-        });
-
-        assert_eq!(twiddles.len(), domain.log_n);
-
-        let par_twiddles = twiddles
-            .peeking_take_while(|ts| ts.len() >= desired_num_jobs())
-            .collect_vec();
-        if let Some(min_blks) = par_twiddles.last().map(|ts| ts.len()) {
-            let max_blk_sz = values.height() / min_blks;
-            debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
-                values
-                    .par_row_chunks_exact_mut(max_blk_sz)
-                    .enumerate()
-                    .for_each(|(chunk_i, submat)| {
-                        for ts in &par_twiddles {
-                            let twiddle_chunk_sz = ts.len() / min_blks;
-                            let twiddle_chunk = &ts
-                                [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
-                        }
-                    });
-            });
-        }
-
-        for ts in twiddles {
-            par_within_blk_layer(&mut values.values, &ts);
-        }
+        let inv_twiddles = debug_span!("twiddles").in_scope(|| compute_inverse_twiddles(domain));
+        assert_eq!(inv_twiddles.len(), domain.log_n);
+        icfft_layers(&mut values, &inv_twiddles, DifButterfly);
 
         // TODO: omit this?
         divide_by_height(&mut values);
@@ -155,43 +170,268 @@ impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
         }
         assert_eq!(coeffs.height(), 1 << domain.log_n);
 
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| ts.into_iter().map(|t| DitButterfly(t)).collect_vec())
-                .rev()
-                .skip(domain.log_n - log_n)
-                .peekable()
-        });
+        let twiddles = debug_span!("twiddles").in_scope(|| compute_twiddles(domain));
+        cfft_layers(&mut coeffs, &twiddles, domain.log_n - log_n, DitButterfly);
+
+        Self::from_cfft_order(domain, coeffs)
+    }
+}
 
-        for ts in twiddles.peeking_take_while(|ts| ts.len() < desired_num_jobs()) {
-            par_within_blk_layer(&mut coeffs.values, &ts);
+/// The radix-2 circle FFT of [`CircleEvaluations`], exposed through the [`CircleCfft`] trait.
+///
+/// Twiddles for standard position domains are memoized per domain size, so repeated transforms
+/// of the same size only pay for the butterflies. Twiddles for other twin-cosets are computed on
+/// each call.
+#[derive(Default, Clone, Debug)]
+pub struct Radix2Cfft<F> {
+    twiddles: CfftTwiddles<F>,
+}
+
+impl<F: ComplexExtendable> Radix2Cfft<F> {
+    /// Create a CFFT with the twiddles for domains of size `2^log_n` already computed.
+    pub fn with_precomputed(log_n: usize) -> Self {
+        Self {
+            twiddles: CfftTwiddles::with_precomputed(log_n),
         }
+    }
 
-        let par_twiddles = twiddles.collect_vec();
-        if let Some(min_blks) = par_twiddles.first().map(|ts| ts.len()) {
-            let max_blk_sz = coeffs.height() / min_blks;
-            debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
-                coeffs
-                    .par_row_chunks_exact_mut(max_blk_sz)
-                    .enumerate()
-                    .for_each(|(chunk_i, submat)| {
-                        for ts in &par_twiddles {
-                            let twiddle_chunk_sz = ts.len() / min_blks;
-                            let twiddle_chunk = &ts
-                                [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
-                        }
-                    });
-            });
+    /// The twiddles for `domain`, taken from the cache if it is in standard position.
+    fn domain_twiddles(&self, domain: CircleDomain<F>) -> Arc<LayerTwiddles<F>> {
+        if domain.is_standard() {
+            self.twiddles.get(domain.log_n)
+        } else {
+            Arc::new(LayerTwiddles::new(domain))
         }
+    }
+}
 
-        Self::from_cfft_order(domain, coeffs)
+impl<F: ComplexExtendable> CircleCfft<F> for Radix2Cfft<F> {
+    fn coset_cfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>, shift: Point<F>) {
+        // A single row holds constant polynomials, which are their own evaluations.
+        if mat.height() == 1 {
+            return;
+        }
+        let domain = CircleDomain::new(log2_strict_usize(mat.height()), shift);
+        let twiddles = self.domain_twiddles(domain);
+        cfft_layers(mat, &twiddles.forward, 0, DitButterfly);
+        cfft_permute_slice_chunked_in_place(&mut mat.values, mat.width);
+    }
+
+    fn coset_icfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>, shift: Point<F>) {
+        if mat.height() == 1 {
+            return;
+        }
+        let domain = CircleDomain::new(log2_strict_usize(mat.height()), shift);
+        let twiddles = self.domain_twiddles(domain);
+        cfft_permute_slice_chunked_in_place(&mut mat.values, mat.width);
+        icfft_layers(mat, &twiddles.inverse, DifButterfly);
+        divide_by_height(mat);
+    }
+
+    fn coset_cfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mut mat: RowMajorMatrix<EF>,
+        shift: Point<F>,
+    ) -> RowMajorMatrix<EF> {
+        if mat.height() == 1 {
+            return mat;
+        }
+        let domain = CircleDomain::new(log2_strict_usize(mat.height()), shift);
+        let twiddles = self.domain_twiddles(domain);
+        cfft_layers(&mut mat, &twiddles.forward, 0, ExtButterfly::Dit);
+        cfft_permute_slice_chunked_in_place(&mut mat.values, mat.width);
+        mat
+    }
+
+    fn coset_icfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mut mat: RowMajorMatrix<EF>,
+        shift: Point<F>,
+    ) -> RowMajorMatrix<EF> {
+        if mat.height() == 1 {
+            return mat;
+        }
+        let domain = CircleDomain::new(log2_strict_usize(mat.height()), shift);
+        let twiddles = self.domain_twiddles(domain);
+        cfft_permute_slice_chunked_in_place(&mut mat.values, mat.width);
+        icfft_layers(&mut mat, &twiddles.inverse, ExtButterfly::Dif);
+        let inv_height = F::ONE.div_2exp_u64(domain.log_n as u64);
+        mat.values.par_iter_mut().for_each(|x| *x *= inv_height);
+        mat
+    }
+}
+
+/// A butterfly which can be applied to pairs of rows of values of type `V`.
+///
+/// The `p3_dft` butterflies implement this for rows of base field values, making use of packing,
+/// while `ExtButterfly` implements it for rows of values in any extension of the base field.
+trait RowButterfly<V>: Copy + Send + Sync {
+    fn apply_to_rows(&self, row_1: &mut [V], row_2: &mut [V]);
+}
+
+impl<F: Field> RowButterfly<F> for DitButterfly<F> {
+    #[inline]
+    fn apply_to_rows(&self, row_1: &mut [F], row_2: &mut [F]) {
+        Butterfly::apply_to_rows(self, row_1, row_2);
+    }
+}
+
+impl<F: Field> RowButterfly<F> for DifButterfly<F> {
+    #[inline]
+    fn apply_to_rows(&self, row_1: &mut [F], row_2: &mut [F]) {
+        Butterfly::apply_to_rows(self, row_1, row_2);
+    }
+}
+
+/// A DIT or DIF butterfly whose twiddle lies in the base field `F`, applied to values in an
+/// extension of `F`. Since the twiddles are always in the base field, this avoids both flattening
+/// the values and any extension field multiplications.
+#[derive(Copy, Clone)]
+enum ExtButterfly<F> {
+    Dit(F),
+    Dif(F),
+}
+
+impl<F: Field> ExtButterfly<F> {
+    #[inline]
+    fn twiddle(self) -> F {
+        match self {
+            Self::Dit(twiddle) | Self::Dif(twiddle) => twiddle,
+        }
+    }
+
+    /// Apply the butterfly to a pair of values, which may be packed, with a twiddle of the
+    /// matching (packed) base field type.
+    #[inline]
+    fn apply<S, T: Algebra<S>>(self, x_1: T, x_2: T, twiddle: S) -> (T, T) {
+        match self {
+            Self::Dit(_) => {
+                let x_2_twiddle = x_2 * twiddle;
+                (x_1.clone() + x_2_twiddle.clone(), x_1 - x_2_twiddle)
+            }
+            Self::Dif(_) => (x_1.clone() + x_2.clone(), (x_1 - x_2) * twiddle),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> RowButterfly<EF> for ExtButterfly<F> {
+    #[inline]
+    fn apply_to_rows(&self, row_1: &mut [EF], row_2: &mut [EF]) {
+        // Handle `F::Packing::WIDTH` butterflies at once for as much of the rows as possible.
+        let width = F::Packing::WIDTH;
+        let packed_len = row_1.len() - row_1.len() % width;
+        let (packed_1, suffix_1) = row_1.split_at_mut(packed_len);
+        let (packed_2, suffix_2) = row_2.split_at_mut(packed_len);
+
+        let packed_twiddle = F::Packing::from(self.twiddle());
+        for (chunk_1, chunk_2) in izip!(
+            packed_1.chunks_exact_mut(width),
+            packed_2.chunks_exact_mut(width)
+        ) {
+            let (y_1, y_2) = self.apply(
+                EF::ExtensionPacking::from_ext_slice(chunk_1),
+                EF::ExtensionPacking::from_ext_slice(chunk_2),
+                packed_twiddle,
+            );
+            for (x, y) in izip!(chunk_1, EF::ExtensionPacking::to_ext_iter([y_1])) {
+                *x = y;
+            }
+            for (x, y) in izip!(chunk_2, EF::ExtensionPacking::to_ext_iter([y_2])) {
+                *x = y;
+            }
+        }
+
+        for (x_1, x_2) in izip!(suffix_1, suffix_2) {
+            (*x_1, *x_2) = self.apply(*x_1, *x_2, self.twiddle());
+        }
+    }
+}
+
+/// Apply the butterfly network of the inverse CFFT to `values`, which are in cfft order.
+///
+/// `inv_twiddles` holds the inverse twiddles of each layer, as returned by
+/// `compute_inverse_twiddles`, and `butterfly` builds the DIF butterfly for each of them. The
+/// result still needs to be divided by the height.
+fn icfft_layers<F: Field, V: Copy + Send + Sync, B: RowButterfly<V>>(
+    values: &mut RowMajorMatrix<V>,
+    inv_twiddles: &[Vec<F>],
+    butterfly: impl Fn(F) -> B,
+) {
+    let mut twiddles = inv_twiddles
+        .iter()
+        .map(|ts| ts.iter().map(|&t| butterfly(t)).collect_vec())
+        .peekable();
+
+    let par_twiddles = twiddles
+        .peeking_take_while(|ts| ts.len() >= desired_num_jobs())
+        .collect_vec();
+    if let Some(min_blks) = par_twiddles.last().map(|ts| ts.len()) {
+        let max_blk_sz = values.height() / min_blks;
+        debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
+            values
+                .par_row_chunks_exact_mut(max_blk_sz)
+                .enumerate()
+                .for_each(|(chunk_i, submat)| {
+                    for ts in &par_twiddles {
+                        let twiddle_chunk_sz = ts.len() / min_blks;
+                        let twiddle_chunk =
+                            &ts[(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
+                        serial_layer(submat.values, twiddle_chunk);
+                    }
+                });
+        });
+    }
+
+    for ts in twiddles {
+        par_within_blk_layer(&mut values.values, &ts);
+    }
+}
+
+/// Apply the butterfly network of the forward CFFT to the coefficients in `values`, leaving
+/// evaluations in cfft order.
+///
+/// `twiddles` holds the twiddles of each layer of the target domain, as returned by
+/// `compute_twiddles`, and `butterfly` builds the DIT butterfly for each of them. The first
+/// `skipped_layers` layers of the network are skipped, which is used when the coefficients have
+/// been repeated to fill out a larger domain.
+fn cfft_layers<F: Field, V: Copy + Send + Sync, B: RowButterfly<V>>(
+    values: &mut RowMajorMatrix<V>,
+    twiddles: &[Vec<F>],
+    skipped_layers: usize,
+    butterfly: impl Fn(F) -> B,
+) {
+    let mut twiddles = twiddles
+        .iter()
+        .rev()
+        .skip(skipped_layers)
+        .map(|ts| ts.iter().map(|&t| butterfly(t)).collect_vec())
+        .peekable();
+
+    for ts in twiddles.peeking_take_while(|ts| ts.len() < desired_num_jobs()) {
+        par_within_blk_layer(&mut values.values, &ts);
+    }
+
+    let par_twiddles = twiddles.collect_vec();
+    if let Some(min_blks) = par_twiddles.first().map(|ts| ts.len()) {
+        let max_blk_sz = values.height() / min_blks;
+        debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
+            values
+                .par_row_chunks_exact_mut(max_blk_sz)
+                .enumerate()
+                .for_each(|(chunk_i, submat)| {
+                    for ts in &par_twiddles {
+                        let twiddle_chunk_sz = ts.len() / min_blks;
+                        let twiddle_chunk =
+                            &ts[(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
+                        serial_layer(submat.values, twiddle_chunk);
+                    }
+                });
+        });
     }
 }
 
 #[inline]
-fn serial_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
+fn serial_layer<V, B: RowButterfly<V>>(values: &mut [V], twiddles: &[B]) {
     let blk_sz = values.len() / twiddles.len();
     for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
         let (lo, hi) = blk.split_at_mut(blk_sz / 2);
@@ -199,17 +439,28 @@ fn serial_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
     }
 }
 
+/// Apply a single butterfly layer, parallelizing both across the blocks of the layer and across
+/// the independent butterflies within each block.
+///
+/// Since `values` is the flattened row-major matrix, splitting a half-block into jobs splits it
+/// across rows and columns alike, so wide matrices with few rows per block still get enough jobs.
 #[inline]
 #[instrument(level = "debug", skip_all, fields(log_blks = log2_strict_usize(twiddles.len())))]
-fn par_within_blk_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
+fn par_within_blk_layer<V: Send, B: RowButterfly<V>>(values: &mut [V], twiddles: &[B]) {
     let blk_sz = values.len() / twiddles.len();
-    for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
-        let (lo, hi) = blk.split_at_mut(blk_sz / 2);
-        let job_sz = core::cmp::max(1, lo.len() >> log2_ceil_usize(desired_num_jobs()));
-        lo.par_chunks_mut(job_sz)
-            .zip(hi.par_chunks_mut(job_sz))
-            .for_each(|(lo_job, hi_job)| t.apply_to_rows(lo_job, hi_job));
-    }
+    // Share the desired number of jobs out between the blocks of this layer.
+    let log_jobs_per_blk =
+        log2_ceil_usize(desired_num_jobs()).saturating_sub(log2_strict_usize(twiddles.len()));
+    values
+        .par_chunks_exact_mut(blk_sz)
+        .zip(twiddles.par_iter())
+        .for_each(|(blk, &t)| {
+            let (lo, hi) = blk.split_at_mut(blk_sz / 2);
+            let job_sz = core::cmp::max(1, lo.len() >> log_jobs_per_blk);
+            lo.par_chunks_mut(job_sz)
+                .zip(hi.par_chunks_mut(job_sz))
+                .for_each(|(lo_job, hi_job)| t.apply_to_rows(lo_job, hi_job));
+        });
 }
 
 #[inline]
@@ -241,7 +492,18 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     }
 }
 
-fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>> {
+/// Compute the twiddles of each layer of the inverse CFFT over `domain`, i.e. the inverses of
+/// `compute_twiddles(domain)`.
+pub(crate) fn compute_inverse_twiddles<F: ComplexExtendable>(
+    domain: CircleDomain<F>,
+) -> Vec<Vec<F>> {
+    compute_twiddles(domain)
+        .iter()
+        .map(|ts| batch_multiplicative_inverse(ts))
+        .collect()
+}
+
+pub(crate) fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>> {
     assert!(domain.log_n >= 1);
     let mut pts = domain.coset0().collect_vec();
     reverse_slice_index_bits(&mut pts);
@@ -298,7 +560,8 @@ mod tests {
             let coeffs = CircleEvaluations::from_natural_order(domain, trace.clone()).interpolate();
             assert_eq!(
                 CircleEvaluations::evaluate(domain, coeffs.clone())
-                    .to_natural_order()
+                    .into_natural_order()
+                    .into_values()
                     .to_row_major_matrix(),
                 trace,
                 "icfft(cfft(evals)) is identity",
@@ -313,6 +576,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cfft_parallel_layers() {
+        // Large enough that both the per-chunk layers and the within-block layers are used.
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!([8, 10], [1, 3, 32]) {
+            let domain = CircleDomain::standard(log_n);
+            let coeffs = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+            let evals = CircleEvaluations::evaluate(domain, coeffs.clone())
+                .into_natural_order()
+                .into_values()
+                .to_row_major_matrix();
+            for (i, pt) in domain.points().enumerate().step_by(37) {
+                assert_eq!(
+                    &*evals.row_slice(i),
+                    coeffs.columnwise_dot_product(&circle_basis(pt, log_n)),
+                );
+            }
+            assert_eq!(
+                CircleEvaluations::from_natural_order(domain, evals).interpolate(),
+                coeffs
+            );
+        }
+    }
+
     #[test]
     fn test_extrapolation() {
         let mut rng = SmallRng::seed_from_u64(1);
@@ -383,4 +670,46 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_packed_ext_butterflies() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let twiddle: F = rng.random();
+        // Lengths below, at and above multiples of the packing width.
+        for len in [1, 3, 8, 16, 37] {
+            let row_1: Vec<EF> = (0..len).map(|_| rng.random()).collect();
+            let row_2: Vec<EF> = (0..len).map(|_| rng.random()).collect();
+            for butterfly in [ExtButterfly::Dit(twiddle), ExtButterfly::Dif(twiddle)] {
+                let (mut x_1, mut x_2) = (row_1.clone(), row_2.clone());
+                butterfly.apply_to_rows(x_1.as_mut_slice(), x_2.as_mut_slice());
+                for (i, (&y_1, &y_2)) in row_1.iter().zip(&row_2).enumerate() {
+                    assert_eq!((x_1[i], x_2[i]), butterfly.apply(y_1, y_2, twiddle));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ordering_round_trip() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let domain = CircleDomain::standard(5);
+        let natural = RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 3);
+
+        let evals = NaturalOrderEvaluations::new(domain, natural.clone());
+        let cfft_ordered = evals.clone().into_cfft_order();
+        assert_eq!(cfft_ordered.domain(), domain);
+        for r in 0..natural.height() {
+            let natural_r = cfft_permute_index(r, domain.log_n);
+            assert!(
+                cfft_ordered
+                    .values()
+                    .row(r)
+                    .eq(evals.values().row(natural_r))
+            );
+        }
+
+        let back = cfft_ordered.into_natural_order();
+        assert_eq!(back.domain(), domain);
+        assert_eq!(back.into_values().to_row_major_matrix(), natural);
+    }
 }
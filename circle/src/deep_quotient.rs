@@ -9,6 +9,7 @@ use p3_util::log2_strict_usize;
 use tracing::instrument;
 
 use crate::domain::CircleDomain;
+use crate::packed_point::packed_v_p;
 use crate::point::Point;
 use crate::{CircleEvaluations, cfft_permute_slice};
 
@@ -44,42 +45,124 @@ pub(crate) fn deep_quotient_reduce_row<F: ComplexExtendable, EF: ExtensionField<
         )
 }
 
+#[cfg(test)]
 impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
     /// Same as `deep_quotient_reduce_row`, but reduces a whole matrix into a column, taking advantage of batch inverses.
-    #[instrument(skip_all, fields(dims = %self.values.dimensions()))]
     pub(crate) fn deep_quotient_reduce<EF: ExtensionField<F>>(
         &self,
         alpha: EF,
         zeta: Point<EF>,
         ps_at_zeta: &[EF],
     ) -> Vec<EF> {
-        let alpha_pow_width = alpha.exp_u64(self.values.width() as u64);
-        let points = cfft_permute_slice(&self.domain.points().collect_vec());
-        let (vp_nums, vp_denoms): (Vec<_>, Vec<_>) = points
-            .into_iter()
-            .map(|x| deep_quotient_vanishing_part(x, zeta, alpha_pow_width))
-            .unzip();
-        let vp_denom_invs = batch_multiplicative_inverse(&vp_denoms);
+        deep_quotient_reduce_batch(
+            alpha,
+            &[OpenedMatrix {
+                evals: self,
+                openings: &[(zeta, ps_at_zeta)],
+            }],
+        )
+    }
+}
 
-        // TODO: packed_alpha_powers and alpha_powers should be passed into deep_quotient_reduce instead of being recomputed every time.
-        let packed_alpha_powers =
-            EF::ExtensionPacking::packed_ext_powers_capped(alpha, self.values.width())
-                .collect_vec();
-        let alpha_powers =
-            EF::ExtensionPacking::to_ext_iter(packed_alpha_powers.iter().copied()).collect_vec();
-
-        let alpha_reduced_ps_at_zeta: EF =
-            dot_product(alpha_powers.iter().copied(), ps_at_zeta.iter().copied());
-
-        self.values
-            .rowwise_packed_dot_product::<EF>(&packed_alpha_powers)
-            .zip(vp_nums.into_par_iter())
-            .zip(vp_denom_invs.into_par_iter())
-            .map(|((reduced_ps_at_x, vp_num), vp_denom_inv)| {
-                vp_num * vp_denom_inv * (reduced_ps_at_x - alpha_reduced_ps_at_zeta)
-            })
-            .collect()
+/// A committed matrix together with everything it is opened at: each opening point and the
+/// claimed values of the matrix's columns there.
+pub(crate) struct OpenedMatrix<'a, F, EF, M> {
+    pub(crate) evals: &'a CircleEvaluations<F, M>,
+    pub(crate) openings: &'a [(Point<EF>, &'a [EF])],
+}
+
+/// Reduce every opening of every matrix over a common domain into a single DEEP quotient column.
+///
+/// The result is the same as summing `deep_quotient_reduce` over all (matrix, point) pairs in
+/// order, with the `i`-th pair offset by `α^(2 * (w_0 + .. + w_(i-1)))` where `w_j` is the width
+/// of the `j`-th pair's matrix. However each matrix is only traversed once, however many points
+/// it is opened at, and the vanishing parts are shared between matrices opened at the same point.
+#[instrument(skip_all, fields(num_mats = mats.len()))]
+pub(crate) fn deep_quotient_reduce_batch<
+    F: ComplexExtendable,
+    EF: ExtensionField<F>,
+    M: Matrix<F>,
+>(
+    alpha: EF,
+    mats: &[OpenedMatrix<'_, F, EF, M>],
+) -> Vec<EF> {
+    let domain = mats[0].evals.domain;
+    assert!(mats.iter().all(|m| m.evals.domain == domain));
+    let points = cfft_permute_slice(&domain.packed_points());
+
+    // The distinct opening points, and for each the real and imaginary parts of v_zeta(x) and the
+    // inverse of its squared norm, at every x in the domain.
+    let mut zetas: Vec<Point<EF>> = Vec::new();
+    for (zeta, _) in mats.iter().flat_map(|m| m.openings) {
+        if !zetas.contains(zeta) {
+            zetas.push(*zeta);
+        }
     }
+    let vanishing_parts = zetas
+        .iter()
+        .map(|&zeta| {
+            let (re, im) = packed_v_p(&points, zeta);
+            let denoms = izip!(&re, &im)
+                .map(|(&re, &im)| re.square() + im.square())
+                .collect_vec();
+            (re, im, batch_multiplicative_inverse(&denoms))
+        })
+        .collect_vec();
+
+    let max_width = mats.iter().map(|m| m.evals.values.width()).max().unwrap();
+    let packed_alpha_powers =
+        EF::ExtensionPacking::packed_ext_powers_capped(alpha, max_width).collect_vec();
+    let alpha_powers =
+        EF::ExtensionPacking::to_ext_iter(packed_alpha_powers.iter().copied()).collect_vec();
+
+    // For each (matrix, point) pair: the index of its point, its alpha offset, α^width for its
+    // matrix, and the alpha-reduced opened values.
+    let mut alpha_offset = EF::ONE;
+    let mut terms = Vec::new();
+    // The alpha-reduced rows of each matrix, computed with a single pass over its values.
+    let reduced_rows = mats
+        .iter()
+        .enumerate()
+        .map(|(mat_idx, m)| {
+            let width = m.evals.values.width();
+            let alpha_pow_width = alpha.exp_u64(width as u64);
+            for (zeta, ps_at_zeta) in m.openings {
+                let zeta_idx = zetas.iter().position(|z| z == zeta).unwrap();
+                let reduced_ps_at_zeta: EF =
+                    dot_product(alpha_powers.iter().copied(), ps_at_zeta.iter().copied());
+                terms.push((
+                    mat_idx,
+                    zeta_idx,
+                    alpha_offset,
+                    alpha_pow_width,
+                    reduced_ps_at_zeta,
+                ));
+                alpha_offset *= alpha_pow_width.square();
+            }
+            m.evals
+                .values
+                .rowwise_packed_dot_product::<EF>(&packed_alpha_powers)
+                .collect::<Vec<_>>()
+        })
+        .collect_vec();
+
+    (0..points.len())
+        .into_par_iter()
+        .map(|i| {
+            terms
+                .iter()
+                .map(
+                    |&(mat_idx, zeta_idx, alpha_offset, alpha_pow_width, reduced_ps_at_zeta)| {
+                        let (re, im, denom_inv) = &vanishing_parts[zeta_idx];
+                        alpha_offset
+                            * (re[i] - alpha_pow_width * im[i])
+                            * denom_inv[i]
+                            * (reduced_rows[mat_idx][i] - reduced_ps_at_zeta)
+                    },
+                )
+                .sum::<EF>()
+        })
+        .collect()
 }
 
 /// Given evaluations over lde_domain, extract the multiple of the vanishing poly of orig_domain
@@ -158,7 +241,8 @@ mod tests {
 
         let mat_reduced = evals.deep_quotient_reduce(alpha, zeta, &ps_at_zeta);
         let row_reduced = evals
-            .to_natural_order()
+            .into_natural_order()
+            .into_values()
             .rows()
             .zip(domain.points())
             .map(|(ps_at_x, x)| {
@@ -236,6 +320,71 @@ mod tests {
         assert!(ros.dim() <= (1 << domain.log_n) + 1);
     }
 
+    #[test]
+    fn reduce_batch_same_as_rows() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let domain = CircleDomain::standard(6);
+
+        let alpha: EF = rng.random();
+        let zeta: Point<EF> = Point::from_projective_line(rng.random());
+        let zeta_next: Point<EF> = Point::from_projective_line(rng.random());
+
+        let all_evals = [3, 8, 1]
+            .into_iter()
+            .map(|width| {
+                CircleEvaluations::from_cfft_order(
+                    domain,
+                    RowMajorMatrix::<F>::rand(&mut rng, 1 << domain.log_n, width),
+                )
+            })
+            .collect_vec();
+        let all_openings = izip!(
+            &all_evals,
+            [vec![zeta, zeta_next], vec![zeta], vec![zeta_next, zeta]]
+        )
+        .map(|(evals, points)| {
+            points
+                .into_iter()
+                .map(|pt| (pt, evals.evaluate_at_point(pt)))
+                .collect_vec()
+        })
+        .collect_vec();
+
+        let mut alpha_offset = EF::ONE;
+        let mut expected = vec![EF::ZERO; 1 << domain.log_n];
+        for (evals, openings) in izip!(&all_evals, &all_openings) {
+            let rows = evals
+                .clone()
+                .into_natural_order()
+                .into_values()
+                .to_row_major_matrix();
+            for (pt, ps_at_pt) in openings {
+                for (ro, ps_at_x, x) in izip!(&mut expected, rows.rows(), domain.points()) {
+                    *ro += alpha_offset
+                        * deep_quotient_reduce_row(alpha, x, *pt, &ps_at_x.collect_vec(), ps_at_pt);
+                }
+                alpha_offset *= alpha.exp_u64(2 * evals.values.width() as u64);
+            }
+        }
+
+        let openings = all_openings
+            .iter()
+            .map(|openings| {
+                openings
+                    .iter()
+                    .map(|(pt, values)| (*pt, values.as_slice()))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let mats = izip!(&all_evals, &openings)
+            .map(|(evals, openings)| OpenedMatrix { evals, openings })
+            .collect_vec();
+        assert_eq!(
+            cfft_permute_slice(&deep_quotient_reduce_batch(alpha, &mats)),
+            expected
+        );
+    }
+
     #[test]
     fn test_extract_lambda() {
         let mut rng = SmallRng::seed_from_u64(1);
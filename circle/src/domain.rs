@@ -1,15 +1,16 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use itertools::{Itertools, iterate};
+use itertools::{Itertools, iterate, izip};
 use p3_commit::{LagrangeSelectors, PolynomialSpace};
-use p3_field::ExtensionField;
 use p3_field::extension::ComplexExtendable;
+use p3_field::{ExtensionField, batch_multiplicative_inverse};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 use tracing::instrument;
 
+use crate::packed_point::packed_coset_points;
 use crate::point::Point;
 
 /// A twin-coset of the circle group on F. It has a power-of-two size and an arbitrary shift.
@@ -54,7 +55,7 @@ impl<F: ComplexExtendable> CircleDomain<F> {
             shift: Point::generator(log_n + 1),
         }
     }
-    fn is_standard(&self) -> bool {
+    pub(crate) fn is_standard(&self) -> bool {
         self.shift == Point::generator(self.log_n + 1)
     }
     pub(crate) fn subgroup_generator(&self) -> Point<F> {
@@ -71,6 +72,16 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     pub(crate) fn points(&self) -> impl Iterator<Item = Point<F>> {
         self.coset0().interleave(self.coset1())
     }
+    /// The same points as `points`, generating each twin coset a packed vector of points at a
+    /// time.
+    pub(crate) fn packed_points(&self) -> Vec<Point<F>> {
+        let g = self.subgroup_generator();
+        let half = 1 << (self.log_n - 1);
+        packed_coset_points(self.shift, g, half)
+            .into_iter()
+            .interleave(packed_coset_points(g - self.shift, g, half))
+            .collect()
+    }
     pub(crate) fn nth_point(&self, idx: usize) -> Point<F> {
         let (idx, lsb) = (idx >> 1, idx & 1);
         if lsb == 0 {
@@ -113,6 +124,11 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
         }
     }
 
+    /// Twin cosets are not multiplicative cosets: their points are generated by the circle group.
+    fn multiplicative_generator(&self) -> Option<F> {
+        None
+    }
+
     fn create_disjoint_domain(&self, min_size: usize) -> Self {
         // Right now we simply guarantee the domain is disjoint by returning a
         // larger standard position coset, which is fine because we always ask for a larger
@@ -132,8 +148,13 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     }
 
     /// Decompose a domain into disjoint twin-cosets.
+    ///
+    /// The `i`-th chunk is the twin-coset of size `n / num_chunks` starting at the `i`-th point of
+    /// `self`. Each half of a twin-coset `D = (s + H) ∪ (-s + H)` splits into cosets of the
+    /// subgroup `H' ⊂ H` of index `num_chunks`, and the chunk starting at `s + jh` pairs up
+    /// `s + jh + H'` with its image `-s - jh + H'` under the inverse map, so this works for any
+    /// shift, not only standard position domains.
     fn split_domains(&self, num_chunks: usize) -> Vec<Self> {
-        assert!(self.is_standard());
         let log_chunks = log2_strict_usize(num_chunks);
         assert!(log_chunks <= self.log_n);
         self.points()
@@ -145,6 +166,11 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
             .collect()
     }
 
+    /// Split evaluations over `self`, in natural order, into evaluations over each of the chunks
+    /// returned by `split_domains`, again in natural order.
+    ///
+    /// Walking the domain in natural order visits the chunks as
+    /// `0 1 .. num_chunks-1 num_chunks-1 .. 1 0 0 1 ..`, see `forward_backward_index`.
     fn split_evals(
         &self,
         num_chunks: usize,
@@ -168,6 +194,14 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
         self.vanishing_poly(Point::from_projective_line(point))
     }
 
+    fn lagrange_basis_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        index: usize,
+        point: Ext,
+    ) -> Ext {
+        self.s_p_normalized(self.nth_point(index), Point::from_projective_line(point))
+    }
+
     fn selectors_at_point<Ext: ExtensionField<Self::Val>>(
         &self,
         point: Ext,
@@ -197,19 +231,55 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     chunks=2: 0 1 1 0 0 1 1 0 0 1 1 0 0 1 1 0
     chunks=4: 0 1 2 3 3 2 1 0 0 1 2 3 3 2 1 0
     */
-    // wow, really slow!
-    // todo: batch inverses
+    /// Evaluate the selectors over every point of `coset`, which must be disjoint from `self`.
+    ///
+    /// This is the batched version of `selectors_at_point`. With `Z` the vanishing polynomial of
+    /// `self`, `s` its shift and `d_q = p - q` for each point `p` of the coset:
+    /// ```text
+    /// is_first_row  = Z(p) / ṽ_s(p)  = Z(p) * (d_s.x + 1) / d_s.y
+    /// is_last_row   = Z(p) / ṽ_-s(p) = Z(p) * (d_-s.x + 1) / d_-s.y
+    /// is_transition = 1 - is_last_row / s_-s(-s)
+    /// inv_vanishing = 1 / Z(p)
+    /// ```
+    /// Circle STARKs, Section 5.1, Lemma 11 and Remark 16 (pages 21-22 of the first revision PDF).
+    /// All denominators are inverted in batches.
     #[instrument(skip_all, fields(log_n = %coset.log_n))]
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>> {
-        let sels = coset
-            .points()
-            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap()))
+        let first = self.shift;
+        let last = -self.shift;
+        let shift_v_n = self.shift.v_n(self.log_n);
+        let last_normalizer_inv = last.s_p_at_p(self.log_n).inverse();
+
+        let pts = coset.packed_points();
+        let vanishing = pts
+            .iter()
+            .map(|p| p.v_n(self.log_n) - shift_v_n)
             .collect_vec();
+        let (first_diffs, last_diffs): (Vec<_>, Vec<_>) =
+            pts.iter().map(|&p| (p - first, p - last)).unzip();
+
+        let inv_vanishing = batch_multiplicative_inverse(&vanishing);
+        let first_y_invs =
+            batch_multiplicative_inverse(&first_diffs.iter().map(|d| d.y).collect_vec());
+        let last_y_invs =
+            batch_multiplicative_inverse(&last_diffs.iter().map(|d| d.y).collect_vec());
+
+        let is_first_row = izip!(&vanishing, &first_diffs, first_y_invs)
+            .map(|(&z, d, y_inv)| z * (d.x + F::ONE) * y_inv)
+            .collect_vec();
+        let is_last_row = izip!(&vanishing, &last_diffs, last_y_invs)
+            .map(|(&z, d, y_inv)| z * (d.x + F::ONE) * y_inv)
+            .collect_vec();
+        let is_transition = is_last_row
+            .iter()
+            .map(|&l| F::ONE - l * last_normalizer_inv)
+            .collect();
+
         LagrangeSelectors {
-            is_first_row: sels.iter().map(|s| s.is_first_row).collect(),
-            is_last_row: sels.iter().map(|s| s.is_last_row).collect(),
-            is_transition: sels.iter().map(|s| s.is_transition).collect(),
-            inv_vanishing: sels.iter().map(|s| s.inv_vanishing).collect(),
+            is_first_row,
+            is_last_row,
+            is_transition,
+            inv_vanishing,
         }
     }
 }
@@ -225,11 +295,11 @@ mod tests {
     use core::iter;
 
     use hashbrown::HashSet;
-    use itertools::izip;
-    use p3_field::{PrimeCharacteristicRing, batch_multiplicative_inverse};
+    use p3_field::PrimeCharacteristicRing;
+    use p3_field::extension::BinomialExtensionField;
     use p3_mersenne_31::Mersenne31;
-    use rand::SeedableRng;
     use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
 
     use super::*;
     use crate::CircleEvaluations;
@@ -259,6 +329,8 @@ mod tests {
         }
         assert_eq!(d.next_point(p1).unwrap(), p0);
 
+        assert_eq!(d.packed_points(), d.points().collect_vec());
+
         // .points() is the same as first_point -> next_point
         let mut uni_point = d.first_point();
         for p in d.points() {
@@ -327,6 +399,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn split_nonstandard_twin_coset() {
+        type F = Mersenne31;
+        let log_n = 6;
+        let n = 1 << log_n;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+        let d = CircleDomain::<F>::new(log_n, shift);
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, n, 3);
+        let orig: HashSet<(Point<F>, Vec<F>)> = d
+            .points()
+            .zip(evals.rows().map(|r| r.collect_vec()))
+            .collect();
+        for num_chunks in [1, 2, 4, 8] {
+            let sds = d.split_domains(num_chunks);
+            let ses = d.split_evals(num_chunks, evals.clone());
+            let mut combined = HashSet::new();
+            for (sd, se) in izip!(sds, ses) {
+                assert_is_twin_coset(sd);
+                assert_eq!(sd.size() * num_chunks, d.size());
+                combined.extend(sd.points().zip(se.rows().map(|r| r.collect_vec())));
+            }
+            assert_eq!(orig, combined);
+        }
+    }
+
     #[test]
     fn selectors() {
         type F = Mersenne31;
@@ -357,7 +455,8 @@ mod tests {
             let (lo, hi) = coeffs.split_rows(n);
             assert_eq!(hi.values, vec![F::ZERO; n]);
             CircleEvaluations::evaluate(d, lo.to_row_major_matrix())
-                .to_natural_order()
+                .into_natural_order()
+                .into_values()
                 .to_row_major_matrix()
                 .values
         };
@@ -395,6 +494,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lagrange_basis_interpolates() {
+        type F = Mersenne31;
+        type EF = BinomialExtensionField<F, 3>;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let d = CircleDomain::<F>::standard(5);
+        let evals: Vec<F> = (0..d.size()).map(|_| rng.random()).collect();
+        let zeta: EF = rng.random();
+
+        let expected =
+            CircleEvaluations::from_natural_order(d, RowMajorMatrix::new_col(evals.clone()))
+                .evaluate_at_point(Point::from_projective_line(zeta));
+        let combined = evals
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| d.lagrange_basis_at_point(i, zeta) * e)
+            .sum::<EF>();
+        assert_eq!(combined, expected[0]);
+        assert_eq!(
+            (0..d.size())
+                .map(|i| d.lagrange_basis_at_point(i, zeta))
+                .sum::<EF>(),
+            EF::ONE
+        );
+    }
+
     #[test]
     fn test_circle_domain() {
         do_test_circle_domain(4, 8);
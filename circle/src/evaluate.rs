@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_field::extension::ComplexExtendable;
+use p3_field::{ExtensionField, dot_product};
+
+use crate::domain::CircleDomain;
+use crate::point::{Point, compute_lagrange_den_batched};
+
+/// Compute the barycentric weights of `domain` at `point`.
+///
+/// For any polynomial `f` in the FFT space of `domain`, `f(point) = sum_i w_i f(x_i)`, where
+/// `x_i` are the points of `domain` in natural order and `w_i` are the returned weights.
+/// Circle STARKs, Section 5.1, Lemma 11 (page 21 of the first revision PDF).
+///
+/// `point` must lie outside of `domain`, as is the case for out-of-domain DEEP samples.
+pub fn barycentric_weights<F: ComplexExtendable, EF: ExtensionField<F>>(
+    domain: CircleDomain<F>,
+    point: Point<EF>,
+) -> Vec<EF> {
+    let lagrange_num = domain.vanishing_poly(point);
+    let points = domain.points().collect_vec();
+    compute_lagrange_den_batched(&points, point, domain.log_n)
+        .into_iter()
+        .map(|den| den * lagrange_num)
+        .collect()
+}
+
+/// Evaluate the polynomial whose evaluations over `domain`, in natural order, are `evals` at an
+/// arbitrary point outside of `domain`, without first converting to coefficients.
+pub fn barycentric_evaluate<F: ComplexExtendable, EF: ExtensionField<F>>(
+    domain: CircleDomain<F>,
+    evals: &[F],
+    point: Point<EF>,
+) -> EF {
+    assert_eq!(evals.len(), 1 << domain.log_n);
+    dot_product(
+        barycentric_weights(domain, point).into_iter(),
+        evals.iter().copied(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::extension::BinomialExtensionField;
+    use p3_matrix::Matrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_mersenne_31::Mersenne31;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{CircleEvaluations, circle_basis};
+
+    type F = Mersenne31;
+    type EF = BinomialExtensionField<F, 3>;
+
+    #[test]
+    fn barycentric_matches_coefficients() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        for log_n in 2..8 {
+            let domain = CircleDomain::<F>::standard(log_n);
+            let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, 1);
+            let coeffs = CircleEvaluations::from_natural_order(domain, evals.clone()).interpolate();
+            let zeta = Point::<EF>::from_projective_line(rng.random());
+            assert_eq!(
+                barycentric_evaluate(domain, &evals.values, zeta),
+                coeffs.columnwise_dot_product(&circle_basis(zeta, log_n))[0],
+            );
+        }
+    }
+
+    #[test]
+    fn weights_match_evaluate_at_point() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let domain = CircleDomain::<F>::standard(5);
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 4);
+        let zeta = Point::<EF>::from_projective_line(rng.random());
+        let weights = barycentric_weights(domain, zeta);
+        assert_eq!(
+            evals.columnwise_dot_product(&weights),
+            CircleEvaluations::from_natural_order(domain, evals).evaluate_at_point(zeta),
+        );
+    }
+}
@@ -13,11 +13,36 @@ use p3_util::{log2_strict_usize, reverse_bits_len};
 use crate::domain::CircleDomain;
 use crate::{CircleInputProof, InputError};
 
-pub(crate) struct CircleFriGenericConfig<F, InputProof, InputError>(
+/// The circle-specific folding of FRI, as a `FriGenericConfig` for the circle FRI prover and
+/// verifier.
+///
+/// The PCS folds each input codeword in y once, with [`fold_y`], before handing it to FRI. Every
+/// round of FRI then folds in x, with [`fold_x`], since after the first fold the codeword is a
+/// function of x alone.
+///
+/// The circle PCS does not use the prover and verifier in `p3_fri`, which interpolate and
+/// evaluate the final polynomial over a two-adic multiplicative subgroup. After folding, a circle
+/// codeword lies over the x coordinates of a twin coset instead, so circle FRI folds down to a
+/// constant.
+#[derive(Debug)]
+pub struct CircleFriGenericConfig<F, InputProof, InputError>(
     pub(crate) PhantomData<(F, InputProof, InputError)>,
 );
 
-pub(crate) type CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig<
+impl<F, InputProof, InputError> CircleFriGenericConfig<F, InputProof, InputError> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F, InputProof, InputError> Default for CircleFriGenericConfig<F, InputProof, InputError> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The FRI config used by the circle PCS.
+pub type CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig<
     Val,
     CircleInputProof<Val, Challenge, InputMmcs, FriMmcs>,
     InputError<<InputMmcs as Mmcs<Val>>::Error, <FriMmcs as Mmcs<Challenge>>::Error>,
@@ -65,7 +90,11 @@ fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
         .collect_vec()
 }
 
-pub(crate) fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
+/// Fold a codeword over a standard position circle domain in y, halving its length.
+///
+/// `evals` holds the codeword in cfft order as a matrix of width 2, so that each row pairs a point
+/// with its reflection over the x axis.
+pub fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
     evals: impl Matrix<EF>,
 ) -> Vec<EF> {
@@ -78,7 +107,9 @@ pub(crate) fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
     )
 }
 
-pub(crate) fn fold_y_row<F: ComplexExtendable, EF: ExtensionField<F>>(
+/// Same as [`fold_y`], but for the single row at `index` of a codeword whose folded height is
+/// `2^log_folded_height`.
+pub fn fold_y_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     index: usize,
     log_folded_height: usize,
     beta: EF,
@@ -94,7 +125,11 @@ pub(crate) fn fold_y_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     (sum + beta * diff).halve()
 }
 
-pub(crate) fn fold_x<F: ComplexExtendable, EF: ExtensionField<F>>(
+/// Fold a codeword which is a function of x alone, halving its length.
+///
+/// `evals` holds the codeword in cfft order as a matrix of width 2, so that each row pairs an x
+/// coordinate with its negation.
+pub fn fold_x<F: ComplexExtendable, EF: ExtensionField<F>>(
     beta: EF,
     evals: impl Matrix<EF>,
 ) -> Vec<EF> {
@@ -108,7 +143,9 @@ pub(crate) fn fold_x<F: ComplexExtendable, EF: ExtensionField<F>>(
     )
 }
 
-pub(crate) fn fold_x_row<F: ComplexExtendable, EF: ExtensionField<F>>(
+/// Same as [`fold_x`], but for the single row at `index` of a codeword whose folded height is
+/// `2^log_folded_height`.
+pub fn fold_x_row<F: ComplexExtendable, EF: ExtensionField<F>>(
     index: usize,
     log_folded_height: usize,
     beta: EF,
@@ -189,4 +226,22 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn config_folds_match() {
+        let config = CircleFriGenericConfig::<F, (), ()>::new();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let log_folded_height = 4;
+        let m = RowMajorMatrix::<EF>::rand(&mut rng, 1 << log_folded_height, 2);
+        let beta: EF = rng.random();
+
+        let mat_folded = config.fold_matrix(beta, m.as_view());
+        assert_eq!(mat_folded, fold_x::<F, EF>(beta, m.as_view()));
+        for (i, &folded) in mat_folded.iter().enumerate() {
+            assert_eq!(
+                config.fold_row(i, log_folded_height, beta, m.row(i)),
+                folded
+            );
+        }
+    }
 }
@@ -0,0 +1,382 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use itertools::Itertools;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_field::ExtensionField;
+use p3_field::extension::ComplexExtendable;
+use p3_fri::FriConfig;
+use p3_fri::verifier::FriError;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::horizontally_truncated::HorizontallyTruncated;
+use p3_maybe_rayon::prelude::*;
+use p3_util::zip_eq::zip_eq;
+use rand::Rng;
+use rand::distr::{Distribution, StandardUniform};
+use tracing::instrument;
+
+use crate::{CircleDomain, CircleEvaluations, CirclePcs, NaturalOrderEvaluations};
+
+/// A hiding variant of [`CirclePcs`]. The input MMCS and the FRI MMCS must also be hiding; this is
+/// not enforced at compile time so it's the user's responsibility to configure.
+///
+/// Each matrix of evaluations of polynomials `f` over a domain `H` of size `n` is committed as
+/// `f + v_H * r` for a random `r` in the FFT space of size `n`, where `v_H` is the vanishing
+/// polynomial of `H`. These agree with `f` on `H`, so any constraints checked over `H` still hold,
+/// but their values outside of `H` reveal nothing about `f`. Note that the committed polynomials
+/// lie in the FFT space of size `2n`, so callers must account for the doubled degree, e.g. when
+/// sizing a quotient domain.
+///
+/// Random codewords are also appended to every matrix, to hide the batched codeword given to FRI.
+#[derive(Debug)]
+pub struct HidingCirclePcs<Val: ComplexExtendable, InputMmcs, FriMmcs, R> {
+    inner: CirclePcs<Val, InputMmcs, FriMmcs>,
+    num_random_codewords: usize,
+    rng: RefCell<R>,
+}
+
+impl<Val: ComplexExtendable, InputMmcs, FriMmcs, R> HidingCirclePcs<Val, InputMmcs, FriMmcs, R> {
+    pub fn new(
+        mmcs: InputMmcs,
+        fri_config: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        rng: R,
+    ) -> Self {
+        Self {
+            inner: CirclePcs::new(mmcs, fri_config),
+            num_random_codewords,
+            rng: rng.into(),
+        }
+    }
+}
+
+impl<Val, InputMmcs, FriMmcs, Challenge, Challenger, R> Pcs<Challenge, Challenger>
+    for HidingCirclePcs<Val, InputMmcs, FriMmcs, R>
+where
+    Val: ComplexExtendable,
+    StandardUniform: Distribution<Val>,
+    Challenge: ExtensionField<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<FriMmcs::Commitment>,
+    R: Rng + Send + Sync,
+{
+    type Domain = CircleDomain<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type EvaluationsOnDomain<'a> = HorizontallyTruncated<
+        Val,
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::EvaluationsOnDomain<'a>,
+    >;
+    /// The first item contains the openings of the random codewords added by this wrapper.
+    /// The second item is the usual circle PCS proof.
+    type Proof = (
+        OpenedValues<Challenge>,
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::Proof,
+    );
+    type Error = <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::Error;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+            &self.inner,
+            degree,
+        )
+    }
+
+    fn num_queries(&self) -> Option<usize> {
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::num_queries(&self.inner)
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let blinded_evaluations = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                let rng = &mut *self.rng.borrow_mut();
+                let (blinded_domain, blinded) = blind(domain, evals, rng);
+                (
+                    blinded_domain,
+                    add_random_cols(blinded, self.num_random_codewords, rng),
+                )
+            })
+            .collect();
+        <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::commit(
+            &self.inner,
+            blinded_evaluations,
+        )
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> Self::EvaluationsOnDomain<'a> {
+        let inner_evals = <CirclePcs<Val, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+            &self.inner,
+            prover_data,
+            idx,
+            domain,
+        );
+        let inner_width = inner_evals.width();
+        // Truncate off the columns representing random codewords we added in `commit` above.
+        HorizontallyTruncated::new(inner_evals, inner_width - self.num_random_codewords)
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let (mut inner_opened_values, inner_proof) = self.inner.open(rounds, challenger);
+
+        // inner_opened_values includes opened values for the random codewords. Those should be
+        // hidden from our caller, so we split them off and store them in the proof.
+        let opened_values_rand = inner_opened_values
+            .iter_mut()
+            .map(|opened_values_for_round| {
+                opened_values_for_round
+                    .iter_mut()
+                    .map(|opened_values_for_mat| {
+                        opened_values_for_mat
+                            .iter_mut()
+                            .map(|opened_values_for_point| {
+                                let split =
+                                    opened_values_for_point.len() - self.num_random_codewords;
+                                opened_values_for_point.drain(split..).collect()
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (inner_opened_values, (opened_values_rand, inner_proof))
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        mut rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let (opened_values_for_rand_cws, inner_proof) = proof;
+        // Merge `opened_values_for_rand_cws` back into the opened values in `rounds`, undoing the
+        // split done in `open`, and replace each domain by the domain its blinded polynomials were
+        // committed over, to get the claims the inner PCS has to check.
+        for (round, rand_round) in zip_eq(
+            rounds.iter_mut(),
+            opened_values_for_rand_cws,
+            FriError::InvalidProofShape,
+        )? {
+            for (mat, rand_mat) in
+                zip_eq(round.1.iter_mut(), rand_round, FriError::InvalidProofShape)?
+            {
+                mat.0 = blinded_domain(mat.0);
+                for (point, rand_point) in
+                    zip_eq(mat.1.iter_mut(), rand_mat, FriError::InvalidProofShape)?
+                {
+                    point.1.extend(rand_point);
+                }
+            }
+        }
+        self.inner.verify(rounds, inner_proof, challenger)
+    }
+}
+
+/// The domain over which the blinded polynomials of a matrix over `domain` are committed. It is
+/// twice as large, as blinding doubles the degree.
+fn blinded_domain<F: ComplexExtendable>(domain: CircleDomain<F>) -> CircleDomain<F> {
+    CircleDomain::standard(domain.log_n + 1)
+}
+
+/// Blind the polynomials whose evaluations over `domain` are the columns of `evals`, returning the
+/// evaluations of `f + v_H * r` over `blinded_domain(domain)`, where `r` is random.
+#[instrument(level = "debug", skip_all, fields(dims = %evals.dimensions()))]
+fn blind<F, R>(
+    domain: CircleDomain<F>,
+    evals: RowMajorMatrix<F>,
+    rng: &mut R,
+) -> (CircleDomain<F>, RowMajorMatrix<F>)
+where
+    F: ComplexExtendable,
+    R: Rng,
+    StandardUniform: Distribution<F>,
+{
+    let target = blinded_domain(domain);
+    let width = evals.width();
+    let mut blinded = NaturalOrderEvaluations::new(domain, evals)
+        .into_cfft_order()
+        .extrapolate(target)
+        .into_natural_order()
+        .into_values()
+        .to_row_major_matrix();
+
+    // Evaluations over `target` of random polynomials in the FFT space of size `|domain|`.
+    let r_coeffs = RowMajorMatrix::rand(rng, domain.size(), width);
+    let r_evals = CircleEvaluations::evaluate(target, r_coeffs)
+        .into_natural_order()
+        .into_values()
+        .to_row_major_matrix();
+    let v_h = target
+        .points()
+        .map(|pt| domain.vanishing_poly(pt))
+        .collect_vec();
+
+    blinded
+        .par_rows_mut()
+        .zip(r_evals.par_row_slices())
+        .zip(v_h.par_iter())
+        .for_each(|((row, r_row), &v)| {
+            for (x, &r) in row.iter_mut().zip(r_row) {
+                *x += v * r;
+            }
+        });
+    (target, blinded)
+}
+
+#[instrument(level = "debug", skip_all)]
+fn add_random_cols<Val, R>(
+    mat: RowMajorMatrix<Val>,
+    num_random_codewords: usize,
+    rng: &mut R,
+) -> RowMajorMatrix<Val>
+where
+    Val: ComplexExtendable,
+    R: Rng,
+    StandardUniform: Distribution<Val>,
+{
+    let old_w = mat.width();
+    let new_w = old_w + num_random_codewords;
+    let h = mat.height();
+
+    let new_values = Val::zero_vec(new_w * h);
+    let mut result = RowMajorMatrix::new(new_values, new_w);
+    // As in the two-adic hiding PCS, this stays serial to keep the RNG generic.
+    result
+        .rows_mut()
+        .zip(mat.row_slices())
+        .for_each(|(new_row, old_row)| {
+            new_row[..old_w].copy_from_slice(old_row);
+            new_row[old_w..].iter_mut().for_each(|v| *v = rng.random());
+        });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_commit::ExtensionMmcs;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::create_test_fri_config;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::Point;
+
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+    #[test]
+    fn blinding_preserves_values_on_domain() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let domain = CircleDomain::<Val>::standard(5);
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+        let (target, blinded) = blind(domain, evals.clone(), &mut rng);
+        assert_eq!(target.size(), 2 * domain.size());
+
+        let blinded = CircleEvaluations::from_natural_order(target, blinded);
+        for (pt, row) in domain.points().zip(evals.rows()) {
+            assert!(row.eq(blinded.evaluate_at_point(pt)));
+        }
+
+        // Away from the domain, the blinded polynomials differ from the originals.
+        let zeta = Point::<Challenge>::from_projective_line(rng.random());
+        assert_ne!(
+            blinded.evaluate_at_point(zeta),
+            CircleEvaluations::from_natural_order(domain, evals).evaluate_at_point(zeta)
+        );
+    }
+
+    #[test]
+    fn hiding_circle_pcs() {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        type ByteHash = Keccak256Hash;
+        type FieldHash = SerializingHasher32<ByteHash>;
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+
+        type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+        let compress = MyCompress::new(byte_hash);
+
+        type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+        let val_mmcs = ValMmcs::new(field_hash, compress);
+
+        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+
+        let fri_config = create_test_fri_config(challenge_mmcs, 0);
+
+        type MyPcs = HidingCirclePcs<Val, ValMmcs, ChallengeMmcs, SmallRng>;
+        let pcs = MyPcs::new(val_mmcs, fri_config, 2, SmallRng::seed_from_u64(1));
+
+        let log_n = 6;
+        let d = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+        let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 3);
+        let (comm, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals.clone())]);
+
+        let zeta: Challenge = rng.random();
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+        assert_eq!(values[0][0][0].len(), 3);
+        assert_ne!(
+            values[0][0][0],
+            CircleEvaluations::from_natural_order(d, evals)
+                .evaluate_at_point(Point::from_projective_line(zeta))
+        );
+
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        pcs.verify(
+            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+            &proof,
+            &mut chal,
+        )
+        .expect("verify err");
+    }
+}
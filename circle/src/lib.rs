@@ -8,16 +8,28 @@ extern crate alloc;
 mod cfft;
 mod deep_quotient;
 mod domain;
+mod evaluate;
 mod folding;
+mod hiding_pcs;
 mod ordering;
+mod packed_point;
 mod pcs;
 mod point;
 mod proof;
 mod prover;
+mod traits;
+mod twiddles;
 mod verifier;
 
 pub use cfft::*;
 pub use domain::*;
+pub use evaluate::*;
+pub use folding::*;
+pub use hiding_pcs::*;
 pub use ordering::*;
+pub use packed_point::*;
 pub use pcs::*;
+pub use point::*;
 pub use proof::*;
+pub use traits::*;
+pub use twiddles::*;
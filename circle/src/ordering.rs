@@ -1,4 +1,7 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::RefCell;
 
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
@@ -49,6 +52,64 @@ pub(crate) fn cfft_permute_slice_chunked_in_place<T>(xs: &mut [T], chunk_size: u
     }
 }
 
+/// Permute the rows of `mat` from natural order into cfft order, in place.
+pub fn cfft_permute_matrix<T>(mat: &mut RowMajorMatrix<T>) {
+    let width = mat.width;
+    cfft_permute_slice_chunked_in_place(&mut mat.values, width);
+}
+
+/// Permute the rows of `mat` from cfft order back into natural order, in place.
+///
+/// The cfft permutation is an involution, so this is the same operation as `cfft_permute_matrix`.
+pub fn natural_order_matrix<T>(mat: &mut RowMajorMatrix<T>) {
+    cfft_permute_matrix(mat);
+}
+
+/// Cached tables of `cfft_permute_index` for each log height.
+///
+/// Useful when permuting many matrices of the same heights, e.g. every round of a commitment,
+/// since the index computations are then only done once per height.
+#[derive(Default, Clone, Debug)]
+pub struct CfftPermutationCache {
+    /// Memoized permutation tables for each log height.
+    tables: RefCell<BTreeMap<usize, Arc<Vec<usize>>>>,
+}
+
+impl CfftPermutationCache {
+    /// The table mapping each row index to its image under the cfft permutation, for height
+    /// `2^log_n`. Computed on first use.
+    pub fn table(&self, log_n: usize) -> Arc<Vec<usize>> {
+        self.tables
+            .borrow_mut()
+            .entry(log_n)
+            .or_insert_with(|| {
+                Arc::new(
+                    (0..1 << log_n)
+                        .map(|i| cfft_permute_index(i, log_n))
+                        .collect(),
+                )
+            })
+            .clone()
+    }
+
+    /// Same as [`cfft_permute_matrix`], using the cached table for the height of `mat`.
+    pub fn cfft_permute_matrix<T: Clone + Send + Sync>(&self, mat: &mut RowMajorMatrix<T>) {
+        let width = mat.width;
+        let table = self.table(log2_strict_usize(mat.height()));
+        for (i, &j) in table.iter().enumerate() {
+            if i < j {
+                let (lo, hi) = mat.values.split_at_mut(j * width);
+                lo[i * width..(i + 1) * width].swap_with_slice(&mut hi[..width]);
+            }
+        }
+    }
+
+    /// Same as [`natural_order_matrix`], using the cached table for the height of `mat`.
+    pub fn natural_order_matrix<T: Clone + Send + Sync>(&self, mat: &mut RowMajorMatrix<T>) {
+        self.cfft_permute_matrix(mat);
+    }
+}
+
 pub type CfftView<M> = RowIndexMappedView<CfftPerm, M>;
 
 #[derive(Copy, Clone)]
@@ -115,4 +176,30 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn permute_matrix() {
+        let cache = CfftPermutationCache::default();
+        for (log_n, width) in [(1, 3), (3, 1), (5, 4), (6, 7)] {
+            let n = 1 << log_n;
+            let mat = RowMajorMatrix::new((0..n * width).collect_vec(), width);
+            let expected = mat.clone().cfft_perm_rows().to_row_major_matrix();
+
+            let mut permuted = mat.clone();
+            cfft_permute_matrix(&mut permuted);
+            assert_eq!(permuted, expected);
+            natural_order_matrix(&mut permuted);
+            assert_eq!(permuted, mat);
+
+            let mut cached = mat.clone();
+            cache.cfft_permute_matrix(&mut cached);
+            assert_eq!(cached, expected);
+            cache.natural_order_matrix(&mut cached);
+            assert_eq!(cached, mat);
+            assert_eq!(
+                *cache.table(log_n),
+                (0..n).map(|i| cfft_permute_index(i, log_n)).collect_vec()
+            );
+        }
+    }
 }
@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+
+use itertools::{Itertools, iterate};
+use p3_field::{ExtensionField, Field, PackedFieldExtension};
+
+use crate::point::Point;
+
+/// `F::Packing::WIDTH` points of the circle over `F`, one per lane, so that circle group
+/// operations on many points run on the packed field's vector arithmetic.
+pub struct PackedPoint<F: Field> {
+    pub x: F::Packing,
+    pub y: F::Packing,
+}
+
+impl<F: Field> Clone for PackedPoint<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: Field> Copy for PackedPoint<F> {}
+
+impl<F: Field> PackedPoint<F> {
+    /// Pack the points of `points`, whose length must be `F::Packing::WIDTH`.
+    #[inline]
+    pub fn from_slice(points: &[Point<F>]) -> Self {
+        assert_eq!(points.len(), F::Packing::WIDTH);
+        Self {
+            x: F::Packing::from_fn(|i| points[i].x),
+            y: F::Packing::from_fn(|i| points[i].y),
+        }
+    }
+
+    /// The point in each lane.
+    pub fn unpack(self) -> impl Iterator<Item = Point<F>> {
+        (0..F::Packing::WIDTH).map(move |i| Point::new(self.x.as_slice()[i], self.y.as_slice()[i]))
+    }
+
+    /// Add `rhs` to the point in every lane, i.e. `(x_1 x_2 - y_1 y_2, x_1 y_2 + y_1 x_2)`.
+    #[inline]
+    pub fn add_point(self, rhs: Point<F>) -> Self {
+        Self {
+            x: self.x * rhs.x - self.y * rhs.y,
+            y: self.x * rhs.y + self.y * rhs.x,
+        }
+    }
+
+    /// The squaring map `(2x^2 - 1, 2xy)` in every lane, as in [`Point::double`].
+    #[inline]
+    pub fn double(self) -> Self {
+        Self {
+            x: self.x.square().double() - F::Packing::ONE,
+            y: self.x.double() * self.y,
+        }
+    }
+
+    /// [`Point::v_p`] at `at` of the point in every lane, as packed real and imaginary parts.
+    #[inline]
+    pub fn v_p<EF: ExtensionField<F>>(
+        self,
+        at: Point<EF>,
+    ) -> (EF::ExtensionPacking, EF::ExtensionPacking) {
+        let (at_x, at_y): (EF::ExtensionPacking, EF::ExtensionPacking) = (at.x.into(), at.y.into());
+        // The point `-at + self`, with `-at = (at_x, -at_y)`.
+        let diff_x = at_x * self.x + at_y * self.y;
+        let diff_y = at_x * self.y - at_y * self.x;
+        (EF::ExtensionPacking::ONE - diff_x, -diff_y)
+    }
+}
+
+/// The points `start + i * step` for `i < len`, stepping a packed vector of points at a time.
+pub(crate) fn packed_coset_points<F: Field>(
+    start: Point<F>,
+    step: Point<F>,
+    len: usize,
+) -> Vec<Point<F>> {
+    let width = F::Packing::WIDTH;
+    if width == 1 || !len.is_multiple_of(width) {
+        return iterate(start, |&p| p + step).take(len).collect();
+    }
+    let first = PackedPoint::from_slice(&iterate(start, |&p| p + step).take(width).collect_vec());
+    let packed_step = step * width;
+    iterate(first, |&p| p.add_point(packed_step))
+        .take(len / width)
+        .flat_map(PackedPoint::unpack)
+        .collect()
+}
+
+/// The real and imaginary parts of [`Point::v_p`] at `at` for each of `points`.
+pub(crate) fn packed_v_p<F: Field, EF: ExtensionField<F>>(
+    points: &[Point<F>],
+    at: Point<EF>,
+) -> (Vec<EF>, Vec<EF>) {
+    let (packed, suffix) = points.split_at(points.len() - points.len() % F::Packing::WIDTH);
+    let (packed_re, packed_im): (Vec<_>, Vec<_>) = packed
+        .chunks_exact(F::Packing::WIDTH)
+        .map(|chunk| PackedPoint::from_slice(chunk).v_p(at))
+        .unzip();
+    let (suffix_re, suffix_im): (Vec<_>, Vec<_>) = suffix.iter().map(|&p| p.v_p(at)).unzip();
+    (
+        EF::ExtensionPacking::to_ext_iter(packed_re)
+            .chain(suffix_re)
+            .collect(),
+        EF::ExtensionPacking::to_ext_iter(packed_im)
+            .chain(suffix_im)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::extension::BinomialExtensionField;
+    use p3_mersenne_31::Mersenne31;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type F = Mersenne31;
+    type EF = BinomialExtensionField<F, 3>;
+
+    #[test]
+    fn matches_scalar() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let points = (0..F::Packing::WIDTH)
+            .map(|_| Point::generator(20) * rng.random::<u16>() as usize)
+            .collect_vec();
+        let packed = PackedPoint::from_slice(&points);
+        let rhs = Point::generator(10);
+        let at = Point::<EF>::from_projective_line(rng.random());
+
+        assert_eq!(packed.unpack().collect_vec(), points);
+        assert!(
+            packed
+                .add_point(rhs)
+                .unpack()
+                .eq(points.iter().map(|&p| p + rhs))
+        );
+        assert!(
+            packed
+                .double()
+                .unpack()
+                .eq(points.iter().map(|p| p.double()))
+        );
+
+        let (re, im): (Vec<EF>, Vec<EF>) = points.iter().map(|p| p.v_p(at)).unzip();
+        assert_eq!(packed_v_p(&points, at), (re, im));
+    }
+
+    #[test]
+    fn coset_points_match_iterated() {
+        let (start, step) = (Point::<F>::generator(12), Point::generator(8));
+        for len in [1, 2, 16, 64] {
+            assert_eq!(
+                packed_coset_points(start, step, len),
+                iterate(start, |&p| p + step).take(len).collect_vec()
+            );
+        }
+    }
+}
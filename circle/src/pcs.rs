@@ -14,19 +14,23 @@ use p3_fri::verifier::FriError;
 use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
 use p3_matrix::row_index_mapped::RowIndexMappedView;
 use p3_matrix::{Dimensions, Matrix};
-use p3_maybe_rayon::prelude::*;
 use p3_util::log2_strict_usize;
 use p3_util::zip_eq::zip_eq;
 use serde::{Deserialize, Serialize};
 use tracing::info_span;
 
-use crate::deep_quotient::{deep_quotient_reduce_row, extract_lambda};
+use crate::deep_quotient::{
+    OpenedMatrix, deep_quotient_reduce_batch, deep_quotient_reduce_row, extract_lambda,
+};
 use crate::domain::CircleDomain;
 use crate::folding::{CircleFriConfig, CircleFriGenericConfig, fold_y, fold_y_row};
 use crate::point::Point;
 use crate::prover::prove;
 use crate::verifier::verify;
-use crate::{CfftPerm, CfftPermutable, CircleEvaluations, CircleFriProof, cfft_permute_index};
+use crate::{
+    CfftPerm, CfftPermutable, CircleEvaluations, CircleFriProof, NaturalOrderEvaluations,
+    cfft_permute_index,
+};
 
 #[derive(Debug)]
 pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
@@ -36,7 +40,12 @@ pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
 }
 
 impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// # Panics
+    ///
+    /// Panics if `fri_config` asks for early stopping, a folding schedule or DEEP rounds, none of
+    /// which circle FRI implements.
     pub const fn new(mmcs: InputMmcs, fri_config: FriConfig<FriMmcs>) -> Self {
+        assert_supported(&fri_config);
         Self {
             mmcs,
             fri_config,
@@ -45,6 +54,25 @@ impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
     }
 }
 
+/// Check that `fri_config` only uses the options circle FRI implements. Circle FRI folds by two in
+/// every round, all the way to a constant, without DEEP rounds.
+///
+/// The fields of `CirclePcs` are public, so this is checked again when opening and verifying.
+const fn assert_supported<FriMmcs>(fri_config: &FriConfig<FriMmcs>) {
+    assert!(
+        fri_config.log_max_final_poly_len <= fri_config.log_final_poly_len,
+        "CirclePcs does not support stopping FRI early"
+    );
+    assert!(
+        fri_config.log_folding_arities.is_empty(),
+        "CirclePcs does not support FRI folding schedules"
+    );
+    assert!(
+        !fri_config.deep_sampling,
+        "CirclePcs does not support DEEP FRI rounds"
+    );
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
@@ -65,7 +93,7 @@ pub struct CircleInputProof<
     first_layer_proof: FriMmcs::Proof,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum InputError<InputMmcsError, FriMmcsError> {
     InputMmcsError(InputMmcsError),
     FirstLayerMmcsError(FriMmcsError),
@@ -114,6 +142,10 @@ where
         CircleDomain::standard(log2_strict_usize(degree))
     }
 
+    fn num_queries(&self) -> Option<usize> {
+        Some(self.fri_config.num_queries)
+    }
+
     fn commit(
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
@@ -126,7 +158,8 @@ where
                     "CirclePcs cannot commit to a matrix with fewer than 4 rows.",
                     // (because we bivariate fold one bit, and fri needs one more bit)
                 );
-                CircleEvaluations::from_natural_order(domain, evals)
+                NaturalOrderEvaluations::new(domain, evals)
+                    .into_cfft_order()
                     .extrapolate(CircleDomain::standard(
                         domain.log_n + self.fri_config.log_blowup,
                     ))
@@ -169,6 +202,8 @@ where
         )>,
         challenger: &mut Challenger,
     ) -> (OpenedValues<Challenge>, Self::Proof) {
+        assert_supported(&self.fri_config);
+
         // Open matrices at points
         let values: OpenedValues<Challenge> = rounds
             .iter()
@@ -216,51 +251,49 @@ where
         reusing the α^0, α^1, etc., then at the end of each column batch we multiply by the α^n.
         (Due to circle stark specifics, we need 2 powers of α for each column, so actually α^(2n)).
         We store this α^(2n), the running reducing factor per log_height, and call it the "alpha offset".
+        See `deep_quotient_reduce_batch`, which does this for all matrices of a given height at once.
         */
 
-        // log_height -> (alpha offset, reduced openings column)
-        let mut reduced_openings: BTreeMap<usize, (Challenge, Vec<Challenge>)> = BTreeMap::new();
-
-        rounds
+        // Every committed matrix, along with the points it is opened at and its values there.
+        let opened_mats = rounds
             .iter()
             .zip(values.iter())
-            .for_each(|((data, points_for_mats), values)| {
-                let mats = self.mmcs.get_matrices(data);
-                izip!(mats, points_for_mats, values).for_each(|(mat, points_for_mat, values)| {
-                    let log_height = log2_strict_usize(mat.height());
-                    // It was committed in cfft order.
-                    let evals = CircleEvaluations::from_cfft_order(
-                        CircleDomain::standard(log_height),
-                        mat.as_view(),
-                    );
+            .flat_map(|((data, points_for_mats), values)| {
+                izip!(self.mmcs.get_matrices(data), points_for_mats, values).map(
+                    |(mat, points_for_mat, values)| {
+                        let log_height = log2_strict_usize(mat.height());
+                        // It was committed in cfft order.
+                        let evals = CircleEvaluations::from_cfft_order(
+                            CircleDomain::standard(log_height),
+                            mat.as_view(),
+                        );
+                        let openings = izip!(points_for_mat, values)
+                            .map(|(&zeta, ps_at_zeta)| {
+                                (Point::from_projective_line(zeta), ps_at_zeta.as_slice())
+                            })
+                            .collect_vec();
+                        (evals, openings)
+                    },
+                )
+            })
+            .collect_vec();
 
-                    let (alpha_offset, reduced_opening_for_log_height) =
-                        reduced_openings.entry(log_height).or_insert_with(|| {
-                            (Challenge::ONE, vec![Challenge::ZERO; 1 << log_height])
-                        });
+        // log_height -> the opened matrices of that height, in the order they were committed
+        let mut mats_by_log_height: BTreeMap<usize, Vec<OpenedMatrix<'_, _, _, _>>> =
+            BTreeMap::new();
+        for (evals, openings) in &opened_mats {
+            mats_by_log_height
+                .entry(evals.domain.log_n)
+                .or_default()
+                .push(OpenedMatrix { evals, openings });
+        }
 
-                    points_for_mat
-                        .iter()
-                        .zip(values.iter())
-                        .for_each(|(&zeta, ps_at_zeta)| {
-                            let zeta = Point::from_projective_line(zeta);
-
-                            // Reduce this matrix, as a deep quotient, into one column with powers of α.
-                            let mat_ros = evals.deep_quotient_reduce(alpha, zeta, ps_at_zeta);
-
-                            // Fold it into our running reduction, offset by alpha_offset.
-                            reduced_opening_for_log_height
-                                .par_iter_mut()
-                                .zip(mat_ros)
-                                .for_each(|(ro, mat_ro)| {
-                                    *ro += *alpha_offset * mat_ro;
-                                });
-
-                            // Update alpha_offset from α^i -> α^(i + 2 * width)
-                            *alpha_offset *= alpha.exp_u64(2 * evals.values.width() as u64);
-                        });
-                });
-            });
+        // Reduce all matrices of each height, as deep quotients, into one column with powers of α.
+        // log_height -> reduced openings column
+        let reduced_openings: BTreeMap<usize, Vec<Challenge>> = mats_by_log_height
+            .into_iter()
+            .map(|(log_height, mats)| (log_height, deep_quotient_reduce_batch(alpha, &mats)))
+            .collect();
 
         // Iterate over our reduced columns and extract lambda - the multiple of the vanishing polynomial
         // which may appear in the reduced quotient due to CFFT dimension gap.
@@ -269,7 +302,7 @@ where
         let mut log_heights = vec![];
         let first_layer_mats: Vec<RowMajorMatrix<Challenge>> = reduced_openings
             .into_iter()
-            .map(|(log_height, (_, mut ro))| {
+            .map(|(log_height, mut ro)| {
                 assert!(log_height > 0);
                 log_heights.push(log_height);
                 let lambda = extract_lambda(&mut ro, self.fri_config.log_blowup);
@@ -302,8 +335,7 @@ where
             .rev()
             .collect();
 
-        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
-            CircleFriGenericConfig(PhantomData);
+        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig::new();
 
         let fri_proof = prove(&g, &self.fri_config, fri_input, challenger, |index| {
             // CircleFriFolder asks for an extra query index bit, so we use that here to index
@@ -374,6 +406,8 @@ where
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
+        assert_supported(&self.fri_config);
+
         // Write evaluations to challenger
         for (_, round) in &rounds {
             for (_, mat) in round {
@@ -394,8 +428,7 @@ where
         let log_global_max_height =
             proof.fri_proof.commit_phase_commits.len() + self.fri_config.log_blowup + 1;
 
-        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
-            CircleFriGenericConfig(PhantomData);
+        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig::new();
 
         verify(
             &g,
@@ -557,60 +590,162 @@ mod tests {
 
     use super::*;
 
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
+
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+    fn test_pcs() -> MyPcs {
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+        let compress = MyCompress::new(byte_hash);
+        let val_mmcs = ValMmcs::new(field_hash, compress);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = create_test_fri_config(challenge_mmcs, 0);
+        MyPcs::new(val_mmcs, fri_config)
+    }
+
+    #[test]
+    #[should_panic(expected = "CirclePcs does not support FRI folding schedules")]
+    fn circle_pcs_rejects_folding_schedule() {
+        let byte_hash = ByteHash {};
+        let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+        let mut fri_config = create_test_fri_config(ChallengeMmcs::new(val_mmcs.clone()), 0);
+        fri_config.log_folding_arities = vec![2];
+        MyPcs::new(val_mmcs, fri_config);
+    }
+
     #[test]
     fn circle_pcs() {
         // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
 
         let mut rng = SmallRng::seed_from_u64(0);
+        let pcs = test_pcs();
 
-        type Val = Mersenne31;
-        type Challenge = BinomialExtensionField<Mersenne31, 3>;
+        let log_n = 10;
 
-        type ByteHash = Keccak256Hash;
-        type FieldHash = SerializingHasher32<ByteHash>;
-        let byte_hash = ByteHash {};
-        let field_hash = FieldHash::new(byte_hash);
+        let d = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
 
-        type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
-        let compress = MyCompress::new(byte_hash);
+        let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 1);
 
-        type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
-        let val_mmcs = ValMmcs::new(field_hash, compress);
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
 
-        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
-        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let zeta: Challenge = rng.random();
 
-        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+        let mut chal = Challenger::from_hasher(vec![], ByteHash {});
+        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
 
-        let fri_config = create_test_fri_config(challenge_mmcs, 0);
+        let mut chal = Challenger::from_hasher(vec![], ByteHash {});
+        pcs.verify(
+            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+            &proof,
+            &mut chal,
+        )
+        .expect("verify err");
+    }
 
-        type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-        let pcs = Pcs {
-            mmcs: val_mmcs,
-            fri_config,
-            _phantom: PhantomData,
-        };
+    #[test]
+    fn circle_pcs_proof_serde_round_trip() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let pcs = test_pcs();
 
-        let log_n = 10;
+        let log_n = 8;
+        let d = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+        let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 2);
+        let (comm, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
 
-        let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
-            &pcs,
-            1 << log_n,
+        let zeta: Challenge = rng.random();
+        let mut chal = Challenger::from_hasher(vec![], ByteHash {});
+        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+
+        type Comm = <MyPcs as Pcs<Challenge, Challenger>>::Commitment;
+        type Proof = <MyPcs as Pcs<Challenge, Challenger>>::Proof;
+        let verify = |comm: Comm, values: &OpenedValues<Challenge>, proof: &Proof| {
+            let mut chal = Challenger::from_hasher(vec![], ByteHash {});
+            pcs.verify(
+                vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+                proof,
+                &mut chal,
+            )
+        };
+
+        let config = bincode::config::standard()
+            .with_little_endian()
+            .with_fixed_int_encoding();
+        let bytes = bincode::serde::encode_to_vec((&comm, &values, &proof), config).unwrap();
+        let ((comm_bc, values_bc, proof_bc), len): ((Comm, OpenedValues<Challenge>, Proof), _) =
+            bincode::serde::decode_from_slice(&bytes, config).unwrap();
+        assert_eq!(len, bytes.len());
+        // Encoding is canonical: re-encoding the decoded artifacts gives the same bytes.
+        assert_eq!(
+            bincode::serde::encode_to_vec((&comm_bc, &values_bc, &proof_bc), config).unwrap(),
+            bytes
         );
+        verify(comm_bc, &values_bc, &proof_bc).expect("verify err after bincode round trip");
+
+        let json = serde_json::to_string(&(&comm, &values, &proof)).unwrap();
+        let (comm_json, values_json, proof_json): (Comm, OpenedValues<Challenge>, Proof) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            serde_json::to_string(&(&comm_json, &values_json, &proof_json)).unwrap(),
+            json
+        );
+        verify(comm_json, &values_json, &proof_json).expect("verify err after JSON round trip");
+    }
 
-        let evals = RowMajorMatrix::rand(&mut rng, 1 << log_n, 1);
+    #[test]
+    fn circle_pcs_multi_point() {
+        // Open matrices of several heights at both zeta and the next point, as the prover does for
+        // the trace, with all deep quotients batched into one FRI proof.
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let pcs = test_pcs();
 
+        let domains_and_evals = [(6, 3), (8, 1), (8, 5)]
+            .map(|(log_n, width)| {
+                let d = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_n,
+                );
+                (d, RowMajorMatrix::rand(&mut rng, 1 << log_n, width))
+            })
+            .to_vec();
+        let domains = domains_and_evals.iter().map(|(d, _)| *d).collect_vec();
         let (comm, data) =
-            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(d, evals)]);
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_evals.clone());
 
         let zeta: Challenge = rng.random();
+        let points = domains
+            .iter()
+            .map(|d| vec![zeta, d.next_point(zeta).unwrap()])
+            .collect_vec();
 
-        let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+        let mut chal = Challenger::from_hasher(vec![], ByteHash {});
+        let (values, proof) = pcs.open(vec![(&data, points.clone())], &mut chal);
 
-        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        // The opened values are those of the interpolants of the committed evaluations.
+        for ((d, evals), points, values) in izip!(&domains_and_evals, &points, &values[0]) {
+            for (&pt, ps_at_pt) in izip!(points, values) {
+                let expected = CircleEvaluations::from_natural_order(*d, evals.clone())
+                    .evaluate_at_point(Point::from_projective_line(pt));
+                assert_eq!(ps_at_pt, &expected);
+            }
+        }
+
+        let mut chal = Challenger::from_hasher(vec![], ByteHash {});
         pcs.verify(
-            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+            vec![(
+                comm,
+                izip!(domains, points, values[0].clone())
+                    .map(|(d, points, values)| (d, izip!(points, values).collect()))
+                    .collect(),
+            )],
             &proof,
             &mut chal,
         )
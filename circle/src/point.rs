@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use core::ops::{Add, AddAssign, Mul, Neg, Sub};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field, batch_multiplicative_inverse};
@@ -22,12 +22,25 @@ impl<F: Field> Point<F> {
         Self { x, y, _private: () }
     }
 
-    const ZERO: Self = Self {
+    /// Construct the point `(x, y)`, returning `None` if it does not lie on the circle.
+    #[inline]
+    pub fn try_new(x: F, y: F) -> Option<Self> {
+        (x.square() + y.square() == F::ONE).then_some(Self { x, y, _private: () })
+    }
+
+    /// The identity of the circle group, `(1, 0)`.
+    pub const ZERO: Self = Self {
         x: F::ONE,
         y: F::ZERO,
         _private: (),
     };
 
+    /// The coordinates `(x, y)` of this point.
+    #[inline]
+    pub const fn coords(self) -> (F, F) {
+        (self.x, self.y)
+    }
+
     /// Circle STARKs, Section 3, Lemma 1: (page 4 of the first revision PDF)
     /// ```ignore
     /// (x, y) = ((1-t^2)/(1+t^2), 2t/(1+t^2))
@@ -136,10 +149,26 @@ pub fn compute_lagrange_den_batched<F: Field, EF: ExtensionField<F>>(
 }
 
 impl<F: ComplexExtendable> Point<F> {
+    /// A generator of the unique subgroup of the circle group of order `2^log_n`.
     pub fn generator(log_n: usize) -> Self {
         let g = F::circle_two_adic_generator(log_n);
         Self::new(g.real(), g.imag())
     }
+
+    /// The log of the order of this point, or `None` if its order is not a power of two.
+    ///
+    /// Points of power of two order are those of the subgroup of order `2^CIRCLE_TWO_ADICITY`,
+    /// which contains every domain we work with.
+    pub fn log_order(self) -> Option<usize> {
+        let mut pt = self;
+        for log_order in 0..=F::CIRCLE_TWO_ADICITY {
+            if pt == Self::ZERO {
+                return Some(log_order);
+            }
+            pt = pt.double();
+        }
+        None
+    }
 }
 
 /// Circle STARKs, Section 3.1, Equation 2: (page 5 of the first revision PDF)
@@ -168,6 +197,12 @@ impl<F: Field> AddAssign for Point<F> {
     }
 }
 
+impl<F: Field> SubAssign for Point<F> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 impl<F: Field, EF: ExtensionField<F>> Sub<Point<F>> for Point<EF> {
     type Output = Self;
     fn sub(self, rhs: Point<F>) -> Self::Output {
@@ -193,6 +228,18 @@ impl<F: Field> Mul<usize> for Point<F> {
     }
 }
 
+impl<F: Field> MulAssign<usize> for Point<F> {
+    fn mul_assign(&mut self, rhs: usize) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: Field> From<Point<F>> for (F, F) {
+    fn from(pt: Point<F>) -> Self {
+        pt.coords()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use p3_mersenne_31::Mersenne31;
@@ -216,4 +263,24 @@ mod tests {
         let vn_prod_gen = (1..log_n).map(|i| generator.v_n(i)).product();
         assert_eq!(generator.v_n_prod(log_n), vn_prod_gen);
     }
+
+    #[test]
+    fn test_group_api() {
+        let g = Pt::generator(5);
+        assert_eq!(g.log_order(), Some(5));
+        assert_eq!((g * 4).log_order(), Some(3));
+        assert_eq!(Pt::ZERO.log_order(), Some(0));
+        assert_eq!((g * 16).log_order(), Some(1));
+
+        let mut pt = g;
+        pt *= 3;
+        pt -= g;
+        assert_eq!(pt, g * 2);
+        assert_eq!(g + (-g), Pt::ZERO);
+
+        let (x, y) = g.into();
+        assert_eq!(Pt::try_new(x, y), Some(g));
+        assert_eq!(Pt::try_new(x, y + F::ONE), None);
+        assert_eq!(Pt::try_new(F::ONE, F::ONE), None);
+    }
 }
@@ -40,7 +40,11 @@ where
 
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    let pow_witness = if config.deterministic_grinding {
+        challenger.grind_sequential(config.proof_of_work_bits)
+    } else {
+        challenger.grind(config.proof_of_work_bits)
+    };
 
     let query_proofs = info_span!("query phase").in_scope(|| {
         iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
@@ -0,0 +1,561 @@
+use alloc::vec::Vec;
+
+use p3_field::extension::ComplexExtendable;
+use p3_field::{ExtensionField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_util::log2_strict_usize;
+
+use crate::domain::CircleDomain;
+use crate::point::Point;
+
+/// A circle FFT over twin-cosets of the circle group of `F`.
+///
+/// Coefficients are expressed in the basis given by [`circle_basis`](crate::circle_basis), and
+/// evaluations are listed in the natural order of the domain, i.e. the order of
+/// `first_point`, `next_point(first_point)`, ...
+pub trait CircleCfft<F: ComplexExtendable>: Clone + Default {
+    /// Evaluate the polynomial with coefficients `vec` over the standard domain of size
+    /// `vec.len()`.
+    fn cfft(&self, vec: Vec<F>) -> Vec<F> {
+        self.cfft_batch(RowMajorMatrix::new_col(vec)).values
+    }
+
+    /// Evaluate each column of `mat`, viewed as coefficients, over the standard domain whose size
+    /// is the height of `mat`.
+    fn cfft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_cfft_batch(mat, domain.shift)
+    }
+
+    /// Evaluate the polynomial with coefficients `vec` over the twin-coset of size `vec.len()`
+    /// starting at `shift`.
+    fn coset_cfft(&self, vec: Vec<F>, shift: Point<F>) -> Vec<F> {
+        self.coset_cfft_batch(RowMajorMatrix::new_col(vec), shift)
+            .values
+    }
+
+    /// Evaluate each column of `mat`, viewed as coefficients, over the twin-coset starting at
+    /// `shift` whose size is the height of `mat`. This can be viewed as evaluation over
+    /// `CircleDomain::new(log2(mat.height()), shift)` rather than the standard domain.
+    fn coset_cfft_batch(&self, mut mat: RowMajorMatrix<F>, shift: Point<F>) -> RowMajorMatrix<F> {
+        self.coset_cfft_batch_in_place(&mut mat, shift);
+        mat
+    }
+
+    /// Same as `cfft_batch`, but overwrites `mat` with the evaluations instead of allocating a
+    /// new matrix.
+    fn cfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>) {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_cfft_batch_in_place(mat, domain.shift);
+    }
+
+    /// Same as `coset_cfft_batch`, but overwrites `mat` with the evaluations instead of
+    /// allocating a new matrix. This is the only forward transform an implementer needs to
+    /// define.
+    fn coset_cfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>, shift: Point<F>);
+
+    /// Interpolate the evaluations `vec` over the standard domain of size `vec.len()`, returning
+    /// the coefficients of the unique polynomial in the FFT space taking those values.
+    fn icfft(&self, vec: Vec<F>) -> Vec<F> {
+        self.icfft_batch(RowMajorMatrix::new_col(vec)).values
+    }
+
+    /// Interpolate each column of `mat`, viewed as evaluations over the standard domain whose size
+    /// is the height of `mat`. This is the inverse of `cfft_batch`.
+    fn icfft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_icfft_batch(mat, domain.shift)
+    }
+
+    /// Interpolate the evaluations `vec` over the twin-coset of size `vec.len()` starting at
+    /// `shift`.
+    fn coset_icfft(&self, vec: Vec<F>, shift: Point<F>) -> Vec<F> {
+        self.coset_icfft_batch(RowMajorMatrix::new_col(vec), shift)
+            .values
+    }
+
+    /// Interpolate each column of `mat`, viewed as evaluations over the twin-coset starting at
+    /// `shift` whose size is the height of `mat`. This is the inverse of `coset_cfft_batch`.
+    fn coset_icfft_batch(&self, mut mat: RowMajorMatrix<F>, shift: Point<F>) -> RowMajorMatrix<F> {
+        self.coset_icfft_batch_in_place(&mut mat, shift);
+        mat
+    }
+
+    /// Same as `icfft_batch`, but overwrites `mat` with the coefficients instead of allocating a
+    /// new matrix.
+    fn icfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>) {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_icfft_batch_in_place(mat, domain.shift);
+    }
+
+    /// Same as `coset_icfft_batch`, but overwrites `mat` with the coefficients instead of
+    /// allocating a new matrix. This is the only inverse transform an implementer needs to
+    /// define.
+    fn coset_icfft_batch_in_place(&self, mat: &mut RowMajorMatrix<F>, shift: Point<F>);
+
+    /// Compute the low-degree extension of `vec` onto the standard domain of size
+    /// `vec.len() << added_bits`.
+    fn lde(&self, vec: Vec<F>, added_bits: usize) -> Vec<F> {
+        self.lde_batch(RowMajorMatrix::new_col(vec), added_bits)
+            .values
+    }
+
+    /// Compute the low-degree extension of each column in `mat`, viewed as evaluations over the
+    /// standard domain whose size is the height of `mat`, onto the standard domain which is
+    /// `2^added_bits` times larger.
+    fn lde_batch(&self, mat: RowMajorMatrix<F>, added_bits: usize) -> RowMajorMatrix<F> {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()) + added_bits);
+        self.coset_lde_batch(mat, added_bits, domain.shift)
+    }
+
+    /// Compute the low-degree extension of `vec` onto the twin-coset of size
+    /// `vec.len() << added_bits` starting at `shift`.
+    fn coset_lde(&self, vec: Vec<F>, added_bits: usize, shift: Point<F>) -> Vec<F> {
+        self.coset_lde_batch(RowMajorMatrix::new_col(vec), added_bits, shift)
+            .values
+    }
+
+    /// Compute the low-degree extension of each column in `mat`, viewed as evaluations over the
+    /// standard domain whose size is the height of `mat`, onto the twin-coset starting at `shift`
+    /// which is `2^added_bits` times larger.
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: Point<F>,
+    ) -> RowMajorMatrix<F> {
+        let mut coeffs = mat;
+        self.icfft_batch_in_place(&mut coeffs);
+        // PANICS: possible panic if the new resized length overflows
+        coeffs.values.resize(
+            coeffs
+                .values
+                .len()
+                .checked_shl(added_bits.try_into().unwrap())
+                .unwrap(),
+            F::ZERO,
+        );
+        self.coset_cfft_batch_in_place(&mut coeffs, shift);
+        coeffs
+    }
+
+    /// Like `coset_cfft_batch`, but transforms `mat` in groups of at most `chunk_width` columns,
+    /// passing each group's evaluations to `sink` along with the index of its first column.
+    ///
+    /// The groups are copied out of `mat` in one pass, then transformed in place one at a time, so
+    /// `sink` can stream each group's evaluations elsewhere before the next is transformed.
+    fn coset_cfft_batch_chunked<M: Matrix<F>>(
+        &self,
+        mat: &M,
+        shift: Point<F>,
+        chunk_width: usize,
+        mut sink: impl FnMut(usize, RowMajorMatrix<F>),
+    ) {
+        for (col, mut chunk) in column_chunks(mat, chunk_width) {
+            self.coset_cfft_batch_in_place(&mut chunk, shift);
+            sink(col, chunk);
+        }
+    }
+
+    /// Like `coset_lde_batch`, but extends `mat` in groups of at most `chunk_width` columns,
+    /// passing each group's extension to `sink` along with the index of its first column.
+    ///
+    /// This bounds the memory used for the extension by `chunk_width << (log2(height) +
+    /// added_bits)` elements, which allows extending traces whose full LDE doesn't fit in memory
+    /// as long as `sink` streams the chunks elsewhere.
+    fn coset_lde_batch_chunked<M: Matrix<F>>(
+        &self,
+        mat: &M,
+        added_bits: usize,
+        shift: Point<F>,
+        chunk_width: usize,
+        mut sink: impl FnMut(usize, RowMajorMatrix<F>),
+    ) {
+        for (col, chunk) in column_chunks(mat, chunk_width) {
+            sink(col, self.coset_lde_batch(chunk, added_bits, shift));
+        }
+    }
+
+    /// Evaluate each column of `mat`, viewed as coefficients in an extension of `F`, over the
+    /// standard domain whose size is the height of `mat`.
+    fn cfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_cfft_algebra_batch(mat, domain.shift)
+    }
+
+    /// Evaluate each column of `mat`, viewed as coefficients in an extension of `F`, over the
+    /// twin-coset starting at `shift` whose size is the height of `mat`.
+    ///
+    /// The default implementation flattens `mat` to the base field and transforms that. Since the
+    /// twiddles all lie in `F`, implementations can avoid this by working on `EF` directly.
+    fn coset_cfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: Point<F>,
+    ) -> RowMajorMatrix<EF> {
+        let mut base_mat = mat.flatten_to_base();
+        self.coset_cfft_batch_in_place(&mut base_mat, shift);
+        reconstitute_from_base(base_mat)
+    }
+
+    /// Interpolate each column of `mat`, viewed as evaluations in an extension of `F` over the
+    /// standard domain whose size is the height of `mat`.
+    fn icfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        let domain = CircleDomain::standard(log2_strict_usize(mat.height()));
+        self.coset_icfft_algebra_batch(mat, domain.shift)
+    }
+
+    /// Interpolate each column of `mat`, viewed as evaluations in an extension of `F` over the
+    /// twin-coset starting at `shift` whose size is the height of `mat`.
+    ///
+    /// As with `coset_cfft_algebra_batch`, the default implementation flattens to the base field.
+    fn coset_icfft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: Point<F>,
+    ) -> RowMajorMatrix<EF> {
+        let mut base_mat = mat.flatten_to_base();
+        self.coset_icfft_batch_in_place(&mut base_mat, shift);
+        reconstitute_from_base(base_mat)
+    }
+}
+
+/// Split `mat` into row-major matrices of at most `chunk_width` consecutive columns, along with the
+/// index of the first column of each.
+///
+/// Each row is read once and split across the chunks, so this takes a single pass over `mat`.
+fn column_chunks<F: Clone + Send + Sync, M: Matrix<F>>(
+    mat: &M,
+    chunk_width: usize,
+) -> impl Iterator<Item = (usize, RowMajorMatrix<F>)> {
+    assert!(chunk_width > 0, "chunk width must be positive");
+    let num_chunks = mat.width().div_ceil(chunk_width);
+    let mut chunks: Vec<Vec<F>> = (0..num_chunks)
+        .map(|_| Vec::with_capacity(chunk_width * mat.height()))
+        .collect();
+    for r in 0..mat.height() {
+        let row = mat.row_slice(r);
+        for (chunk, values) in chunks.iter_mut().zip(row.chunks(chunk_width)) {
+            chunk.extend_from_slice(values);
+        }
+    }
+    let width = mat.width();
+    chunks.into_iter().enumerate().map(move |(i, values)| {
+        let col = i * chunk_width;
+        (
+            col,
+            RowMajorMatrix::new(values, chunk_width.min(width - col)),
+        )
+    })
+}
+
+/// Convert a matrix over `F`, produced by `flatten_to_base`, back into a matrix over `EF`.
+fn reconstitute_from_base<F: Field, EF: ExtensionField<F>>(
+    mat: RowMajorMatrix<F>,
+) -> RowMajorMatrix<EF> {
+    let width = mat.width / EF::DIMENSION;
+    let values = mat
+        .values
+        .chunks_exact(EF::DIMENSION)
+        .map(|coeffs| EF::from_basis_coefficients_slice(coeffs).unwrap())
+        .collect();
+    RowMajorMatrix::new(values, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::iproduct;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_mersenne_31::Mersenne31;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{CircleEvaluations, Radix2Cfft, circle_basis};
+
+    type F = Mersenne31;
+    type EF = BinomialExtensionField<Mersenne31, 3>;
+
+    #[test]
+    fn cfft_icfft_round_trip() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!(2..8, [1, 4, 11]) {
+            let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+            let coeffs = cfft.icfft_batch(evals.clone());
+            assert_eq!(
+                cfft.cfft_batch(coeffs.clone()),
+                evals,
+                "cfft(icfft(x)) == x"
+            );
+            assert_eq!(cfft.icfft_batch(cfft.cfft_batch(coeffs.clone())), coeffs);
+        }
+    }
+
+    #[test]
+    fn cfft_matches_reference() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!(2..6, [1, 4]) {
+            let domain = CircleDomain::standard(log_n);
+            let coeffs = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+            let evals = cfft.cfft_batch(coeffs.clone());
+            assert_eq!(
+                evals,
+                CircleEvaluations::evaluate(domain, coeffs.clone())
+                    .into_natural_order()
+                    .into_values()
+                    .to_row_major_matrix()
+            );
+            for (i, pt) in domain.points().enumerate() {
+                assert_eq!(
+                    &*evals.row_slice(i),
+                    coeffs.columnwise_dot_product(&circle_basis(pt, log_n)),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_column_api() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 1).values;
+        assert_eq!(cfft.cfft(cfft.icfft(evals.clone())), evals);
+    }
+
+    #[test]
+    fn coset_cfft_matches_reference() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!(2..6, [1, 4]) {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+            let domain = CircleDomain::new(log_n, shift);
+            let coeffs = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+            let evals = cfft.coset_cfft_batch(coeffs.clone(), shift);
+            for (i, pt) in domain.points().enumerate() {
+                assert_eq!(
+                    &*evals.row_slice(i),
+                    coeffs.columnwise_dot_product(&circle_basis(pt, log_n)),
+                );
+            }
+            assert_eq!(cfft.coset_icfft_batch(evals, shift), coeffs);
+        }
+    }
+
+    #[test]
+    fn coset_cfft_with_standard_shift() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let coeffs = RowMajorMatrix::<F>::rand(&mut rng, 1 << 6, 3);
+        let shift = CircleDomain::<F>::standard(6).shift;
+        assert_eq!(
+            cfft.coset_cfft_batch(coeffs.clone(), shift),
+            cfft.cfft_batch(coeffs)
+        );
+    }
+
+    #[test]
+    fn lde_matches_extrapolate() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, added_bits) in iproduct!(2..6, [0, 1, 2, 3]) {
+            let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, 5);
+            let lde = cfft.lde_batch(evals.clone(), added_bits);
+            assert_eq!(lde.height(), 1 << (log_n + added_bits));
+            assert_eq!(
+                lde,
+                CircleEvaluations::from_natural_order(CircleDomain::standard(log_n), evals)
+                    .extrapolate(CircleDomain::standard(log_n + added_bits))
+                    .into_natural_order()
+                    .into_values()
+                    .to_row_major_matrix()
+            );
+
+            // The extension is low degree: its top coefficients vanish.
+            let lde_coeffs = cfft.icfft_batch(lde);
+            for r in (1 << log_n)..lde_coeffs.height() {
+                assert!(lde_coeffs.row(r).all(|x| x.is_zero()));
+            }
+        }
+    }
+
+    #[test]
+    fn coset_lde_agrees_on_shared_points() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let (log_n, added_bits) = (4, 2);
+        let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, 3);
+        let coeffs = cfft.icfft_batch(evals.clone());
+        let lde = cfft.coset_lde_batch(evals, added_bits, shift);
+        for (i, pt) in CircleDomain::new(log_n + added_bits, shift)
+            .points()
+            .enumerate()
+        {
+            assert_eq!(
+                &*lde.row_slice(i),
+                coeffs.columnwise_dot_product(&circle_basis(pt, log_n)),
+            );
+        }
+    }
+
+    #[test]
+    fn in_place_matches_out_of_place() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!(2..7, [1, 4, 11]) {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+            let orig = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+
+            let mut mat = orig.clone();
+            cfft.cfft_batch_in_place(&mut mat);
+            assert_eq!(mat, cfft.cfft_batch(orig.clone()));
+            cfft.icfft_batch_in_place(&mut mat);
+            assert_eq!(mat, orig);
+
+            let mut mat = orig.clone();
+            cfft.coset_icfft_batch_in_place(&mut mat, shift);
+            assert_eq!(mat, cfft.coset_icfft_batch(orig.clone(), shift));
+            cfft.coset_cfft_batch_in_place(&mut mat, shift);
+            assert_eq!(mat, orig);
+        }
+    }
+
+    #[test]
+    fn algebra_cfft_matches_flattened() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in iproduct!(2..7, [1, 4, 17]) {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+            let mat = RowMajorMatrix::<EF>::rand(&mut rng, 1 << log_n, width);
+
+            let mut base_mat = mat.flatten_to_base();
+            cfft.coset_cfft_batch_in_place(&mut base_mat, shift);
+            let evals = cfft.coset_cfft_algebra_batch(mat.clone(), shift);
+            assert_eq!(evals.flatten_to_base(), base_mat);
+            assert_eq!(cfft.coset_icfft_algebra_batch(evals, shift), mat);
+
+            let mut base_mat = mat.flatten_to_base();
+            cfft.icfft_batch_in_place(&mut base_mat);
+            let coeffs = cfft.icfft_algebra_batch(mat.clone());
+            assert_eq!(coeffs.flatten_to_base(), base_mat);
+            assert_eq!(cfft.cfft_algebra_batch(coeffs), mat);
+        }
+    }
+
+    /// Check `cfft` against a naive evaluation of the circle basis at the points of the domain, on
+    /// a random matrix of the given shape, over both the standard domain and a random twin-coset.
+    ///
+    /// The naive evaluation costs `O(n)` per point, so large domains are only checked at a random
+    /// sample of points.
+    fn check_against_naive<R: Rng>(cfft: &Radix2Cfft<F>, rng: &mut R, log_n: usize, width: usize) {
+        let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+        for domain in [
+            CircleDomain::standard(log_n),
+            CircleDomain::new(log_n, shift),
+        ] {
+            let coeffs = RowMajorMatrix::<F>::rand(rng, 1 << log_n, width);
+            let evals = cfft.coset_cfft_batch(coeffs.clone(), domain.shift);
+            let rows: Vec<usize> = if log_n <= 8 {
+                (0..1 << log_n).collect()
+            } else {
+                (0..16).map(|_| rng.random_range(0..1 << log_n)).collect()
+            };
+            for i in rows {
+                assert_eq!(
+                    &*evals.row_slice(i),
+                    coeffs.columnwise_dot_product(&circle_basis(domain.nth_point(i), log_n)),
+                    "cfft mismatch at row {i} for log_n = {log_n}, width = {width}"
+                );
+            }
+            assert_eq!(
+                cfft.coset_icfft_batch(evals, domain.shift),
+                coeffs,
+                "icfft mismatch for log_n = {log_n}, width = {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn differential_edge_shapes() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_n, width) in [(1, 1), (1, 64), (2, 1), (2, 63), (14, 1)] {
+            check_against_naive(&cfft, &mut rng, log_n, width);
+        }
+    }
+
+    #[test]
+    fn differential_random_shapes() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        for _ in 0..32 {
+            let log_n = rng.random_range(1..=14);
+            // Keep the matrices small enough for debug builds.
+            let width = rng.random_range(1..=64).min(1 << 18 >> log_n);
+            check_against_naive(&cfft, &mut rng, log_n, width);
+        }
+    }
+
+    #[test]
+    fn height_one() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+        for width in [1, 7, 64] {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, 1, width);
+            assert_eq!(cfft.cfft_batch(mat.clone()), mat);
+            assert_eq!(cfft.icfft_batch(mat.clone()), mat);
+            assert_eq!(cfft.coset_cfft_batch(mat.clone(), shift), mat);
+            assert_eq!(cfft.coset_icfft_batch(mat.clone(), shift), mat);
+
+            // A constant extends to the same constant everywhere.
+            let lde = cfft.coset_lde_batch(mat.clone(), 3, shift);
+            assert_eq!(lde.height(), 8);
+            assert!(lde.rows().all(|row| row.eq(mat.values.iter().copied())));
+        }
+    }
+
+    #[test]
+    fn chunked_matches_unchunked() {
+        let cfft = Radix2Cfft::<F>::default();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (rng.random::<u16>() as usize);
+        let (log_n, added_bits, width) = (5, 2, 11);
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, width);
+        let evals = cfft.coset_cfft_batch(mat.clone(), shift);
+        let lde = cfft.coset_lde_batch(mat.clone(), added_bits, shift);
+
+        for chunk_width in [1, 3, 11, 64] {
+            let mut cols_seen = 0;
+            cfft.coset_cfft_batch_chunked(&mat, shift, chunk_width, |col, chunk| {
+                assert_eq!(col, cols_seen);
+                assert!(chunk.width() <= chunk_width);
+                for (r, row) in chunk.rows().enumerate() {
+                    assert!(row.eq(evals.row(r).skip(col).take(chunk.width())));
+                }
+                cols_seen += chunk.width();
+            });
+            assert_eq!(cols_seen, width);
+
+            let mut cols_seen = 0;
+            cfft.coset_lde_batch_chunked(&mat, added_bits, shift, chunk_width, |col, chunk| {
+                assert_eq!(col, cols_seen);
+                assert_eq!(chunk.height(), lde.height());
+                for (r, row) in chunk.rows().enumerate() {
+                    assert!(row.eq(lde.row(r).skip(col).take(chunk.width())));
+                }
+                cols_seen += chunk.width();
+            });
+            assert_eq!(cols_seen, width);
+        }
+    }
+}
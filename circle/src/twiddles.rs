@@ -1,78 +1,96 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
-use core::mem;
+use core::cell::RefCell;
 
-use itertools::Itertools;
-use p3_field::extension::{Complex, ComplexExtendable};
-use p3_field::{batch_multiplicative_inverse, Field};
-use p3_util::linear_map::LinearMap;
-use tracing::instrument;
+use p3_field::extension::ComplexExtendable;
 
+use crate::cfft::{compute_inverse_twiddles, compute_twiddles};
 use crate::domain::CircleDomain;
 
-#[derive(Debug, Default)]
-pub(crate) struct TwiddleCache<F: Field>(
-    // (log_n, shift) -> (twiddles, inverse_twiddles)
-    #[allow(clippy::type_complexity)]
-    LinearMap<(usize, Complex<F>), (Vec<Vec<F>>, Option<Vec<Vec<F>>>)>,
-);
+/// The forward and inverse twiddles of every layer of the CFFT over a domain.
+#[derive(Clone, Debug)]
+pub(crate) struct LayerTwiddles<F> {
+    pub(crate) forward: Vec<Vec<F>>,
+    pub(crate) inverse: Vec<Vec<F>>,
+}
 
-impl<F: ComplexExtendable> TwiddleCache<F> {
-    pub(crate) fn get_twiddles(
-        &mut self,
-        log_n: usize,
-        shift: Complex<F>,
-        inv: bool,
-    ) -> &Vec<Vec<F>> {
-        let cache = self
-            .0
-            .get_or_insert_with((log_n, shift), || (compute_twiddles(log_n, shift), None));
-        if !inv {
-            &cache.0
-        } else {
-            cache.1.get_or_insert_with(|| {
-                cache
-                    .0
-                    .iter()
-                    .map(|xs| batch_multiplicative_inverse(xs))
-                    .collect()
-            })
+impl<F: ComplexExtendable> LayerTwiddles<F> {
+    pub(crate) fn new(domain: CircleDomain<F>) -> Self {
+        Self {
+            forward: compute_twiddles(domain),
+            inverse: compute_inverse_twiddles(domain),
         }
     }
 }
 
-/// Computes all (non-inverted) twiddles for the FFT over a circle domain of size 2^log_n, for all layers of the FFT.
-#[instrument(skip(shift))]
-fn compute_twiddles<F: ComplexExtendable>(log_n: usize, shift: Complex<F>) -> Vec<Vec<F>> {
-    let size = 1 << (log_n - 1);
+/// A cache of CFFT twiddles, keyed by the log size of the standard position domain.
+///
+/// This lets repeated transforms of the same size, e.g. across proving runs, reuse the twiddles
+/// computed by the first one.
+#[derive(Default, Clone, Debug)]
+pub struct CfftTwiddles<F> {
+    /// Memoized twiddles for each length log_n.
+    twiddles: RefCell<BTreeMap<usize, Arc<LayerTwiddles<F>>>>,
+}
+
+impl<F: ComplexExtendable> CfftTwiddles<F> {
+    /// Create a cache with the twiddles for domains of size `2^log_n` already computed.
+    pub fn with_precomputed(log_n: usize) -> Self {
+        let cache = Self::default();
+        cache.get(log_n);
+        cache
+    }
+
+    /// Whether the twiddles for domains of size `2^log_n` have been computed.
+    pub fn is_cached(&self, log_n: usize) -> bool {
+        self.twiddles.borrow().contains_key(&log_n)
+    }
+
+    /// Get the twiddles for domains of size `2^log_n`, computing them if they are not cached.
+    pub(crate) fn get(&self, log_n: usize) -> Arc<LayerTwiddles<F>> {
+        self.twiddles
+            .borrow_mut()
+            .entry(log_n)
+            .or_insert_with(|| Arc::new(LayerTwiddles::new(CircleDomain::standard(log_n))))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_mersenne_31::Mersenne31;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
 
-    let init_domain = CircleDomain::new(log_n, shift)
-        .points()
-        .take(size)
-        .collect_vec();
+    use super::*;
+    use crate::{CircleCfft, Radix2Cfft};
 
-    // After the first step we only need the real part.
-    let mut working_domain: Vec<_> = init_domain
-        .iter()
-        .take(size / 2)
-        .map(|x| x.real())
-        .collect();
+    type F = Mersenne31;
 
-    (0..log_n)
-        .map(|i| {
-            let size = working_domain.len();
-            let output = if i == 0 {
-                // The twiddles in step one are the inverse imaginary parts.
-                init_domain.iter().map(|x| x.imag()).collect_vec()
-            } else {
-                let new_working_domain = working_domain
-                    .iter()
-                    .take(size / 2)
-                    // When we square a point, the real part changes as x -> 2x^2 - 1.
-                    .map(|x| x.square().double() - F::ONE)
-                    .collect();
-                mem::replace(&mut working_domain, new_working_domain)
-            };
-            output
-        })
-        .collect()
+    #[test]
+    fn twiddles_are_reused() {
+        let cache = CfftTwiddles::<F>::with_precomputed(6);
+        assert!(cache.is_cached(6));
+        assert!(!cache.is_cached(7));
+        assert!(Arc::ptr_eq(&cache.get(6), &cache.get(6)));
+        cache.get(7);
+        assert!(cache.is_cached(7));
+    }
+
+    #[test]
+    fn precomputed_matches_fresh() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let cached = Radix2Cfft::<F>::with_precomputed(5);
+        for _ in 0..3 {
+            let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << 5, 3);
+            let coeffs = cached.icfft_batch(evals.clone());
+            assert_eq!(
+                coeffs,
+                Radix2Cfft::<F>::default().icfft_batch(evals.clone())
+            );
+            assert_eq!(cached.cfft_batch(coeffs), evals);
+        }
+    }
 }
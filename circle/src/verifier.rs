@@ -124,11 +124,13 @@ where
     // We start with evaluations over a domain of size (1 << log_max_height). We fold
     // using FRI until the domain size reaches (1 << log_final_height). This is equal to 1 << log_blowup
     // currently as we have not yet implemented early stopping.
-    for (log_folded_height, ((&beta, comm), opening)) in zip_eq(
+    for (layer, (log_folded_height, ((&beta, comm), opening))) in zip_eq(
         (config.log_blowup..log_max_height).rev(),
         steps,
         FriError::InvalidProofShape,
-    )? {
+    )?
+    .enumerate()
+    {
         // If there are new polynomials to roll in at this height, do so.
         if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
             folded_eval += ro;
@@ -152,7 +154,7 @@ where
         config
             .mmcs
             .verify_batch(comm, dims, index, &[evals.clone()], &opening.opening_proof)
-            .map_err(FriError::CommitPhaseMmcsError)?;
+            .map_err(|error| FriError::CommitPhaseMmcsError { layer, error })?;
 
         // Fold the pair of evaluations of sibling nodes into the evaluation of the parent fri node.
         folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
@@ -6,7 +6,7 @@ use p3_field::{ExtensionField, Field};
 use p3_matrix::extension::FlatMatrixView;
 use p3_matrix::{Dimensions, Matrix};
 
-use crate::Mmcs;
+use crate::{Mmcs, PathMmcs};
 
 #[derive(Clone, Debug)]
 pub struct ExtensionMmcs<F, EF, InnerMmcs> {
@@ -96,3 +96,20 @@ where
             .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
     }
 }
+
+impl<F, EF, InnerMmcs> PathMmcs<EF> for ExtensionMmcs<F, EF, InnerMmcs>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    InnerMmcs: PathMmcs<F>,
+{
+    type Node = InnerMmcs::Node;
+
+    fn proof_path(proof: &Self::Proof) -> &[Self::Node] {
+        InnerMmcs::proof_path(proof)
+    }
+
+    fn proof_from_path(path: Vec<Self::Node>) -> Self::Proof {
+        InnerMmcs::proof_from_path(path)
+    }
+}
@@ -72,6 +72,10 @@ pub trait PolynomialSpace: Copy {
     /// case this will return `None`.
     fn next_point<Ext: ExtensionField<Self::Val>>(&self, x: Ext) -> Option<Ext>;
 
+    /// The generator `g` of the subgroup when the space is a coset `s<g>` of a multiplicative
+    /// subgroup, so that its points are `s g^i`, or `None` if the space has no such form.
+    fn multiplicative_generator(&self) -> Option<Self::Val>;
+
     /// Return another `PolynomialSpace` with size at least `min_size` disjoint from this space.
     ///
     /// When working with spaces of power of two size, this will return a space of size `2^ceil(log_2(min_size))`.
@@ -109,6 +113,18 @@ pub trait PolynomialSpace: Copy {
     /// to `0` at any point not in `self`.
     fn vanishing_poly_at_point<Ext: ExtensionField<Self::Val>>(&self, point: Ext) -> Ext;
 
+    /// Compute the normalized Lagrange basis polynomial of the `index`-th point of the space,
+    /// evaluated at the given point.
+    ///
+    /// Points are indexed in the order of `first_point` and `next_point`, and the polynomial is
+    /// `1` at the `index`-th point and `0` at every other point of the space. The given point
+    /// must not lie in the space.
+    fn lagrange_basis_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        index: usize,
+        point: Ext,
+    ) -> Ext;
+
     /// Compute several Lagrange selectors at a given point.
     /// - The Lagrange selector of the first point.
     /// - The Lagrange selector of the last point.
@@ -147,6 +163,10 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
         Some(x * self.subgroup_generator())
     }
 
+    fn multiplicative_generator(&self) -> Option<Val> {
+        Some(self.subgroup_generator())
+    }
+
     /// Given the coset `gH`, return the disjoint coset `gfK` where `f`
     /// is a fixed generator of `F^*` and `K` is the unique two-adic subgroup
     /// of with size `2^(ceil(log_2(min_size)))`.
@@ -212,6 +232,16 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
         (point * self.shift().inverse()).exp_power_of_2(self.log_size()) - Ext::ONE
     }
 
+    /// Compute the normalized Lagrange basis polynomial of the point `x_i = gh^i` at the given
+    /// point:
+    ///
+    /// `L_i(X) = x_i Z_{gH}(X) / (|H| (X - x_i))`
+    fn lagrange_basis_at_point<Ext: ExtensionField<Val>>(&self, index: usize, point: Ext) -> Ext {
+        let x_i = self.shift() * self.subgroup_generator().exp_u64(index as u64);
+        let size_inv = Val::ONE.div_2exp_u64(self.log_size() as u64);
+        self.vanishing_poly_at_point(point) * (point - x_i).inverse() * (x_i * size_inv)
+    }
+
     /// Compute several Lagrange selectors at the given point:
     ///
     /// Defining the vanishing polynomial by `Z_{gH}(X) = g^{-|H|}\prod_{h \in H} (X - gh) = (g^{-1}X)^|H| - 1` return:
@@ -281,3 +311,41 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_field::extension::BinomialExtensionField;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn lagrange_basis_interpolates() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let d = TwoAdicMultiplicativeCoset::new(F::GENERATOR, 5).unwrap();
+        let coeffs: Vec<F> = (0..d.size()).map(|_| rng.random()).collect();
+        let zeta: EF = rng.random();
+
+        // Evaluate the polynomial with these coefficients by Horner's rule.
+        let eval = |x: EF| coeffs.iter().rev().fold(EF::ZERO, |acc, &c| acc * x + c);
+
+        let combined = (0..d.size())
+            .map(|i| {
+                let x_i = d.shift() * d.subgroup_generator().exp_u64(i as u64);
+                d.lagrange_basis_at_point(i, zeta) * eval(EF::from(x_i))
+            })
+            .sum::<EF>();
+        assert_eq!(combined, eval(zeta));
+        assert_eq!(
+            (0..d.size())
+                .map(|i| d.lagrange_basis_at_point(i, zeta))
+                .sum::<EF>(),
+            EF::ONE
+        );
+    }
+}
@@ -80,3 +80,18 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
 }
+
+/// An MMCS whose opening proofs are paths of nodes, like the sibling nodes from an opened leaf up
+/// to the root of a Merkle tree.
+///
+/// Openings of the same commitment at nearby indices share the top of their paths, which lets
+/// proofs with many openings store each shared node once.
+pub trait PathMmcs<T: Send + Sync>: Mmcs<T> {
+    type Node: Clone + PartialEq + Serialize + DeserializeOwned;
+
+    /// The nodes of `proof`, from the leaf upwards.
+    fn proof_path(proof: &Self::Proof) -> &[Self::Node];
+
+    /// The proof whose path is `path`.
+    fn proof_from_path(path: Vec<Self::Node>) -> Self::Proof;
+}
@@ -35,15 +35,39 @@ where
 
     type Error: Debug;
 
+    /// Whether this PCS supports `commit_zk`.
+    const ZK: bool = false;
+
     /// This should return a coset domain (s.t. Domain::next_point returns Some)
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain;
 
+    /// The number of queries made by the opening argument, if it is query-based.
+    fn num_queries(&self) -> Option<usize> {
+        None
+    }
+
     #[allow(clippy::type_complexity)]
     fn commit(
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Like `commit`, but randomizes each matrix first, for zero-knowledge.
+    ///
+    /// Each column is extended to a random polynomial of twice the degree which agrees with it on
+    /// its domain, so the committed matrices live on domains twice the size, i.e. those of
+    /// `natural_domain_for_degree(2 * domain.size())`, and must be opened and verified as such.
+    ///
+    /// # Panics
+    /// The default implementation panics; only PCSs with `ZK` set support this.
+    #[allow(clippy::type_complexity)]
+    fn commit_zk(
+        &self,
+        _evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        panic!("this PCS does not support zero-knowledge commitments")
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -1,6 +1,9 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use p3_baby_bear::BabyBear;
-use p3_dft::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_dft::{
+    FourStepDftParallel, Radix2Bowers, Radix2BowersBitReversed, Radix2Dit, Radix2DitParallel,
+    TwoAdicSubgroupDft,
+};
 use p3_field::TwoAdicField;
 use p3_field::extension::Complex;
 use p3_goldilocks::Goldilocks;
@@ -21,6 +24,11 @@ fn bench_fft(c: &mut Criterion) {
 
     const BATCH_SIZE: usize = 256;
 
+    // The four-step algorithm targets transforms too large for the cache, so compare it with
+    // fewer columns at larger sizes.
+    let log_large_sizes = &[20, 22, 24];
+    const NARROW_BATCH_SIZE: usize = 16;
+
     fft::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     fft::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
@@ -28,6 +36,10 @@ fn bench_fft(c: &mut Criterion) {
     fft::<Goldilocks, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     fft::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     fft::<Goldilocks, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
+    fft::<BabyBear, Radix2DitParallel<_>, NARROW_BATCH_SIZE>(c, log_large_sizes);
+    fft::<BabyBear, FourStepDftParallel<_>, NARROW_BATCH_SIZE>(c, log_large_sizes);
+    fft::<Goldilocks, Radix2DitParallel<_>, NARROW_BATCH_SIZE>(c, log_large_sizes);
+    fft::<Goldilocks, FourStepDftParallel<_>, NARROW_BATCH_SIZE>(c, log_large_sizes);
     fft::<Complex<Mersenne31>, Radix2Dit<_>, BATCH_SIZE>(c, log_half_sizes);
     fft::<Complex<Mersenne31>, Radix2Bowers, BATCH_SIZE>(c, log_half_sizes);
     fft::<Complex<Mersenne31>, Radix2DitParallel<_>, BATCH_SIZE>(c, log_half_sizes);
@@ -41,8 +53,13 @@ fn bench_fft(c: &mut Criterion) {
     coset_lde::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
+    coset_lde::<BabyBear, Radix2BowersBitReversed, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
+
+    fft_colmajor::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
+    fft_colmajor::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
+    fft_colmajor::<Goldilocks, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
 }
 
 fn fft<F, Dft, const BATCH_SIZE: usize>(c: &mut Criterion, log_sizes: &[usize])
@@ -158,5 +175,35 @@ where
     }
 }
 
+fn fft_colmajor<F, Dft, const BATCH_SIZE: usize>(c: &mut Criterion, log_sizes: &[usize])
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+    StandardUniform: Distribution<F>,
+{
+    let mut group = c.benchmark_group(format!(
+        "fft_colmajor/{}/{}/ncols={}",
+        pretty_name::<F>(),
+        pretty_name::<Dft>(),
+        BATCH_SIZE
+    ));
+    group.sample_size(10);
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    for n_log in log_sizes {
+        let n = 1 << n_log;
+
+        // Each row is one column of the trace.
+        let cols = RowMajorMatrix::rand(&mut rng, BATCH_SIZE, n);
+
+        let dft = Dft::default();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &dft, |b, dft| {
+            b.iter(|| {
+                dft.dft_batch_colmajor(cols.clone());
+            });
+        });
+    }
+}
+
 criterion_group!(benches, bench_fft);
 criterion_main!(benches);
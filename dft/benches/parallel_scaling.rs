@@ -0,0 +1,67 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use p3_baby_bear::BabyBear;
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::TwoAdicField;
+use p3_goldilocks::Goldilocks;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_util::pretty_name;
+use rand::SeedableRng;
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::SmallRng;
+use rayon::ThreadPoolBuilder;
+
+/// Measure how `Radix2DitParallel` scales with the number of threads, on a wide matrix where each
+/// thread transforms its own block of columns, and on a narrow one where every layer's rows are
+/// split across all threads instead. Throughput is per element, so the two strategies can be
+/// compared at each thread count.
+fn bench_parallel_scaling(c: &mut Criterion) {
+    const LOG_HEIGHT: usize = 18;
+    const BATCH_SIZES: [usize; 2] = [16, 1024];
+
+    for batch_size in BATCH_SIZES {
+        scaling::<BabyBear, Radix2DitParallel<_>>(c, LOG_HEIGHT, batch_size);
+        scaling::<Goldilocks, Radix2DitParallel<_>>(c, LOG_HEIGHT, batch_size);
+    }
+}
+
+fn scaling<F, Dft>(c: &mut Criterion, log_height: usize, batch_size: usize)
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+    StandardUniform: Distribution<F>,
+{
+    let mut group = c.benchmark_group(format!(
+        "parallel_scaling/{}/{}/n={}/ncols={}",
+        pretty_name::<F>(),
+        pretty_name::<Dft>(),
+        1 << log_height,
+        batch_size
+    ));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements((batch_size << log_height) as u64));
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let messages = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_height, batch_size);
+
+    let max_threads = rayon::current_num_threads();
+    let thread_counts = (0..)
+        .map(|i| 1 << i)
+        .take_while(|&threads| threads <= max_threads);
+    for threads in thread_counts {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let dft = Dft::default();
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &dft, |b, dft| {
+            pool.install(|| {
+                b.iter(|| {
+                    dft.dft_batch(messages.clone());
+                });
+            });
+        });
+    }
+}
+
+criterion_group!(benches, bench_parallel_scaling);
+criterion_main!(benches);
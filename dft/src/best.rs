@@ -0,0 +1,159 @@
+use p3_field::TwoAdicField;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+
+use crate::{FourStepDftParallel, Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+
+/// The smallest `log2` of the height for which the four-step algorithm is used on narrow matrices.
+const FOUR_STEP_MIN_LOG_HEIGHT: usize = 20;
+
+/// The widest matrix for which the four-step algorithm is used.
+const FOUR_STEP_MAX_WIDTH: usize = 16;
+
+/// The smallest number of elements for which a parallel backend is used.
+const PARALLEL_MIN_SIZE: usize = 1 << 14;
+
+/// The largest `log2` of the height for which a serial transform memoizes its twiddles.
+const MEMOIZED_MAX_LOG_HEIGHT: usize = 16;
+
+/// The DFT algorithms `BestDft` chooses between.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DftBackend {
+    /// `Radix2Dit`, for small serial transforms, whose twiddles are cheap to keep.
+    Radix2Dit,
+    /// `Radix2DitParallel`, for transforms with enough work to share between threads.
+    Radix2DitParallel,
+    /// `Radix2Bowers`, for tall serial transforms, which computes its twiddles as it goes rather
+    /// than keeping a table for each height.
+    Bowers,
+    /// `FourStepDftParallel`, for tall narrow matrices, which the other algorithms walk with poor
+    /// locality and little column parallelism.
+    FourStep,
+}
+
+impl DftBackend {
+    /// Choose the backend for a matrix with the given dimensions, given the number of threads
+    /// available.
+    pub fn choose(height: usize, width: usize, num_threads: usize) -> Self {
+        let log_h = log2_strict_usize(height);
+        if log_h >= FOUR_STEP_MIN_LOG_HEIGHT && width <= FOUR_STEP_MAX_WIDTH {
+            Self::FourStep
+        } else if num_threads > 1 && height * width >= PARALLEL_MIN_SIZE {
+            Self::Radix2DitParallel
+        } else if log_h > MEMOIZED_MAX_LOG_HEIGHT {
+            Self::Bowers
+        } else {
+            Self::Radix2Dit
+        }
+    }
+}
+
+/// A DFT which picks a backend for each matrix from its dimensions and the number of threads
+/// available, so that callers don't need to tune the choice to their workload.
+///
+/// Each backend keeps its own twiddle memos, so repeated transforms of the same shape are as cheap
+/// as with that backend alone. The evaluations are always returned in natural order.
+#[derive(Default, Clone, Debug)]
+pub struct BestDft<F: TwoAdicField> {
+    radix_2_dit: Radix2Dit<F>,
+    radix_2_dit_parallel: Radix2DitParallel<F>,
+    bowers: Radix2Bowers,
+    four_step: FourStepDftParallel<F>,
+}
+
+impl<F: TwoAdicField> BestDft<F> {
+    /// The backend used for a matrix with the given dimensions.
+    pub fn backend(&self, height: usize, width: usize) -> DftBackend {
+        DftBackend::choose(height, width, current_num_threads())
+    }
+}
+
+impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for BestDft<F> {
+    type Evaluations = RowMajorMatrix<F>;
+
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        match self.backend(mat.height(), mat.width()) {
+            DftBackend::Radix2Dit => self.radix_2_dit.dft_batch(mat),
+            DftBackend::Radix2DitParallel => self
+                .radix_2_dit_parallel
+                .dft_batch(mat)
+                .to_row_major_matrix(),
+            DftBackend::Bowers => self.bowers.dft_batch(mat),
+            DftBackend::FourStep => self.four_step.dft_batch(mat),
+        }
+    }
+
+    fn idft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        match self.backend(mat.height(), mat.width()) {
+            DftBackend::Radix2Dit => self.radix_2_dit.idft_batch(mat),
+            DftBackend::Radix2DitParallel => self.radix_2_dit_parallel.idft_batch(mat),
+            DftBackend::Bowers => self.bowers.idft_batch(mat),
+            DftBackend::FourStep => self.four_step.idft_batch(mat),
+        }
+    }
+
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        // The extension is dominated by the transform of the extended matrix.
+        match self.backend(mat.height() << added_bits, mat.width()) {
+            DftBackend::Radix2Dit => self.radix_2_dit.coset_lde_batch(mat, added_bits, shift),
+            DftBackend::Radix2DitParallel => self
+                .radix_2_dit_parallel
+                .coset_lde_batch(mat, added_bits, shift)
+                .to_row_major_matrix(),
+            DftBackend::Bowers => self.bowers.coset_lde_batch(mat, added_bits, shift),
+            DftBackend::FourStep => self.four_step.coset_lde_batch(mat, added_bits, shift),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+
+    #[test]
+    fn choose_backend() {
+        assert_eq!(DftBackend::choose(1 << 10, 8, 1), DftBackend::Radix2Dit);
+        assert_eq!(DftBackend::choose(1 << 18, 8, 1), DftBackend::Bowers);
+        assert_eq!(
+            DftBackend::choose(1 << 12, 8, 8),
+            DftBackend::Radix2DitParallel
+        );
+        assert_eq!(
+            DftBackend::choose(1 << 22, 256, 8),
+            DftBackend::Radix2DitParallel
+        );
+        assert_eq!(DftBackend::choose(1 << 22, 4, 1), DftBackend::FourStep);
+    }
+
+    #[test]
+    fn matches_radix_2_dit() {
+        type F = BabyBear;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let best = BestDft::default();
+        let radix_2 = Radix2Dit::default();
+        for (log_h, width) in [(0, 1), (4, 3), (10, 32), (17, 1)] {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, width);
+            assert_eq!(best.dft_batch(mat.clone()), radix_2.dft_batch(mat.clone()));
+            assert_eq!(
+                best.idft_batch(mat.clone()),
+                radix_2.idft_batch(mat.clone())
+            );
+            assert_eq!(
+                best.coset_lde_batch(mat.clone(), 1, F::GENERATOR),
+                radix_2.coset_lde_batch(mat, 1, F::GENERATOR)
+            );
+        }
+    }
+}
@@ -85,3 +85,14 @@ impl<F: Field> Butterfly<F> for TwiddleFreeButterfly {
         (x_1 + x_2, x_1 - x_2)
     }
 }
+
+/// Twiddle-free butterfly whose outputs are both scaled by a constant, to fuse a rescaling into
+/// the last layer of a DIF network.
+#[derive(Copy, Clone)]
+pub struct ScaledTwiddleFreeButterfly<F>(pub F);
+impl<F: Field> Butterfly<F> for ScaledTwiddleFreeButterfly<F> {
+    #[inline]
+    fn apply<PF: PackedField<Scalar = F>>(&self, x_1: PF, x_2: PF) -> (PF, PF) {
+        ((x_1 + x_2) * self.0, (x_1 - x_2) * self.0)
+    }
+}
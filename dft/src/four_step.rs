@@ -0,0 +1,149 @@
+use alloc::vec;
+
+use p3_field::TwoAdicField;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::{debug_span, instrument};
+
+use crate::{Radix2DitParallel, TwoAdicSubgroupDft};
+
+/// The four-step FFT algorithm, for transforms too large for the cache.
+///
+/// A transform of height `n = n_1 n_2` becomes `n_1` interleaved transforms of height `n_2`, a
+/// twiddle pass, a transpose, and `n_2` interleaved transforms of height `n_1`, each done as one
+/// batch by `inner`. Every pass walks the whole matrix once and the sub-transforms are only about
+/// `sqrt(n)` rows tall, so they stay in cache where a direct transform of height `n` would not.
+#[derive(Default, Clone, Debug)]
+pub struct FourStepDft<Dft> {
+    inner: Dft,
+}
+
+impl<Dft> FourStepDft<Dft> {
+    /// Creates a four-step DFT that does its sub-transforms with `inner`.
+    pub const fn new(inner: Dft) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F, Dft> TwoAdicSubgroupDft<F> for FourStepDft<Dft>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    type Evaluations = RowMajorMatrix<F>;
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let w = mat.width();
+        let log_h = log2_strict_usize(h);
+        if log_h < 2 {
+            return self.inner.dft_batch(mat).to_row_major_matrix();
+        }
+
+        // Write the row index as `i = i_1 + n_1 i_2` and the output index as `k = n_2 k_1 + k_2`,
+        // so that
+        //     y_k = \sum_{i_1} g_1^{i_1 k_1} g^{i_1 k_2} \sum_{i_2} g_2^{i_2 k_2} x_i
+        // where `g_1` and `g_2` generate the subgroups of order `n_1` and `n_2`.
+        let log_n_1 = log_h / 2;
+        let (n_1, n_2) = (1 << log_n_1, 1 << (log_h - log_n_1));
+
+        // Rows `i_1 + n_1 i_2` are row `i_2` of a matrix `n_1` times as wide, so the inner sums
+        // are one batch of transforms of height `n_2`.
+        let mut inner = self
+            .inner
+            .dft_batch(RowMajorMatrix::new(mat.values, n_1 * w))
+            .to_row_major_matrix();
+
+        // Row `k_2` is now `n_1` blocks of `w`, with block `i_1` to be scaled by `g^{i_1 k_2}`.
+        debug_span!("twiddles").in_scope(|| {
+            let g = F::two_adic_generator(log_h);
+            inner
+                .par_rows_mut()
+                .enumerate()
+                .skip(1)
+                .for_each(|(k_2, row)| {
+                    let g_k_2 = g.exp_u64(k_2 as u64);
+                    for (block, twiddle) in row.chunks_exact_mut(w).zip(g_k_2.powers()).skip(1) {
+                        block.iter_mut().for_each(|x| *x *= twiddle);
+                    }
+                });
+        });
+
+        // Bring the blocks of each `i_1` together, so that the outer sums are again one batch, of
+        // transforms of height `n_1`, whose output rows `n_2 k_1 + k_2` are in natural order.
+        let transposed = debug_span!("transpose").in_scope(|| transpose_blocks(&inner, n_2, w));
+        let outer = self
+            .inner
+            .dft_batch(RowMajorMatrix::new(transposed.values, n_2 * w))
+            .to_row_major_matrix();
+        RowMajorMatrix::new(outer.values, w)
+    }
+}
+
+/// Transpose a matrix of `rows` rows of equal width, seen as a grid of blocks of `block_width`
+/// consecutive values, keeping each block intact.
+fn transpose_blocks<F: TwoAdicField>(
+    mat: &RowMajorMatrix<F>,
+    rows: usize,
+    block_width: usize,
+) -> RowMajorMatrix<F> {
+    if block_width == 1 {
+        return mat.transpose();
+    }
+    let cols = mat.width() / block_width;
+    let mut values = vec![F::ZERO; mat.values.len()];
+    values
+        .par_chunks_exact_mut(rows * block_width)
+        .enumerate()
+        .for_each(|(col, out_row)| {
+            for (row, block) in out_row.chunks_exact_mut(block_width).enumerate() {
+                let start = (row * cols + col) * block_width;
+                block.copy_from_slice(&mat.values[start..start + block_width]);
+            }
+        });
+    RowMajorMatrix::new(values, rows * block_width)
+}
+
+/// A four-step DFT doing its sub-transforms in parallel.
+pub type FourStepDftParallel<F> = FourStepDft<Radix2DitParallel<F>>;
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::Field;
+    use p3_goldilocks::Goldilocks;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::Radix2Dit;
+
+    #[test]
+    fn matches_radix_2_dit() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let dft = FourStepDft::<Radix2Dit<BabyBear>>::default();
+        for log_h in 0..12 {
+            for width in [1, 5] {
+                let mat = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_h, width);
+                assert_eq!(
+                    dft.dft_batch(mat.clone()),
+                    Radix2Dit::default().dft_batch(mat)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn coset_lde_matches_radix_2_dit() {
+        type F = Goldilocks;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << 7, 3);
+        assert_eq!(
+            FourStepDftParallel::<F>::default().coset_lde_batch(mat.clone(), 3, F::GENERATOR),
+            Radix2Dit::default().coset_lde_batch(mat, 3, F::GENERATOR)
+        );
+    }
+}
@@ -4,16 +4,22 @@
 
 extern crate alloc;
 
+mod best;
 mod butterflies;
+mod four_step;
 mod naive;
+mod plan;
 mod radix_2_bowers;
 mod radix_2_dit;
 mod radix_2_dit_parallel;
 mod traits;
 mod util;
 
+pub use best::*;
 pub use butterflies::*;
+pub use four_step::*;
 pub use naive::*;
+pub use plan::*;
 pub use radix_2_bowers::*;
 pub use radix_2_dit::*;
 pub use radix_2_dit_parallel::*;
@@ -38,6 +38,7 @@ mod tests {
     use p3_baby_bear::BabyBear;
     use p3_field::{Field, PrimeCharacteristicRing};
     use p3_goldilocks::Goldilocks;
+    use p3_matrix::Matrix;
     use p3_matrix::dense::RowMajorMatrix;
     use rand::SeedableRng;
     use rand::rngs::SmallRng;
@@ -105,4 +106,61 @@ mod tests {
         let idft = NaiveDft.coset_idft_batch(dft, generator);
         assert_eq!(original, idft);
     }
+
+    #[test]
+    fn coset_lde_zk_agrees_on_subgroup() {
+        type F = Goldilocks;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let original = RowMajorMatrix::<F>::rand(&mut rng, 8, 3);
+        let random_rows = RowMajorMatrix::<F>::rand(&mut rng, 8, 3);
+        // With a trivial shift, every fourth row of the extension lies on the original subgroup,
+        // and the rows halfway between them on the points the random rows were placed at.
+        let lde = NaiveDft.coset_lde_batch_zk(original.clone(), 1, F::ONE, random_rows.clone());
+        assert_eq!(lde.height(), 32);
+        for r in 0..8 {
+            assert_eq!(
+                lde.row_slice(4 * r).to_vec(),
+                original.row_slice(r).to_vec()
+            );
+            assert_eq!(
+                lde.row_slice(4 * r + 2).to_vec(),
+                random_rows.row_slice(r).to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn dft_colmajor_matches_transposed_dft() {
+        type F = Goldilocks;
+        let mut rng = SmallRng::seed_from_u64(1);
+        for log_h in 0..5 {
+            let original = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 3);
+            assert_eq!(
+                NaiveDft.dft_batch_colmajor(original.transpose()),
+                NaiveDft.dft_batch(original).transpose()
+            );
+        }
+
+        let empty = RowMajorMatrix::<F>::new(vec![], 0);
+        assert_eq!(NaiveDft.dft_batch_colmajor(empty.clone()), empty);
+    }
+
+    #[test]
+    fn coset_lde_subset_matches_strided_rows() {
+        type F = Goldilocks;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let original = RowMajorMatrix::<F>::rand(&mut rng, 8, 3);
+        let lde = NaiveDft.coset_lde_batch(original.clone(), 2, F::GENERATOR);
+        for which_coset in 0..4 {
+            let subset =
+                NaiveDft.coset_lde_batch_subset(original.clone(), 2, F::GENERATOR, which_coset);
+            assert_eq!(subset.height(), 8);
+            for r in 0..8 {
+                assert_eq!(
+                    subset.row_slice(r).to_vec(),
+                    lde.row_slice(4 * r + which_coset).to_vec()
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use alloc::vec::Vec;
+
+use p3_field::{Field, PrimeCharacteristicRing, TwoAdicField};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::{log2_strict_usize, reverse_bits_len};
+use tracing::instrument;
+
+use crate::radix_2_dit::{dif_layer, dit_layer};
+
+/// A coset low-degree extension of a fixed shape, precomputed so that it can be run repeatedly
+/// without allocating.
+///
+/// The plan owns the twiddles of both butterfly networks and the weights applied between them, so
+/// `execute` only touches the matrix it is given, which it extends in place. A caller reusing one
+/// matrix for each batch only allocates on the first call, after which the matrix has the
+/// capacity of the extension.
+///
+/// The result agrees with `TwoAdicSubgroupDft::coset_lde_batch` for the same parameters.
+#[derive(Clone, Debug)]
+pub struct DftPlan<F> {
+    log_height: usize,
+    width: usize,
+    added_bits: usize,
+    /// Powers of the inverse generator of the subgroup of order `height`.
+    inverse_twiddles: Vec<F>,
+    /// Powers of the generator of the subgroup of order `height << added_bits`.
+    twiddles: Vec<F>,
+    /// The weight `shift^i / height` of each coefficient `i`, indexed by the row holding it in
+    /// bit-reversed order.
+    coeff_weights: Vec<F>,
+}
+
+impl<F: TwoAdicField> DftPlan<F> {
+    /// Plan the extension of matrices of `height` rows and `width` columns by `added_bits` onto
+    /// the coset of the larger subgroup shifted by `shift`.
+    pub fn new(height: usize, width: usize, added_bits: usize, shift: F) -> Self {
+        let log_height = log2_strict_usize(height);
+        let log_lde_height = log_height + added_bits;
+
+        let root_inv = F::two_adic_generator(log_height).inverse();
+        let root = F::two_adic_generator(log_lde_height);
+        let height_inv =
+            F::from_prime_subfield(F::PrimeSubfield::ONE.div_2exp_u64(log_height as u64));
+        let weights = shift
+            .shifted_powers(height_inv)
+            .take(height)
+            .collect::<Vec<_>>();
+
+        Self {
+            log_height,
+            width,
+            added_bits,
+            inverse_twiddles: root_inv.powers().take(height).collect(),
+            twiddles: root.powers().take(1 << log_lde_height).collect(),
+            coeff_weights: (0..height)
+                .map(|row| weights[reverse_bits_len(row, log_height)])
+                .collect(),
+        }
+    }
+
+    /// The height of the matrices this plan extends.
+    pub const fn height(&self) -> usize {
+        1 << self.log_height
+    }
+
+    /// The width of the matrices this plan extends.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the extended matrices.
+    pub const fn lde_height(&self) -> usize {
+        1 << (self.log_height + self.added_bits)
+    }
+
+    /// Replace the evaluations in `mat` with their coset low-degree extension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mat` doesn't have the planned height and width.
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits = self.added_bits))]
+    pub fn execute(&self, mat: &mut RowMajorMatrix<F>) {
+        assert_eq!(
+            mat.width(),
+            self.width,
+            "Matrix width doesn't match the plan."
+        );
+        assert_eq!(
+            mat.height(),
+            self.height(),
+            "Matrix height doesn't match the plan."
+        );
+
+        // Interpolate, leaving the coefficients in bit-reversed order, and weight them.
+        for layer in 0..self.log_height {
+            dif_layer(&mut mat.as_view_mut(), layer, &self.inverse_twiddles);
+        }
+        mat.par_rows_mut()
+            .zip(self.coeff_weights.par_iter())
+            .for_each(|(row, &weight)| row.iter_mut().for_each(|x| *x *= weight));
+
+        // Pad the coefficients with zeros in place. In bit-reversed order, coefficient row `i`
+        // moves to row `i << added_bits`, and going from the last row down never overwrites a
+        // row that has yet to move.
+        let w = self.width;
+        mat.values.resize(self.lde_height() * w, F::ZERO);
+        if self.added_bits > 0 {
+            for row in (1..self.height()).rev() {
+                let dst = (row << self.added_bits) * w;
+                mat.values.copy_within(row * w..(row + 1) * w, dst);
+                mat.values[row * w..(row + 1) * w].fill(F::ZERO);
+            }
+        }
+
+        // Evaluate from bit-reversed coefficients, leaving the evaluations in natural order.
+        for layer in 0..self.log_height + self.added_bits {
+            dit_layer(&mut mat.as_view_mut(), layer, &self.twiddles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::{NaiveDft, TwoAdicSubgroupDft};
+
+    #[test]
+    fn matches_coset_lde_batch() {
+        type F = BabyBear;
+        let mut rng = SmallRng::seed_from_u64(1);
+        for (log_h, added_bits) in [(0, 1), (3, 0), (3, 1), (4, 3)] {
+            let plan = DftPlan::new(1 << log_h, 3, added_bits, F::GENERATOR);
+            // Reuse one matrix across runs, as a proving loop would.
+            let mut mat = RowMajorMatrix::new(Vec::new(), 3);
+            for _ in 0..2 {
+                let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 3);
+                mat.values.clear();
+                mat.values.extend_from_slice(&evals.values);
+                plan.execute(&mut mat);
+                assert_eq!(
+                    mat,
+                    NaiveDft.coset_lde_batch(evals, added_bits, F::GENERATOR)
+                );
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use alloc::vec::Vec;
 
 use p3_field::{Field, Powers, PrimeCharacteristicRing, TwoAdicField};
 use p3_matrix::Matrix;
+use p3_matrix::bitrev::{BitReversalPerm, BitReversedMatrixView};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_maybe_rayon::prelude::*;
@@ -14,7 +15,7 @@ use crate::util::divide_by_height;
 
 /// The Bowers G FFT algorithm.
 /// See: "Improved Twiddle Access for Fast Fourier Transforms"
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug)]
 pub struct Radix2Bowers;
 
 impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
@@ -79,13 +80,82 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
     }
 }
 
+/// A variant of the Bowers FFT whose evaluations are left in bit-reversed order, which its
+/// `Evaluations` type advertises.
+///
+/// The forward transform runs the G^T network on natural order input, so `dft_batch` needs no
+/// permutation at all. `coset_lde_batch` permutes the coefficients once, before they are padded,
+/// rather than permuting the `2^added_bits` times larger extension, and callers that want
+/// bit-reversed rows, like a PCS committing to an LDE, get them without another pass.
+#[derive(Default, Clone)]
+pub struct Radix2BowersBitReversed;
+
+impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2BowersBitReversed {
+    type Evaluations = BitReversedMatrixView<RowMajorMatrix<F>>;
+
+    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> Self::Evaluations {
+        let log_h = log2_strict_usize(mat.height());
+        bowers_g_t_with_root(&mut mat.as_view_mut(), F::two_adic_generator(log_h));
+        BitReversalPerm::new_view(mat)
+    }
+
+    fn idft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        Radix2Bowers.idft_batch(mat)
+    }
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits))]
+    fn coset_lde_batch(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Self::Evaluations {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+        let h_inv_subfield = F::PrimeSubfield::ONE.div_2exp_u64(log_h as u64);
+        let h_inv = F::from_prime_subfield(h_inv_subfield);
+
+        bowers_g_t(&mut mat.as_view_mut());
+
+        // Rescale the bit-reversed coefficients as in `Radix2Bowers::coset_lde_batch`, then put
+        // them in natural order, so that padding them is a resize.
+        let weights = Powers {
+            base: shift,
+            current: h_inv,
+        }
+        .take(h);
+        for (row, weight) in weights.enumerate() {
+            mat.scale_row(reverse_bits(row, h), weight);
+        }
+        reverse_matrix_index_bits(&mut mat);
+        mat.values.resize(mat.values.len() << added_bits, F::ZERO);
+
+        let log_lde_h = log_h + added_bits;
+        bowers_g_t_with_root(&mut mat.as_view_mut(), F::two_adic_generator(log_lde_h));
+        BitReversalPerm::new_view(mat)
+    }
+}
+
 /// Executes the Bowers G network. This is like a DFT, except it assumes the input is in
 /// bit-reversed order.
 fn bowers_g<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>) {
+    let log_h = log2_strict_usize(mat.height());
+    bowers_g_with_root(mat, F::two_adic_generator(log_h));
+}
+
+/// Executes the Bowers G^T network. This is like an inverse DFT, except we skip rescaling by
+/// 1/height, and the output is bit-reversed.
+fn bowers_g_t<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>) {
+    let log_h = log2_strict_usize(mat.height());
+    bowers_g_t_with_root(mat, F::two_adic_generator(log_h).inverse());
+}
+
+/// Executes the Bowers G network with twiddles from powers of `root`, which must generate the
+/// subgroup of order `mat.height()`.
+fn bowers_g_with_root<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>, root: F) {
     let h = mat.height();
     let log_h = log2_strict_usize(h);
 
-    let root = F::two_adic_generator(log_h);
     let mut twiddles: Vec<_> = root.powers().take(h / 2).map(DifButterfly).collect();
     reverse_slice_index_bits(&mut twiddles);
 
@@ -94,14 +164,14 @@ fn bowers_g<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>) {
     }
 }
 
-/// Executes the Bowers G^T network. This is like an inverse DFT, except we skip rescaling by
-/// 1/height, and the output is bit-reversed.
-fn bowers_g_t<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>) {
+/// Executes the Bowers G^T network with twiddles from powers of `root`, which must generate the
+/// subgroup of order `mat.height()`. With the generator's inverse this is `bowers_g_t`, and with
+/// the generator itself it is a DFT taking natural order input to bit-reversed output.
+fn bowers_g_t_with_root<F: TwoAdicField>(mat: &mut RowMajorMatrixViewMut<F>, root: F) {
     let h = mat.height();
     let log_h = log2_strict_usize(h);
 
-    let root_inv = F::two_adic_generator(log_h).inverse();
-    let mut twiddles: Vec<_> = root_inv.powers().take(h / 2).map(DitButterfly).collect();
+    let mut twiddles: Vec<_> = root.powers().take(h / 2).map(DitButterfly).collect();
     reverse_slice_index_bits(&mut twiddles);
 
     for log_half_block_size in (0..log_h).rev() {
@@ -1,22 +1,29 @@
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::iter;
+use core::ops::Index;
 
-use p3_field::{Field, TwoAdicField};
+use p3_field::{Field, PrimeCharacteristicRing, RootsOfUnityCache, TwoAdicField};
 use p3_matrix::Matrix;
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_maybe_rayon::prelude::*;
-use p3_util::log2_strict_usize;
+use p3_util::{log2_strict_usize, reverse_bits_len};
 
 use crate::TwoAdicSubgroupDft;
-use crate::butterflies::{Butterfly, DitButterfly, TwiddleFreeButterfly};
+use crate::butterflies::{
+    Butterfly, DifButterfly, DitButterfly, ScaledTwiddleFreeButterfly, TwiddleFreeButterfly,
+};
 
 /// The DIT FFT algorithm.
 #[derive(Default, Clone, Debug)]
 pub struct Radix2Dit<F: TwoAdicField> {
-    /// Memoized twiddle factors for each length log_n.
-    twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    /// Memoized twiddle factors for each length log_n, all computed from the powers of the
+    /// largest generator asked for so far.
+    twiddles: RootsOfUnityCache<F>,
+    /// Memoized inverse twiddle factors for each length log_n.
+    inverse_twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
 }
 
 impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
@@ -27,23 +34,116 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
         let log_h = log2_strict_usize(h);
 
         // Compute twiddle factors, or take memoized ones if already available.
-        let mut twiddles_ref_mut = self.twiddles.borrow_mut();
-        let twiddles = twiddles_ref_mut.entry(log_h).or_insert_with(|| {
-            let root = F::two_adic_generator(log_h);
-            root.powers().take(1 << log_h).collect()
-        });
+        let twiddles = self.twiddles.roots(log_h);
 
         // DIT butterfly
         reverse_matrix_index_bits(&mut mat);
         for layer in 0..log_h {
-            dit_layer(&mut mat.as_view_mut(), layer, twiddles);
+            dit_layer(&mut mat.as_view_mut(), layer, &twiddles);
+        }
+        mat
+    }
+
+    fn idft_batch(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        self.inverse_dif(&mut mat);
+        reverse_matrix_index_bits(&mut mat);
+        mat
+    }
+
+    fn coset_lde_batch(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+
+        // The inverse DIF network leaves the coefficients in bit-reversed order, which is the
+        // order the DIT network wants its input in, so neither needs a separate permutation.
+        self.inverse_dif(&mut mat);
+        for (i, weight) in shift.powers().take(h).enumerate() {
+            mat.scale_row(reverse_bits_len(i, log_h), weight);
+        }
+        let mut mat = mat.bit_reversed_zero_pad(added_bits);
+
+        let log_lde_h = log_h + added_bits;
+        let twiddles = self.twiddles.roots(log_lde_h);
+        for layer in 0..log_lde_h {
+            dit_layer(&mut mat.as_view_mut(), layer, &twiddles);
         }
         mat
     }
 }
 
+impl<F: TwoAdicField> Radix2Dit<F> {
+    /// Run the inverse DFT of each column in place with a DIF network, scaling by the inverse of
+    /// the height in its last layer, which leaves the coefficients in bit-reversed order.
+    fn inverse_dif(&self, mat: &mut RowMajorMatrix<F>) {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+        if log_h == 0 {
+            return;
+        }
+
+        let mut inverse_twiddles_ref_mut = self.inverse_twiddles.borrow_mut();
+        let inverse_twiddles = inverse_twiddles_ref_mut.entry(log_h).or_insert_with(|| {
+            // `g^-i = g^(n - i)`, so the inverse powers are the powers in reverse after the first.
+            let roots = self.twiddles.roots(log_h);
+            iter::once(roots[0])
+                .chain((1..roots.len()).rev().map(|i| roots[i]))
+                .collect()
+        });
+
+        for layer in 0..log_h - 1 {
+            dif_layer(&mut mat.as_view_mut(), layer, inverse_twiddles);
+        }
+
+        // In the last layer every butterfly is twiddle-free, so scale by `1/h` there.
+        let h_inv = F::from_prime_subfield(F::PrimeSubfield::ONE.div_2exp_u64(log_h as u64));
+        let butterfly = ScaledTwiddleFreeButterfly(h_inv);
+        mat.par_row_chunks_exact_mut(2).for_each(|mut pair| {
+            let (mut hi, mut lo) = pair.split_rows_mut(1);
+            butterfly.apply_to_rows(hi.row_mut(0), lo.row_mut(0));
+        });
+    }
+}
+
+/// One layer of a DIF butterfly network, counting layers from the widest butterflies.
+pub(crate) fn dif_layer<F, T>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &T)
+where
+    F: Field,
+    T: Index<usize, Output = F> + Sync + ?Sized,
+{
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+
+    let half_block_size = 1 << (log_h - 1 - layer);
+    let block_size = half_block_size * 2;
+
+    mat.par_row_chunks_exact_mut(block_size)
+        .for_each(|mut block_chunks| {
+            let (mut hi_chunks, mut lo_chunks) = block_chunks.split_rows_mut(half_block_size);
+            hi_chunks
+                .par_rows_mut()
+                .zip(lo_chunks.par_rows_mut())
+                .enumerate()
+                .for_each(|(ind, (hi_chunk, lo_chunk))| {
+                    if ind == 0 {
+                        TwiddleFreeButterfly.apply_to_rows(hi_chunk, lo_chunk)
+                    } else {
+                        DifButterfly(twiddles[ind << layer]).apply_to_rows(hi_chunk, lo_chunk)
+                    }
+                });
+        });
+}
+
 /// One layer of a DIT butterfly network.
-fn dit_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &[F]) {
+pub(crate) fn dit_layer<F, T>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &T)
+where
+    F: Field,
+    T: Index<usize, Output = F> + Sync + ?Sized,
+{
     let h = mat.height();
     let log_h = log2_strict_usize(h);
     let layer_rev = log_h - 1 - layer;
@@ -2,7 +2,9 @@ use alloc::collections::BTreeMap;
 use alloc::slice;
 use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::marker::PhantomData;
 use core::mem::{MaybeUninit, transmute};
+use core::ops::Range;
 
 use itertools::{Itertools, izip};
 use p3_field::integers::QuotientMap;
@@ -16,7 +18,7 @@ use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 use tracing::{debug_span, instrument};
 
 use crate::TwoAdicSubgroupDft;
-use crate::butterflies::{Butterfly, DitButterfly};
+use crate::butterflies::{Butterfly, DifButterfly, DitButterfly, TwiddleFreeButterfly};
 
 /// A parallel FFT algorithm which divides a butterfly network's layers into two halves.
 ///
@@ -113,15 +115,12 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
             .entry(log_h)
             .or_insert_with(|| compute_twiddles(log_h));
 
-        let mid = log_h.div_ceil(2);
-
-        // The first half looks like a normal DIT.
-        reverse_matrix_index_bits(&mut mat);
-        first_half(&mut mat, mid, &twiddles.twiddles);
-
-        // For the second half, we flip the DIT, working in bit-reversed order.
-        reverse_matrix_index_bits(&mut mat);
-        second_half(&mut mat, mid, &twiddles.bitrev_twiddles, None);
+        let num_blocks = num_column_blocks(mat.width());
+        if num_blocks > 1 {
+            dft_column_blocks(&mut mat, num_blocks, twiddles);
+        } else {
+            dft_in_place(&mut mat, twiddles);
+        }
 
         mat.bit_reverse_rows()
     }
@@ -192,6 +191,115 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
     }
 }
 
+/// The fewest columns worth giving a task a block of, so that the part of a row each task touches
+/// spans several cache lines and neighbouring blocks rarely share one.
+const MIN_COLUMN_BLOCK_WIDTH: usize = 64;
+
+/// The number of contiguous column blocks to split a matrix of width `w` into, one per thread, or
+/// one if it's too narrow to be worth splitting.
+fn num_column_blocks(w: usize) -> usize {
+    current_num_threads().min(w / MIN_COLUMN_BLOCK_WIDTH).max(1)
+}
+
+/// Run the forward network on `mat` in place, leaving the result in bit-reversed order.
+fn dft_in_place<F: Field>(mat: &mut RowMajorMatrix<F>, twiddles: &VectorPair<F>) {
+    let log_h = log2_strict_usize(mat.height());
+    let mid = log_h.div_ceil(2);
+
+    // The first half looks like a normal DIT.
+    reverse_matrix_index_bits(mat);
+    first_half(mat, mid, &twiddles.twiddles);
+
+    // For the second half, we flip the DIT, working in bit-reversed order.
+    reverse_matrix_index_bits(mat);
+    second_half(mat, mid, &twiddles.bitrev_twiddles, None);
+}
+
+/// Like `dft_in_place`, but splitting `mat` into `num_blocks` blocks of adjacent columns, each
+/// transformed in place by a single rayon task with its own copy of the twiddles.
+///
+/// Splitting the rows of every layer across all threads, as `dft_in_place` does, has each thread
+/// touch every cache line of the matrix and of the shared twiddle tables, which stops scaling once
+/// there are many threads. Here each task runs a sequential DIF network over its own columns, so
+/// threads only share the cache lines where two blocks meet, and no data is copied.
+#[instrument(level = "debug", skip_all, fields(num_blocks = num_blocks))]
+fn dft_column_blocks<F: Field>(
+    mat: &mut RowMajorMatrix<F>,
+    num_blocks: usize,
+    twiddles: &VectorPair<F>,
+) {
+    let w = mat.width();
+    let h = mat.height();
+    let values = mat.values.as_mut_ptr();
+    let blocks = (0..num_blocks)
+        .map(|i| ColumnBlockMut {
+            values,
+            width: w,
+            height: h,
+            cols: (i * w / num_blocks)..((i + 1) * w / num_blocks),
+            _marker: PhantomData,
+        })
+        .collect_vec();
+
+    blocks.into_par_iter().for_each(|mut block| {
+        let twiddles = twiddles.twiddles.clone();
+        dif_column_block(&mut block, &twiddles);
+    });
+}
+
+/// A block of adjacent columns of a row-major matrix, mutated by one task while other tasks mutate
+/// the other blocks of the same matrix.
+struct ColumnBlockMut<'a, F> {
+    values: *mut F,
+    width: usize,
+    height: usize,
+    cols: Range<usize>,
+    _marker: PhantomData<&'a mut [F]>,
+}
+
+// SAFETY: A block only touches its own columns, which no other block covers.
+unsafe impl<F: Send> Send for ColumnBlockMut<'_, F> {}
+
+impl<F> ColumnBlockMut<'_, F> {
+    /// The block's parts of rows `r_1` and `r_2`, with `r_1 < r_2`.
+    fn row_pair_mut(&mut self, r_1: usize, r_2: usize) -> (&mut [F], &mut [F]) {
+        assert!(r_1 < r_2 && r_2 < self.height);
+        // SAFETY: The rows are distinct and in bounds, and only this block's columns of them are
+        // borrowed, for as long as the block is.
+        unsafe {
+            let row = |r: usize| {
+                slice::from_raw_parts_mut(
+                    self.values.add(r * self.width + self.cols.start),
+                    self.cols.len(),
+                )
+            };
+            (row(r_1), row(r_2))
+        }
+    }
+}
+
+/// Run the forward DIF network on the columns of `block`, which leaves them in bit-reversed order,
+/// as `dft_in_place` does. `twiddles` are the powers of the generator of the subgroup of order the
+/// height.
+fn dif_column_block<F: Field>(block: &mut ColumnBlockMut<'_, F>, twiddles: &[F]) {
+    let h = block.height;
+    let log_h = log2_strict_usize(h);
+    for layer in 0..log_h {
+        let half_block_size = 1 << (log_h - 1 - layer);
+        for block_start in (0..h).step_by(2 * half_block_size) {
+            for ind in 0..half_block_size {
+                let hi = block_start + ind;
+                let (hi_row, lo_row) = block.row_pair_mut(hi, hi + half_block_size);
+                if ind == 0 {
+                    TwiddleFreeButterfly.apply_to_rows(hi_row, lo_row);
+                } else {
+                    DifButterfly(twiddles[ind << layer]).apply_to_rows(hi_row, lo_row);
+                }
+            }
+        }
+    }
+}
+
 #[instrument(level = "debug", skip_all)]
 fn coset_dft<F: TwoAdicField + Ord>(
     dft: &Radix2DitParallel<F>,
@@ -521,3 +629,28 @@ fn dit_layer_rev<F: Field>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::Radix2Dit;
+
+    #[test]
+    fn column_blocks_match_radix_2_dit() {
+        type F = BabyBear;
+        let mut rng = SmallRng::seed_from_u64(1);
+        for log_h in 0..6 {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 10);
+            let mut blocked = mat.clone();
+            dft_column_blocks(&mut blocked, 3, &compute_twiddles(log_h));
+            assert_eq!(
+                blocked.bit_reverse_rows().to_row_major_matrix(),
+                Radix2Dit::default().dft_batch(mat)
+            );
+        }
+    }
+}
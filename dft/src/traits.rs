@@ -5,6 +5,8 @@ use p3_matrix::Matrix;
 use p3_matrix::bitrev::BitReversibleMatrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::swap_rows;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
 
 use crate::util::{coset_shift_cols, divide_by_height};
 
@@ -25,6 +27,22 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     /// methods can be derived from this one.
     fn dft_batch(&self, mat: RowMajorMatrix<F>) -> Self::Evaluations;
 
+    /// Compute the discrete Fourier transform (DFT) of each column of a column-major matrix,
+    /// given as the rows of `cols`, and return the result in the same layout.
+    ///
+    /// The default transposes `cols` into row-major order, runs `dft_batch`, and transposes back.
+    /// The row-major transform packs and parallelizes across columns, which outweighs the two
+    /// transposes even for wide traces.
+    fn dft_batch_colmajor(&self, cols: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        if cols.width() == 0 {
+            // Columns of height zero have nothing to transform.
+            return cols;
+        }
+        self.dft_batch(cols.transpose())
+            .to_row_major_matrix()
+            .transpose()
+    }
+
     /// Compute the "coset DFT" of `vec`. This can be viewed as interpolation onto a coset of a
     /// multiplicative subgroup, rather than the subgroup itself.
     fn coset_dft(&self, vec: Vec<F>, shift: F) -> Vec<F> {
@@ -123,4 +141,53 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         );
         self.coset_dft_batch(coeffs, shift)
     }
+
+    /// Compute the rows `which_coset, which_coset + 2^added_bits, ...` of
+    /// `coset_lde_batch(mat, added_bits, shift)`, which are the evaluations on the coset
+    /// `shift * g^which_coset * H` of the original subgroup `H`, where `g` generates the larger
+    /// subgroup.
+    ///
+    /// Only a transform of the original height is needed after interpolating, rather than one
+    /// `2^added_bits` times taller, as the butterflies producing the other cosets are skipped.
+    fn coset_lde_batch_subset(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+        which_coset: usize,
+    ) -> Self::Evaluations {
+        assert!(
+            which_coset < 1 << added_bits,
+            "There are only 2^added_bits cosets."
+        );
+        let log_h = log2_strict_usize(mat.height());
+        let coset_shift =
+            shift * F::two_adic_generator(log_h + added_bits).exp_u64(which_coset as u64);
+        let coeffs = self.idft_batch(mat);
+        self.coset_dft_batch(coeffs, coset_shift)
+    }
+
+    /// Like `coset_lde_batch`, but first randomizes each column of `mat` into a polynomial of
+    /// twice the degree, which still agrees with the column on the original subgroup.
+    ///
+    /// The rows of `mat` become the even rows of a matrix twice as tall, whose odd rows are those
+    /// of `random_rows`, which must have the same shape as `mat`. The result is the low-degree
+    /// extension of that matrix, of height `2 * mat.height() << added_bits`.
+    fn coset_lde_batch_zk(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+        random_rows: RowMajorMatrix<F>,
+    ) -> Self::Evaluations {
+        assert_eq!(mat.width(), random_rows.width());
+        assert_eq!(mat.height(), random_rows.height());
+        let width = mat.width();
+        let values = mat
+            .row_slices()
+            .zip(random_rows.row_slices())
+            .flat_map(|(row, random_row)| row.iter().chain(random_row).copied())
+            .collect();
+        self.coset_lde_batch(RowMajorMatrix::new(values, width), added_bits, shift)
+    }
 }
@@ -15,9 +15,10 @@ pub use bench_func::*;
 pub use dft_testing::*;
 use num_bigint::BigUint;
 use p3_field::{
-    ExtensionField, Field, PrimeCharacteristicRing, TwoAdicField,
-    cyclic_subgroup_coset_known_order, cyclic_subgroup_known_order,
-    two_adic_coset_vanishing_polynomial, two_adic_subgroup_vanishing_polynomial,
+    ExtensionField, Field, PackedValue, PrimeCharacteristicRing, PrimeField64, RootsOfUnityCache,
+    TwoAdicField, batch_multiplicative_inverse_packed, cyclic_subgroup_coset_known_order,
+    cyclic_subgroup_known_order, two_adic_coset_vanishing_polynomial,
+    two_adic_subgroup_vanishing_polynomial,
 };
 pub use packedfield_testing::*;
 use rand::distr::{Distribution, StandardUniform};
@@ -283,6 +284,30 @@ where
     }
 }
 
+pub fn test_batch_inverse<F: Field>()
+where
+    StandardUniform: Distribution<F>,
+{
+    let mut rng = SmallRng::seed_from_u64(1);
+    // Not a multiple of the chunk size, so that the packed and serial paths are both taken.
+    let xs: Vec<F> = (0..1027)
+        .map(|_| rng.random::<F>())
+        .filter(|x| !x.is_zero())
+        .collect();
+    let expected: Vec<F> = xs.iter().map(|x| x.inverse()).collect();
+    assert_eq!(F::batch_inverse(&xs), expected);
+    assert!(F::batch_inverse(&[]).is_empty());
+
+    let mut in_place = xs.clone();
+    F::batch_inverse_in_place(&mut in_place);
+    assert_eq!(in_place, expected);
+
+    let width = <F::Packing as PackedValue>::WIDTH;
+    let len = xs.len() - xs.len() % width;
+    let packed = batch_multiplicative_inverse_packed(F::Packing::pack_slice(&xs[..len]));
+    assert_eq!(F::Packing::unpack_slice(&packed), &expected[..len]);
+}
+
 pub fn test_dot_product<R: PrimeCharacteristicRing + Eq + Copy>(u: &[R; 64], v: &[R; 64]) {
     let mut dot = R::ZERO;
     assert_eq!(
@@ -563,6 +588,52 @@ pub fn test_generator<F: Field>(multiplicative_group_factors: &[(BigUint, u32)])
     }
 }
 
+pub fn test_prime_field_64_conversions<F: PrimeField64>() {
+    let p = F::ORDER_U64;
+    let mut rng = SmallRng::seed_from_u64(1);
+
+    for x in [0, 1, p / 2, p / 2 + 1, p - 1] {
+        let centered = F::from_u64(x).as_centered_i64();
+        assert!(centered.unsigned_abs() <= p / 2);
+        assert_eq!(F::from_i64(centered), F::from_u64(x));
+    }
+    assert_eq!(F::NEG_ONE.as_centered_i64(), -1);
+    assert_eq!(F::from_i64(-5), -F::from_u8(5));
+
+    for len in [0, 1, 7, 8, 9, 31, 64] {
+        let bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+        let expected = BigUint::from_bytes_le(&bytes) % p;
+        assert_eq!(
+            F::from_le_bytes_mod_order(&bytes).as_canonical_u64(),
+            expected.to_u64_digits().first().copied().unwrap_or(0)
+        );
+    }
+
+    let bytes: [u8; 16] = rng.random();
+    assert_eq!(
+        F::from_uniform_bytes(&bytes),
+        F::from_le_bytes_mod_order(&bytes)
+    );
+}
+
+pub fn test_roots_of_unity_table<F: TwoAdicField>() {
+    let table = F::roots_of_unity_table(6);
+    assert_eq!(table.max_bits(), 6);
+    for bits in 0..=6 {
+        let expected: Vec<F> =
+            cyclic_subgroup_known_order(F::two_adic_generator(bits), 1 << bits).collect();
+        assert_eq!(table.roots(bits).to_vec(), expected);
+    }
+
+    let cache = RootsOfUnityCache::<F>::default();
+    let small = cache.roots(3);
+    assert_eq!(small.to_vec(), table.roots(3).to_vec());
+    // Growing the table leaves earlier views valid.
+    assert_eq!(cache.roots(6).to_vec(), table.roots(6).to_vec());
+    assert_eq!(small.to_vec(), table.roots(3).to_vec());
+    assert_eq!(cache.roots(2).to_vec(), table.roots(2).to_vec());
+}
+
 pub fn test_two_adic_subgroup_vanishing_polynomial<F: TwoAdicField>() {
     for log_n in 0..5 {
         let g = F::two_adic_generator(log_n);
@@ -619,6 +690,10 @@ macro_rules! test_field {
                 $crate::test_inverse::<$field>();
             }
             #[test]
+            fn test_batch_inverse() {
+                $crate::test_batch_inverse::<$field>();
+            }
+            #[test]
             fn test_generator() {
                 $crate::test_generator::<$field>($factors);
             }
@@ -721,6 +796,11 @@ macro_rules! test_prime_field_64 {
             fn test_large_signed_integer_conversions() {
                 $crate::generate_from_large_i_int_tests!($field, <$field>::ORDER_U64, [i64, i128]);
             }
+
+            #[test]
+            fn test_conversion_helpers() {
+                $crate::test_prime_field_64_conversions::<$field>();
+            }
         }
     };
 }
@@ -774,6 +854,17 @@ macro_rules! test_prime_field_32 {
                 );
             }
 
+            #[test]
+            fn test_as_centered_i32() {
+                let half = <$field>::ORDER_U32 / 2;
+                assert_eq!(<$field>::NEG_ONE.as_centered_i32(), -1);
+                assert_eq!(<$field>::from_u32(half).as_centered_i32(), half as i32);
+                assert_eq!(
+                    <$field>::from_u32(half + 1).as_centered_i32(),
+                    -(half as i32)
+                );
+            }
+
             #[test]
             fn test_large_unsigned_integer_conversions() {
                 $crate::generate_from_large_u_int_tests!(
@@ -811,6 +902,10 @@ macro_rules! test_two_adic_field {
             fn test_two_adic_consistency() {
                 $crate::test_two_adic_generator_consistency::<$field>();
             }
+            #[test]
+            fn test_roots_of_unity_table() {
+                $crate::test_roots_of_unity_table::<$field>();
+            }
         }
     };
 }
@@ -321,6 +321,55 @@ where
     );
 }
 
+pub fn test_exp_power_of_2<PF>(special_vals: PF)
+where
+    PF: PackedField + Eq,
+    StandardUniform: Distribution<PF::Scalar>,
+{
+    let vec: PF = packed_from_random(0x3d1f7c2b9a5e4860);
+    for power_log in [0, 1, 5, 17] {
+        for x in [vec, special_vals] {
+            let res = x.exp_power_of_2(power_log);
+            for i in 0..PF::WIDTH {
+                assert_eq!(
+                    res.as_slice()[i],
+                    x.as_slice()[i].exp_power_of_2(power_log),
+                    "Error when testing exp_power_of_2 consistency of packed and scalar at location {}.",
+                    i
+                );
+            }
+        }
+    }
+}
+
+pub fn test_fma<PF>(special_vals: PF)
+where
+    PF: PackedField + Eq,
+    StandardUniform: Distribution<PF::Scalar>,
+{
+    let vec0: PF = packed_from_random(0x5c5a3f5d1b8c9a07);
+    let vec1: PF = packed_from_random(0x0e2d4f6a8b1c3e5f);
+    let vec2: PF = packed_from_random(0x7a9b1c2d3e4f5061);
+
+    for (a, b, c) in [
+        (vec0, vec1, vec2),
+        (special_vals, vec1, vec2),
+        (vec0, special_vals, vec2),
+        (vec0, vec1, special_vals),
+        (special_vals, special_vals, special_vals),
+    ] {
+        let res = a.fma(b, c);
+        for i in 0..PF::WIDTH {
+            assert_eq!(
+                res.as_slice()[i],
+                a.as_slice()[i] * b.as_slice()[i] + c.as_slice()[i],
+                "Error when testing fma consistency of packed and scalar at location {}.",
+                i
+            );
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! test_packed_field {
     ($packedfield:ty, $zeros:expr, $ones:expr, $specials:expr) => {
@@ -351,6 +400,14 @@ macro_rules! test_packed_field {
             fn test_mul_2exp_u64() {
                 $crate::test_mul_2exp_u64::<$packedfield>();
             }
+            #[test]
+            fn test_exp_power_of_2() {
+                $crate::test_exp_power_of_2::<$packedfield>($specials);
+            }
+            #[test]
+            fn test_fma() {
+                $crate::test_fma::<$packedfield>($specials);
+            }
         }
     };
 }
@@ -4,7 +4,10 @@ use p3_maybe_rayon::prelude::*;
 use tracing::instrument;
 
 use crate::field::Field;
-use crate::{FieldArray, PackedValue, PrimeCharacteristicRing};
+use crate::{FieldArray, PackedField, PackedValue, PrimeCharacteristicRing};
+
+/// How many elements to invert in one thread.
+const CHUNK_SIZE: usize = 1024;
 
 /// Batch multiplicative inverses with Montgomery's trick
 /// This is Montgomery's trick. At a high level, we invert the product of the given field
@@ -18,9 +21,6 @@ use crate::{FieldArray, PackedValue, PrimeCharacteristicRing};
 /// This will panic if any of the inputs is zero.
 #[instrument(level = "debug", skip_all)]
 pub fn batch_multiplicative_inverse<F: Field>(x: &[F]) -> Vec<F> {
-    // How many elements to invert in one thread.
-    const CHUNK_SIZE: usize = 1024;
-
     let n = x.len();
     let mut result = F::zero_vec(n);
 
@@ -33,6 +33,37 @@ pub fn batch_multiplicative_inverse<F: Field>(x: &[F]) -> Vec<F> {
     result
 }
 
+/// Like `batch_multiplicative_inverse`, but replaces each element of `x` with its inverse.
+///
+/// # Panics
+/// This will panic if any of the inputs is zero.
+#[instrument(level = "debug", skip_all)]
+pub fn batch_multiplicative_inverse_in_place<F: Field>(x: &mut [F]) {
+    // One scratch buffer for all chunks, rather than one per chunk.
+    let mut scratch = F::zero_vec(x.len());
+    x.par_chunks_mut(CHUNK_SIZE)
+        .zip(scratch.par_chunks_mut(CHUNK_SIZE))
+        .for_each(|(x, scratch)| {
+            batch_multiplicative_inverse_helper(x, scratch);
+            x.copy_from_slice(scratch);
+        });
+}
+
+/// Batch multiplicative inverses of packed field elements, inverting every lane.
+///
+/// The product of `x` is inverted lane by lane, so this costs `P::WIDTH` scalar inversions however
+/// long `x` is.
+///
+/// # Panics
+/// This will panic if any lane of any of the inputs is zero.
+pub fn batch_multiplicative_inverse_packed<P: PackedField>(x: &[P]) -> Vec<P> {
+    let mut result = P::zero_vec(x.len());
+    batch_multiplicative_inverse_general(x, &mut result, |x| {
+        P::from_fn(|i| x.as_slice()[i].inverse())
+    });
+    result
+}
+
 /// Like `batch_multiplicative_inverse`, but writes the result to the given output buffer.
 fn batch_multiplicative_inverse_helper<F: Field>(x: &[F], result: &mut [F]) {
     // Higher WIDTH increases instruction-level parallelism, but too high a value will cause us
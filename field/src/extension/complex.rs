@@ -1,4 +1,7 @@
-use super::{BinomialExtensionField, BinomiallyExtendable, HasTwoAdicBinomialExtension};
+use super::{
+    BinomialExtensionField, BinomiallyExtendable, BinomiallyExtendableAlgebra,
+    HasTwoAdicBinomialExtension,
+};
 use crate::{Algebra, Field, PrimeCharacteristicRing};
 
 pub type Complex<F> = BinomialExtensionField<F, 2>;
@@ -24,6 +27,8 @@ impl<F: ComplexExtendable> BinomiallyExtendable<2> for F {
     const EXT_GENERATOR: [Self; 2] = F::COMPLEX_GENERATOR.value;
 }
 
+impl<F: ComplexExtendable> BinomiallyExtendableAlgebra<F, 2> for F {}
+
 /// Convenience methods for complex extensions
 impl<R: PrimeCharacteristicRing> Complex<R> {
     #[inline(always)]
@@ -102,6 +107,11 @@ where
     const EXT_GENERATOR: [Self; D] = F::EXT_GENERATOR;
 }
 
+impl<F, const D: usize> BinomiallyExtendableAlgebra<Self, D> for Complex<F> where
+    F: HasComplexBinomialExtension<D>
+{
+}
+
 /// The complex extension of this field has a two-adic binomial extension.
 pub trait HasTwoAdicComplexBinomialExtension<const D: usize>:
     HasComplexBinomialExtension<D>
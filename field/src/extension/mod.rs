@@ -1,7 +1,7 @@
 use core::iter;
 
-use crate::ExtensionField;
 use crate::field::Field;
+use crate::{Algebra, ExtensionField};
 
 mod binomial_extension;
 mod complex;
@@ -17,7 +17,9 @@ pub use packed_binomial_extension::*;
 ///
 /// This exists if the polynomial ring `F[X]` has an irreducible polynomial `X^d-W`
 /// allowing us to define the binomial extension field `F[X]/(X^d-W)`.
-pub trait BinomiallyExtendable<const D: usize>: Field {
+pub trait BinomiallyExtendable<const D: usize>:
+    Field + BinomiallyExtendableAlgebra<Self, D>
+{
     const W: Self;
 
     /// DTH_ROOT = W^((n - 1)/D).
@@ -28,6 +30,19 @@ pub trait BinomiallyExtendable<const D: usize>: Field {
     const EXT_GENERATOR: [Self; D];
 }
 
+/// The multiplication of a binomial extension of `F` with coefficients in `Self`.
+///
+/// The provided method is the schoolbook one. Fields with a faster kernel for their own extensions
+/// override it, and every other implementation can be empty.
+pub trait BinomiallyExtendableAlgebra<F: Field, const D: usize>: Algebra<F> {
+    /// Multiply the elements of `F[X]/(X^D - w)` with coefficients `a` and `b`, writing the
+    /// coefficients of the product to `res`, which must start out as zero.
+    #[inline]
+    fn binomial_mul(a: &[Self; D], b: &[Self; D], res: &mut [Self; D], w: F) {
+        binomial_mul(a, b, res, w);
+    }
+}
+
 pub trait HasFrobenius<F: Field>: ExtensionField<F> {
     fn frobenius(&self) -> Self;
     fn repeated_frobenius(&self, count: usize) -> Self;
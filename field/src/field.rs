@@ -13,7 +13,10 @@ use serde::de::DeserializeOwned;
 use crate::exponentiation::bits_u64;
 use crate::integers::{QuotientMap, from_integer_types};
 use crate::packed::PackedField;
-use crate::{Packable, PackedFieldExtension};
+use crate::{
+    Packable, PackedFieldExtension, RootsOfUnityTable, batch_multiplicative_inverse,
+    batch_multiplicative_inverse_in_place,
+};
 
 /// A commutative ring, `R`, with prime characteristic, `p`.
 ///
@@ -603,6 +606,26 @@ pub trait Field:
         self.try_inverse().expect("Tried to invert zero")
     }
 
+    /// The multiplicative inverses of all the given field elements, computed with Montgomery's
+    /// trick, which takes a few inversions per chunk of a thousand or so elements rather than one
+    /// per element.
+    ///
+    /// # Panics
+    /// The function will panic if any of the field elements is `0`.
+    #[must_use]
+    fn batch_inverse(values: &[Self]) -> Vec<Self> {
+        batch_multiplicative_inverse(values)
+    }
+
+    /// Replace each of the given field elements with its multiplicative inverse, as in
+    /// `batch_inverse`.
+    ///
+    /// # Panics
+    /// The function will panic if any of the field elements is `0`.
+    fn batch_inverse_in_place(values: &mut [Self]) {
+        batch_multiplicative_inverse_in_place(values);
+    }
+
     /// The elementary function `halve(a) = a/2`.
     ///
     /// # Panics
@@ -701,6 +724,45 @@ pub trait PrimeField64: PrimeField {
         // A simple default which is optimal for some fields.
         self.as_canonical_u64()
     }
+
+    /// Return the representative of `value` closest to zero, which lies in the range
+    /// `-(ORDER_U64 - 1)/2 <= x <= (ORDER_U64 - 1)/2`.
+    ///
+    /// This is the inverse of `from_i64` on that range.
+    #[must_use]
+    #[inline]
+    fn as_centered_i64(&self) -> i64 {
+        let x = self.as_canonical_u64();
+        if x <= Self::ORDER_U64 / 2 {
+            x as i64
+        } else {
+            -((Self::ORDER_U64 - x) as i64)
+        }
+    }
+
+    /// Interpret `bytes` as a little-endian integer of any length and reduce it modulo
+    /// `ORDER_U64`.
+    #[must_use]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        // Horner's rule on 64-bit limbs, from the most significant. Only the least significant
+        // limb, which comes last, can be short.
+        bytes.rchunks(8).fold(Self::ZERO, |acc, limb| {
+            let mut buf = [0; 8];
+            buf[..limb.len()].copy_from_slice(limb);
+            acc.mul_2exp_u64(8 * limb.len() as u64) + Self::from_u64(u64::from_le_bytes(buf))
+        })
+    }
+
+    /// Map 16 uniformly random bytes to a field element.
+    ///
+    /// The bytes are read as a little-endian `u128` and reduced modulo `ORDER_U64`. As the order
+    /// is below `2^64`, the result is within statistical distance `2^-64` of uniform, so no
+    /// rejection sampling is needed.
+    #[must_use]
+    #[inline]
+    fn from_uniform_bytes(bytes: &[u8; 16]) -> Self {
+        Self::from_u128(u128::from_le_bytes(*bytes))
+    }
 }
 
 /// A prime field `ℤ/p` with order `p < 2^32`.
@@ -723,6 +785,14 @@ pub trait PrimeField32: PrimeField64 {
         // A simple default which is optimal for some fields.
         self.as_canonical_u32()
     }
+
+    /// Return the representative of `value` closest to zero, which lies in the range
+    /// `-(ORDER_U32 - 1)/2 <= x <= (ORDER_U32 - 1)/2`.
+    #[must_use]
+    #[inline]
+    fn as_centered_i32(&self) -> i32 {
+        self.as_centered_i64() as i32
+    }
 }
 
 /// A field `EF` which is also an algebra over a field `F`.
@@ -769,6 +839,15 @@ pub trait TwoAdicField: Field {
     /// Assumes `bits <= TWO_ADICITY`, otherwise the result is undefined.
     #[must_use]
     fn two_adic_generator(bits: usize) -> Self;
+
+    /// The powers of `two_adic_generator(max_bits)`, from which those of every
+    /// `two_adic_generator(bits)` with `bits <= max_bits` are read as strided views, as each
+    /// smaller generator is the square of the next.
+    /// Assumes `max_bits <= TWO_ADICITY`.
+    #[must_use]
+    fn roots_of_unity_table(max_bits: usize) -> RootsOfUnityTable<Self> {
+        RootsOfUnityTable::new(Self::two_adic_generator(max_bits), max_bits)
+    }
 }
 
 /// An iterator which returns the powers of a base element `b` shifted by current `c`: `c, c * b, c * b^2, ...`.
@@ -13,9 +13,11 @@ mod field;
 mod helpers;
 pub mod integers;
 mod packed;
+mod roots_of_unity;
 
 pub use array::*;
 pub use batch_inverse::*;
 pub use field::*;
 pub use helpers::*;
 pub use packed::*;
+pub use roots_of_unity::*;
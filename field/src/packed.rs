@@ -178,6 +178,21 @@ pub unsafe trait PackedField: Algebra<Self::Scalar>
         let combined: [Self; N] = array::from_fn(|i| vecs[i] * coeffs[i]);
         Self::sum_array::<N>(&combined)
     }
+
+    /// The fused multiply-add `fma(a, b, c) = a * b + c`.
+    ///
+    /// Packings whose multiplication forms a double-width product before reducing it override
+    /// this to add `c` to the product, saving the separate modular addition. Only the Goldilocks
+    /// AVX2 and AVX-512 packings do so far.
+    ///
+    /// Fields and extension fields act as their own packing of width one, for which this is the
+    /// plain `a * b + c`. This is why the FRI and circle folding loops, which still work on scalar
+    /// extension elements, don't call it.
+    #[must_use]
+    #[inline(always)]
+    fn fma(self, b: Self, c: Self) -> Self {
+        self * b + c
+    }
 }
 
 /// # Safety
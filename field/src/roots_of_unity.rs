@@ -0,0 +1,122 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Index;
+
+use spin::RwLock;
+
+use crate::{Field, TwoAdicField};
+
+/// The powers of a generator of a subgroup of order `2^max_bits`, from which the powers of the
+/// generator of every smaller subgroup are read as a strided view, as each smaller generator is
+/// a power of two of the largest.
+///
+/// Only the powers of the largest generator are stored, and views share them rather than copy.
+#[derive(Clone, Debug)]
+pub struct RootsOfUnityTable<F> {
+    max_bits: usize,
+    powers: Arc<[F]>,
+}
+
+impl<F: Field> RootsOfUnityTable<F> {
+    /// The table of the powers of `generator`, which must have order `2^max_bits`.
+    pub fn new(generator: F, max_bits: usize) -> Self {
+        Self {
+            max_bits,
+            powers: generator.powers().take(1 << max_bits).collect(),
+        }
+    }
+
+    /// The log2 of the order of the largest subgroup in the table.
+    pub const fn max_bits(&self) -> usize {
+        self.max_bits
+    }
+
+    /// The powers `h^0, ..., h^(2^bits - 1)` of `h = g^(2^(max_bits - bits))`, the generator of
+    /// the subgroup of order `2^bits`, where `g` is the generator the table was built from.
+    ///
+    /// # Panics
+    /// Panics if `bits > max_bits`.
+    pub fn roots(&self, bits: usize) -> RootsOfUnity<F> {
+        assert!(
+            bits <= self.max_bits,
+            "The table only holds subgroups of order up to 2^{}.",
+            self.max_bits
+        );
+        RootsOfUnity {
+            powers: self.powers.clone(),
+            log_stride: self.max_bits - bits,
+            len: 1 << bits,
+        }
+    }
+}
+
+/// The powers of the generator of a subgroup of order `2^bits`, viewed in a `RootsOfUnityTable`
+/// of a larger subgroup.
+#[derive(Clone, Debug)]
+pub struct RootsOfUnity<F> {
+    powers: Arc<[F]>,
+    log_stride: usize,
+    len: usize,
+}
+
+impl<F: Copy> RootsOfUnity<F> {
+    /// The order of the subgroup.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = F> + '_ {
+        (0..self.len).map(|i| self[i])
+    }
+
+    pub fn to_vec(&self) -> Vec<F> {
+        self.iter().collect()
+    }
+}
+
+impl<F> Index<usize> for RootsOfUnity<F> {
+    type Output = F;
+
+    fn index(&self, i: usize) -> &F {
+        assert!(i < self.len, "Root index out of bounds.");
+        &self.powers[i << self.log_stride]
+    }
+}
+
+/// A lazily computed `TwoAdicField::roots_of_unity_table`, recomputed for a larger subgroup the
+/// first time one is asked for.
+///
+/// The table for a subgroup holds the roots of all smaller subgroups, so one cache serves
+/// transforms of every size up to the largest asked for, as in `Radix2Dit`'s twiddles. It can be
+/// shared across threads, and views handed out before the table grows stay valid.
+#[derive(Debug, Default)]
+pub struct RootsOfUnityCache<F> {
+    table: RwLock<Option<RootsOfUnityTable<F>>>,
+}
+
+impl<F: Clone> Clone for RootsOfUnityCache<F> {
+    fn clone(&self) -> Self {
+        Self {
+            table: RwLock::new(self.table.read().clone()),
+        }
+    }
+}
+
+impl<F: TwoAdicField> RootsOfUnityCache<F> {
+    /// The powers `g^0, ..., g^(2^bits - 1)` of `g = F::two_adic_generator(bits)`.
+    pub fn roots(&self, bits: usize) -> RootsOfUnity<F> {
+        if let Some(table) = self.table.read().as_ref().filter(|t| t.max_bits() >= bits) {
+            return table.roots(bits);
+        }
+        // Another thread may have grown the table since it was read.
+        let mut table = self.table.write();
+        if !table.as_ref().is_some_and(|t| t.max_bits() >= bits) {
+            *table = Some(F::roots_of_unity_table(bits));
+        }
+        table.as_ref().unwrap().roots(bits)
+    }
+}
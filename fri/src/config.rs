@@ -1,20 +1,104 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
+use core::mem::size_of;
 
+use p3_commit::Mmcs;
 use p3_field::Field;
 use p3_matrix::Matrix;
 
 #[derive(Debug)]
 pub struct FriConfig<M> {
+    /// The log of the blowup of the input codewords. Inputs given to
+    /// `prover::prove_with_log_blowups` may have larger blowups, but are tested at this rate, so it
+    /// alone decides the soundness of each query.
     pub log_blowup: usize,
     // TODO: This parameter and FRI early stopping are not yet implemented in `CirclePcs`.
     pub log_final_poly_len: usize,
+    /// The log of the length of the longest final polynomial the prover may send instead of
+    /// folding further. Folding stops early, within this bound, rather than finish with a round
+    /// that folds by less than its scheduled arity.
+    pub log_max_final_poly_len: usize,
+    /// The log of the arity of each folding round, in order, with any rounds past the end of the
+    /// schedule folding by two. A round folds by less when it would otherwise fold past the next
+    /// input codeword or the final polynomial.
+    pub log_folding_arities: Vec<usize>,
+    /// Whether each folding round samples an out-of-domain point at which the prover evaluates the
+    /// committed codeword, as in [DEEP-FRI](https://eprint.iacr.org/2019/336), and folds the
+    /// quotient by that evaluation instead of the codeword itself.
+    pub deep_sampling: bool,
     pub num_queries: usize,
     pub proof_of_work_bits: usize,
+    /// Whether to search for the proof of work witness sequentially from zero, rather than in
+    /// parallel, so that proving the same statement twice gives byte-identical proofs.
+    pub deterministic_grinding: bool,
     pub mmcs: M,
 }
 
+/// The bound on the distance of a codeword from the code up to which FRI queries are assumed to
+/// catch a false claim, which decides how much soundness each query gives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecurityAssumption {
+    /// Proven soundness within the unique decoding radius `(1 - rate) / 2`.
+    UniqueDecoding,
+    /// Proven soundness within the Johnson bound `1 - sqrt(rate)`, in the list decoding regime.
+    JohnsonBound,
+    /// The [ethSTARK](https://eprint.iacr.org/2021/582) conjecture that soundness holds up to
+    /// capacity, `1 - rate`.
+    CapacityBound,
+}
+
 impl<M> FriConfig<M> {
+    /// The most proof of work bits `with_security_bits` asks for.
+    pub const MAX_PROOF_OF_WORK_BITS: usize = 16;
+
+    /// Creates a config targeting `security_bits` bits of query phase soundness under
+    /// `assumption`, with a rate of `2^-log_blowup`.
+    ///
+    /// Proof of work supplies half of the target, up to `MAX_PROOF_OF_WORK_BITS`, and queries the
+    /// rest. The challenge field must be large enough that the commit phase isn't the weaker link.
+    pub fn with_security_bits(
+        mmcs: M,
+        security_bits: usize,
+        log_blowup: usize,
+        assumption: SecurityAssumption,
+    ) -> Self {
+        assert!(log_blowup > 0, "FRI needs a rate below 1 to be sound");
+        let proof_of_work_bits = (security_bits / 2).min(Self::MAX_PROOF_OF_WORK_BITS);
+        let query_bits = security_bits - proof_of_work_bits;
+
+        let num_queries = match assumption {
+            SecurityAssumption::UniqueDecoding => {
+                // A query catches a word at the unique decoding radius with probability
+                // `(1 - rate) / 2`, so find the fewest queries all missing it with probability at
+                // most `2^-query_bits`.
+                let miss = (1.0 + 1.0 / (1u64 << log_blowup) as f64) / 2.0;
+                let target = (0..query_bits).fold(1.0, |p: f64, _| p / 2.0);
+                let mut p = 1.0;
+                let mut num_queries = 0;
+                while p > target {
+                    p *= miss;
+                    num_queries += 1;
+                }
+                num_queries
+            }
+            // Each query gives `-log2(sqrt(rate)) = log_blowup / 2` bits.
+            SecurityAssumption::JohnsonBound => (2 * query_bits).div_ceil(log_blowup),
+            SecurityAssumption::CapacityBound => query_bits.div_ceil(log_blowup),
+        };
+
+        Self {
+            log_blowup,
+            log_final_poly_len: 0,
+            log_max_final_poly_len: 0,
+            log_folding_arities: Vec::new(),
+            deep_sampling: false,
+            num_queries,
+            proof_of_work_bits,
+            deterministic_grinding: false,
+            mmcs,
+        }
+    }
+
     pub const fn blowup(&self) -> usize {
         1 << self.log_blowup
     }
@@ -23,14 +107,111 @@ impl<M> FriConfig<M> {
         1 << self.log_final_poly_len
     }
 
+    /// The log of the arity scheduled for folding round `round`.
+    pub fn log_folding_arity(&self, round: usize) -> usize {
+        self.log_folding_arities.get(round).copied().unwrap_or(1)
+    }
+
+    /// The log of the arity with which folding round `round` folds a codeword of height
+    /// `2^log_height`, without folding past height `2^log_next_height` or the final polynomial.
+    pub(crate) fn round_log_arity(
+        &self,
+        round: usize,
+        log_height: usize,
+        log_next_height: usize,
+    ) -> usize {
+        let log_next_height = log_next_height.max(self.log_blowup + self.log_final_poly_len);
+        self.log_folding_arity(round)
+            .min(log_height.saturating_sub(log_next_height))
+            .max(1)
+    }
+
+    /// Whether folding stops before round `round`, leaving a codeword of height `2^log_height` for
+    /// the final polynomial, when the next input codeword has height `2^log_next_height`.
+    pub(crate) fn folding_stops(
+        &self,
+        round: usize,
+        log_height: usize,
+        log_next_height: usize,
+    ) -> bool {
+        let log_min_final_height = self.log_blowup + self.log_final_poly_len;
+        if log_height <= log_min_final_height {
+            return true;
+        }
+        log_height <= self.log_blowup + self.log_max_final_poly_len
+            && log_next_height <= log_min_final_height
+            && self.log_folding_arity(round) > log_height - log_min_final_height
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
-    /// Certain users may instead want to look at proven soundness, a more complex calculation which
-    /// isn't currently supported by this crate.
+    /// Certain users may instead want to look at proven soundness; `proof_size_estimate` gives a
+    /// simplified lower bound for it.
     pub const fn conjectured_soundness_bits(&self) -> usize {
         self.log_blowup * self.num_queries + self.proof_of_work_bits
     }
+
+    /// Estimate the size of a proof that the codewords of `num_inputs` inputs, the longest of
+    /// length `2^log_max_word_len`, are close to low degree, without generating one.
+    ///
+    /// Field elements and digests are sized by their in-memory representation, and each input is
+    /// assumed to be committed in its own full-height tree, opened at one value per query. The
+    /// proof of work witness and any serialization overhead are ignored.
+    pub fn proof_size_estimate<F: Field>(
+        &self,
+        log_max_word_len: usize,
+        num_inputs: usize,
+    ) -> FriProofSizeEstimate
+    where
+        M: Mmcs<F>,
+    {
+        let digest_bytes = size_of::<M::Commitment>();
+        let elem_bytes = size_of::<F>();
+
+        let mut num_rounds = 0;
+        let mut query_bytes = num_inputs * (log_max_word_len * digest_bytes + elem_bytes);
+        let mut log_height = log_max_word_len;
+        while !self.folding_stops(num_rounds, log_height, 0) {
+            let log_arity = self.round_log_arity(num_rounds, log_height, 0);
+            query_bytes += ((1 << log_arity) - 1) * elem_bytes;
+            query_bytes += (log_height - log_arity) * digest_bytes;
+            log_height -= log_arity;
+            num_rounds += 1;
+        }
+        let final_poly_len = 1 << log_height.saturating_sub(self.log_blowup);
+
+        // Each query rejects a word in the Johnson radius with probability at least
+        // `1 - sqrt(rate)`, while the commit phase is limited by the size of the field relative to
+        // the longest codeword.
+        let query_bits = self.num_queries * self.log_blowup / 2 + self.proof_of_work_bits;
+        let commit_phase_bits = F::bits().saturating_sub(2 * log_max_word_len);
+
+        let round_bytes = digest_bytes + if self.deep_sampling { elem_bytes } else { 0 };
+
+        FriProofSizeEstimate {
+            bytes: num_rounds * round_bytes
+                + self.num_queries * query_bytes
+                + final_poly_len * elem_bytes,
+            num_rounds,
+            conjectured_soundness_bits: self.conjectured_soundness_bits(),
+            proven_soundness_bits: query_bits.min(commit_phase_bits),
+        }
+    }
+}
+
+/// The expected size and soundness of a FRI proof, as estimated by
+/// `FriConfig::proof_size_estimate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriProofSizeEstimate {
+    /// The approximate size of the proof in bytes.
+    pub bytes: usize,
+    /// The number of commit phase rounds.
+    pub num_rounds: usize,
+    /// Soundness bits under the ethSTARK conjecture.
+    pub conjectured_soundness_bits: usize,
+    /// A simplified lower bound on provable soundness bits, from the Johnson bound.
+    pub proven_soundness_bits: usize,
 }
 
 /// Whereas `FriConfig` encompasses parameters the end user can set, `FriGenericConfig` is
@@ -44,8 +225,8 @@ pub trait FriGenericConfig<F: Field> {
     fn extra_query_index_bits(&self) -> usize;
 
     /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
+    /// The input row will always be 2 columns wide; FRI folds by higher arities by folding
+    /// repeatedly.
     fn fold_row(
         &self,
         index: usize,
@@ -67,8 +248,12 @@ pub const fn create_test_fri_config<Mmcs>(
     FriConfig {
         log_blowup: 2,
         log_final_poly_len,
+        log_max_final_poly_len: log_final_poly_len,
+        log_folding_arities: Vec::new(),
+        deep_sampling: false,
         num_queries: 2,
         proof_of_work_bits: 1,
+        deterministic_grinding: false,
         mmcs,
     }
 }
@@ -79,8 +264,12 @@ pub const fn create_benchmark_fri_config<Mmcs>(mmcs: Mmcs) -> FriConfig<Mmcs> {
     FriConfig {
         log_blowup: 1,
         log_final_poly_len: 0,
+        log_max_final_poly_len: 0,
+        log_folding_arities: Vec::new(),
+        deep_sampling: false,
         num_queries: 100,
         proof_of_work_bits: 16,
+        deterministic_grinding: false,
         mmcs,
     }
 }
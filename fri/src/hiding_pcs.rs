@@ -8,7 +8,7 @@ use p3_dft::TwoAdicSubgroupDft;
 use p3_field::coset::TwoAdicMultiplicativeCoset;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Matrix;
-use p3_matrix::bitrev::BitReversalPerm;
+use p3_matrix::bitrev::{BitReversalPerm, BitReversibleMatrix};
 use p3_matrix::dense::{DenseMatrix, RowMajorMatrix};
 use p3_matrix::horizontally_truncated::HorizontallyTruncated;
 use p3_matrix::row_index_mapped::RowIndexMappedView;
@@ -44,6 +44,11 @@ impl<Val, Dft, InputMmcs, FriMmcs, R> HidingFriPcs<Val, Dft, InputMmcs, FriMmcs,
             rng: rng.into(),
         }
     }
+
+    /// The FRI parameters openings are proven with.
+    pub const fn fri_config(&self) -> &FriConfig<FriMmcs> {
+        self.inner.fri_config()
+    }
 }
 
 impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger, R> Pcs<Challenge, Challenger>
@@ -53,7 +58,9 @@ where
     StandardUniform: Distribution<Val>,
     Dft: TwoAdicSubgroupDft<Val>,
     InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
+    FriMmcs: Mmcs<Challenge> + Sync,
+    FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    FriMmcs::Proof: Send,
     Challenge: TwoAdicField + ExtensionField<Val>,
     Challenger:
         FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
@@ -74,11 +81,19 @@ where
     );
     type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
 
+    const ZK: bool = true;
+
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
         <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
             &self.inner, degree)
     }
 
+    fn num_queries(&self) -> Option<usize> {
+        <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::num_queries(
+            &self.inner,
+        )
+    }
+
     fn commit(
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
@@ -98,6 +113,30 @@ where
         )
     }
 
+    fn commit_zk(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let rng = &mut *self.rng.borrow_mut();
+        let ldes = evaluations
+            .into_iter()
+            .map(|(domain, mat)| {
+                assert_eq!(domain.size(), mat.height());
+                let mat = add_random_cols(mat, self.num_random_codewords, &mut *rng);
+                let random_rows = RowMajorMatrix::rand(rng, mat.height(), mat.width());
+                // The randomized matrix lives on the domain of twice the size with the same shift,
+                // so its low-degree extension is taken to the same coset as in `commit`.
+                let shift = Val::GENERATOR / domain.shift();
+                self.inner
+                    .dft
+                    .coset_lde_batch_zk(mat, self.inner.fri.log_blowup, shift, random_rows)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix()
+            })
+            .collect();
+        self.inner.mmcs.commit(ldes)
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
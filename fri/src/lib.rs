@@ -7,8 +7,10 @@ extern crate alloc;
 mod config;
 mod fold_even_odd;
 mod hiding_pcs;
+pub mod presets;
 mod proof;
 pub mod prover;
+pub mod stir;
 mod two_adic_pcs;
 pub mod verifier;
 
@@ -0,0 +1,44 @@
+//! Ready-made `FriConfig`s trading prover time against proof size at a given security level.
+//!
+//! Every preset targets `security_bits` bits under the ethSTARK conjecture and keeps
+//! `log_final_poly_len` at zero, so any input a PCS commits to can be folded. They differ in the
+//! rate, the folding arities and how early folding may stop:
+//!
+//! - [`fast_prover`] uses the highest rate and folds by two, keeping the LDE and every commit
+//!   phase tree as small as possible at the cost of many queries.
+//! - [`small_proof`] uses a low rate, folds by eight and may stop with a final polynomial of up to
+//!   eight coefficients, giving few queries with short authentication paths.
+//! - [`balanced`] sits in between, folding by four.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{FriConfig, SecurityAssumption};
+
+/// A folding schedule that folds by `2^log_arity` in every round of any two-adic codeword.
+fn uniform_arities(log_arity: usize) -> Vec<usize> {
+    vec![log_arity; 32]
+}
+
+/// A rate 1/2 config folding by two, for the fastest proving.
+pub fn fast_prover<M>(mmcs: M, security_bits: usize) -> FriConfig<M> {
+    FriConfig::with_security_bits(mmcs, security_bits, 1, SecurityAssumption::CapacityBound)
+}
+
+/// A rate 1/8 config folding by eight, for the smallest proofs.
+pub fn small_proof<M>(mmcs: M, security_bits: usize) -> FriConfig<M> {
+    FriConfig {
+        log_max_final_poly_len: 3,
+        log_folding_arities: uniform_arities(3),
+        ..FriConfig::with_security_bits(mmcs, security_bits, 3, SecurityAssumption::CapacityBound)
+    }
+}
+
+/// A rate 1/4 config folding by four.
+pub fn balanced<M>(mmcs: M, security_bits: usize) -> FriConfig<M> {
+    FriConfig {
+        log_max_final_poly_len: 2,
+        log_folding_arities: uniform_arities(2),
+        ..FriConfig::with_security_bits(mmcs, security_bits, 2, SecurityAssumption::CapacityBound)
+    }
+}
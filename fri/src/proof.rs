@@ -1,6 +1,8 @@
 use alloc::vec::Vec;
+use core::iter::zip;
 
-use p3_commit::Mmcs;
+use itertools::zip_eq;
+use p3_commit::{Mmcs, PathMmcs};
 use p3_field::Field;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +13,9 @@ use serde::{Deserialize, Serialize};
 ))]
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
+    /// With `FriConfig::deep_sampling`, the evaluation of each commit phase codeword at the
+    /// out-of-domain point sampled after its commitment. Empty otherwise.
+    pub deep_evals: Vec<F>,
     pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
     pub final_poly: Vec<F>,
     pub pow_witness: Witness,
@@ -31,10 +36,204 @@ pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The openings of the commit phase codeword at the other locations in the queried coset, in
+    /// order. A round folding by `2^log_arity` has `2^log_arity - 1` siblings.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
+
+/// A `FriProof` storing each node shared by the commit phase opening paths of several queries once.
+///
+/// Queries whose indices agree above some level of a commit phase tree have the same path nodes
+/// from that level up, so each path keeps only the nodes below the part it shares with an earlier
+/// query's path.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
+))]
+pub struct CompressedFriProof<F: Field, M: PathMmcs<F>, Witness, InputProof> {
+    pub commit_phase_commits: Vec<M::Commitment>,
+    pub deep_evals: Vec<F>,
+    pub query_proofs: Vec<CompressedQueryProof<F, M, InputProof>>,
+    pub final_poly: Vec<F>,
+    pub pow_witness: Witness,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "InputProof: Serialize",
+    deserialize = "InputProof: Deserialize<'de>",
+))]
+pub struct CompressedQueryProof<F: Field, M: PathMmcs<F>, InputProof> {
+    pub input_proof: InputProof,
+    pub commit_phase_openings: Vec<CompressedCommitPhaseProofStep<F, M>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct CompressedCommitPhaseProofStep<F: Field, M: PathMmcs<F>> {
+    pub sibling_values: Vec<F>,
+    /// The nodes of the opening path below the part shared with `shared_with`.
+    pub path_nodes: Vec<M::Node>,
+    /// The earlier query whose opening path of the same round continues this one, if any.
+    pub shared_with: Option<usize>,
+}
+
+impl<F, M, Witness, InputProof> FriProof<F, M, Witness, InputProof>
+where
+    F: Field,
+    M: PathMmcs<F>,
+{
+    /// Drop the nodes each commit phase opening path shares with that of an earlier query.
+    pub fn compress(self) -> CompressedFriProof<F, M, Witness, InputProof> {
+        let paths = self
+            .query_proofs
+            .iter()
+            .map(|qp| {
+                qp.commit_phase_openings
+                    .iter()
+                    .map(|step| M::proof_path(&step.opening_proof))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let compressed_paths = share_path_tops(&paths);
+
+        let query_proofs = zip_eq(self.query_proofs, compressed_paths)
+            .map(|(qp, paths)| CompressedQueryProof {
+                input_proof: qp.input_proof,
+                commit_phase_openings: zip_eq(qp.commit_phase_openings, paths)
+                    .map(
+                        |(step, (path_nodes, shared_with))| CompressedCommitPhaseProofStep {
+                            sibling_values: step.sibling_values,
+                            path_nodes,
+                            shared_with,
+                        },
+                    )
+                    .collect(),
+            })
+            .collect();
+
+        CompressedFriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            deep_evals: self.deep_evals,
+            query_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        }
+    }
+}
+
+/// For the paths `paths[query][i]` of each query, the nodes of each path below the part it shares
+/// with the `i`-th path of an earlier query, and that query, if any.
+pub(crate) fn share_path_tops<N: Clone + PartialEq>(
+    paths: &[Vec<&[N]>],
+) -> Vec<Vec<(Vec<N>, Option<usize>)>> {
+    paths
+        .iter()
+        .enumerate()
+        .map(|(query, query_paths)| {
+            query_paths
+                .iter()
+                .enumerate()
+                .map(|(i, &path)| {
+                    let earlier = paths[..query].iter().map(|p| p.get(i).copied());
+                    match longest_shared_top(path, earlier) {
+                        Some((shared_with, shared_len)) => {
+                            (path[..path.len() - shared_len].to_vec(), Some(shared_with))
+                        }
+                        None => (path.to_vec(), None),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The inverse of `share_path_tops`, or `None` if a path continues one that isn't from an earlier
+/// query, or that is too short.
+pub(crate) fn restore_path_tops<N: Clone>(
+    compressed: Vec<Vec<(Vec<N>, Option<usize>)>>,
+) -> Option<Vec<Vec<Vec<N>>>> {
+    let mut paths: Vec<Vec<Vec<N>>> = Vec::with_capacity(compressed.len());
+    for query_paths in compressed {
+        let query_paths = query_paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, (mut path, shared_with))| {
+                if let Some(shared_with) = shared_with {
+                    let shared = paths.get(shared_with)?.get(i)?;
+                    path.extend_from_slice(shared.get(path.len()..)?);
+                }
+                Some(path)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        paths.push(query_paths);
+    }
+    Some(paths)
+}
+
+/// The index of the path among `others` which shares the most nodes at its top with `path`, if
+/// any, and the number of nodes shared. Only paths of the same length are considered, and ties go
+/// to the earliest.
+fn longest_shared_top<'a, N: PartialEq + 'a>(
+    path: &[N],
+    others: impl Iterator<Item = Option<&'a [N]>>,
+) -> Option<(usize, usize)> {
+    let mut best = None;
+    for (index, other) in others.enumerate() {
+        let Some(other) = other.filter(|other| other.len() == path.len()) else {
+            continue;
+        };
+        let shared_len = zip(path.iter().rev(), other.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if shared_len > best.map_or(0, |(_, len)| len) {
+            best = Some((index, shared_len));
+        }
+    }
+    best
+}
+
+impl<F, M, Witness, InputProof> CompressedFriProof<F, M, Witness, InputProof>
+where
+    F: Field,
+    M: PathMmcs<F>,
+{
+    /// Restore the full commit phase opening paths, or return `None` if a path continues one that
+    /// isn't from an earlier query, or that is too short.
+    pub fn decompress(self) -> Option<FriProof<F, M, Witness, InputProof>> {
+        let compressed_paths = self
+            .query_proofs
+            .iter()
+            .map(|qp| {
+                qp.commit_phase_openings
+                    .iter()
+                    .map(|step| (step.path_nodes.clone(), step.shared_with))
+                    .collect()
+            })
+            .collect();
+        let paths = restore_path_tops(compressed_paths)?;
+
+        let query_proofs = zip(self.query_proofs, paths)
+            .map(|(qp, paths)| QueryProof {
+                input_proof: qp.input_proof,
+                commit_phase_openings: zip(qp.commit_phase_openings, paths)
+                    .map(|(step, path)| CommitPhaseProofStep {
+                        sibling_values: step.sibling_values,
+                        opening_proof: M::proof_from_path(path),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Some(FriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            deep_evals: self.deep_evals,
+            query_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        })
+    }
+}
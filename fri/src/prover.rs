@@ -2,18 +2,19 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::iter;
 
-use itertools::{Itertools, izip};
+use itertools::{Itertools, zip_eq};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::{ExtensionField, Field, TwoAdicField};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_interpolation::interpolate_subgroup;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_slice_index_bits};
 use tracing::{debug_span, info_span, instrument};
 
 use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof};
 
-#[instrument(name = "FRI prover", skip_all)]
 pub fn prove<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
@@ -24,7 +25,40 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
 where
     Val: Field,
     Challenge: ExtensionField<Val> + TwoAdicField,
-    M: Mmcs<Challenge>,
+    M: Mmcs<Challenge> + Sync,
+    M::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    M::Proof: Send,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+{
+    let inputs = inputs
+        .into_iter()
+        .map(|input| (config.log_blowup, input))
+        .collect();
+    prove_with_log_blowups(g, config, inputs, challenger, open_input)
+}
+
+/// Like `prove`, but with each input paired with the log of its own blowup, which may be larger
+/// than `config.log_blowup`.
+///
+/// An input of height `2^h` with a larger blowup `b` is a codeword of a polynomial `p` of degree
+/// less than `2^(h - b)`. Before it is folded in, it is replaced by the codeword of
+/// `p(x) (1 + gamma x^k)`, where `k = 2^(h - config.log_blowup) - 2^(h - b)`, so that the low
+/// degree test at rate `2^-config.log_blowup` also enforces the smaller degree bound of `p`.
+#[instrument(name = "FRI prover", skip_all)]
+pub fn prove_with_log_blowups<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    inputs: Vec<(usize, Vec<Challenge>)>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> G::InputProof,
+) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge> + Sync,
+    M::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    M::Proof: Send,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
@@ -33,45 +67,133 @@ where
         inputs
             .iter()
             .tuple_windows()
-            .all(|(l, r)| l.len() >= r.len()),
+            .all(|((_, l), (_, r))| l.len() >= r.len()),
         "Inputs are not sorted in descending order of length."
     );
+    assert!(
+        inputs
+            .iter()
+            .all(|&(log_blowup, _)| log_blowup >= config.log_blowup),
+        "Inputs must have at least the configured blowup."
+    );
 
-    let log_max_height = log2_strict_usize(inputs[0].len());
-    let log_min_height = log2_strict_usize(inputs.last().unwrap().len());
+    let log_max_height = log2_strict_usize(inputs[0].1.len());
+    let log_min_height = log2_strict_usize(inputs.last().unwrap().1.len());
     if config.log_final_poly_len > 0 {
         assert!(log_min_height > config.log_final_poly_len + config.log_blowup);
     }
 
+    let alpha: Challenge = challenger.sample_algebra_element();
+    let gamma: Challenge = challenger.sample_algebra_element();
+
+    // Bring the inputs with larger blowups up to the configured degree bound, then combine the
+    // inputs of each length, so that each length is committed and folded in once.
+    let inputs = inputs
+        .into_iter()
+        .map(|(log_blowup, input)| correct_degree(config, log_blowup, gamma, input))
+        .collect();
+    let inputs = batch_inputs(inputs, alpha);
+
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    let pow_witness = if config.deterministic_grinding {
+        challenger.grind_sequential(config.proof_of_work_bits)
+    } else {
+        challenger.grind(config.proof_of_work_bits)
+    };
 
     let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
-            .take(config.num_queries)
-            .map(|index| QueryProof {
-                input_proof: open_input(index),
-                commit_phase_openings: answer_query(
-                    config,
+        // Sample every index before answering any query, which leaves the transcript as if the
+        // queries were answered in turn, as `open_input` doesn't touch it.
+        let indices = iter::repeat_with(|| {
+            challenger.sample_bits(log_max_height + g.extra_query_index_bits())
+        })
+        .take(config.num_queries)
+        .collect_vec();
+        let extra_query_index_bits = g.extra_query_index_bits();
+        let commit_phase_openings: Vec<_> = indices
+            .par_iter()
+            .map(|&index| {
+                answer_query(
+                    &config.mmcs,
                     &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
-                ),
+                    &commit_phase_result.log_arities,
+                    index >> extra_query_index_bits,
+                )
+            })
+            .collect();
+        zip_eq(indices, commit_phase_openings)
+            .map(|(index, commit_phase_openings)| QueryProof {
+                input_proof: open_input(index),
+                commit_phase_openings,
             })
             .collect()
     });
 
     FriProof {
         commit_phase_commits: commit_phase_result.commits,
+        deep_evals: commit_phase_result.deep_evals,
         query_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
     }
 }
 
+/// Multiply the codeword `input`, with blowup `2^log_blowup`, pointwise by `1 + gamma x^k`, where
+/// `k` makes up the difference between its degree bound and that of the configured blowup.
+fn correct_degree<F: TwoAdicField, M>(
+    config: &FriConfig<M>,
+    log_blowup: usize,
+    gamma: F,
+    mut input: Vec<F>,
+) -> Vec<F> {
+    if log_blowup == config.log_blowup {
+        return input;
+    }
+    let log_height = log2_strict_usize(input.len());
+    let k = (1 << (log_height - config.log_blowup)) - (1 << (log_height - log_blowup));
+
+    // The codeword is in bit-reversed order, so bit-reverse the powers of `x^k` to match.
+    let mut x_pows = F::two_adic_generator(log_height)
+        .exp_u64(k as u64)
+        .shifted_powers(gamma)
+        .take(input.len())
+        .collect_vec();
+    reverse_slice_index_bits(&mut x_pows);
+    input
+        .par_iter_mut()
+        .zip(x_pows)
+        .for_each(|(v, gamma_x_pow)| *v += *v * gamma_x_pow);
+    input
+}
+
+/// Replace each run of inputs `v_0, v_1, ...` of the same length by `sum_i alpha^i v_i`.
+fn batch_inputs<F: Field>(inputs: Vec<Vec<F>>, alpha: F) -> Vec<Vec<F>> {
+    let mut batched: Vec<Vec<F>> = Vec::with_capacity(inputs.len());
+    let mut alpha_pow = F::ONE;
+    for input in inputs {
+        match batched.last_mut() {
+            Some(last) if last.len() == input.len() => {
+                alpha_pow *= alpha;
+                last.par_iter_mut()
+                    .zip(input)
+                    .for_each(|(c, x)| *c += alpha_pow * x);
+            }
+            _ => {
+                alpha_pow = F::ONE;
+                batched.push(input);
+            }
+        }
+    }
+    batched
+}
+
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
+    deep_evals: Vec<F>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
+    /// The log of the arity of each folding round.
+    log_arities: Vec<usize>,
     final_poly: Vec<F>,
 }
 
@@ -92,23 +214,45 @@ where
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
+    let mut deep_evals = vec![];
     let mut data = vec![];
+    let mut log_arities = vec![];
 
-    while folded.len() > config.blowup() * config.final_poly_len() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    loop {
+        let log_height = log2_strict_usize(folded.len());
+        let log_next_height = inputs_iter.peek().map_or(0, |v| log2_strict_usize(v.len()));
+        if config.folding_stops(commits.len(), log_height, log_next_height) {
+            break;
+        }
+        let log_arity = config.round_log_arity(commits.len(), log_height, log_next_height);
+
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
-        let beta: Challenge = challenger.sample_algebra_element();
         // We passed ownership of `current` to the MMCS, so get a reference to it
         let leaves = config.mmcs.get_matrices(&prover_data).pop().unwrap();
-        folded = g.fold_matrix(beta, leaves.as_view());
+        let quotient;
+        let codeword = if config.deep_sampling {
+            let z: Challenge = challenger.sample_algebra_element();
+            let (eval, q) = deep_quotient(config.log_blowup, &leaves.values, z);
+            challenger.observe_algebra_element(eval);
+            deep_evals.push(eval);
+            quotient = q;
+            &quotient
+        } else {
+            &leaves.values
+        };
+
+        let beta: Challenge = challenger.sample_algebra_element();
+        folded = fold_by_arity(g, beta, log_arity, codeword);
 
         commits.push(commit);
         data.push(prover_data);
+        log_arities.push(log_arity);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
-            izip!(&mut folded, v).for_each(|(c, x)| *c += x);
+            folded.par_iter_mut().zip(v).for_each(|(c, x)| *c += x);
         }
     }
 
@@ -122,17 +266,17 @@ where
     reverse_slice_index_bits(&mut folded);
     // TODO: For better performance, we could run the IDFT on only the first half
     //       (or less, depending on `log_blowup`) of `final_poly`.
-    let final_poly = debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
+    let final_poly_len = folded.len() >> config.log_blowup;
+    let mut final_poly =
+        debug_span!("idft final poly").in_scope(|| Radix2Dit::default().idft(folded));
 
     // The evaluation domain is "blown-up" relative to the polynomial degree of `final_poly`,
     // so all coefficients after the first final_poly_len should be zero.
     debug_assert!(
-        final_poly
-            .iter()
-            .skip(1 << config.log_final_poly_len)
-            .all(|x| x.is_zero()),
+        final_poly.iter().skip(final_poly_len).all(|x| x.is_zero()),
         "All coefficients beyond final_poly_len must be zero"
     );
+    final_poly.truncate(final_poly_len);
 
     // Observe all coefficients of the final polynomial.
     for &x in &final_poly {
@@ -141,36 +285,81 @@ where
 
     CommitPhaseResult {
         commits,
+        deep_evals,
         data,
+        log_arities,
         final_poly,
     }
 }
 
+/// Evaluate the polynomial `p` of the codeword `evals`, with blowup `2^log_blowup`, at the
+/// out-of-domain point `z`, and compute the codeword of the quotient `(p(z) - p(x)) / (z - x)`.
+fn deep_quotient<F: TwoAdicField>(log_blowup: usize, evals: &[F], z: F) -> (F, Vec<F>) {
+    // The first `1 / 2^log_blowup` of the bit-reversed codeword are the evaluations of `p` over
+    // a subgroup large enough to interpolate it.
+    let mut subgroup_evals = evals[..evals.len() >> log_blowup].to_vec();
+    reverse_slice_index_bits(&mut subgroup_evals);
+    let eval = interpolate_subgroup::<F, F, _>(&RowMajorMatrix::new_col(subgroup_evals), z)[0];
+
+    let log_height = log2_strict_usize(evals.len());
+    let mut xs = F::roots_of_unity_table(log_height)
+        .roots(log_height)
+        .to_vec();
+    reverse_slice_index_bits(&mut xs);
+    let mut inv_denoms = xs.into_iter().map(|x| z - x).collect_vec();
+    F::batch_inverse_in_place(&mut inv_denoms);
+    let quotient = evals
+        .par_iter()
+        .zip(inv_denoms)
+        .map(|(&v, inv_denom)| (eval - v) * inv_denom)
+        .collect();
+    (eval, quotient)
+}
+
+/// Fold `evals` by `2^log_arity`, by folding by two with `beta`, then `beta^2`, `beta^4` and so on.
+fn fold_by_arity<G, F>(g: &G, beta: F, log_arity: usize, evals: &[F]) -> Vec<F>
+where
+    F: Field,
+    G: FriGenericConfig<F>,
+{
+    let mut folded = g.fold_matrix(beta, RowMajorMatrixView::new(evals, 2));
+    let mut beta = beta;
+    for _ in 1..log_arity {
+        beta = beta.square();
+        folded = g.fold_matrix(beta, RowMajorMatrix::new(folded, 2));
+    }
+    folded
+}
+
+/// Open each commit phase codeword, committed in `data` and folded with the arities
+/// `log_arities`, at the coset of the query at `index`.
 fn answer_query<F, M>(
-    config: &FriConfig<M>,
-    commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    mmcs: &M,
+    data: &[M::ProverData<RowMajorMatrix<F>>],
+    log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
-
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+    zip_eq(data, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_in_coset = index & ((1 << log_arity) - 1);
+            index >>= log_arity;
+
+            let (mut opened_rows, opening_proof) = mmcs.open_batch(index, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut opened_row = opened_rows.pop().unwrap();
+            assert_eq!(
+                opened_row.len(),
+                1 << log_arity,
+                "Committed data should be in cosets of the folding arity"
+            );
+            opened_row.remove(index_in_coset);
 
             CommitPhaseProofStep {
-                sibling_value,
+                sibling_values: opened_row,
                 opening_proof,
             }
         })
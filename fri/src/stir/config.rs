@@ -0,0 +1,176 @@
+use core::mem::size_of;
+
+use p3_commit::Mmcs;
+use p3_field::{Field, TwoAdicField};
+
+/// The parameters of a STIR low degree test.
+///
+/// Each round folds the polynomial by `2^log_folding_factor` but only halves the evaluation
+/// domain, so the rate of the codeword improves by `2^(log_folding_factor - 1)` per round, and
+/// later rounds need fewer queries for the same soundness.
+#[derive(Debug)]
+pub struct StirConfig<M> {
+    /// The log of the blowup of the initial codeword.
+    pub log_blowup: usize,
+    /// The log of the factor `k` by which each round folds the degree.
+    pub log_folding_factor: usize,
+    /// The log of the length below which the prover sends the final polynomial rather than
+    /// folding further.
+    pub log_final_poly_len: usize,
+    /// The number of out-of-domain points at which each committed codeword is evaluated.
+    pub num_ood_samples: usize,
+    /// The conjectured soundness each round targets, from queries and proof of work together.
+    pub security_bits: usize,
+    /// The proof of work bits ground before the queries of each round.
+    pub proof_of_work_bits: usize,
+    pub mmcs: M,
+}
+
+impl<M> StirConfig<M> {
+    /// Creates a config targeting `security_bits` bits of conjectured soundness, with an initial
+    /// rate of `2^-log_blowup` and folding by `2^log_folding_factor` in each round.
+    ///
+    /// As in `FriConfig::with_security_bits`, proof of work supplies half of the target, up to
+    /// `FriConfig::MAX_PROOF_OF_WORK_BITS`, and each query gives `-log2(rate)` bits under the
+    /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
+    pub fn with_security_bits(
+        mmcs: M,
+        security_bits: usize,
+        log_blowup: usize,
+        log_folding_factor: usize,
+    ) -> Self {
+        assert!(log_blowup > 0, "STIR needs a rate below 1 to be sound");
+        assert!(
+            log_folding_factor > 1,
+            "STIR needs to fold by more than two"
+        );
+        Self {
+            log_blowup,
+            log_folding_factor,
+            log_final_poly_len: 0,
+            num_ood_samples: 2,
+            security_bits,
+            proof_of_work_bits: (security_bits / 2)
+                .min(crate::FriConfig::<M>::MAX_PROOF_OF_WORK_BITS),
+            mmcs,
+        }
+    }
+
+    pub const fn folding_factor(&self) -> usize {
+        1 << self.log_folding_factor
+    }
+
+    /// The log of the inverse rate of the codeword committed in round `round`, with round 0 the
+    /// initial codeword.
+    pub const fn log_inv_rate(&self, round: usize) -> usize {
+        self.log_blowup + round * (self.log_folding_factor - 1)
+    }
+
+    /// The number of queries made to the codeword committed in round `round`.
+    pub const fn num_queries(&self, round: usize) -> usize {
+        self.security_bits
+            .saturating_sub(self.proof_of_work_bits)
+            .div_ceil(self.log_inv_rate(round))
+    }
+
+    /// The number of rounds committing a folded codeword, for an initial polynomial of degree
+    /// less than `2^log_degree`.
+    ///
+    /// Folding continues while it leaves a fold for the final polynomial, and while the folded
+    /// degree exceeds the number of points at which the next codeword is constrained, which the
+    /// quotient in each round removes from its degree.
+    pub const fn num_rounds(&self, log_degree: usize) -> usize {
+        let mut log_degree = log_degree;
+        let mut round = 0;
+        while log_degree >= self.log_final_poly_len + 2 * self.log_folding_factor
+            && (1 << (log_degree - self.log_folding_factor))
+                > self.num_queries(round) + self.num_ood_samples
+        {
+            log_degree -= self.log_folding_factor;
+            round += 1;
+        }
+        round
+    }
+
+    /// The log of the degree bound of the polynomial after `num_rounds` folds.
+    pub(crate) const fn log_degree_after(&self, log_degree: usize, num_rounds: usize) -> usize {
+        log_degree - num_rounds * self.log_folding_factor
+    }
+
+    /// The length of the final polynomial, the last folded polynomial folded once more.
+    pub const fn final_poly_len(&self, log_degree: usize) -> usize {
+        let log_degree = self.log_degree_after(log_degree, self.num_rounds(log_degree));
+        1 << log_degree.saturating_sub(self.log_folding_factor)
+    }
+
+    /// The point `(s w^row)^k` of the folded domain for row `row` of a codeword over `s H` of size
+    /// `2^log_size`, where `w` generates `H` and `s` is the multiplicative generator of the field.
+    pub(crate) fn row_point<Val: TwoAdicField>(&self, log_size: usize, row: usize) -> Val {
+        (Val::GENERATOR * Val::two_adic_generator(log_size).exp_u64(row as u64))
+            .exp_power_of_2(self.log_folding_factor)
+    }
+
+    /// Check that the codeword of each round is evaluated away from the points its answers are
+    /// checked at, for an initial polynomial of degree less than `2^log_degree`.
+    ///
+    /// Round `i` commits over `s H_i` and checks answers at the `k`-th powers of the previous
+    /// domain `s H_{i - 1}`, so the two are disjoint unless `s^(k - 1)` lies in `H_i`.
+    pub(crate) fn assert_disjoint_domains<Val: TwoAdicField>(&self, log_degree: usize) {
+        let shift_pow = Val::GENERATOR.exp_u64(self.folding_factor() as u64 - 1);
+        for round in 1..=self.num_rounds(log_degree) {
+            let log_size = log_degree + self.log_blowup - round;
+            assert_ne!(
+                shift_pow.exp_power_of_2(log_size),
+                Val::ONE,
+                "The domain of round {round} meets the points it is checked at"
+            );
+        }
+    }
+
+    /// Estimate the size of a proof that a word of length `2^log_word_len` is close to low degree,
+    /// without generating one.
+    ///
+    /// Field elements and digests are sized by their in-memory representation, and every query is
+    /// assumed to open a distinct row. Proof of work witnesses and any serialization overhead are
+    /// ignored.
+    pub fn proof_size_estimate<F: Field>(&self, log_word_len: usize) -> StirProofSizeEstimate
+    where
+        M: Mmcs<F>,
+    {
+        let digest_bytes = size_of::<M::Commitment>();
+        let elem_bytes = size_of::<F>();
+        let log_degree = log_word_len - self.log_blowup;
+        let num_rounds = self.num_rounds(log_degree);
+
+        // Each round commits a codeword, evaluates it out of domain, and opens rows of `k` values
+        // of the previous codeword, whose height starts at `2^(log_word_len - log k)` and halves.
+        let row_bytes = |round: usize| {
+            let log_height = log_word_len - round - self.log_folding_factor;
+            self.folding_factor() * elem_bytes + log_height * digest_bytes
+        };
+        let mut bytes = digest_bytes + self.final_poly_len(log_degree) * elem_bytes;
+        for round in 0..num_rounds {
+            bytes += digest_bytes + self.num_ood_samples * elem_bytes;
+            bytes += self.num_queries(round) * row_bytes(round);
+        }
+        bytes += self.num_queries(num_rounds) * row_bytes(num_rounds);
+
+        StirProofSizeEstimate {
+            bytes,
+            num_rounds,
+            conjectured_soundness_bits: self.security_bits,
+        }
+    }
+}
+
+/// The expected size and soundness of a STIR proof, as estimated by
+/// `StirConfig::proof_size_estimate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StirProofSizeEstimate {
+    /// The approximate size of the proof in bytes.
+    pub bytes: usize,
+    /// The number of rounds committing a folded codeword.
+    pub num_rounds: usize,
+    /// Soundness bits under the ethSTARK conjecture.
+    pub conjectured_soundness_bits: usize,
+}
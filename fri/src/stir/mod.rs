@@ -0,0 +1,44 @@
+//! The STIR low degree test of [Arnon, Chiesa, Fenzi and Yogev](https://eprint.iacr.org/2024/390).
+//!
+//! Like FRI, each round folds the polynomial under test by a random challenge, but the folded
+//! polynomial is committed over a domain only half the size of the previous one rather than
+//! `k` times smaller. The rate of the code therefore improves from round to round, and later rounds
+//! need fewer queries. The queries of each round constrain the next polynomial through a quotient
+//! by the points they checked, together with a few out-of-domain samples.
+//!
+//! All domains are cosets `s H` of two-adic subgroups with `s` the multiplicative generator of the
+//! field, and each codeword is committed with the `k` points of each coset of `k`-th roots of unity
+//! in one row, so that one opening gives everything a fold needs.
+
+use alloc::vec::Vec;
+
+use p3_challenger::CanSampleBits;
+
+mod config;
+mod poly;
+mod proof;
+pub mod prover;
+pub mod verifier;
+
+pub use config::*;
+pub use proof::*;
+
+/// Sample the distinct rows queried in round `round` of a codeword over a domain of size
+/// `2^log_size`, in increasing order.
+pub(crate) fn sample_queries<M, Challenger>(
+    config: &StirConfig<M>,
+    challenger: &mut Challenger,
+    round: usize,
+    log_size: usize,
+) -> Vec<usize>
+where
+    Challenger: CanSampleBits<usize>,
+{
+    let log_height = log_size - config.log_folding_factor;
+    let mut rows: Vec<usize> = (0..config.num_queries(round))
+        .map(|_| challenger.sample_bits(log_height))
+        .collect();
+    rows.sort_unstable();
+    rows.dedup();
+    rows
+}
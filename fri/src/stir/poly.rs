@@ -0,0 +1,164 @@
+//! Arithmetic on polynomials given by their coefficients, lowest degree first.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_util::log2_strict_usize;
+
+/// Evaluate `coeffs` at `x` by Horner's method.
+pub(crate) fn eval_poly<F: Field>(coeffs: &[F], x: F) -> F {
+    coeffs
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, &coeff| acc * x + coeff)
+}
+
+/// Fold `f(X) = sum_j X^j f_j(X^k)` into `sum_j r^j f_j(X)`, for `k = 2^log_k`.
+pub(crate) fn fold_poly<F: Field>(coeffs: &[F], log_k: usize, r: F) -> Vec<F> {
+    coeffs
+        .chunks(1 << log_k)
+        .map(|chunk| eval_poly(chunk, r))
+        .collect()
+}
+
+/// Fold the evaluations `values` of a polynomial `f` over the coset `x0 <zeta>`, with `zeta` the
+/// two-adic generator of order `values.len()`, into the value at `x0^k` of `fold_poly(f, r)`.
+///
+/// The fold is the polynomial of degree less than `k` interpolating the coset, evaluated at `r`.
+pub(crate) fn fold_coset<Val, F>(values: &[F], x0: Val, r: F) -> F
+where
+    Val: TwoAdicField,
+    F: ExtensionField<Val>,
+{
+    let k = values.len();
+    // Writing the interpolant as `P(x0 Z)`, the coefficient of `Z^j` is
+    // `(1 / k) sum_m values[m] zeta^{-jm}`, so evaluate it at `Z = r / x0`.
+    let zeta_inv = Val::two_adic_generator(log2_strict_usize(k)).inverse();
+    let z = r * x0.inverse();
+    let sum: F = zeta_inv
+        .powers()
+        .zip(z.powers())
+        .take(k)
+        .map(|(zeta_inv_j, z_j)| {
+            z_j * zeta_inv_j
+                .powers()
+                .zip(values)
+                .map(|(zeta_inv_jm, &value)| value * zeta_inv_jm)
+                .sum::<F>()
+        })
+        .sum();
+    sum * Val::from_usize(k).inverse()
+}
+
+/// Divide `coeffs` by `X - a`, returning the quotient and the remainder.
+pub(crate) fn divide_by_linear<F: Field>(coeffs: &[F], a: F) -> (Vec<F>, F) {
+    let Some((&leading, rest)) = coeffs.split_last() else {
+        return (Vec::new(), F::ZERO);
+    };
+    let mut quotient = vec![F::ZERO; rest.len()];
+    let mut carry = leading;
+    for (q, &coeff) in quotient.iter_mut().zip(rest).rev() {
+        *q = carry;
+        carry = coeff + carry * a;
+    }
+    (quotient, carry)
+}
+
+/// The polynomial of degree less than `points.len()` through `points`, whose first coordinates
+/// must be distinct.
+pub(crate) fn interpolate<F: Field>(points: &[(F, F)]) -> Vec<F> {
+    let mut vanishing = vec![F::ONE];
+    for &(a, _) in points {
+        vanishing.insert(0, F::ZERO);
+        for i in 0..vanishing.len() - 1 {
+            let next = vanishing[i + 1];
+            vanishing[i] -= next * a;
+        }
+    }
+
+    let mut coeffs = vec![F::ZERO; points.len()];
+    for &(a, y) in points {
+        let (basis, _) = divide_by_linear(&vanishing, a);
+        let scale = y * eval_poly(&basis, a).inverse();
+        for (coeff, b) in coeffs.iter_mut().zip(basis) {
+            *coeff += scale * b;
+        }
+    }
+    coeffs
+}
+
+/// Multiply `coeffs` by `sum_{l = 0}^{e} (r X)^l`, keeping the first `len` coefficients.
+///
+/// Writing the product as `h`, `(1 - r X) h = coeffs (1 - (r X)^{e + 1})`, which gives each
+/// coefficient of `h` from the one before.
+pub(crate) fn mul_degree_correction<F: Field>(coeffs: &[F], r: F, e: usize, len: usize) -> Vec<F> {
+    let r_pow = r.exp_u64(e as u64 + 1);
+    let coeff = |i: usize| coeffs.get(i).copied().unwrap_or(F::ZERO);
+    let mut product = Vec::with_capacity(len);
+    let mut prev = F::ZERO;
+    for i in 0..len {
+        let shifted = i.checked_sub(e + 1).map_or(F::ZERO, coeff);
+        prev = r * prev + coeff(i) - r_pow * shifted;
+        product.push(prev);
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_field::extension::BinomialExtensionField;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+
+    #[test]
+    fn test_fold_coset_matches_fold_poly() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let coeffs: Vec<EF> = (0..64).map(|_| rng.random()).collect();
+        let r: EF = rng.random();
+        let folded = fold_poly(&coeffs, 2, r);
+
+        let x0 = F::GENERATOR.exp_u64(5);
+        let zeta = F::two_adic_generator(2);
+        let values = zeta
+            .powers()
+            .take(4)
+            .map(|zeta_m| eval_poly(&coeffs, EF::from(x0 * zeta_m)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fold_coset(&values, x0, r),
+            eval_poly(&folded, EF::from(x0.exp_u64(4)))
+        );
+    }
+
+    #[test]
+    fn test_interpolate_and_divide() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let points: Vec<(EF, EF)> = (0..5).map(|_| (rng.random(), rng.random())).collect();
+        let coeffs = interpolate(&points);
+        for &(a, y) in &points {
+            assert_eq!(eval_poly(&coeffs, a), y);
+            let (quotient, remainder) = divide_by_linear(&coeffs, a);
+            assert_eq!(remainder, y);
+            let x: EF = rng.random();
+            assert_eq!(eval_poly(&quotient, x) * (x - a) + y, eval_poly(&coeffs, x));
+        }
+    }
+
+    #[test]
+    fn test_mul_degree_correction() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let coeffs: Vec<EF> = (0..10).map(|_| rng.random()).collect();
+        let (r, x): (EF, EF) = (rng.random(), rng.random());
+        let product = mul_degree_correction(&coeffs, r, 3, 13);
+        let correction: EF = (r * x).powers().take(4).sum();
+        assert_eq!(eval_poly(&product, x), eval_poly(&coeffs, x) * correction);
+    }
+}
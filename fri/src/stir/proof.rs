@@ -0,0 +1,45 @@
+use alloc::vec::Vec;
+
+use p3_commit::Mmcs;
+use p3_field::Field;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct StirProof<F: Field, M: Mmcs<F>, Witness> {
+    /// The commitment to the initial codeword.
+    pub commitment: M::Commitment,
+    pub rounds: Vec<StirRoundProof<F, M, Witness>>,
+    pub final_poly: Vec<F>,
+    pub final_pow_witness: Witness,
+    /// The openings of the last committed codeword at the final queries.
+    pub final_queries: Vec<StirQueryProof<F, M>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct StirRoundProof<F: Field, M: Mmcs<F>, Witness> {
+    /// The commitment to the folded codeword of this round.
+    pub commitment: M::Commitment,
+    /// The evaluations of the folded polynomial at the out-of-domain points of this round.
+    pub ood_answers: Vec<F>,
+    pub pow_witness: Witness,
+    /// The openings of the previous codeword at the distinct queries of this round, in increasing
+    /// order of row.
+    pub queries: Vec<StirQueryProof<F, M>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct StirQueryProof<F: Field, M: Mmcs<F>> {
+    /// The `k` evaluations of a codeword over the coset of `k`-th roots of a point of the folded
+    /// domain.
+    pub row: Vec<F>,
+    pub opening_proof: M::Proof,
+}
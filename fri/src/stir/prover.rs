@@ -0,0 +1,167 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::Itertools;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_util::log2_strict_usize;
+use tracing::{info_span, instrument};
+
+use super::poly::{divide_by_linear, eval_poly, fold_poly, interpolate, mul_degree_correction};
+use super::{StirConfig, StirProof, StirQueryProof, StirRoundProof, sample_queries};
+
+/// Prove that the polynomial with coefficients `coeffs` has degree less than `coeffs.len()`,
+/// which must be a power of two, by committing to its codeword and running STIR on it.
+#[instrument(name = "STIR prover", skip_all)]
+pub fn prove<Val, Challenge, M, Challenger>(
+    config: &StirConfig<M>,
+    coeffs: Vec<Challenge>,
+    challenger: &mut Challenger,
+) -> StirProof<Challenge, M, Challenger::Witness>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let log_degree = log2_strict_usize(coeffs.len());
+    let num_rounds = config.num_rounds(log_degree);
+    config.assert_disjoint_domains::<Val>(log_degree);
+
+    let mut log_size = log_degree + config.log_blowup;
+    let (commitment, mut data) = commit_codeword::<Val, _, _>(config, &coeffs, log_size);
+    challenger.observe(commitment.clone());
+
+    let mut poly = coeffs;
+    let mut rounds = Vec::with_capacity(num_rounds);
+    for round in 0..num_rounds {
+        let _span = info_span!("round", round).entered();
+        let r_fold: Challenge = challenger.sample_algebra_element();
+        let folded = fold_poly(&poly, config.log_folding_factor, r_fold);
+
+        let (commitment, next_data) = commit_codeword::<Val, _, _>(config, &folded, log_size - 1);
+        challenger.observe(commitment.clone());
+
+        let ood_points: Vec<Challenge> = (0..config.num_ood_samples)
+            .map(|_| challenger.sample_algebra_element())
+            .collect();
+        let ood_answers = ood_points
+            .iter()
+            .map(|&point| eval_poly(&folded, point))
+            .collect_vec();
+        for &answer in &ood_answers {
+            challenger.observe_algebra_element(answer);
+        }
+        let r_comb: Challenge = challenger.sample_algebra_element();
+
+        let pow_witness = challenger.grind(config.proof_of_work_bits);
+        let rows = sample_queries(config, challenger, round, log_size);
+        let queries = open_rows(config, &data, &rows);
+
+        // The folded polynomial agrees with its answers at the out-of-domain points and at the
+        // `k`-th powers of the queried cosets, so it is divisible by their vanishing polynomial.
+        let shift_points = rows
+            .iter()
+            .map(|&row| Challenge::from(config.row_point::<Val>(log_size, row)));
+        let points = ood_points.into_iter().chain(shift_points).collect_vec();
+        let answers = interpolate(
+            &points
+                .iter()
+                .map(|&point| (point, eval_poly(&folded, point)))
+                .collect_vec(),
+        );
+        let mut quotient = folded.clone();
+        for (coeff, answer) in quotient.iter_mut().zip(answers) {
+            *coeff -= answer;
+        }
+        for &point in &points {
+            let (next, remainder) = divide_by_linear(&quotient, point);
+            debug_assert!(remainder.is_zero());
+            quotient = next;
+        }
+        poly = mul_degree_correction(&quotient, r_comb, points.len(), folded.len());
+
+        rounds.push(StirRoundProof {
+            commitment,
+            ood_answers,
+            pow_witness,
+            queries,
+        });
+        data = next_data;
+        log_size -= 1;
+    }
+
+    let r_fold: Challenge = challenger.sample_algebra_element();
+    let final_poly = fold_poly(&poly, config.log_folding_factor, r_fold);
+    debug_assert_eq!(final_poly.len(), config.final_poly_len(log_degree));
+    for &coeff in &final_poly {
+        challenger.observe_algebra_element(coeff);
+    }
+    let final_pow_witness = challenger.grind(config.proof_of_work_bits);
+    let rows = sample_queries(config, challenger, num_rounds, log_size);
+    let final_queries = open_rows(config, &data, &rows);
+
+    StirProof {
+        commitment,
+        rounds,
+        final_poly,
+        final_pow_witness,
+        final_queries,
+    }
+}
+
+/// Commit to the evaluations of `coeffs` over the coset `s H` of size `2^log_size`, with each row
+/// holding the `k` points of a coset of `k`-th roots of unity.
+fn commit_codeword<Val, Challenge, M>(
+    config: &StirConfig<M>,
+    coeffs: &[Challenge],
+    log_size: usize,
+) -> (M::Commitment, M::ProverData<RowMajorMatrix<Challenge>>)
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+{
+    let size = 1 << log_size;
+    let padded = coeffs
+        .iter()
+        .copied()
+        .chain(iter::repeat(Challenge::ZERO))
+        .take(size)
+        .collect();
+    let evals = Radix2Dit::default().coset_dft(padded, Challenge::from(Val::GENERATOR));
+
+    // The points `s w^{row + j (size / k)}` of a row are `s w^row` times the `k`-th roots of unity.
+    let height = size >> config.log_folding_factor;
+    let evals = &evals;
+    let leaves = (0..height)
+        .flat_map(|row| (0..config.folding_factor()).map(move |j| evals[row + j * height]))
+        .collect();
+    config
+        .mmcs
+        .commit_matrix(RowMajorMatrix::new(leaves, config.folding_factor()))
+}
+
+/// Open the rows `rows` of a codeword committed by `commit_codeword`.
+fn open_rows<F, M>(
+    config: &StirConfig<M>,
+    data: &M::ProverData<RowMajorMatrix<F>>,
+    rows: &[usize],
+) -> Vec<StirQueryProof<F, M>>
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    rows.iter()
+        .map(|&row| {
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(row, data);
+            StirQueryProof {
+                row: opened_rows.pop().unwrap(),
+                opening_proof,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::Dimensions;
+
+use super::poly::{eval_poly, fold_coset, interpolate};
+use super::{StirConfig, StirProof, StirQueryProof, sample_queries};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StirError<CommitMmcsErr> {
+    /// The proof has the wrong number of rounds, out-of-domain answers, queries or final
+    /// polynomial coefficients.
+    InvalidProofShape,
+    /// An opening of a committed codeword did not match its commitment.
+    CommitMmcsError {
+        /// The index of the codeword the opening was checked against, starting at zero for the
+        /// initial codeword.
+        round: usize,
+        error: CommitMmcsErr,
+    },
+    /// Folding the openings of a final query did not match the final polynomial.
+    FinalPolyMismatch,
+    /// The proof of work witness of a round did not meet the configured number of bits.
+    InvalidPowWitness,
+}
+
+/// What the verifier knows of the polynomial `f_i` which round `i` leaves to be tested, given the
+/// commitment to the folded polynomial `g_i` it is derived from.
+///
+/// With `G` the points at which `g_i` was checked, `f_i = (g_i - Ans) / V_G` times the degree
+/// correction `sum_{l = 0}^{|G|} (r X)^l`, where `Ans` interpolates the answers at `G` and `V_G`
+/// vanishes on `G`.
+struct VirtualPoly<F> {
+    points: Vec<F>,
+    answers: Vec<F>,
+    r_comb: F,
+}
+
+impl<F: Field> VirtualPoly<F> {
+    /// Evaluate `f_i` at `x`, given the committed value `g_i(x)`.
+    fn eval<Val: Field>(&self, x: Val, committed: F) -> F
+    where
+        F: ExtensionField<Val>,
+    {
+        let x = F::from(x);
+        let vanishing: F = self.points.iter().map(|&point| x - point).product();
+        let correction: F = (self.r_comb * x).powers().take(self.points.len() + 1).sum();
+        (committed - eval_poly(&self.answers, x)) * vanishing.inverse() * correction
+    }
+}
+
+/// Verify that the codeword committed in `proof` is close to a polynomial of degree less than
+/// `2^log_degree`.
+pub fn verify<Val, Challenge, M, Challenger>(
+    config: &StirConfig<M>,
+    log_degree: usize,
+    proof: &StirProof<Challenge, M, Challenger::Witness>,
+    challenger: &mut Challenger,
+) -> Result<(), StirError<M::Error>>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let num_rounds = config.num_rounds(log_degree);
+    if proof.rounds.len() != num_rounds
+        || proof.final_poly.len() != config.final_poly_len(log_degree)
+    {
+        return Err(StirError::InvalidProofShape);
+    }
+    config.assert_disjoint_domains::<Val>(log_degree);
+    challenger.observe(proof.commitment.clone());
+
+    let mut log_size = log_degree + config.log_blowup;
+    let mut commitment = &proof.commitment;
+    let mut virtual_poly: Option<VirtualPoly<Challenge>> = None;
+    for (round, round_proof) in proof.rounds.iter().enumerate() {
+        let r_fold: Challenge = challenger.sample_algebra_element();
+        challenger.observe(round_proof.commitment.clone());
+
+        let ood_points: Vec<Challenge> = (0..config.num_ood_samples)
+            .map(|_| challenger.sample_algebra_element())
+            .collect();
+        if round_proof.ood_answers.len() != config.num_ood_samples {
+            return Err(StirError::InvalidProofShape);
+        }
+        for &answer in &round_proof.ood_answers {
+            challenger.observe_algebra_element(answer);
+        }
+        let r_comb: Challenge = challenger.sample_algebra_element();
+
+        if !challenger.check_witness(config.proof_of_work_bits, round_proof.pow_witness) {
+            return Err(StirError::InvalidPowWitness);
+        }
+        let rows = sample_queries(config, challenger, round, log_size);
+        let folds = verify_queries::<Val, _, _>(
+            config,
+            round,
+            log_size,
+            commitment,
+            virtual_poly.as_ref(),
+            &rows,
+            &round_proof.queries,
+            r_fold,
+        )?;
+
+        let shift_points = rows
+            .iter()
+            .map(|&row| Challenge::from(config.row_point::<Val>(log_size, row)));
+        let points = ood_points.into_iter().chain(shift_points).collect_vec();
+        let answers = interpolate(
+            &points
+                .iter()
+                .copied()
+                .zip(round_proof.ood_answers.iter().copied().chain(folds))
+                .collect_vec(),
+        );
+        virtual_poly = Some(VirtualPoly {
+            points,
+            answers,
+            r_comb,
+        });
+        commitment = &round_proof.commitment;
+        log_size -= 1;
+    }
+
+    let r_fold: Challenge = challenger.sample_algebra_element();
+    for &coeff in &proof.final_poly {
+        challenger.observe_algebra_element(coeff);
+    }
+    if !challenger.check_witness(config.proof_of_work_bits, proof.final_pow_witness) {
+        return Err(StirError::InvalidPowWitness);
+    }
+    let rows = sample_queries(config, challenger, num_rounds, log_size);
+    let folds = verify_queries::<Val, _, _>(
+        config,
+        num_rounds,
+        log_size,
+        commitment,
+        virtual_poly.as_ref(),
+        &rows,
+        &proof.final_queries,
+        r_fold,
+    )?;
+    for (&row, fold) in rows.iter().zip(folds) {
+        let point = Challenge::from(config.row_point::<Val>(log_size, row));
+        if eval_poly(&proof.final_poly, point) != fold {
+            return Err(StirError::FinalPolyMismatch);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the openings `queries` of the rows `rows` of the codeword of round `round`, over a
+/// domain of size `2^log_size`, and fold the values of `f_round` on each row with `r_fold`.
+#[allow(clippy::too_many_arguments)]
+fn verify_queries<Val, Challenge, M>(
+    config: &StirConfig<M>,
+    round: usize,
+    log_size: usize,
+    commitment: &M::Commitment,
+    virtual_poly: Option<&VirtualPoly<Challenge>>,
+    rows: &[usize],
+    queries: &[StirQueryProof<Challenge, M>],
+    r_fold: Challenge,
+) -> Result<Vec<Challenge>, StirError<M::Error>>
+where
+    Val: TwoAdicField,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+{
+    if queries.len() != rows.len() {
+        return Err(StirError::InvalidProofShape);
+    }
+    let k = config.folding_factor();
+    let dims = [Dimensions {
+        width: k,
+        height: 1 << (log_size - config.log_folding_factor),
+    }];
+    let generator = Val::two_adic_generator(log_size);
+    let zeta = Val::two_adic_generator(config.log_folding_factor);
+
+    rows.iter()
+        .zip(queries)
+        .map(|(&row, query)| {
+            if query.row.len() != k {
+                return Err(StirError::InvalidProofShape);
+            }
+            config
+                .mmcs
+                .verify_batch(
+                    commitment,
+                    &dims,
+                    row,
+                    core::slice::from_ref(&query.row),
+                    &query.opening_proof,
+                )
+                .map_err(|error| StirError::CommitMmcsError { round, error })?;
+
+            let x0 = Val::GENERATOR * generator.exp_u64(row as u64);
+            let values = match virtual_poly {
+                None => query.row.clone(),
+                Some(virtual_poly) => zeta
+                    .shifted_powers(x0)
+                    .zip(&query.row)
+                    .map(|(x, &committed)| virtual_poly.eval(x, committed))
+                    .collect(),
+            };
+            Ok(fold_coset(&values, x0, r_fold))
+        })
+        .collect()
+}
@@ -1,17 +1,18 @@
-use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cmp::Reverse;
 use core::fmt::Debug;
+use core::iter::zip;
 use core::marker::PhantomData;
 
 use itertools::{Itertools, izip};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs};
+use p3_commit::{Mmcs, OpenedValues, PathMmcs, Pcs};
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::coset::TwoAdicMultiplicativeCoset;
 use p3_field::{
-    ExtensionField, Field, PackedFieldExtension, TwoAdicField, batch_multiplicative_inverse,
-    cyclic_subgroup_coset_known_order, dot_product,
+    ExtensionField, Field, PackedFieldExtension, TwoAdicField, cyclic_subgroup_coset_known_order,
+    dot_product,
 };
 use p3_interpolation::interpolate_coset;
 use p3_matrix::bitrev::{BitReversalPerm, BitReversedMatrixView, BitReversibleMatrix};
@@ -24,14 +25,18 @@ use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
+use crate::proof::{restore_path_tops, share_path_tops};
 use crate::verifier::{self, FriError};
-use crate::{FriConfig, FriGenericConfig, FriProof, prover};
+use crate::{
+    CompressedBatchOpening, CompressedFriProof, CompressedQueryProof, FriConfig, FriGenericConfig,
+    FriProof, prover,
+};
 
 #[derive(Debug)]
 pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
-    dft: Dft,
-    mmcs: InputMmcs,
-    fri: FriConfig<FriMmcs>,
+    pub(crate) dft: Dft,
+    pub(crate) mmcs: InputMmcs,
+    pub(crate) fri: FriConfig<FriMmcs>,
     _phantom: PhantomData<Val>,
 }
 
@@ -44,6 +49,12 @@ impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
             _phantom: PhantomData,
         }
     }
+
+    /// The FRI parameters, including the folding arities and final polynomial length, that
+    /// openings are proven with.
+    pub const fn fri_config(&self) -> &FriConfig<FriMmcs> {
+        &self.fri
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -53,6 +64,117 @@ pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
 }
 
+/// A `BatchOpening` whose opening path shares the nodes at its top with the opening of the same
+/// batch in an earlier query, as the commit phase paths of a `CompressedFriProof` do.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct CompressedBatchOpening<Val: Field, InputMmcs: PathMmcs<Val>> {
+    pub opened_values: Vec<Vec<Val>>,
+    /// The nodes of the opening path below the part shared with `shared_with`.
+    pub path_nodes: Vec<InputMmcs::Node>,
+    /// The earlier query whose opening path of the same batch continues this one, if any.
+    pub shared_with: Option<usize>,
+}
+
+impl<Challenge, FriMmcs, Val, InputMmcs>
+    CompressedFriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>
+where
+    Challenge: Field,
+    FriMmcs: PathMmcs<Challenge>,
+    Val: Field,
+    InputMmcs: PathMmcs<Val>,
+{
+    /// Drop the nodes each input opening path shares with that of the same batch in an earlier
+    /// query, on top of the commit phase paths `FriProof::compress` already shares.
+    pub fn compress_input_openings(
+        self,
+    ) -> CompressedFriProof<Challenge, FriMmcs, Val, Vec<CompressedBatchOpening<Val, InputMmcs>>>
+    {
+        let paths = self
+            .query_proofs
+            .iter()
+            .map(|qp| {
+                qp.input_proof
+                    .iter()
+                    .map(|opening| InputMmcs::proof_path(&opening.opening_proof))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let compressed_paths = share_path_tops(&paths);
+
+        let query_proofs = zip(self.query_proofs, compressed_paths)
+            .map(|(qp, paths)| CompressedQueryProof {
+                input_proof: zip(qp.input_proof, paths)
+                    .map(
+                        |(opening, (path_nodes, shared_with))| CompressedBatchOpening {
+                            opened_values: opening.opened_values,
+                            path_nodes,
+                            shared_with,
+                        },
+                    )
+                    .collect(),
+                commit_phase_openings: qp.commit_phase_openings,
+            })
+            .collect();
+
+        CompressedFriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            deep_evals: self.deep_evals,
+            query_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        }
+    }
+}
+
+impl<Challenge, FriMmcs, Val, InputMmcs>
+    CompressedFriProof<Challenge, FriMmcs, Val, Vec<CompressedBatchOpening<Val, InputMmcs>>>
+where
+    Challenge: Field,
+    FriMmcs: PathMmcs<Challenge>,
+    Val: Field,
+    InputMmcs: PathMmcs<Val>,
+{
+    /// Restore the full input opening paths, or return `None` if a path continues one that isn't
+    /// from an earlier query, or that is too short.
+    pub fn decompress_input_openings(
+        self,
+    ) -> Option<CompressedFriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>>
+    {
+        let compressed_paths = self
+            .query_proofs
+            .iter()
+            .map(|qp| {
+                qp.input_proof
+                    .iter()
+                    .map(|opening| (opening.path_nodes.clone(), opening.shared_with))
+                    .collect()
+            })
+            .collect();
+        let paths = restore_path_tops(compressed_paths)?;
+
+        let query_proofs = zip(self.query_proofs, paths)
+            .map(|(qp, paths)| CompressedQueryProof {
+                input_proof: zip(qp.input_proof, paths)
+                    .map(|(opening, path)| BatchOpening {
+                        opened_values: opening.opened_values,
+                        opening_proof: InputMmcs::proof_from_path(path),
+                    })
+                    .collect(),
+                commit_phase_openings: qp.commit_phase_openings,
+            })
+            .collect();
+
+        Some(CompressedFriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            deep_evals: self.deep_evals,
+            query_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        })
+    }
+}
+
 pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
     pub PhantomData<(InputProof, InputError)>,
 );
@@ -112,7 +234,8 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
         let one_half = F::ONE.halve();
         let half_beta = beta * one_half;
 
-        // TODO: vectorize this (after we have packed extension fields)
+        // TODO: vectorize this (after we have packed extension fields), at which point the fold
+        // can use `PackedField::fma`.
 
         // beta/2 times successive powers of g_inv
         let mut powers = g_inv
@@ -131,42 +254,36 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
     }
 }
 
-impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
-    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
 where
     Val: TwoAdicField,
     Dft: TwoAdicSubgroupDft<Val>,
     InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
-    Challenge: TwoAdicField + ExtensionField<Val>,
-    Challenger:
-        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
 {
-    type Domain = TwoAdicMultiplicativeCoset<Val>;
-    type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
-    type EvaluationsOnDomain<'a> = BitReversedMatrixView<DenseMatrix<Val, &'a [Val]>>;
-    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
-    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
-
-    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
-        // This panics if (and only if) `degree` is not a power of 2 or `degree`
-        // > `1 << Val::TWO_ADICITY`.
-        TwoAdicMultiplicativeCoset::new(Val::ONE, log2_strict_usize(degree)).unwrap()
-    }
-
-    fn commit(
+    /// Like `Pcs::commit`, but with each matrix paired with the log of its own blowup, which must
+    /// be at least the configured `log_blowup`.
+    ///
+    /// This lets, for example, trace columns be committed at a lower rate than quotient chunks.
+    /// The same blowups must be passed to `open_with_log_blowups` and `verify_with_log_blowups`.
+    pub fn commit_with_log_blowups(
         &self,
-        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
-    ) -> (Self::Commitment, Self::ProverData) {
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>, usize)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    ) {
         let ldes: Vec<_> = evaluations
             .into_iter()
-            .map(|(domain, evals)| {
+            .map(|(domain, evals, log_blowup)| {
                 assert_eq!(domain.size(), evals.height());
+                assert!(
+                    log_blowup >= self.fri.log_blowup,
+                    "Matrices must have at least the configured blowup."
+                );
                 let shift = Val::GENERATOR / domain.shift();
                 // Commit to the bit-reversed LDE.
                 self.dft
-                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
+                    .coset_lde_batch(evals, log_blowup, shift)
                     .bit_reverse_rows()
                     .to_row_major_matrix()
             })
@@ -175,32 +292,36 @@ where
         self.mmcs.commit(ldes)
     }
 
-    fn get_evaluations_on_domain<'a>(
-        &self,
-        prover_data: &'a Self::ProverData,
-        idx: usize,
-        domain: Self::Domain,
-    ) -> Self::EvaluationsOnDomain<'a> {
-        // todo: handle extrapolation for LDEs we don't have
-        assert_eq!(domain.shift(), Val::GENERATOR);
-        let lde = self.mmcs.get_matrices(prover_data)[idx];
-        assert!(lde.height() >= domain.size());
-        lde.split_rows(domain.size()).0.bit_reverse_rows()
-    }
-
-    fn open(
+    /// Like `Pcs::open`, but with the points of each matrix paired with the log of the blowup it
+    /// was committed with by `commit_with_log_blowups`.
+    #[allow(clippy::type_complexity)]
+    pub fn open_with_log_blowups<Challenge, Challenger>(
         &self,
         // For each round,
         rounds: Vec<(
-            &Self::ProverData,
+            &InputMmcs::ProverData<RowMajorMatrix<Val>>,
             // for each matrix,
-            Vec<
+            Vec<(
+                // the log of its blowup,
+                usize,
                 // points to open
                 Vec<Challenge>,
-            >,
+            )>,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+    ) -> (
+        OpenedValues<Challenge>,
+        FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
+    )
+    where
+        FriMmcs: Mmcs<Challenge> + Sync,
+        FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+        FriMmcs::Proof: Send,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -238,6 +359,15 @@ where
 
         */
 
+        // Split the blowup of each matrix from the points it is opened at.
+        let (rounds, log_blowups): (Vec<_>, Vec<Vec<usize>>) = rounds
+            .into_iter()
+            .map(|(data, mats)| {
+                let (log_blowups, points): (Vec<_>, Vec<_>) = mats.into_iter().unzip();
+                ((data, points), log_blowups)
+            })
+            .unzip();
+
         let mats_and_points = rounds
             .iter()
             .map(|(data, points)| {
@@ -272,9 +402,10 @@ where
         // Evaluate coset representations and write openings to the challenger
         let all_opened_values = mats_and_points
             .iter()
-            .map(|(mats, points)| {
-                izip!(mats.iter(), points.iter())
-                    .map(|(mat, points_for_mat)| {
+            .zip(&log_blowups)
+            .map(|((mats, points), log_blowups)| {
+                izip!(mats.iter(), points.iter(), log_blowups)
+                    .map(|(mat, points_for_mat, &log_blowup)| {
                         points_for_mat
                             .iter()
                             .map(|&point| {
@@ -286,7 +417,7 @@ where
                                 let ys =
                                     info_span!("compute opened values with Lagrange interpolation")
                                         .in_scope(|| {
-                                            let h = mat.height() >> self.fri.log_blowup;
+                                            let h = mat.height() >> log_blowup;
                                             let (low_coset, _) = mat.split_rows(h);
                                             let mut inv_denoms =
                                                 inv_denoms.get(&point).unwrap()[..h].to_vec();
@@ -328,38 +459,30 @@ where
 
         // Given a low degree polynomial `f(x)` with claimed evaluation `f(zeta)`, we can check
         // that `f(zeta)` is correct by doing a low degree test on `(f(zeta) - f(x))/(zeta - x)`.
-        // We will use `alpha` to batch together both different claimed openings `zeta` and
-        // different polynomials `f` whose evaluation vectors have the same height.
+        // We will use `alpha` to batch together the different claimed openings `zeta` and columns
+        // `f` of each matrix. The quotients of different matrices are passed to FRI separately,
+        // which corrects the degrees of those with larger blowups and batches those of the same
+        // height with its own challenge before the commit phase.
 
-        // TODO: If we allow different polynomials to have different blow_up factors
-        // we may need to revisit this and to ensure it is safe to batch them together.
-
-        // num_reduced records the number of reduced function opening point pairs
-        // of each given `log_height`.
-        let mut num_reduced = [0; 32];
-
-        // For each `log_height` from 2^1 -> 2^32, reduced_openings will contain either `None`
-        // if there are no matrices of that height, or `Some(vec)` where `vec` is equal to
-        // a sum of `(f(zeta) - f(x))/(zeta - x)` over all `f`'s of that height and
+        // For each matrix, reduced_openings will contain the log of its height and blowup, and a
+        // vector equal to a sum of `(f(zeta) - f(x))/(zeta - x)` over all its columns `f` and
         // opening points `zeta` with the sum weighted by powers of alpha.
-        let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
+        let mut reduced_openings = Vec::new();
 
-        for ((mats, points), openings_for_round) in
-            mats_and_points.iter().zip(all_opened_values.iter())
+        for ((mats, points), openings_for_round, log_blowups) in
+            izip!(&mats_and_points, &all_opened_values, &log_blowups)
         {
-            for (mat, points_for_mat, openings_for_mat) in
-                izip!(mats.iter(), points.iter(), openings_for_round.iter())
-            {
+            for (mat, points_for_mat, openings_for_mat, &log_blowup) in izip!(
+                mats.iter(),
+                points.iter(),
+                openings_for_round.iter(),
+                log_blowups
+            ) {
                 let _guard =
                     info_span!("reduce matrix quotient", dims = %mat.dimensions()).entered();
 
                 let log_height = log2_strict_usize(mat.height());
-
-                // If this is our first matrix at this height, initialise reduced_openings to zero.
-                // Otherwise, get a mutable reference to it.
-                let reduced_opening_for_log_height = reduced_openings[log_height]
-                    .get_or_insert_with(|| vec![Challenge::ZERO; mat.height()]);
-                debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
+                let mut reduced_opening_for_mat = vec![Challenge::ZERO; mat.height()];
 
                 // Treating our matrix M as the evaluations of functions M0, M1, ...
                 // Compute the evaluations of `Mred(x) = M0(x) + alpha*M1(x) + ...`
@@ -369,10 +492,10 @@ where
                         .collect::<Vec<_>>()
                 });
 
+                // Each point after the first continues the powers of alpha where the last left off.
+                let mut alpha_pow_offset = Challenge::ONE;
+                let alpha_pow_width = alpha.exp_u64(mat.width() as u64);
                 for (&point, openings) in points_for_mat.iter().zip(openings_for_mat) {
-                    // If we have multiple matrices at the same height, we need to scale mat to combine them.
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
-
                     // As we have all the openings `Mi(z)`, we can combine them using `alpha`
                     // in an identical way to before to also compute `Mred(z)`.
                     let reduced_openings: Challenge =
@@ -380,7 +503,7 @@ where
 
                     mat_compressed
                         .par_iter()
-                        .zip(reduced_opening_for_log_height.par_iter_mut())
+                        .zip(reduced_opening_for_mat.par_iter_mut())
                         // inv_denoms contains `1/(point - x)` for `x` in a coset `gK`.
                         // If `|K| =/= mat.height()` we actually want a subset of this
                         // corresponding to the evaluations over `gH` for `|H| = mat.height()`.
@@ -393,44 +516,58 @@ where
                         .for_each(|((&reduced_row, ro), &inv_denom)| {
                             *ro += alpha_pow_offset * (reduced_openings - reduced_row) * inv_denom
                         });
-                    num_reduced[log_height] += mat.width();
+                    alpha_pow_offset *= alpha_pow_width;
                 }
+                reduced_openings.push((log_height, log_blowup, reduced_opening_for_mat));
             }
         }
 
-        let fri_input = reduced_openings.into_iter().rev().flatten().collect_vec();
+        // FRI takes its inputs in descending order of height. The sort is stable, so the verifier
+        // can recover the order of matrices of the same height from the order of the rounds.
+        reduced_openings.sort_by_key(|&(log_height, _, _)| Reverse(log_height));
+        let fri_input = reduced_openings
+            .into_iter()
+            .map(|(_, log_blowup, ro)| (log_blowup, ro))
+            .collect_vec();
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
-            rounds
-                .iter()
-                .map(|(data, _)| {
-                    let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let bits_reduced = log_global_max_height - log_max_height;
-                    let reduced_index = index >> bits_reduced;
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
-                    BatchOpening {
-                        opened_values,
-                        opening_proof,
-                    }
-                })
-                .collect()
-        });
+        let fri_proof =
+            prover::prove_with_log_blowups(&g, &self.fri, fri_input, challenger, |index| {
+                rounds
+                    .iter()
+                    .map(|(data, _)| {
+                        let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
+                        let bits_reduced = log_global_max_height - log_max_height;
+                        let reduced_index = index >> bits_reduced;
+                        let (opened_values, opening_proof) =
+                            self.mmcs.open_batch(reduced_index, data);
+                        BatchOpening {
+                            opened_values,
+                            opening_proof,
+                        }
+                    })
+                    .collect()
+            });
 
         (all_opened_values, fri_proof)
     }
 
-    fn verify(
+    /// Like `Pcs::verify`, but with each matrix paired with the log of the blowup it was committed
+    /// with by `commit_with_log_blowups`.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_with_log_blowups<Challenge, Challenger>(
         &self,
         // For each round:
         rounds: Vec<(
-            Self::Commitment,
+            InputMmcs::Commitment,
             // for each matrix:
             Vec<(
                 // its domain,
-                Self::Domain,
+                TwoAdicMultiplicativeCoset<Val>,
+                // the log of its blowup,
+                usize,
                 // for each point:
                 Vec<(
                     // the point,
@@ -440,12 +577,19 @@ where
                 )>,
             )>,
         )>,
-        proof: &Self::Proof,
+        proof: &FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>,
         challenger: &mut Challenger,
-    ) -> Result<(), Self::Error> {
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+    {
         // Write evaluations to challenger
         for (_, round) in &rounds {
-            for (_, mat) in round {
+            for (_, _, mat) in round {
                 for (_, point) in mat {
                     point
                         .iter()
@@ -457,105 +601,227 @@ where
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_algebra_element();
 
-        let log_global_max_height =
-            proof.commit_phase_commits.len() + self.fri.log_blowup + self.fri.log_final_poly_len;
+        let log_global_max_height = verifier::log_max_height(&self.fri, proof)?;
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
-            // TODO: separate this out into functions
-
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
-
-            for (batch_opening, (batch_commit, mats)) in
-                zip_eq(input_proof, &rounds, FriError::InvalidProofShape)?
-            {
-                let batch_heights = mats
-                    .iter()
-                    .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
-                    // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
-
-                if let Some(batch_max_height) = batch_heights.iter().max() {
-                    let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                    let bits_reduced = log_global_max_height - log_batch_max_height;
-                    let reduced_index = index >> bits_reduced;
+        verifier::verify_with_log_blowups(
+            &g,
+            &self.fri,
+            proof,
+            challenger,
+            |index, input_proof| {
+                // TODO: separate this out into functions
+
+                // (log_height, log_blowup, reduced_opening) for each matrix
+                let mut reduced_openings = Vec::new();
+
+                for (batch_opening, (batch_commit, mats)) in
+                    zip_eq(input_proof, &rounds, FriError::InvalidProofShape)?
+                {
+                    let batch_heights = mats
+                        .iter()
+                        .map(|(domain, log_blowup, _)| domain.size() << log_blowup)
+                        .collect_vec();
+                    let batch_dims = batch_heights
+                        .iter()
+                        // TODO: MMCS doesn't really need width; we put 0 for now.
+                        .map(|&height| Dimensions { width: 0, height })
+                        .collect_vec();
+
+                    if let Some(batch_max_height) = batch_heights.iter().max() {
+                        let log_batch_max_height = log2_strict_usize(*batch_max_height);
+                        let bits_reduced = log_global_max_height - log_batch_max_height;
+                        let reduced_index = index >> bits_reduced;
+
+                        self.mmcs.verify_batch(
+                            batch_commit,
+                            &batch_dims,
+                            reduced_index,
+                            &batch_opening.opened_values,
+                            &batch_opening.opening_proof,
+                        )
+                    } else {
+                        // Empty batch?
+                        self.mmcs.verify_batch(
+                            batch_commit,
+                            &[],
+                            0,
+                            &batch_opening.opened_values,
+                            &batch_opening.opening_proof,
+                        )
+                    }
+                    .map_err(FriError::InputError)?;
 
-                    self.mmcs.verify_batch(
-                        batch_commit,
-                        &batch_dims,
-                        reduced_index,
+                    for (mat_opening, (mat_domain, log_blowup, mat_points_and_values)) in zip_eq(
                         &batch_opening.opened_values,
-                        &batch_opening.opening_proof,
-                    )
-                } else {
-                    // Empty batch?
-                    self.mmcs.verify_batch(
-                        batch_commit,
-                        &[],
-                        0,
-                        &batch_opening.opened_values,
-                        &batch_opening.opening_proof,
-                    )
-                }
-                .map_err(FriError::InputError)?;
-
-                for (mat_opening, (mat_domain, mat_points_and_values)) in zip_eq(
-                    &batch_opening.opened_values,
-                    mats,
-                    FriError::InvalidProofShape,
-                )? {
-                    let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
-
-                    let bits_reduced = log_global_max_height - log_height;
-                    let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
-
-                    // todo: this can be nicer with domain methods?
-
-                    let x = Val::GENERATOR
-                        * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
-
-                    let (alpha_pow, ro) = reduced_openings
-                        .entry(log_height)
-                        .or_insert((Challenge::ONE, Challenge::ZERO));
-
-                    for (z, ps_at_z) in mat_points_and_values {
-                        for (&p_at_x, &p_at_z) in
-                            zip_eq(mat_opening, ps_at_z, FriError::InvalidProofShape)?
-                        {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
-                            *ro += *alpha_pow * quotient;
-                            *alpha_pow *= alpha;
+                        mats,
+                        FriError::InvalidProofShape,
+                    )? {
+                        let log_height = log2_strict_usize(mat_domain.size()) + log_blowup;
+
+                        let bits_reduced = log_global_max_height - log_height;
+                        let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
+
+                        // todo: this can be nicer with domain methods?
+
+                        let x = Val::GENERATOR
+                            * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+
+                        let mut alpha_pow = Challenge::ONE;
+                        let mut ro = Challenge::ZERO;
+                        for (z, ps_at_z) in mat_points_and_values {
+                            for (&p_at_x, &p_at_z) in
+                                zip_eq(mat_opening, ps_at_z, FriError::InvalidProofShape)?
+                            {
+                                let quotient = (-p_at_z + p_at_x) / (-*z + x);
+                                ro += alpha_pow * quotient;
+                                alpha_pow *= alpha;
+                            }
                         }
+                        reduced_openings.push((log_height, *log_blowup, ro));
                     }
                 }
-            }
 
-            // `reduced_openings` would have a log_height = log_blowup entry only if there was a
-            // trace matrix of height 1. In this case the reduced opening can be skipped as it will
-            // not be checked against any commit phase commit.
-            if let Some((_alpha_pow, ro)) = reduced_openings.remove(&self.fri.log_blowup) {
-                assert!(ro.is_zero());
-            }
+                // `reduced_openings` would have a log_height = log_blowup entry only if there was a
+                // trace matrix of height 1. In this case the reduced opening can be skipped as it will
+                // not be checked against any commit phase commit.
+                reduced_openings.retain(|&(log_height, _, ro)| {
+                    if log_height == self.fri.log_blowup {
+                        assert!(ro.is_zero());
+                        return false;
+                    }
+                    true
+                });
 
-            // Return reduced openings descending by log_height.
-            Ok(reduced_openings
-                .into_iter()
-                .rev()
-                .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
-                .collect())
-        })?;
+                // Return reduced openings descending by log_height, with those of the same height
+                // in the order of their matrices, as the prover passed its inputs to FRI.
+                reduced_openings.sort_by_key(|&(log_height, _, _)| Reverse(log_height));
+                Ok(reduced_openings)
+            },
+        )?;
 
         Ok(())
     }
 }
 
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge> + Sync,
+    FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    FriMmcs::Proof: Send,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type EvaluationsOnDomain<'a> = BitReversedMatrixView<DenseMatrix<Val, &'a [Val]>>;
+    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
+    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        // This panics if (and only if) `degree` is not a power of 2 or `degree`
+        // > `1 << Val::TWO_ADICITY`.
+        TwoAdicMultiplicativeCoset::new(Val::ONE, log2_strict_usize(degree)).unwrap()
+    }
+
+    fn num_queries(&self) -> Option<usize> {
+        Some(self.fri.num_queries)
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        self.commit_with_log_blowups(
+            evaluations
+                .into_iter()
+                .map(|(domain, evals)| (domain, evals, self.fri.log_blowup))
+                .collect(),
+        )
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> Self::EvaluationsOnDomain<'a> {
+        // todo: handle extrapolation for LDEs we don't have
+        assert_eq!(domain.shift(), Val::GENERATOR);
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        assert!(lde.height() >= domain.size());
+        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, points)| {
+                let points = points
+                    .into_iter()
+                    .map(|points_for_mat| (self.fri.log_blowup, points_for_mat))
+                    .collect();
+                (data, points)
+            })
+            .collect();
+        self.open_with_log_blowups(rounds, challenger)
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let rounds = rounds
+            .into_iter()
+            .map(|(commit, mats)| {
+                let mats = mats
+                    .into_iter()
+                    .map(|(domain, claims)| (domain, self.fri.log_blowup, claims))
+                    .collect();
+                (commit, mats)
+            })
+            .collect();
+        self.verify_with_log_blowups(rounds, proof, challenger)
+    }
+}
+
 #[instrument(skip_all)]
 fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
     mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
@@ -588,15 +854,12 @@ fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matri
     max_log_height_for_point
         .into_iter()
         .map(|(z, log_height)| {
-            (
-                z,
-                batch_multiplicative_inverse(
-                    &subgroup[..(1 << log_height)]
-                        .iter()
-                        .map(|&x| z - x)
-                        .collect_vec(),
-                ),
-            )
+            let mut inv_denoms = subgroup[..(1 << log_height)]
+                .iter()
+                .map(|&x| z - x)
+                .collect_vec();
+            EF::batch_inverse_in_place(&mut inv_denoms);
+            (z, inv_denoms)
         })
         .collect()
 }
@@ -1,34 +1,53 @@
-use alloc::vec;
 use alloc::vec::Vec;
+use core::iter::Peekable;
+use core::slice;
 
-use itertools::Itertools;
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Dimensions;
-use p3_util::reverse_bits_len;
 use p3_util::zip_eq::zip_eq;
+use p3_util::{log2_strict_usize, reverse_bits_len};
 
 use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum FriError<CommitMmcsErr, InputError> {
+    /// The proof has the wrong number of queries, commit phase steps or final polynomial
+    /// coefficients.
     InvalidProofShape,
-    CommitPhaseMmcsError(CommitMmcsErr),
+    /// An opening of a commit phase codeword did not match its commitment.
+    CommitPhaseMmcsError {
+        /// The index of the fold whose commitment the opening was checked against, starting at
+        /// zero for the largest codeword.
+        layer: usize,
+        error: CommitMmcsErr,
+    },
+    /// An opening of the committed input polynomials was rejected.
     InputError(InputError),
+    /// Folding the openings of a query did not end at the final polynomial.
     FinalPolyMismatch,
+    /// The proof of work witness did not meet the configured number of bits.
     InvalidPowWitness,
 }
 
-pub fn verify<G, Val, Challenge, M, Challenger>(
+/// The most folding rounds a proof can have, as each round at least halves a codeword whose height
+/// fits in a `usize`.
+const MAX_ROUNDS: usize = usize::BITS as usize;
+
+/// Verify a FRI proof.
+///
+/// `open_input` checks the input opening proof of a query, and returns the reduced openings of the
+/// inputs at the queried index in descending order of height, as any iterator. Apart from the
+/// buffer `Mmcs::verify_batch` needs for the openings of each round, the verifier works in place
+/// on the borrowed proof, so a verifier on a small heap can avoid allocating by returning an
+/// iterator which doesn't.
+pub fn verify<G, Val, Challenge, M, Challenger, I>(
     g: &G,
     config: &FriConfig<M>,
     proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
-    open_input: impl Fn(
-        usize,
-        &G::InputProof,
-    ) -> Result<Vec<(usize, Challenge)>, FriError<M::Error, G::InputError>>,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<I, FriError<M::Error, G::InputError>>,
 ) -> Result<(), FriError<M::Error, G::InputError>>
 where
     Val: Field,
@@ -36,15 +55,163 @@ where
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
+    I: IntoIterator<Item = (usize, Challenge)>,
 {
-    let betas: Vec<Challenge> = proof
-        .commit_phase_commits
-        .iter()
-        .map(|comm| {
-            challenger.observe(comm.clone());
-            challenger.sample_algebra_element()
-        })
-        .collect();
+    let log_blowup = config.log_blowup;
+    verify_with_log_blowups(g, config, proof, challenger, |index, input_proof| {
+        let reduced_openings = open_input(index, input_proof)?;
+        Ok(reduced_openings
+            .into_iter()
+            .map(move |(log_height, ro)| (log_height, log_blowup, ro)))
+    })
+}
+
+/// Verify a FRI proof made by `prover::prove_with_log_blowups`.
+///
+/// `open_input` returns the log of the height and of the blowup of each input along with its
+/// reduced opening, and the verifier applies the same degree correction to the inputs with a
+/// larger blowup than `config.log_blowup` as the prover did.
+pub fn verify_with_log_blowups<G, Val, Challenge, M, Challenger, I>(
+    g: &G,
+    config: &FriConfig<M>,
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<I, FriError<M::Error, G::InputError>>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+    I: IntoIterator<Item = (usize, usize, Challenge)>,
+{
+    verify_and_record(g, config, proof, challenger, open_input, None)
+}
+
+/// The challenges a FRI verifier samples for a proof, and the values it folds each query to, for
+/// verifiers which replay them elsewhere, like recursive verifier circuits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriTranscript<F> {
+    /// The challenge combining inputs of the same height.
+    pub alpha: F,
+    /// The challenge correcting the degree of inputs with larger blowups.
+    pub gamma: F,
+    /// With `FriConfig::deep_sampling`, the out-of-domain point of each round and the claimed
+    /// evaluation there. Empty otherwise.
+    pub deep_points: Vec<(F, F)>,
+    /// The folding challenge of each round.
+    pub betas: Vec<F>,
+    pub queries: Vec<QueryTranscript<F>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryTranscript<F> {
+    /// The sampled query index, including any extra bits requested by the `FriGenericConfig`.
+    pub index: usize,
+    /// The value the query folds to in each round, the last of which the final polynomial
+    /// evaluates to at the query's point.
+    pub folded_evals: Vec<F>,
+}
+
+/// Verify a FRI proof like `verify`, and return the challenges sampled and the values each query
+/// folds to.
+pub fn transcript<G, Val, Challenge, M, Challenger, I>(
+    g: &G,
+    config: &FriConfig<M>,
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<I, FriError<M::Error, G::InputError>>,
+) -> Result<FriTranscript<Challenge>, FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+    I: IntoIterator<Item = (usize, Challenge)>,
+{
+    let log_blowup = config.log_blowup;
+    let mut transcript = FriTranscript {
+        alpha: Challenge::ZERO,
+        gamma: Challenge::ZERO,
+        deep_points: Vec::new(),
+        betas: Vec::new(),
+        queries: Vec::with_capacity(proof.query_proofs.len()),
+    };
+    verify_and_record(
+        g,
+        config,
+        proof,
+        challenger,
+        |index, input_proof| {
+            let reduced_openings = open_input(index, input_proof)?;
+            Ok(reduced_openings
+                .into_iter()
+                .map(move |(log_height, ro)| (log_height, log_blowup, ro)))
+        },
+        Some(&mut transcript),
+    )?;
+    Ok(transcript)
+}
+
+/// Verify a FRI proof, recording the challenges and folded values in `transcript` if given.
+fn verify_and_record<G, Val, Challenge, M, Challenger, I>(
+    g: &G,
+    config: &FriConfig<M>,
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<I, FriError<M::Error, G::InputError>>,
+    mut transcript: Option<&mut FriTranscript<Challenge>>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+    I: IntoIterator<Item = (usize, usize, Challenge)>,
+{
+    // The log of the final domain size, and of the maximum domain size.
+    let log_final_height = log_final_height(config, proof)?;
+    let log_max_height = log_max_height(config, proof)?;
+
+    let num_rounds = proof.commit_phase_commits.len();
+    if num_rounds > MAX_ROUNDS {
+        return Err(FriError::InvalidProofShape);
+    }
+
+    // The challenge with which the prover combined inputs of the same height, and the one with
+    // which it corrected the degree of inputs with larger blowups.
+    let alpha: Challenge = challenger.sample_algebra_element();
+    let gamma: Challenge = challenger.sample_algebra_element();
+
+    let num_deep_evals = if config.deep_sampling { num_rounds } else { 0 };
+    if proof.deep_evals.len() != num_deep_evals {
+        return Err(FriError::InvalidProofShape);
+    }
+
+    // The out-of-domain point sampled in each round, with the claimed evaluation there.
+    let mut deep_points = [(Challenge::ZERO, Challenge::ZERO); MAX_ROUNDS];
+    let mut betas = [Challenge::ZERO; MAX_ROUNDS];
+    for (round, comm) in proof.commit_phase_commits.iter().enumerate() {
+        challenger.observe(comm.clone());
+        if config.deep_sampling {
+            let z = challenger.sample_algebra_element();
+            let eval = proof.deep_evals[round];
+            challenger.observe_algebra_element(eval);
+            deep_points[round] = (z, eval);
+        }
+        betas[round] = challenger.sample_algebra_element();
+    }
+    let betas = &betas[..num_rounds];
+    let deep_points = config.deep_sampling.then_some(&deep_points[..num_rounds]);
+    if let Some(transcript) = transcript.as_deref_mut() {
+        transcript.alpha = alpha;
+        transcript.gamma = gamma;
+        transcript.deep_points = deep_points.unwrap_or_default().to_vec();
+        transcript.betas = betas.to_vec();
+    }
 
     // Observe all coefficients of the final polynomial.
     proof
@@ -61,27 +228,22 @@ where
         return Err(FriError::InvalidPowWitness);
     }
 
-    // The log of the maximum domain size.
-    let log_max_height =
-        proof.commit_phase_commits.len() + config.log_blowup + config.log_final_poly_len;
-
-    // The log of the final domain size.
-    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    // The openings of a coset in the current round, reused across rounds and queries.
+    let mut evals = Vec::new();
 
     for qp in &proof.query_proofs {
         let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
         let ro = open_input(index, &qp.input_proof)?;
 
-        debug_assert!(
-            ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
-            "reduced openings sorted by height descending"
-        );
-
         let mut domain_index = index >> g.extra_query_index_bits();
+        let mut query_transcript = transcript.as_ref().map(|_| QueryTranscript {
+            index,
+            folded_evals: Vec::with_capacity(num_rounds),
+        });
 
         // Starting at the evaluation at `index` of the initial domain,
         // perform fri folds until the domain size reaches the final domain size.
-        // Check after each fold that the pair of sibling evaluations at the current
+        // Check after each fold that the coset of sibling evaluations at the current
         // node match the commitment.
         let folded_eval = verify_query(
             g,
@@ -89,47 +251,137 @@ where
             &mut domain_index,
             zip_eq(
                 zip_eq(
-                    &betas,
+                    betas,
                     &proof.commit_phase_commits,
                     FriError::InvalidProofShape,
                 )?,
                 &qp.commit_phase_openings,
                 FriError::InvalidProofShape,
             )?,
-            ro,
+            ro.into_iter(),
+            (alpha, gamma),
+            deep_points,
+            &mut evals,
+            query_transcript.as_mut().map(|qt| &mut qt.folded_evals),
             log_max_height,
             log_final_height,
         )?;
 
-        let mut eval = Challenge::ZERO;
-
         // We open the final polynomial at index `domain_index`, which corresponds to evaluating
         // the polynomial at x^k, where x is the 2-adic generator of order `max_height` and k is
         // `reverse_bits_len(domain_index, log_max_height)`.
         let x = Challenge::two_adic_generator(log_max_height)
             .exp_u64(reverse_bits_len(domain_index, log_max_height) as u64);
-        let mut x_pow = Challenge::ONE;
 
-        // Evaluate the final polynomial at x.
-        for coeff in &proof.final_poly {
-            eval += *coeff * x_pow;
-            x_pow *= x;
-        }
+        // Evaluate the final polynomial at x, using Horner's method.
+        let eval = proof
+            .final_poly
+            .iter()
+            .rev()
+            .fold(Challenge::ZERO, |acc, &coeff| acc * x + coeff);
 
         if eval != folded_eval {
             return Err(FriError::FinalPolyMismatch);
         }
+
+        if let (Some(transcript), Some(query_transcript)) =
+            (transcript.as_deref_mut(), query_transcript)
+        {
+            transcript.queries.push(query_transcript);
+        }
     }
 
     Ok(())
 }
 
+/// Take the reduced openings at index `index` of height `2^log_height` from the front of
+/// `reduced_openings`, correct the degree of those with a larger blowup than `config.log_blowup`,
+/// and combine them as `sum_i alpha^i ro_i`, as the prover did with its inputs of the same height.
+fn next_reduced_opening<F: TwoAdicField, M>(
+    config: &FriConfig<M>,
+    reduced_openings: &mut Peekable<impl Iterator<Item = (usize, usize, F)>>,
+    index: usize,
+    log_height: usize,
+    (alpha, gamma): (F, F),
+) -> F {
+    let mut sum = F::ZERO;
+    let mut alpha_pow = F::ONE;
+    while let Some((_, log_blowup, mut ro)) =
+        reduced_openings.next_if(|(lh, _, _)| *lh == log_height)
+    {
+        if log_blowup > config.log_blowup {
+            let k = (1 << (log_height - config.log_blowup)) - (1 << (log_height - log_blowup));
+            let x = F::two_adic_generator(log_height)
+                .exp_u64(reverse_bits_len(index, log_height) as u64);
+            ro += ro * gamma * x.exp_u64(k as u64);
+        }
+        sum += alpha_pow * ro;
+        alpha_pow *= alpha;
+    }
+    sum
+}
+
+/// The log of the height of the codeword the final polynomial of `proof` was interpolated from.
+///
+/// This is at least `log_blowup + log_final_poly_len`, and may be up to
+/// `log_blowup + log_max_final_poly_len` if the prover stopped folding early.
+pub fn log_final_height<F, M, Witness, InputProof, InputError>(
+    config: &FriConfig<M>,
+    proof: &FriProof<F, M, Witness, InputProof>,
+) -> Result<usize, FriError<M::Error, InputError>>
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let final_poly_len = proof.final_poly.len();
+    if !final_poly_len.is_power_of_two()
+        || final_poly_len < config.final_poly_len()
+        || final_poly_len > 1 << config.log_max_final_poly_len.max(config.log_final_poly_len)
+    {
+        return Err(FriError::InvalidProofShape);
+    }
+    Ok(config.log_blowup + log2_strict_usize(final_poly_len))
+}
+
+/// The log of the height of the largest codeword folded in `proof`, given by the arity of each
+/// folding round as opened by its first query.
+pub fn log_max_height<F, M, Witness, InputProof, InputError>(
+    config: &FriConfig<M>,
+    proof: &FriProof<F, M, Witness, InputProof>,
+) -> Result<usize, FriError<M::Error, InputError>>
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let log_final_height = log_final_height(config, proof)?;
+    let Some(query_proof) = proof.query_proofs.first() else {
+        // Without any queries, only the folding schedule is left to go by.
+        return Ok(log_final_height
+            + (0..proof.commit_phase_commits.len())
+                .map(|round| config.log_folding_arity(round))
+                .sum::<usize>());
+    };
+    query_proof
+        .commit_phase_openings
+        .iter()
+        .map(|opening| {
+            let arity = opening.sibling_values.len() + 1;
+            if arity.is_power_of_two() {
+                Ok(log2_strict_usize(arity))
+            } else {
+                Err(FriError::InvalidProofShape)
+            }
+        })
+        .sum::<Result<usize, _>>()
+        .map(|log_reduction| log_final_height + log_reduction)
+}
+
 type CommitStep<'a, F, M> = (
     (
         &'a F, // The challenge point beta used for the next fold of FRI evaluations.
         &'a <M as Mmcs<F>>::Commitment, // A commitment to the FRI evaluations on the current domain.
     ),
-    &'a CommitPhaseProofStep<F, M>, // The sibling and opening proof for the current FRI node.
+    &'a CommitPhaseProofStep<F, M>, // The siblings and opening proof for the current FRI node.
 );
 
 /// Verifies a single query chain in the FRI proof.
@@ -137,59 +389,115 @@ type CommitStep<'a, F, M> = (
 /// Given an initial `index` corresponding to a point in the initial domain
 /// and a series of `reduced_openings` corresponding to evaluations of
 /// polynomials to be added in at specific domain sizes, perform the standard
-/// sequence of FRI folds, checking at each step that the pair of sibling evaluations
-/// match the commitment.
+/// sequence of FRI folds, checking at each step that the coset of sibling evaluations
+/// match the commitment. Each coset is gathered and folded in place in `evals`.
+#[allow(clippy::too_many_arguments)]
 fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
     index: &mut usize,
     steps: impl ExactSizeIterator<Item = CommitStep<'a, F, M>>,
-    reduced_openings: Vec<(usize, F)>,
+    reduced_openings: impl Iterator<Item = (usize, usize, F)>,
+    challenges: (F, F),
+    deep_points: Option<&[(F, F)]>,
+    evals: &mut Vec<F>,
+    mut folded_evals: Option<&mut Vec<F>>,
     log_max_height: usize,
     log_final_height: usize,
 ) -> Result<F, FriError<M::Error, G::InputError>>
 where
-    F: Field,
+    F: TwoAdicField,
     M: Mmcs<F> + 'a,
     G: FriGenericConfig<F>,
 {
     let mut folded_eval = F::ZERO;
-    let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut ro_iter = reduced_openings.peekable();
 
     // We start with evaluations over a domain of size (1 << log_max_height). We fold
     // using FRI until the domain size reaches (1 << log_final_height).
-    for (log_folded_height, ((&beta, comm), opening)) in zip_eq(
-        (log_final_height..log_max_height).rev(),
-        steps,
-        FriError::InvalidProofShape,
-    )? {
+    let mut log_height = log_max_height;
+    let num_layers = steps.len();
+    for (layer, ((&beta, comm), opening)) in steps.enumerate() {
         // If there are new polynomials to roll in at this height, do so.
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
-            folded_eval += ro;
-        }
+        folded_eval += next_reduced_opening(config, &mut ro_iter, *index, log_height, challenges);
 
-        // Get the index of the other sibling of the current fri node.
-        let index_sibling = *index ^ 1;
+        // The prover must fold with the arity the schedule gives this round, unless it should
+        // have stopped folding.
+        let log_next_height = ro_iter.peek().map_or(0, |(lh, _, _)| *lh);
+        if config.folding_stops(layer, log_height, log_next_height) {
+            return Err(FriError::InvalidProofShape);
+        }
+        let log_arity = config.round_log_arity(layer, log_height, log_next_height);
+        if opening.sibling_values.len() + 1 != 1 << log_arity
+            || log_height < log_final_height + log_arity
+        {
+            return Err(FriError::InvalidProofShape);
+        }
+        let log_folded_height = log_height - log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        // Insert the current evaluation among its siblings to get the whole coset.
+        let index_in_coset = *index & ((1 << log_arity) - 1);
+        evals.clear();
+        evals.extend_from_slice(&opening.sibling_values[..index_in_coset]);
+        evals.push(folded_eval);
+        evals.extend_from_slice(&opening.sibling_values[index_in_coset..]);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
 
         // Replace index with the index of the parent fri node.
-        *index >>= 1;
+        *index >>= log_arity;
 
         // Verify the commitment to the evaluations of the sibling nodes.
         config
             .mmcs
-            .verify_batch(comm, dims, *index, &[evals.clone()], &opening.opening_proof)
-            .map_err(FriError::CommitPhaseMmcsError)?;
+            .verify_batch(
+                comm,
+                dims,
+                *index,
+                slice::from_ref(evals),
+                &opening.opening_proof,
+            )
+            .map_err(|error| FriError::CommitPhaseMmcsError { layer, error })?;
+
+        // With DEEP rounds, fold the quotient `(p(z) - p(x)) / (z - x)` of the opened coset.
+        if let Some(deep_points) = deep_points {
+            let (z, eval) = deep_points[layer];
+            let generator = F::two_adic_generator(log_height);
+            for (i, e) in evals.iter_mut().enumerate() {
+                let x_index = reverse_bits_len((*index << log_arity) + i, log_height);
+                *e = (eval - *e) / (z - generator.exp_u64(x_index as u64));
+            }
+        }
+
+        // Fold the coset two at a time into the evaluation of the parent fri node, squaring beta
+        // each time. Each fold writes its results over the pairs it has already read.
+        let mut beta = beta;
+        for log_sub_height in (log_folded_height..log_height).rev() {
+            let offset = *index << (log_sub_height - log_folded_height);
+            let half = evals.len() / 2;
+            for i in 0..half {
+                let pair = [evals[2 * i], evals[2 * i + 1]];
+                evals[i] = g.fold_row(offset + i, log_sub_height, beta, pair.into_iter());
+            }
+            evals.truncate(half);
+            beta = beta.square();
+        }
+        folded_eval = evals[0];
+        log_height = log_folded_height;
+        if let Some(folded_evals) = folded_evals.as_deref_mut() {
+            folded_evals.push(folded_eval);
+        }
+    }
 
-        // Fold the pair of evaluations of sibling nodes into the evaluation of the parent fri node.
-        folded_eval = g.fold_row(*index, log_folded_height, beta, evals.into_iter());
+    // The prover must have stopped folding exactly at the final height.
+    let log_next_height = ro_iter.peek().map_or(0, |(lh, _, _)| *lh);
+    if log_height != log_final_height
+        || !config.folding_stops(num_layers, log_height, log_next_height)
+    {
+        return Err(FriError::InvalidProofShape);
     }
 
     // If ro_iter is not empty, we failed to fold in some polynomial evaluations.
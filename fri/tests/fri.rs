@@ -3,17 +3,18 @@ use core::marker::PhantomData;
 
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::{CanSampleBits, DuplexChallenger, FieldChallenger};
-use p3_commit::ExtensionMmcs;
+use p3_commit::{ExtensionMmcs, Mmcs};
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, PrimeCharacteristicRing};
-use p3_fri::{FriConfig, TwoAdicFriGenericConfig, prover, verifier};
+use p3_fri::verifier::FriError;
+use p3_fri::{FriConfig, FriProof, SecurityAssumption, TwoAdicFriGenericConfig, prover, verifier};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_util::log2_strict_usize;
+use p3_util::{log2_strict_usize, reverse_slice_index_bits};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 
@@ -28,6 +29,8 @@ type ValMmcs =
 type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
+type MyFriProof = FriProof<Challenge, ChallengeMmcs, Val, Vec<(usize, usize, Challenge)>>;
+type MyFriError = FriError<<ChallengeMmcs as Mmcs<Challenge>>::Error, ()>;
 
 fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm, MyFriConfig) {
     let perm = Perm::new_from_rng_128(rng);
@@ -35,73 +38,90 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm,
     let compress = MyCompress::new(perm.clone());
     let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
     let fri_config = FriConfig {
-        log_blowup: 1,
         log_final_poly_len,
-        num_queries: 10,
-        proof_of_work_bits: 8,
-        mmcs,
+        log_max_final_poly_len: log_final_poly_len,
+        ..FriConfig::with_security_bits(mmcs, 18, 1, SecurityAssumption::CapacityBound)
     };
     (perm, fri_config)
 }
 
 fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
-    let (perm, fc) = get_ldt_for_testing(rng, log_final_poly_len);
+    do_test_fri_ldt_with(
+        rng,
+        log_final_poly_len,
+        |_| {},
+        (5..10).map(|d| (d, 1)),
+        |_| {},
+    )
+    .unwrap();
+}
+
+/// Prove a low degree test of one batch of polynomials for each degree and blowup in `inputs`, with
+/// the test config changed by `configure`, let `tamper` modify the proof, and verify it.
+fn do_test_fri_ldt_with<R: Rng>(
+    rng: &mut R,
+    log_final_poly_len: usize,
+    configure: impl FnOnce(&mut MyFriConfig),
+    inputs: impl IntoIterator<Item = (usize, usize)>,
+    tamper: impl FnOnce(&mut MyFriProof),
+) -> Result<(), MyFriError> {
+    let (perm, mut fc) = get_ldt_for_testing(rng, log_final_poly_len);
+    configure(&mut fc);
     let dft = Radix2Dit::default();
 
     let shift = Val::GENERATOR;
 
-    let ldes: Vec<RowMajorMatrix<Val>> = (5..10)
-        .map(|deg_bits| {
+    let ldes: Vec<(usize, RowMajorMatrix<Val>)> = inputs
+        .into_iter()
+        .map(|(deg_bits, log_blowup)| {
             let evals = RowMajorMatrix::<Val>::rand_nonzero(rng, 1 << deg_bits, 16);
-            let mut lde = dft.coset_lde_batch(evals, 1, shift);
+            let mut lde = dft.coset_lde_batch(evals, log_blowup, shift);
             reverse_matrix_index_bits(&mut lde);
-            lde
+            (log_blowup, lde)
         })
         .collect();
 
-    let (proof, p_sample) = {
+    let (mut proof, p_sample) = {
         // Prover world
         let mut chal = Challenger::new(perm.clone());
         let alpha: Challenge = chal.sample_algebra_element();
 
-        let input: [_; 32] = core::array::from_fn(|log_height| {
-            let matrices_with_log_height: Vec<&RowMajorMatrix<Val>> = ldes
-                .iter()
-                .filter(|m| log2_strict_usize(m.height()) == log_height)
-                .collect();
-            if matrices_with_log_height.is_empty() {
-                None
-            } else {
-                let reduced: Vec<Challenge> = (0..(1 << log_height))
+        let mut input: Vec<(usize, Vec<Challenge>)> = ldes
+            .iter()
+            .map(|(log_blowup, m)| {
+                let reduced = (0..m.height())
                     .map(|r| {
                         alpha
                             .powers()
-                            .zip(matrices_with_log_height.iter().flat_map(|m| m.row(r)))
+                            .zip(m.row(r))
                             .map(|(alpha_pow, v)| alpha_pow * v)
                             .sum()
                     })
                     .collect();
-                Some(reduced)
-            }
-        });
+                (*log_blowup, reduced)
+            })
+            .collect();
+        input.sort_by_key(|(_, v)| Reverse(v.len()));
 
-        let input: Vec<Vec<Challenge>> = input.into_iter().rev().flatten().collect();
+        let log_max_height = log2_strict_usize(input[0].1.len());
 
-        let log_max_height = log2_strict_usize(input[0].len());
-
-        let proof = prover::prove(
-            &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
+        let proof = prover::prove_with_log_blowups(
+            &TwoAdicFriGenericConfig::<Vec<(usize, usize, Challenge)>, ()>(PhantomData),
             &fc,
             input.clone(),
             &mut chal,
             |idx| {
                 // As our "input opening proof", just pass through the literal reduced openings.
                 let mut ro = vec![];
-                for v in &input {
+                for (log_blowup, v) in &input {
                     let log_height = log2_strict_usize(v.len());
-                    ro.push((log_height, v[idx >> (log_max_height - log_height)]));
+                    ro.push((
+                        log_height,
+                        *log_blowup,
+                        v[idx >> (log_max_height - log_height)],
+                    ));
                 }
-                ro.sort_by_key(|(lh, _)| Reverse(*lh));
+                ro.sort_by_key(|(lh, _, _)| Reverse(*lh));
                 ro
             },
         );
@@ -109,22 +129,24 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
         (proof, chal.sample_bits(8))
     };
 
+    tamper(&mut proof);
+
     let mut v_challenger = Challenger::new(perm);
     let _alpha: Challenge = v_challenger.sample_algebra_element();
-    verifier::verify(
-        &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
+    verifier::verify_with_log_blowups(
+        &TwoAdicFriGenericConfig::<Vec<(usize, usize, Challenge)>, ()>(PhantomData),
         &fc,
         &proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
-    )
-    .unwrap();
+    )?;
 
     assert_eq!(
         p_sample,
         v_challenger.sample_bits(8),
         "prover and verifier transcript have same state after FRI"
     );
+    Ok(())
 }
 
 #[test]
@@ -146,3 +168,266 @@ fn test_fri_ldt_should_panic() {
         do_test_fri_ldt(&mut rng, 5);
     }
 }
+
+#[test]
+fn test_fri_ldt_reports_failing_layer() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let result = do_test_fri_ldt_with(
+        &mut rng,
+        1,
+        |_| {},
+        (5..10).map(|d| (d, 1)),
+        |proof| {
+            proof.query_proofs[0].commit_phase_openings[2].sibling_values[0] += Challenge::ONE;
+        },
+    );
+    assert!(matches!(
+        result,
+        Err(FriError::CommitPhaseMmcsError { layer: 2, .. })
+    ));
+}
+
+#[test]
+fn test_fri_ldt_folding_schedule() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Fold codewords of heights 2^10 and 2^6 by 16, then by 4 twice, down to height 2^2.
+    let schedule = |fc: &mut MyFriConfig| fc.log_folding_arities = vec![4, 2, 2];
+    do_test_fri_ldt_with(&mut rng, 1, schedule, [(9, 1), (5, 1)], |proof| {
+        assert_eq!(proof.commit_phase_commits.len(), 3);
+        let siblings = proof.query_proofs[0]
+            .commit_phase_openings
+            .iter()
+            .map(|opening| opening.sibling_values.len())
+            .collect::<Vec<_>>();
+        assert_eq!(siblings, [15, 3, 3]);
+    })
+    .unwrap();
+
+    // A round folds by less rather than fold past the next input.
+    let mut rng = SmallRng::seed_from_u64(1);
+    let schedule = |fc: &mut MyFriConfig| fc.log_folding_arities = vec![3];
+    do_test_fri_ldt_with(&mut rng, 1, schedule, [(9, 1), (7, 1)], |proof| {
+        assert_eq!(
+            proof.query_proofs[0].commit_phase_openings[0]
+                .sibling_values
+                .len(),
+            3
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_fri_ldt_early_stop() {
+    // Heights 2^10 and 2^6 fold by 16 twice to 2^2, but can't then fold by 16 again.
+    let schedule = |fc: &mut MyFriConfig| fc.log_folding_arities = vec![4, 4, 4];
+
+    // Without room for a longer final polynomial, the last round folds by 2 instead.
+    let mut rng = SmallRng::seed_from_u64(0);
+    do_test_fri_ldt_with(&mut rng, 0, schedule, [(9, 1), (5, 1)], |proof| {
+        assert_eq!(proof.commit_phase_commits.len(), 3);
+        assert_eq!(proof.final_poly.len(), 1);
+    })
+    .unwrap();
+
+    // Otherwise the prover skips the last round and sends a final polynomial of length 2.
+    let mut rng = SmallRng::seed_from_u64(0);
+    let early_stop = |fc: &mut MyFriConfig| {
+        schedule(fc);
+        fc.log_max_final_poly_len = 1;
+    };
+    do_test_fri_ldt_with(&mut rng, 0, early_stop, [(9, 1), (5, 1)], |proof| {
+        assert_eq!(proof.commit_phase_commits.len(), 2);
+        assert_eq!(proof.final_poly.len(), 2);
+    })
+    .unwrap();
+
+    // The final polynomial is checked against the degree it should have.
+    let mut rng = SmallRng::seed_from_u64(0);
+    let result = do_test_fri_ldt_with(&mut rng, 0, early_stop, [(9, 1), (5, 1)], |proof| {
+        proof.final_poly.push(Challenge::ZERO);
+    });
+    assert_eq!(result, Err(FriError::InvalidProofShape));
+}
+
+#[test]
+fn test_fri_ldt_batches_inputs_of_same_height() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Three inputs of height 2^8 and two of height 2^6 are folded in once per height.
+    do_test_fri_ldt_with(
+        &mut rng,
+        1,
+        |_| {},
+        [(7, 1), (7, 1), (7, 1), (5, 1), (5, 1)],
+        |proof| {
+            assert_eq!(proof.commit_phase_commits.len(), 6);
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_fri_proof_size_estimate() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (_, mut fri_config) = get_ldt_for_testing(&mut rng, 1);
+    fri_config.log_folding_arities = vec![4, 2, 2];
+    let estimate = fri_config.proof_size_estimate::<Challenge>(10, 1);
+    assert_eq!(estimate.num_rounds, 3);
+    assert_eq!(estimate.conjectured_soundness_bits, 18);
+    assert_eq!(estimate.proven_soundness_bits, 13);
+
+    // The estimate matches the shape of a real proof with the same parameters.
+    let mut rng = SmallRng::seed_from_u64(0);
+    let schedule = |fc: &mut MyFriConfig| fc.log_folding_arities = vec![4, 2, 2];
+    do_test_fri_ldt_with(&mut rng, 1, schedule, [(9, 1)], |proof| {
+        assert_eq!(proof.commit_phase_commits.len(), estimate.num_rounds);
+    })
+    .unwrap();
+
+    // More queries cost more bytes.
+    fri_config.num_queries *= 2;
+    assert!(fri_config.proof_size_estimate::<Challenge>(10, 1).bytes > estimate.bytes);
+}
+
+#[test]
+fn test_fri_config_with_security_bits() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut config = |assumption| {
+        let (_, fri_config) = get_ldt_for_testing(&mut rng, 0);
+        FriConfig::with_security_bits(fri_config.mmcs, 100, 1, assumption)
+    };
+
+    let capacity = config(SecurityAssumption::CapacityBound);
+    assert_eq!(capacity.proof_of_work_bits, 16);
+    assert_eq!(capacity.num_queries, 84);
+    assert_eq!(capacity.conjectured_soundness_bits(), 100);
+
+    // Each query gives half a bit within the Johnson bound, and `-log2(3/4) ≈ 0.415` bits within
+    // the unique decoding radius.
+    assert_eq!(config(SecurityAssumption::JohnsonBound).num_queries, 168);
+    assert_eq!(config(SecurityAssumption::UniqueDecoding).num_queries, 203);
+}
+
+#[test]
+fn test_fri_ldt_mixed_blowups() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Codewords of height 2^8 at rates 1/2 and 1/4, and of height 2^6 at rate 1/8.
+    do_test_fri_ldt_with(&mut rng, 1, |_| {}, [(7, 1), (6, 2), (3, 3)], |_| {}).unwrap();
+}
+
+// A codeword claimed at rate 1/4 but only of degree low enough for rate 1/2 fails the degree
+// corrected test.
+#[test]
+#[should_panic(expected = "All coefficients beyond final_poly_len must be zero")]
+fn test_fri_ldt_mixed_blowups_should_panic() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (perm, fc) = get_ldt_for_testing(&mut rng, 1);
+    let mut chal = Challenger::new(perm);
+    let evals = (0..1 << 7)
+        .map(|_| rng.random())
+        .collect::<Vec<Challenge>>();
+    let mut lde = Radix2Dit::default().coset_lde(evals, 1, Challenge::ONE);
+    reverse_slice_index_bits(&mut lde);
+    prover::prove_with_log_blowups(
+        &TwoAdicFriGenericConfig::<(), ()>(PhantomData),
+        &fc,
+        vec![(2, lde)],
+        &mut chal,
+        |_| (),
+    );
+}
+
+#[test]
+fn test_fri_ldt_deep_sampling() {
+    let deep = |fc: &mut MyFriConfig| {
+        fc.deep_sampling = true;
+        fc.log_folding_arities = vec![2, 1, 3];
+    };
+    let mut rng = SmallRng::seed_from_u64(0);
+    do_test_fri_ldt_with(&mut rng, 1, deep, [(9, 1), (7, 2), (5, 1)], |proof| {
+        assert_eq!(proof.deep_evals.len(), proof.commit_phase_commits.len());
+    })
+    .unwrap();
+
+    // A wrong out-of-domain evaluation is rejected.
+    let mut rng = SmallRng::seed_from_u64(0);
+    let result = do_test_fri_ldt_with(&mut rng, 1, deep, [(9, 1), (5, 1)], |proof| {
+        proof.deep_evals[1] += Challenge::ONE;
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_fri_proof_compression() {
+    let num_nodes = |proof: &MyFriProof| {
+        proof
+            .query_proofs
+            .iter()
+            .flat_map(|qp| &qp.commit_phase_openings)
+            .map(|step| step.opening_proof.len())
+            .sum::<usize>()
+    };
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let many_queries = |fc: &mut MyFriConfig| fc.num_queries = 40;
+    do_test_fri_ldt_with(&mut rng, 1, many_queries, [(9, 1), (5, 1)], |proof| {
+        let original_nodes = num_nodes(proof);
+        let compressed = proof.clone().compress();
+        let compressed_nodes = compressed
+            .query_proofs
+            .iter()
+            .flat_map(|qp| &qp.commit_phase_openings)
+            .map(|step| step.path_nodes.len())
+            .sum::<usize>();
+        assert!(compressed_nodes < original_nodes);
+
+        // The verifier checks the restored proof.
+        *proof = compressed.decompress().unwrap();
+        assert_eq!(num_nodes(proof), original_nodes);
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_fri_transcript() {
+    let mut proof = None;
+    let mut rng = SmallRng::seed_from_u64(0);
+    do_test_fri_ldt_with(
+        &mut rng,
+        1,
+        |_| {},
+        [(9, 1), (5, 1)],
+        |p| {
+            proof = Some(p.clone());
+        },
+    )
+    .unwrap();
+    let proof = proof.unwrap();
+
+    // Replay the verifier with the same permutation the test proved with.
+    let (perm, fc) = get_ldt_for_testing(&mut SmallRng::seed_from_u64(0), 1);
+    let mut challenger = Challenger::new(perm);
+    let _alpha: Challenge = challenger.sample_algebra_element();
+    let transcript = verifier::transcript(
+        &TwoAdicFriGenericConfig::<Vec<(usize, usize, Challenge)>, ()>(PhantomData),
+        &fc,
+        &proof,
+        &mut challenger,
+        |_index, input_proof| {
+            Ok(input_proof
+                .iter()
+                .map(|&(log_height, _, ro)| (log_height, ro))
+                .collect::<Vec<_>>())
+        },
+    )
+    .unwrap();
+
+    let num_rounds = proof.commit_phase_commits.len();
+    assert_eq!(transcript.betas.len(), num_rounds);
+    assert!(transcript.deep_points.is_empty());
+    assert_eq!(transcript.queries.len(), fc.num_queries);
+    for query in &transcript.queries {
+        assert!(query.index < 1 << 10);
+        assert_eq!(query.folded_evals.len(), num_rounds);
+    }
+}
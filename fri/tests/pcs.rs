@@ -5,7 +5,7 @@ use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs, presets};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
@@ -168,7 +168,9 @@ mod babybear_fri_pcs {
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+    fn get_pcs_with(
+        fri_config: impl FnOnce(ChallengeMmcs) -> FriConfig<ChallengeMmcs>,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(&mut seeded_rng());
         let hash = MyHash::new(perm.clone());
         let compress = MyCompress::new(perm.clone());
@@ -176,24 +178,172 @@ mod babybear_fri_pcs {
         let val_mmcs = ValMmcs::new(hash, compress);
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
-        let fri_config = FriConfig {
-            log_blowup,
-            log_final_poly_len: 0,
-            num_queries: 10,
-            proof_of_work_bits: 8,
-            mmcs: challenge_mmcs,
-        };
-
-        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config(challenge_mmcs));
         (pcs, Challenger::new(perm))
     }
 
+    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        get_pcs_with(|mmcs| {
+            FriConfig::with_security_bits(mmcs, 16, log_blowup, SecurityAssumption::CapacityBound)
+        })
+    }
+
     mod blowup_1 {
         make_tests_for_pcs!(super::get_pcs(1));
     }
     mod blowup_2 {
         make_tests_for_pcs!(super::get_pcs(2));
     }
+    mod preset_fast_prover {
+        make_tests_for_pcs!(super::get_pcs_with(|mmcs| super::presets::fast_prover(
+            mmcs, 16
+        )));
+    }
+    mod preset_small_proof {
+        make_tests_for_pcs!(super::get_pcs_with(|mmcs| super::presets::small_proof(
+            mmcs, 16
+        )));
+    }
+    mod preset_balanced {
+        make_tests_for_pcs!(super::get_pcs_with(|mmcs| super::presets::balanced(
+            mmcs, 16
+        )));
+    }
+
+    /// Commit random matrices of each `(log_degree, log_blowup)` in `log_degrees_and_blowups`, open
+    /// them at one point, and verify with the blowups given by `verifier_log_blowup`.
+    fn do_test_mixed_blowups(
+        log_degrees_and_blowups: &[(usize, usize)],
+        verifier_log_blowup: impl Fn(usize) -> usize,
+    ) -> Result<(), <MyPcs as Pcs<Challenge, Challenger>>::Error> {
+        let (pcs, challenger) = get_pcs(1);
+        let mut rng = seeded_rng();
+        let evaluations = log_degrees_and_blowups
+            .iter()
+            .map(|&(log_degree, log_blowup)| {
+                let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_degree,
+                );
+                let width = 5 + rng.random_range(0..=10);
+                let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_degree, width);
+                (domain, evals, log_blowup)
+            })
+            .collect_vec();
+
+        let mut p_challenger = challenger.clone();
+        let (commit, data) = pcs.commit_with_log_blowups(evaluations.clone());
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_algebra_element();
+        let points = log_degrees_and_blowups
+            .iter()
+            .map(|&(_, log_blowup)| (log_blowup, vec![zeta]))
+            .collect();
+        let (openings, proof) = pcs.open_with_log_blowups(vec![(&data, points)], &mut p_challenger);
+
+        let mut v_challenger = challenger;
+        v_challenger.observe(commit);
+        let zeta: Challenge = v_challenger.sample_algebra_element();
+        let claims = izip!(evaluations, openings.into_iter().flatten())
+            .map(|((domain, _, log_blowup), mut mat_openings)| {
+                let claim = vec![(zeta, mat_openings.remove(0))];
+                (domain, verifier_log_blowup(log_blowup), claim)
+            })
+            .collect();
+        pcs.verify_with_log_blowups(vec![(commit, claims)], &proof, &mut v_challenger)
+    }
+
+    #[test]
+    fn mixed_blowups() {
+        // Trace-like matrices at rate 1/4 next to quotient-like ones at rate 1/2, including pairs
+        // whose codewords have the same height but different blowups.
+        do_test_mixed_blowups(&[(4, 2), (5, 1), (4, 1), (3, 2), (2, 3)], |b| b).unwrap();
+        do_test_mixed_blowups(&[(6, 3), (6, 1), (5, 2)], |b| b).unwrap();
+    }
+
+    #[test]
+    fn mixed_blowups_reject_wrong_blowup() {
+        // A verifier expecting a lower rate than the matrix was committed at sees the wrong height.
+        let result = do_test_mixed_blowups(&[(4, 2), (5, 1)], |_| 1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn compressed_proof() {
+        let (pcs, challenger) = get_pcs(1);
+        let mut rng = seeded_rng();
+        let domains_and_polys = [8, 6]
+            .map(|log_degree| {
+                let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_degree,
+                );
+                (
+                    domain,
+                    RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_degree, 8),
+                )
+            })
+            .to_vec();
+
+        let mut p_challenger = challenger.clone();
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_polys.clone());
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_algebra_element();
+        let (openings, proof) = pcs.open(vec![(&data, vec![vec![zeta]; 2])], &mut p_challenger);
+
+        let num_input_nodes = |proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof| {
+            proof
+                .query_proofs
+                .iter()
+                .flat_map(|qp| &qp.input_proof)
+                .map(|opening| opening.opening_proof.len())
+                .sum::<usize>()
+        };
+        let original_nodes = num_input_nodes(&proof);
+        let compressed = proof.compress().compress_input_openings();
+        let compressed_nodes = compressed
+            .query_proofs
+            .iter()
+            .flat_map(|qp| &qp.input_proof)
+            .map(|opening| opening.path_nodes.len())
+            .sum::<usize>();
+        assert!(compressed_nodes < original_nodes);
+
+        // The verifier checks the restored proof.
+        let proof = compressed
+            .decompress_input_openings()
+            .and_then(|compressed| compressed.decompress())
+            .unwrap();
+        assert_eq!(num_input_nodes(&proof), original_nodes);
+
+        let mut v_challenger = challenger;
+        v_challenger.observe(commit);
+        let zeta: Challenge = v_challenger.sample_algebra_element();
+        let claims = izip!(domains_and_polys, openings.into_iter().flatten())
+            .map(|((domain, _), mut mat_openings)| (domain, vec![(zeta, mat_openings.remove(0))]))
+            .collect();
+        pcs.verify(vec![(commit, claims)], &proof, &mut v_challenger)
+            .unwrap();
+    }
+
+    #[test]
+    fn preset_proof_sizes() {
+        let estimate = |(pcs, _): (MyPcs, Challenger)| {
+            pcs.fri_config().proof_size_estimate::<Challenge>(20, 4)
+        };
+        let fast = estimate(get_pcs_with(|mmcs| presets::fast_prover(mmcs, 100)));
+        let small = estimate(get_pcs_with(|mmcs| presets::small_proof(mmcs, 100)));
+        let balanced = estimate(get_pcs_with(|mmcs| presets::balanced(mmcs, 100)));
+
+        for e in [fast, small, balanced] {
+            assert!(e.conjectured_soundness_bits >= 100);
+        }
+        assert!(small.num_rounds < balanced.num_rounds);
+        assert!(balanced.num_rounds < fast.num_rounds);
+        assert!(small.bytes < balanced.bytes);
+        assert!(balanced.bytes < fast.bytes);
+    }
 }
 
 mod m31_fri_pcs {
@@ -229,13 +379,12 @@ mod m31_fri_pcs {
         let compress = MyCompress::new(byte_hash);
         let val_mmcs = ValMmcs::new(field_hash, compress);
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-        let fri_config = FriConfig {
+        let fri_config = FriConfig::with_security_bits(
+            challenge_mmcs,
+            16,
             log_blowup,
-            log_final_poly_len: 0,
-            num_queries: 10,
-            proof_of_work_bits: 8,
-            mmcs: challenge_mmcs,
-        };
+            SecurityAssumption::CapacityBound,
+        );
         let pcs = Pcs {
             mmcs: val_mmcs,
             fri_config,
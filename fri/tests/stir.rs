@@ -0,0 +1,113 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::stir::verifier::StirError;
+use p3_fri::stir::{StirConfig, StirProof, prover, verifier};
+use p3_fri::{FriConfig, SecurityAssumption};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyStirProof = StirProof<Challenge, ChallengeMmcs, Val>;
+
+fn get_mmcs(perm: &Perm) -> ChallengeMmcs {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    ChallengeMmcs::new(ValMmcs::new(hash, compress))
+}
+
+/// Prove a random polynomial of degree less than `2^log_degree`, let `tamper` modify the proof,
+/// and verify it.
+fn do_test_stir_with(
+    log_degree: usize,
+    tamper: impl FnOnce(&mut MyStirProof),
+) -> Result<(), StirError<<ChallengeMmcs as p3_commit::Mmcs<Challenge>>::Error>> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let config = StirConfig::with_security_bits(get_mmcs(&perm), 24, 1, 2);
+    let coeffs: Vec<Challenge> = (0..1 << log_degree).map(|_| rng.random()).collect();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let mut proof = prover::prove::<Val, _, _, _>(&config, coeffs, &mut challenger);
+    assert_eq!(proof.rounds.len(), config.num_rounds(log_degree));
+    tamper(&mut proof);
+
+    let mut challenger = Challenger::new(perm);
+    verifier::verify::<Val, _, _, _>(&config, log_degree, &proof, &mut challenger)
+}
+
+#[test]
+fn test_stir() {
+    for log_degree in [6, 8, 10] {
+        do_test_stir_with(log_degree, |_| {}).unwrap();
+    }
+}
+
+#[test]
+fn test_stir_rejects_tampered_final_poly() {
+    let result = do_test_stir_with(10, |proof| proof.final_poly[0] += Challenge::ONE);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_stir_rejects_tampered_ood_answer() {
+    let result = do_test_stir_with(10, |proof| proof.rounds[0].ood_answers[0] += Challenge::ONE);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_stir_rejects_tampered_row() {
+    let result = do_test_stir_with(10, |proof| {
+        proof.rounds[1].queries[0].row[1] += Challenge::ONE
+    });
+    assert!(matches!(
+        result,
+        Err(StirError::CommitMmcsError { round: 1, .. })
+    ));
+}
+
+#[test]
+fn test_stir_rejects_missing_round() {
+    let result = do_test_stir_with(10, |proof| {
+        proof.rounds.pop();
+    });
+    assert_eq!(result, Err(StirError::InvalidProofShape));
+}
+
+#[test]
+fn test_stir_proof_smaller_than_fri() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+
+    let fri_config = FriConfig {
+        log_folding_arities: vec![4; 6],
+        ..FriConfig::with_security_bits(get_mmcs(&perm), 128, 1, SecurityAssumption::CapacityBound)
+    };
+    let stir_config = StirConfig::with_security_bits(get_mmcs(&perm), 128, 1, 4);
+
+    let fri = fri_config.proof_size_estimate::<Challenge>(24, 1);
+    let stir = stir_config.proof_size_estimate::<Challenge>(24);
+    assert_eq!(
+        fri.conjectured_soundness_bits,
+        stir.conjectured_soundness_bits
+    );
+    assert!(
+        stir.bytes < fri.bytes,
+        "STIR: {} bytes, FRI: {} bytes",
+        stir.bytes,
+        fri.bytes
+    );
+}
@@ -1,8 +1,12 @@
-use p3_field::extension::{BinomiallyExtendable, HasTwoAdicBinomialExtension};
+use p3_field::extension::{
+    BinomiallyExtendable, BinomiallyExtendableAlgebra, HasTwoAdicBinomialExtension,
+};
 use p3_field::{PrimeCharacteristicRing, TwoAdicField, field_to_array};
 
 use crate::Goldilocks;
 
+impl BinomiallyExtendableAlgebra<Self, 2> for Goldilocks {}
+
 impl BinomiallyExtendable<2> for Goldilocks {
     // Verifiable in Sage with
     // `R.<x> = GF(p)[]; assert (x^2 - 7).is_irreducible()`.
@@ -31,6 +35,8 @@ impl HasTwoAdicBinomialExtension<2> for Goldilocks {
     }
 }
 
+impl BinomiallyExtendableAlgebra<Self, 5> for Goldilocks {}
+
 impl BinomiallyExtendable<5> for Goldilocks {
     // Verifiable via:
     //  ```sage
@@ -393,7 +393,9 @@ impl PrimeField64 for Goldilocks {
     fn as_canonical_u64(&self) -> u64 {
         let mut c = self.value;
         // We only need one condition subtraction, since 2 * ORDER would not fit in a u64.
-        if c >= Self::ORDER_U64 {
+        if cfg!(feature = "ct") {
+            c -= select_u64(c >= Self::ORDER_U64, Self::ORDER_U64);
+        } else if c >= Self::ORDER_U64 {
             c -= Self::ORDER_U64;
         }
         c
@@ -416,7 +418,9 @@ impl Add for Goldilocks {
     fn add(self, rhs: Self) -> Self {
         let (sum, over) = self.value.overflowing_add(rhs.value);
         let (mut sum, over) = sum.overflowing_add(u64::from(over) * Self::NEG_ORDER);
-        if over {
+        if cfg!(feature = "ct") {
+            sum += select_u64(over, Self::NEG_ORDER); // Cannot overflow.
+        } else if over {
             // NB: self.value > Self::ORDER && rhs.value > Self::ORDER is necessary but not
             // sufficient for double-overflow.
             // This assume does two things:
@@ -456,7 +460,9 @@ impl Sub for Goldilocks {
     fn sub(self, rhs: Self) -> Self {
         let (diff, under) = self.value.overflowing_sub(rhs.value);
         let (mut diff, under) = diff.overflowing_sub(u64::from(under) * Self::NEG_ORDER);
-        if under {
+        if cfg!(feature = "ct") {
+            diff -= select_u64(under, Self::NEG_ORDER); // Cannot underflow.
+        } else if under {
             // NB: self.value < NEG_ORDER - 1 && rhs.value > ORDER is necessary but not
             // sufficient for double-underflow.
             // This assume does two things:
@@ -534,7 +540,9 @@ pub(crate) fn reduce128(x: u128) -> Goldilocks {
     let x_hi_lo = x_hi & Goldilocks::NEG_ORDER;
 
     let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
-    if borrow {
+    if cfg!(feature = "ct") {
+        t0 -= select_u64(borrow, Goldilocks::NEG_ORDER); // Cannot underflow.
+    } else if borrow {
         branch_hint(); // A borrow is exceedingly rare. It is faster to branch.
         t0 -= Goldilocks::NEG_ORDER; // Cannot underflow.
     }
@@ -543,6 +551,13 @@ pub(crate) fn reduce128(x: u128) -> Goldilocks {
     Goldilocks::new(t2)
 }
 
+/// Return `x` if `cond` holds and zero otherwise, using a mask rather than a branch so that the
+/// control flow doesn't depend on `cond`.
+#[inline(always)]
+const fn select_u64(cond: bool, x: u64) -> u64 {
+    x & (cond as u64).wrapping_neg()
+}
+
 #[inline]
 #[allow(clippy::cast_possible_truncation)]
 const fn split(x: u128) -> (u64, u64) {
@@ -670,6 +685,11 @@ mod tests {
 
     test_field_dft!(radix2dit, crate::Goldilocks, p3_dft::Radix2Dit<_>);
     test_field_dft!(bowers, crate::Goldilocks, p3_dft::Radix2Bowers);
+    test_field_dft!(
+        bowers_bitrev,
+        crate::Goldilocks,
+        p3_dft::Radix2BowersBitReversed
+    );
     test_field_dft!(
         parallel,
         crate::Goldilocks,
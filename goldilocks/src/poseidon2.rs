@@ -11,10 +11,10 @@ use alloc::vec::Vec;
 
 use p3_field::{Algebra, InjectiveMonomial};
 use p3_poseidon2::{
-    ExternalLayer, ExternalLayerConstants, ExternalLayerConstructor, HLMDSMat4, InternalLayer,
-    InternalLayerConstructor, MDSMat4, Poseidon2, add_rc_and_sbox_generic,
-    external_initial_permute_state, external_terminal_permute_state, internal_permute_state,
-    matmul_internal,
+    ExternalLayer, ExternalLayerConstants, ExternalLayerConstructor, GenericPoseidon2LinearLayers,
+    HLMDSMat4, InternalLayer, InternalLayerConstructor, MDSMat4, Poseidon2,
+    add_rc_and_sbox_generic, external_initial_permute_state, external_terminal_permute_state,
+    internal_permute_state, matmul_internal,
 };
 
 use crate::Goldilocks;
@@ -22,13 +22,14 @@ use crate::Goldilocks;
 /// Degree of the chosen permutation polynomial for Goldilocks, used as the Poseidon2 S-Box.
 ///
 /// As p - 1 = 2^32 * 3 * 5 * 17 * ... the smallest choice for a degree D satisfying gcd(p - 1, D) = 1 is 7.
-const GOLDILOCKS_S_BOX_DEGREE: u64 = 7;
+pub(crate) const GOLDILOCKS_S_BOX_DEGREE: u64 = 7;
 
 /// An implementation of the Poseidon2 hash function for the Goldilocks field.
 ///
-/// It acts on arrays of the form `[Goldilocks; WIDTH]`.
-/// Currently the internal layers are unoptimized. These could be sped up in a similar way to
-/// how it was done for Monty31 fields.
+/// It acts on arrays of the form `[Goldilocks; WIDTH]` or `[Goldilocks::Packing; WIDTH]`. On
+/// AVX2 and AVX-512 the internal layers of the packed permutation fold the sum of the state into
+/// each diagonal product before reducing. Permutations over other rings implementing
+/// `Algebra<Goldilocks>` can be built from `GenericPoseidon2LinearLayersGoldilocks`.
 pub type Poseidon2Goldilocks<const WIDTH: usize> = Poseidon2<
     Goldilocks,
     Poseidon2ExternalLayerGoldilocks<WIDTH>,
@@ -122,7 +123,7 @@ pub const MATRIX_DIAG_20_GOLDILOCKS: [Goldilocks; 20] = Goldilocks::new_array([
 /// The internal layers of the Poseidon2 permutation.
 #[derive(Debug, Clone, Default)]
 pub struct Poseidon2InternalLayerGoldilocks {
-    internal_constants: Vec<Goldilocks>,
+    pub(crate) internal_constants: Vec<Goldilocks>,
 }
 
 impl InternalLayerConstructor<Goldilocks> for Poseidon2InternalLayerGoldilocks {
@@ -131,11 +132,9 @@ impl InternalLayerConstructor<Goldilocks> for Poseidon2InternalLayerGoldilocks {
     }
 }
 
-impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
-    InternalLayer<A, 8, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks
-{
+impl InternalLayer<Goldilocks, 8, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
-    fn permute_state(&self, state: &mut [A; 8]) {
+    fn permute_state(&self, state: &mut [Goldilocks; 8]) {
         internal_permute_state(
             state,
             |x| matmul_internal(x, MATRIX_DIAG_8_GOLDILOCKS),
@@ -144,11 +143,9 @@ impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
     }
 }
 
-impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
-    InternalLayer<A, 12, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks
-{
+impl InternalLayer<Goldilocks, 12, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
-    fn permute_state(&self, state: &mut [A; 12]) {
+    fn permute_state(&self, state: &mut [Goldilocks; 12]) {
         internal_permute_state(
             state,
             |x| matmul_internal(x, MATRIX_DIAG_12_GOLDILOCKS),
@@ -157,11 +154,9 @@ impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
     }
 }
 
-impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
-    InternalLayer<A, 16, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks
-{
+impl InternalLayer<Goldilocks, 16, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
-    fn permute_state(&self, state: &mut [A; 16]) {
+    fn permute_state(&self, state: &mut [Goldilocks; 16]) {
         internal_permute_state(
             state,
             |x| matmul_internal(x, MATRIX_DIAG_16_GOLDILOCKS),
@@ -170,11 +165,9 @@ impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
     }
 }
 
-impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
-    InternalLayer<A, 20, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks
-{
+impl InternalLayer<Goldilocks, 20, GOLDILOCKS_S_BOX_DEGREE> for Poseidon2InternalLayerGoldilocks {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
-    fn permute_state(&self, state: &mut [A; 20]) {
+    fn permute_state(&self, state: &mut [Goldilocks; 20]) {
         internal_permute_state(
             state,
             |x| matmul_internal(x, MATRIX_DIAG_20_GOLDILOCKS),
@@ -183,6 +176,47 @@ impl<A: Algebra<Goldilocks> + InjectiveMonomial<GOLDILOCKS_S_BOX_DEGREE>>
     }
 }
 
+/// An implementation of the matrix multiplications in the internal and external layers of Poseidon2.
+///
+/// This can act on `[A; WIDTH]` for any ring implementing `Algebra<Goldilocks>`.
+/// This will usually be slower than the Poseidon2 permutation built from
+/// `Poseidon2InternalLayerGoldilocks` and `Poseidon2ExternalLayerGoldilocks` but it does work in
+/// more cases.
+#[derive(Debug, Clone, Default)]
+pub struct GenericPoseidon2LinearLayersGoldilocks;
+
+impl<A: Algebra<Goldilocks>> GenericPoseidon2LinearLayers<A, 8>
+    for GenericPoseidon2LinearLayersGoldilocks
+{
+    fn internal_linear_layer(state: &mut [A; 8]) {
+        matmul_internal(state, MATRIX_DIAG_8_GOLDILOCKS);
+    }
+}
+
+impl<A: Algebra<Goldilocks>> GenericPoseidon2LinearLayers<A, 12>
+    for GenericPoseidon2LinearLayersGoldilocks
+{
+    fn internal_linear_layer(state: &mut [A; 12]) {
+        matmul_internal(state, MATRIX_DIAG_12_GOLDILOCKS);
+    }
+}
+
+impl<A: Algebra<Goldilocks>> GenericPoseidon2LinearLayers<A, 16>
+    for GenericPoseidon2LinearLayersGoldilocks
+{
+    fn internal_linear_layer(state: &mut [A; 16]) {
+        matmul_internal(state, MATRIX_DIAG_16_GOLDILOCKS);
+    }
+}
+
+impl<A: Algebra<Goldilocks>> GenericPoseidon2LinearLayers<A, 20>
+    for GenericPoseidon2LinearLayersGoldilocks
+{
+    fn internal_linear_layer(state: &mut [A; 20]) {
+        matmul_internal(state, MATRIX_DIAG_20_GOLDILOCKS);
+    }
+}
+
 /// The external layers of the Poseidon2 permutation.
 #[derive(Clone)]
 pub struct Poseidon2ExternalLayerGoldilocks<const WIDTH: usize> {
@@ -374,9 +408,11 @@ pub const HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS: [u64; 22] = [
 mod tests {
     use core::array;
 
-    use p3_field::PrimeCharacteristicRing;
+    use p3_field::{Field, PackedValue, PrimeCharacteristicRing};
     use p3_poseidon2::Poseidon2;
     use p3_symmetric::Permutation;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
 
     use super::*;
 
@@ -468,4 +504,33 @@ mod tests {
         hl_poseidon2_goldilocks_width_8(&mut input);
         assert_eq!(input, expected);
     }
+
+    /// Check that permuting packed states agrees with permuting each lane on its own.
+    fn packed_matches_scalar<const WIDTH: usize>()
+    where
+        Poseidon2Goldilocks<WIDTH>:
+            Permutation<[F; WIDTH]> + Permutation<[<F as Field>::Packing; WIDTH]>,
+    {
+        type P = <F as Field>::Packing;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let poseidon2 = Poseidon2Goldilocks::<WIDTH>::new_from_rng(8, 22, &mut rng);
+
+        let inputs: Vec<[F; WIDTH]> = (0..P::WIDTH).map(|_| rng.random()).collect();
+        let mut packed: [P; WIDTH] = array::from_fn(|i| P::from_fn(|lane| inputs[lane][i]));
+        poseidon2.permute_mut(&mut packed);
+
+        for (lane, input) in inputs.into_iter().enumerate() {
+            let expected = poseidon2.permute(input);
+            let output: [F; WIDTH] = array::from_fn(|i| packed[i].as_slice()[lane]);
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_packed_poseidon2() {
+        packed_matches_scalar::<8>();
+        packed_matches_scalar::<12>();
+        packed_matches_scalar::<16>();
+        packed_matches_scalar::<20>();
+    }
 }
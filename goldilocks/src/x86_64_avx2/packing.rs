@@ -178,6 +178,16 @@ impl PrimeCharacteristicRing for PackedGoldilocksAVX2 {
         Self::new(unsafe { square(self.get()) })
     }
 
+    #[inline]
+    fn exp_power_of_2(&self, power_log: usize) -> Self {
+        // Square the vector in registers, without going through `Self` at every step.
+        let mut x = self.get();
+        for _ in 0..power_log {
+            x = unsafe { square(x) };
+        }
+        Self::new(x)
+    }
+
     #[inline]
     fn zero_vec(len: usize) -> Vec<Self> {
         // SAFETY: this is a repr(transparent) wrapper around an array.
@@ -235,6 +245,11 @@ unsafe impl PackedValue for PackedGoldilocksAVX2 {
 
 unsafe impl PackedField for PackedGoldilocksAVX2 {
     type Scalar = Goldilocks;
+
+    #[inline]
+    fn fma(self, b: Self, c: Self) -> Self {
+        Self::new(unsafe { mul_add(self.get(), b.get(), c.get()) })
+    }
 }
 
 unsafe impl PackedFieldPow2 for PackedGoldilocksAVX2 {
@@ -545,6 +560,20 @@ unsafe fn mul(x: __m256i, y: __m256i) -> __m256i {
     unsafe { reduce128(mul64_64(x, y)) }
 }
 
+/// Compute `x * y + z` modulo FIELD_ORDER, adding `z` to the 128-bit product so that only one
+/// reduction is needed.
+#[inline]
+unsafe fn mul_add(x: __m256i, y: __m256i, z: __m256i) -> __m256i {
+    unsafe {
+        let (hi, lo) = mul64_64(x, y);
+        // The sum is at most (2^64 - 1)^2 + 2^64 - 1 < 2^128, so the high half can't overflow.
+        let res_lo = _mm256_add_epi64(lo, z);
+        let carry = _mm256_cmpgt_epi64(shift(z), shift(res_lo)); // -1 if carried else 0.
+        let res_hi = _mm256_sub_epi64(hi, carry);
+        reduce128((res_hi, res_lo))
+    }
+}
+
 /// Square an integer modulo FIELD_ORDER.
 #[inline]
 unsafe fn square(x: __m256i) -> __m256i {
@@ -177,6 +177,16 @@ impl PrimeCharacteristicRing for PackedGoldilocksAVX512 {
         Self::new(unsafe { square(self.get()) })
     }
 
+    #[inline]
+    fn exp_power_of_2(&self, power_log: usize) -> Self {
+        // Square the vector in registers, without going through `Self` at every step.
+        let mut x = self.get();
+        for _ in 0..power_log {
+            x = unsafe { square(x) };
+        }
+        Self::new(x)
+    }
+
     #[inline]
     fn zero_vec(len: usize) -> Vec<Self> {
         // SAFETY: this is a repr(transparent) wrapper around an array.
@@ -234,6 +244,11 @@ unsafe impl PackedValue for PackedGoldilocksAVX512 {
 
 unsafe impl PackedField for PackedGoldilocksAVX512 {
     type Scalar = Goldilocks;
+
+    #[inline]
+    fn fma(self, b: Self, c: Self) -> Self {
+        Self::new(unsafe { mul_add(self.get(), b.get(), c.get()) })
+    }
 }
 
 unsafe impl PackedFieldPow2 for PackedGoldilocksAVX512 {
@@ -430,6 +445,20 @@ unsafe fn mul(x: __m512i, y: __m512i) -> __m512i {
     unsafe { reduce128(mul64_64(x, y)) }
 }
 
+/// Compute `x * y + z` modulo FIELD_ORDER, adding `z` to the 128-bit product so that only one
+/// reduction is needed.
+#[inline]
+unsafe fn mul_add(x: __m512i, y: __m512i, z: __m512i) -> __m512i {
+    unsafe {
+        let (hi, lo) = mul64_64(x, y);
+        // The sum is at most (2^64 - 1)^2 + 2^64 - 1 < 2^128, so the high half can't overflow.
+        let res_lo = _mm512_add_epi64(lo, z);
+        let carry = _mm512_cmplt_epu64_mask(res_lo, z); // mask set if the addition carried
+        let res_hi = _mm512_mask_add_epi64(hi, carry, hi, _mm512_set1_epi64(1));
+        reduce128((res_hi, res_lo))
+    }
+}
+
 #[inline]
 unsafe fn square(x: __m512i) -> __m512i {
     unsafe { reduce128(square64(x)) }
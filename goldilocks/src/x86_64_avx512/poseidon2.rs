@@ -0,0 +1,68 @@
+//! Internal layers of Poseidon2 for `PackedGoldilocksAVX512`.
+
+use p3_field::{InjectiveMonomial, PackedField, PrimeCharacteristicRing};
+use p3_poseidon2::InternalLayer;
+
+use crate::poseidon2::GOLDILOCKS_S_BOX_DEGREE;
+use crate::{
+    Goldilocks, MATRIX_DIAG_8_GOLDILOCKS, MATRIX_DIAG_12_GOLDILOCKS, MATRIX_DIAG_16_GOLDILOCKS,
+    MATRIX_DIAG_20_GOLDILOCKS, PackedGoldilocksAVX512, Poseidon2InternalLayerGoldilocks,
+};
+
+/// Perform the internal layers of the Poseidon2 permutation on a packed state.
+///
+/// Each round maps `s_0 -> (s_0 + rc)^7` and then `s_i -> s_i * d_i + sum`, where `d` is the
+/// diagonal and `sum` is the sum of the state. Adding `sum` to the 128-bit product with `fma`
+/// means each entry is reduced once rather than twice.
+#[inline]
+fn internal_permute_state_packed<const WIDTH: usize>(
+    state: &mut [PackedGoldilocksAVX512; WIDTH],
+    diag: [Goldilocks; WIDTH],
+    internal_constants: &[Goldilocks],
+) {
+    let diag = diag.map(PackedGoldilocksAVX512::from);
+    for &rc in internal_constants {
+        state[0] += rc;
+        state[0] = state[0].injective_exp_n();
+        let sum = PackedGoldilocksAVX512::sum_array::<WIDTH>(state);
+        for (s, d) in state.iter_mut().zip(diag) {
+            *s = s.fma(d, sum);
+        }
+    }
+}
+
+impl InternalLayer<PackedGoldilocksAVX512, 8, GOLDILOCKS_S_BOX_DEGREE>
+    for Poseidon2InternalLayerGoldilocks
+{
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [PackedGoldilocksAVX512; 8]) {
+        internal_permute_state_packed(state, MATRIX_DIAG_8_GOLDILOCKS, &self.internal_constants)
+    }
+}
+
+impl InternalLayer<PackedGoldilocksAVX512, 12, GOLDILOCKS_S_BOX_DEGREE>
+    for Poseidon2InternalLayerGoldilocks
+{
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [PackedGoldilocksAVX512; 12]) {
+        internal_permute_state_packed(state, MATRIX_DIAG_12_GOLDILOCKS, &self.internal_constants)
+    }
+}
+
+impl InternalLayer<PackedGoldilocksAVX512, 16, GOLDILOCKS_S_BOX_DEGREE>
+    for Poseidon2InternalLayerGoldilocks
+{
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [PackedGoldilocksAVX512; 16]) {
+        internal_permute_state_packed(state, MATRIX_DIAG_16_GOLDILOCKS, &self.internal_constants)
+    }
+}
+
+impl InternalLayer<PackedGoldilocksAVX512, 20, GOLDILOCKS_S_BOX_DEGREE>
+    for Poseidon2InternalLayerGoldilocks
+{
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [PackedGoldilocksAVX512; 20]) {
+        internal_permute_state_packed(state, MATRIX_DIAG_20_GOLDILOCKS, &self.internal_constants)
+    }
+}
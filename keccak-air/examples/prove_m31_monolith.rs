@@ -0,0 +1,74 @@
+use core::fmt::Debug;
+
+use p3_challenger::DuplexChallenger;
+use p3_circle::CirclePcs;
+use p3_commit::ExtensionMmcs;
+use p3_field::Field;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::create_benchmark_fri_config;
+use p3_keccak_air::{KeccakAir, generate_trace_rows};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_monolith::{
+    MonolithCompress, MonolithHash, MonolithMdsMatrixMersenne31, MonolithMersenne31Default,
+};
+use p3_uni_stark::{StarkConfig, prove, verify};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use tracing_forest::ForestLayer;
+use tracing_forest::util::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+const NUM_HASHES: usize = 1365;
+
+fn main() -> Result<(), impl Debug> {
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+
+    Registry::default()
+        .with(env_filter)
+        .with(ForestLayer::default())
+        .init();
+
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Val, 3>;
+
+    type Perm = MonolithMersenne31Default<16>;
+    let perm = Perm::new(MonolithMdsMatrixMersenne31);
+
+    type MyHash = MonolithHash<16, 8, 8>;
+    let hash = MyHash::new(perm.clone());
+
+    type MyCompress = MonolithCompress<16, 8>;
+    let compress = MyCompress::new(perm.clone());
+
+    type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    let val_mmcs = ValMmcs::new(hash, compress);
+
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+
+    let fri_config = create_benchmark_fri_config(challenge_mmcs);
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let inputs = (0..NUM_HASHES).map(|_| rng.random()).collect::<Vec<_>>();
+    let trace = generate_trace_rows::<Val>(inputs, fri_config.log_blowup);
+
+    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+    let pcs = Pcs::new(val_mmcs, fri_config);
+
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+    let config = MyConfig::new(pcs);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &KeccakAir {}, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &KeccakAir {}, &mut challenger, &proof, &vec![])
+}
@@ -0,0 +1,80 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use p3_field::extension::{BinomialExtensionField, BinomiallyExtendable, binomial_mul};
+use p3_field::{BasedVectorSpace, PrimeCharacteristicRing};
+use p3_field_testing::bench_func::{
+    benchmark_inv, benchmark_mul_latency, benchmark_mul_throughput, benchmark_square,
+};
+use p3_koala_bear::KoalaBear;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+type F = KoalaBear;
+type EF4 = BinomialExtensionField<KoalaBear, 4>;
+
+// Note that each round of throughput has 10 operations
+// So we should have 10 * more repetitions for latency tests.
+const REPS: usize = 100;
+const L_REPS: usize = 10 * REPS;
+
+fn bench_quartic_extension(c: &mut Criterion) {
+    let name = "BinomialExtensionField<KoalaBear, 4>";
+    benchmark_square::<EF4>(c, name);
+    benchmark_inv::<EF4>(c, name);
+    benchmark_mul_throughput::<EF4, REPS>(c, name);
+    benchmark_mul_latency::<EF4, L_REPS>(c, name);
+}
+
+/// The schoolbook multiplication `mul` used before the packed quartic kernel, as a baseline.
+fn bench_quartic_schoolbook(c: &mut Criterion) {
+    let name = "BinomialExtensionField<KoalaBear, 4> schoolbook";
+    let w = <F as BinomiallyExtendable<4>>::W;
+    let schoolbook = |a: &[F; 4], b: &[F; 4]| {
+        let mut res = [F::ZERO; 4];
+        binomial_mul(a, b, &mut res, w);
+        res
+    };
+
+    c.bench_function(&format!("mul-latency/{L_REPS} {name}"), |b| {
+        b.iter_batched(
+            || {
+                let mut rng = SmallRng::seed_from_u64(1);
+                (0..L_REPS)
+                    .map(|_| to_array(rng.random::<EF4>()))
+                    .collect::<Vec<_>>()
+            },
+            |x| {
+                x.iter().fold([F::ONE, F::ZERO, F::ZERO, F::ZERO], |x, y| {
+                    schoolbook(&x, y)
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    c.bench_function(&format!("mul-throughput/{REPS} {name}"), |b| {
+        b.iter_batched(
+            || {
+                let mut rng = SmallRng::seed_from_u64(1);
+                core::array::from_fn::<_, 10, _>(|_| to_array(rng.random::<EF4>()))
+            },
+            |mut x| {
+                for _ in 0..REPS {
+                    x = core::array::from_fn(|i| schoolbook(&x[i], &x[(i + 1) % 10]));
+                }
+                x
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn to_array(x: EF4) -> [F; 4] {
+    x.as_basis_coefficients_slice().try_into().unwrap()
+}
+
+criterion_group!(
+    bench_koalabear_ef,
+    bench_quartic_extension,
+    bench_quartic_schoolbook
+);
+criterion_main!(bench_koalabear_ef);
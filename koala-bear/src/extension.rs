@@ -3,9 +3,11 @@ mod test_quartic_extension {
     use alloc::format;
 
     use num_bigint::BigUint;
-    use p3_field::extension::BinomialExtensionField;
+    use p3_field::extension::{BinomialExtensionField, BinomiallyExtendable, binomial_mul};
     use p3_field::{BasedVectorSpace, PrimeCharacteristicRing};
     use p3_field_testing::{test_field, test_two_adic_extension_field};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
 
     use crate::KoalaBear;
 
@@ -38,6 +40,23 @@ mod test_quartic_extension {
     );
     test_two_adic_extension_field!(super::F, super::EF);
 
+    #[test]
+    fn mul_matches_schoolbook() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let (a, b): (EF, EF) = (rng.random(), rng.random());
+            let mut expected = [F::ZERO; 4];
+            binomial_mul(
+                a.as_basis_coefficients_slice().try_into().unwrap(),
+                b.as_basis_coefficients_slice().try_into().unwrap(),
+                &mut expected,
+                <F as BinomiallyExtendable<4>>::W,
+            );
+            assert_eq!((a * b).as_basis_coefficients_slice(), expected);
+            assert_eq!(a.square(), a * a);
+        }
+    }
+
     #[test]
     fn display() {
         assert_eq!(format!("{}", EF::ZERO), "0");
@@ -53,3 +72,63 @@ mod test_quartic_extension {
         );
     }
 }
+
+#[cfg(test)]
+mod test_octic_extension {
+    use alloc::vec::Vec;
+
+    use num_bigint::BigUint;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{
+        ExtensionField, Field, PackedFieldExtension, PackedValue, PrimeCharacteristicRing,
+    };
+    use p3_field_testing::{test_field, test_two_adic_extension_field};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::KoalaBear;
+
+    type F = KoalaBear;
+    type EF = BinomialExtensionField<F, 8>;
+
+    // MontyField31's have no redundant representations.
+    const ZEROS: [EF; 1] = [EF::ZERO];
+    const ONES: [EF; 1] = [EF::ONE];
+
+    // Get the prime factorization of the order of the multiplicative group.
+    // i.e. the prime factorization of P^8 - 1.
+    fn multiplicative_group_prime_factorization() -> [(BigUint, u32); 10] {
+        [
+            (BigUint::from(2u8), 27),
+            (BigUint::from(3u8), 1),
+            (BigUint::from(5u8), 1),
+            (BigUint::from(17u8), 2),
+            (BigUint::from(127u8), 1),
+            (BigUint::from(137u8), 1),
+            (BigUint::from(283u16), 1),
+            (BigUint::from(1254833u32), 1),
+            (BigUint::from(453990990362758349u64), 1),
+            (BigUint::from(260283155268050089696848485460377u128), 1),
+        ]
+    }
+
+    test_field!(
+        super::EF,
+        &super::ZEROS,
+        &super::ONES,
+        &super::multiplicative_group_prime_factorization()
+    );
+    test_two_adic_extension_field!(super::F, super::EF);
+
+    #[test]
+    fn packed_mul_matches_scalar() {
+        type P = <EF as ExtensionField<F>>::ExtensionPacking;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let width = <F as Field>::Packing::WIDTH;
+        let a: Vec<EF> = (0..width).map(|_| rng.random()).collect();
+        let b: Vec<EF> = (0..width).map(|_| rng.random()).collect();
+        let product = P::from_ext_slice(&a) * P::from_ext_slice(&b);
+        let expected: Vec<EF> = a.iter().zip(&b).map(|(&x, &y)| x * y).collect();
+        assert_eq!(P::to_ext_iter([product]).collect::<Vec<_>>(), expected);
+    }
+}
@@ -103,6 +103,25 @@ impl BinomialExtensionData<4> for KoalaBearParameters {
         KoalaBear::new_2d_array([[0, 0, 1759267465, 0], [0, 0, 0, 777715144]]);
 }
 
+// As p - 1 = 2^24 * 127, KoalaBear has no binomial extension of degree 3, 5, 6 or 7. Those degrees
+// need a tower over a smaller extension or a trinomial modulus.
+// TODO: Neither construction exists in `p3_field` yet.
+impl BinomialExtensionData<8> for KoalaBearParameters {
+    // As 3 is not a square and p = 1 mod 4, x^8 - 3 is irreducible.
+    const W: KoalaBear = KoalaBear::new(3);
+    const DTH_ROOT: KoalaBear = KoalaBear::new(1748172362);
+    const EXT_GENERATOR: [KoalaBear; 8] = KoalaBear::new_array([10, 1, 0, 0, 0, 0, 0, 0]);
+    const EXT_TWO_ADICITY: usize = 27;
+
+    type ArrayLike = [[KoalaBear; 8]; 3];
+
+    const TWO_ADIC_EXTENSION_GENERATORS: Self::ArrayLike = KoalaBear::new_2d_array([
+        [0, 0, 0, 0, 1759267465, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 777715144, 0],
+        [0, 0, 0, 0, 0, 0, 0, 14348907],
+    ]);
+}
+
 #[cfg(test)]
 mod tests {
     use num_bigint::BigUint;
@@ -0,0 +1,35 @@
+use p3_blake3::{Blake3, Blake3Compress};
+use p3_symmetric::SerializingHasher32;
+
+use crate::MerkleTreeMmcs;
+
+/// A Merkle tree MMCS over a 32-bit field hashed with blake3, for proofs which aren't verified
+/// recursively, where blake3 commits far faster than an algebraic hash.
+///
+/// Build it with `Blake3Mmcs::new(SerializingHasher32::new(Blake3), Blake3Compress)`.
+pub type Blake3Mmcs<F> = MerkleTreeMmcs<F, u8, SerializingHasher32<Blake3>, Blake3Compress, 32>;
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_commit::Mmcs;
+    use p3_matrix::Matrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+
+    #[test]
+    fn commit_open_verify() {
+        let mmcs = Blake3Mmcs::<BabyBear>::new(SerializingHasher32::new(Blake3), Blake3Compress);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mat = RowMajorMatrix::<BabyBear>::rand(&mut rng, 64, 7);
+        let dims = [mat.dimensions()];
+
+        let (commit, prover_data) = mmcs.commit_matrix(mat);
+        let (opened_values, proof) = mmcs.open_batch(42, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 42, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+}
@@ -54,6 +54,46 @@ impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
     }
 }
 
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
+    MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
+        + Sync,
+    PW::Value: Eq,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+    StandardUniform: Distribution<P::Value>,
+{
+    /// Commit to `inputs`, salting every row of every matrix with `SALT_ELEMS` elements drawn
+    /// from `rng` rather than from the MMCS's own generator.
+    ///
+    /// The salts are kept in the prover data and revealed with the rows they salt, so that an
+    /// opening reveals nothing about the rows which aren't opened.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_hiding<M: Matrix<P::Value>, Rn: Rng>(
+        &self,
+        rng: &mut Rn,
+        inputs: Vec<M>,
+    ) -> (
+        Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+        MerkleTree<P::Value, PW::Value, HorizontalPair<M, RowMajorMatrix<P::Value>>, DIGEST_ELEMS>,
+    ) {
+        let salted_inputs = inputs
+            .into_iter()
+            .map(|mat| {
+                let salts = RowMajorMatrix::rand(rng, mat.height(), SALT_ELEMS);
+                HorizontalPair::new(mat, salts)
+            })
+            .collect();
+        self.inner.commit(salted_inputs)
+    }
+}
+
 impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize> Mmcs<P::Value>
     for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
 where
@@ -82,15 +122,7 @@ where
         &self,
         inputs: Vec<M>,
     ) -> (Self::Commitment, Self::ProverData<M>) {
-        let salted_inputs = inputs
-            .into_iter()
-            .map(|mat| {
-                let salts =
-                    RowMajorMatrix::rand(&mut *self.rng.borrow_mut(), mat.height(), SALT_ELEMS);
-                HorizontalPair::new(mat, salts)
-            })
-            .collect();
-        self.inner.commit(salted_inputs)
+        self.commit_hiding(&mut *self.rng.borrow_mut(), inputs)
     }
 
     fn open_batch<M: Matrix<P::Value>>(
@@ -125,6 +157,9 @@ where
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
         let (salts, siblings) = proof;
+        if salts.iter().any(|salt| salt.len() != SALT_ELEMS) {
+            return Err(MerkleTreeError::WrongWidth);
+        }
 
         let opened_salted_values = zip_eq(opened_values, salts, MerkleTreeError::WrongBatchSize)?
             .map(|(opened, salt)| opened.iter().chain(salt.iter()).copied().collect_vec())
@@ -201,4 +236,42 @@ mod tests {
         let (opened_values, proof) = mmcs.open_batch(17, &prover_data);
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
     }
+
+    #[test]
+    fn commit_hiding_checks_salts() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 16, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 4, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), rng);
+
+        // The commitment depends on the salts, and only on them beside the matrices.
+        let commit_with =
+            |seed| mmcs.commit_hiding(&mut SmallRng::seed_from_u64(seed), mats.clone());
+        assert_eq!(commit_with(2).0, commit_with(2).0);
+        assert_ne!(commit_with(2).0, commit_with(3).0);
+
+        let (commit, prover_data) = commit_with(2);
+        let (opened_values, proof) = mmcs.open_batch(9, &prover_data);
+        assert!(proof.0.iter().all(|salt| salt.len() == SALT_ELEMS));
+        mmcs.verify_batch(&commit, &dims, 9, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered = proof.clone();
+        tampered.0[1][0] += F::ONE;
+        assert_eq!(
+            mmcs.verify_batch(&commit, &dims, 9, &opened_values, &tampered),
+            Err(MerkleTreeError::RootMismatch)
+        );
+
+        let mut truncated = proof;
+        truncated.0[0].pop();
+        assert_eq!(
+            mmcs.verify_batch(&commit, &dims, 9, &opened_values, &truncated),
+            Err(MerkleTreeError::WrongWidth)
+        );
+    }
 }
@@ -2,10 +2,16 @@
 
 extern crate alloc;
 
+#[cfg(feature = "blake3")]
+mod blake3_mmcs;
 mod hiding_mmcs;
 mod merkle_tree;
 mod mmcs;
+mod padding;
 
+#[cfg(feature = "blake3")]
+pub use blake3_mmcs::*;
 pub use hiding_mmcs::*;
 pub use merkle_tree::*;
 pub use mmcs::*;
+pub use padding::*;
@@ -1,12 +1,14 @@
 use alloc::vec;
 use alloc::vec::Vec;
 use core::array;
+use core::borrow::BorrowMut;
 use core::cmp::Reverse;
 use core::marker::PhantomData;
 
 use itertools::Itertools;
 use p3_field::PackedValue;
 use p3_matrix::Matrix;
+use p3_matrix::dense::{DenseMatrix, DenseStorage};
 use p3_maybe_rayon::prelude::*;
 use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
 use serde::{Deserialize, Serialize};
@@ -112,6 +114,114 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
     }
 }
 
+impl<F, W, V, const DIGEST_ELEMS: usize> MerkleTree<F, W, DenseMatrix<F, V>, DIGEST_ELEMS>
+where
+    F: Clone + Send + Sync,
+    W: Copy + Default,
+    V: DenseStorage<F> + BorrowMut<[F]>,
+{
+    /// Overwrite the rows `indices` of the matrix `matrix_index` with `new_rows`, and recompute
+    /// only the digests on their paths to the root, which is returned.
+    ///
+    /// The result is the tree which `new` would build from the updated matrices, for the cost of
+    /// hashing the updated rows and a path per row rather than every row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix or one of the rows doesn't exist, or if a new row has the wrong
+    /// width.
+    pub fn update_rows<H, C>(
+        &mut self,
+        h: &H,
+        c: &C,
+        matrix_index: usize,
+        indices: &[usize],
+        new_rows: &[Vec<F>],
+    ) -> Hash<F, W, DIGEST_ELEMS>
+    where
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>,
+    {
+        assert_eq!(indices.len(), new_rows.len(), "Need one new row per index.");
+        let matrix = &mut self.leaves[matrix_index];
+        for (&i, row) in indices.iter().zip(new_rows) {
+            assert!(i < matrix.height(), "Row index out of bounds.");
+            assert_eq!(row.len(), matrix.width(), "New row has the wrong width.");
+            matrix.row_mut(i).clone_from_slice(row);
+        }
+
+        let layer_leaves = self.layer_leaves();
+        let first_layer = layer_leaves
+            .iter()
+            .position(|leaves| leaves.contains(&matrix_index))
+            .unwrap();
+
+        // A row `i` of a matrix is hashed into node `i` of its layer, whose parent is node `i / 2`
+        // of the next layer.
+        let mut dirty = indices.iter().copied().sorted().dedup().collect_vec();
+        for layer in first_layer..self.digest_layers.len() {
+            let digests = dirty
+                .iter()
+                .map(|&i| self.node_digest(h, c, &layer_leaves[layer], layer, i))
+                .collect_vec();
+            for (&i, digest) in dirty.iter().zip(digests) {
+                self.digest_layers[layer][i] = digest;
+            }
+            dirty = dirty.iter().map(|i| i / 2).dedup().collect();
+        }
+
+        self.root()
+    }
+
+    /// The indices of the matrices hashed into each digest layer, in the order `new` hashes them.
+    fn layer_leaves(&self) -> Vec<Vec<usize>> {
+        let max_height = self.leaves.iter().map(|m| m.height()).max().unwrap();
+        (0..self.digest_layers.len())
+            .map(|layer| {
+                (0..self.leaves.len())
+                    .filter(|&m| {
+                        let height = self.leaves[m].height();
+                        if layer == 0 {
+                            height == max_height
+                        } else {
+                            height != max_height
+                                && height.next_power_of_two()
+                                    == (self.digest_layers[layer - 1].len() / 2).next_power_of_two()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Recompute the digest of node `i` of `layer`, into which the matrices `leaves` are hashed,
+    /// from the layer below.
+    fn node_digest<H, C>(
+        &self,
+        h: &H,
+        c: &C,
+        leaves: &[usize],
+        layer: usize,
+        i: usize,
+    ) -> [W; DIGEST_ELEMS]
+    where
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>,
+    {
+        let rows_digest = || h.hash_iter(leaves.iter().flat_map(|&m| self.leaves[m].row(i)));
+        if layer == 0 {
+            return rows_digest();
+        }
+        let prev_layer = &self.digest_layers[layer - 1];
+        let digest = c.compress([prev_layer[2 * i], prev_layer[2 * i + 1]]);
+        match leaves.first() {
+            None => digest,
+            Some(&m) if i < self.leaves[m].height() => c.compress([digest, rows_digest()]),
+            Some(_) => c.compress([digest, [W::default(); DIGEST_ELEMS]]),
+        }
+    }
+}
+
 #[instrument(name = "first digest layer", level = "debug", skip_all)]
 fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize>(
     h: &H,
@@ -20,21 +20,23 @@
 //!
 
 use alloc::vec::Vec;
+use core::borrow::BorrowMut;
 use core::cmp::Reverse;
 use core::marker::PhantomData;
 
 use itertools::Itertools;
-use p3_commit::Mmcs;
+use p3_commit::{Mmcs, PathMmcs};
 use p3_field::PackedValue;
+use p3_matrix::dense::{DenseMatrix, DenseStorage};
 use p3_matrix::{Dimensions, Matrix};
 use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 use serde::{Deserialize, Serialize};
 
-use crate::MerkleTree;
 use crate::MerkleTreeError::{
-    EmptyBatch, IncompatibleHeights, RootMismatch, WrongBatchSize, WrongHeight,
+    EmptyBatch, IncompatibleHeights, InvalidPadding, RootMismatch, WrongBatchSize, WrongHeight,
 };
+use crate::{MerkleTree, OpenedRow, PaddedMatrix, PaddingPolicy};
 
 /// A vector commitment scheme backed by a `MerkleTree`.
 ///
@@ -50,7 +52,7 @@ pub struct MerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize> {
     _phantom: PhantomData<(P, PW)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum MerkleTreeError {
     WrongBatchSize,
     WrongWidth,
@@ -61,6 +63,11 @@ pub enum MerkleTreeError {
     IncompatibleHeights,
     RootMismatch,
     EmptyBatch,
+    /// An opened row which the padding policy fills with zeros isn't zero, or one which it
+    /// repeats differs from the opened last row of its matrix.
+    InvalidPadding,
+    /// A matrix has no rows, so there is no last row for `PaddingPolicy::RepeatLastRow` to repeat.
+    EmptyMatrix,
 }
 
 impl<P, PW, H, C, const DIGEST_ELEMS: usize> MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS> {
@@ -71,6 +78,28 @@ impl<P, PW, H, C, const DIGEST_ELEMS: usize> MerkleTreeMmcs<P, PW, H, C, DIGEST_
             _phantom: PhantomData,
         }
     }
+
+    /// Overwrite the rows `indices` of the committed matrix `matrix_index` with `new_rows`, and
+    /// return the commitment to the updated matrices.
+    ///
+    /// Only the digests on the paths from the updated rows to the root are recomputed, so this is
+    /// much cheaper than committing again when few rows change. See `MerkleTree::update_rows`.
+    pub fn update_rows<V>(
+        &self,
+        prover_data: &mut MerkleTree<P::Value, PW::Value, DenseMatrix<P::Value, V>, DIGEST_ELEMS>,
+        matrix_index: usize,
+        indices: &[usize],
+        new_rows: &[Vec<P::Value>],
+    ) -> Hash<P::Value, PW::Value, DIGEST_ELEMS>
+    where
+        P: PackedValue,
+        PW: PackedValue,
+        H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+        C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
+        V: DenseStorage<P::Value> + BorrowMut<[P::Value]>,
+    {
+        prover_data.update_rows(&self.hash, &self.compress, matrix_index, indices, new_rows)
+    }
 }
 
 impl<P, PW, H, C, const DIGEST_ELEMS: usize> Mmcs<P::Value>
@@ -261,6 +290,307 @@ where
     }
 }
 
+impl<P, PW, H, C, const DIGEST_ELEMS: usize> MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
+        + Sync,
+    PW::Value: Eq,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+{
+    /// Commit to matrices of possibly different heights, placing them in the tree according to
+    /// `policy` rather than always injecting the shorter ones.
+    ///
+    /// Padding is virtual: the matrices are wrapped rather than copied.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_with_policy<M: Matrix<P::Value>>(
+        &self,
+        policy: PaddingPolicy,
+        inputs: Vec<M>,
+    ) -> Result<
+        (
+            Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+            MerkleTree<P::Value, PW::Value, PaddedMatrix<M>, DIGEST_ELEMS>,
+        ),
+        MerkleTreeError,
+    > {
+        if inputs.is_empty() {
+            return Err(EmptyBatch);
+        }
+        let dims = inputs.iter().map(|m| m.dimensions()).collect_vec();
+        let padded_dims = policy.padded_dimensions(&dims)?;
+        let leaves = inputs
+            .into_iter()
+            .zip(padded_dims)
+            .map(|(m, dims)| PaddedMatrix::new(m, dims.height, policy))
+            .collect();
+        Ok(self.commit(leaves))
+    }
+
+    /// Like `open_batch`, but also report which row of each matrix, as given to
+    /// `commit_with_policy`, every opened row is.
+    ///
+    /// Under `PaddingPolicy::RepeatLastRow`, this also opens each last row which an opened row
+    /// repeats, at the indices `PaddingPolicy::repeated_last_rows` gives, for
+    /// `verify_batch_with_policy` to compare the repeated rows against.
+    #[allow(clippy::type_complexity)]
+    pub fn open_batch_with_rows<M: Matrix<P::Value>>(
+        &self,
+        index: usize,
+        prover_data: &MerkleTree<P::Value, PW::Value, PaddedMatrix<M>, DIGEST_ELEMS>,
+    ) -> (
+        Vec<Vec<P::Value>>,
+        Vec<OpenedRow>,
+        Vec<[PW::Value; DIGEST_ELEMS]>,
+        Vec<(Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>)>,
+    ) {
+        let dims = prover_data
+            .leaves
+            .iter()
+            .map(|m| m.inner.dimensions())
+            .collect_vec();
+        let policy = prover_data.leaves[0].policy;
+        let rows = policy.opened_rows(&dims, index);
+        let (openings, proof) = self.open_batch(index, prover_data);
+        let last_row_openings = policy
+            .repeated_last_rows(&dims, index)
+            .into_iter()
+            .map(|last_row| self.open_batch(last_row, prover_data))
+            .collect();
+        (openings, rows, proof, last_row_openings)
+    }
+
+    /// Verify an opening of matrices committed with `commit_with_policy`, where `dimensions` are
+    /// those of the matrices before padding.
+    ///
+    /// Opened rows which `policy` fills with zeros must be zero. Under
+    /// `PaddingPolicy::RepeatLastRow`, `last_row_openings` holds the openings of the repeated
+    /// last rows made by `open_batch_with_rows`; each is verified against `commit`, and every row
+    /// opened below a matrix must equal the matrix's opened last row. It is empty under other
+    /// policies.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_batch_with_policy(
+        &self,
+        commit: &Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+        policy: PaddingPolicy,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<P::Value>],
+        proof: &<Self as Mmcs<P::Value>>::Proof,
+        last_row_openings: &[(Vec<Vec<P::Value>>, <Self as Mmcs<P::Value>>::Proof)],
+    ) -> Result<(), MerkleTreeError> {
+        if dimensions.len() != opened_values.len() {
+            return Err(WrongBatchSize);
+        }
+        let padded_dims = policy.padded_dimensions(dimensions)?;
+        let padding_is_zero = policy
+            .opened_rows(dimensions, index)
+            .into_iter()
+            .zip(opened_values)
+            .filter(|(row, _)| *row == OpenedRow::Zeros)
+            .all(|(_, values)| values.iter().all(|v| *v == P::Value::default()));
+        if !padding_is_zero {
+            return Err(InvalidPadding);
+        }
+
+        let last_rows = policy.repeated_last_rows(dimensions, index);
+        if last_rows.len() != last_row_openings.len() {
+            return Err(WrongBatchSize);
+        }
+        for (&last_row, (last_values, last_proof)) in last_rows.iter().zip(last_row_openings) {
+            self.verify_batch(commit, &padded_dims, last_row, last_values, last_proof)?;
+        }
+        for (i, (dims, values)) in dimensions.iter().zip(opened_values).enumerate() {
+            if policy == PaddingPolicy::RepeatLastRow && index >= dims.height {
+                let j = last_rows.binary_search(&(dims.height - 1)).unwrap();
+                if last_row_openings[j].0[i] != *values {
+                    return Err(InvalidPadding);
+                }
+            }
+        }
+
+        self.verify_batch(commit, &padded_dims, index, opened_values, proof)
+    }
+
+    /// Open the rows at each of `indices`, as `open_batch` would, with a single proof for all of
+    /// them.
+    ///
+    /// The proof holds the sibling nodes of the paths from the opened leaves to the root, layer
+    /// by layer and left to right, except those the verifier computes from the openings itself.
+    /// When indices share a path above some layer, which happens often in upper layers for many
+    /// queries, the shared nodes are only sent once.
+    #[allow(clippy::type_complexity)]
+    pub fn open_indices<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        let openings = indices
+            .iter()
+            .map(|&index| self.open_batch(index, prover_data).0)
+            .collect();
+
+        let log_max_height = log2_ceil_usize(self.get_max_height(prover_data));
+        let mut known = indices.iter().copied().sorted().dedup().collect_vec();
+        let mut proof = Vec::new();
+        for layer in &prover_data.digest_layers[..log_max_height] {
+            for (i, &node) in known.iter().enumerate() {
+                let sibling = node ^ 1;
+                let sibling_known = (node & 1 == 0 && known.get(i + 1) == Some(&sibling))
+                    || (node & 1 == 1 && i > 0 && known[i - 1] == sibling);
+                if !sibling_known {
+                    proof.push(layer[sibling]);
+                }
+            }
+            known = known.iter().map(|node| node >> 1).dedup().collect();
+        }
+
+        (openings, proof)
+    }
+
+    /// Verify openings at each of `indices` against a proof made by `open_indices`.
+    ///
+    /// `opened_values[q]` holds the rows opened at `indices[q]`, as `open_batch` would return
+    /// them. Indices may repeat, in which case the rows they share must agree.
+    pub fn verify_indices(
+        &self,
+        commit: &Hash<P::Value, PW::Value, DIGEST_ELEMS>,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proof: &[[PW::Value; DIGEST_ELEMS]],
+    ) -> Result<(), MerkleTreeError> {
+        if indices.is_empty() || dimensions.is_empty() {
+            return Err(EmptyBatch);
+        }
+        if indices.len() != opened_values.len()
+            || opened_values
+                .iter()
+                .any(|rows| rows.len() != dimensions.len())
+        {
+            return Err(WrongBatchSize);
+        }
+
+        let heights_tallest_first = dimensions
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, dims)| Reverse(dims.height))
+            .collect_vec();
+        if !heights_tallest_first
+            .iter()
+            .map(|(_, dims)| dims.height)
+            .tuple_windows()
+            .all(|(curr, next)| {
+                curr == next || curr.next_power_of_two() != next.next_power_of_two()
+            })
+        {
+            return Err(IncompatibleHeights);
+        }
+        let log_max_height = log2_ceil_usize(heights_tallest_first[0].1.height);
+
+        // The digest of the rows hashed into `node` of `layer`, which every query whose path
+        // passes through the node must agree on, or `None` if no matrix is hashed into the layer.
+        let rows_digest = |layer: usize, node: usize| {
+            let matrices = heights_tallest_first
+                .iter()
+                .filter(|(_, dims)| log2_ceil_usize(dims.height) + layer == log_max_height)
+                .map(|&(j, _)| j)
+                .collect_vec();
+            if matrices.is_empty() {
+                return Ok(None);
+            }
+            indices
+                .iter()
+                .zip(opened_values)
+                .filter(|&(&index, _)| index >> layer == node)
+                .map(|(_, rows)| {
+                    self.hash
+                        .hash_iter_slices(matrices.iter().map(|&j| rows[j].as_slice()))
+                })
+                .all_equal_value()
+                .map(Some)
+                .map_err(|_| RootMismatch)
+        };
+
+        let mut nodes = Vec::new();
+        for node in indices.iter().copied().sorted().dedup() {
+            nodes.push((node, rows_digest(0, node)?.unwrap()));
+        }
+        let mut siblings = proof.iter();
+        let mut next_sibling = || {
+            siblings.next().copied().ok_or(WrongHeight {
+                log_max_height,
+                num_siblings: proof.len(),
+            })
+        };
+        for layer in 1..=log_max_height {
+            let mut parents = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (node, digest) = nodes[i];
+                let (left, right) = if node & 1 == 1 {
+                    (next_sibling()?, digest)
+                } else if nodes.get(i + 1).is_some_and(|&(next, _)| next == node ^ 1) {
+                    i += 1;
+                    (digest, nodes[i].1)
+                } else {
+                    (digest, next_sibling()?)
+                };
+                i += 1;
+
+                let parent = node >> 1;
+                let mut digest = self.compress.compress([left, right]);
+                if let Some(rows_digest) = rows_digest(layer, parent)? {
+                    digest = self.compress.compress([digest, rows_digest]);
+                }
+                parents.push((parent, digest));
+            }
+            nodes = parents;
+        }
+
+        if siblings.next().is_some() {
+            return Err(WrongHeight {
+                log_max_height,
+                num_siblings: proof.len(),
+            });
+        }
+        match nodes[..] {
+            [(0, root)] if commit == &root => Ok(()),
+            _ => Err(RootMismatch),
+        }
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize> PathMmcs<P::Value>
+    for MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>
+        + CryptographicHasher<P, [PW; DIGEST_ELEMS]>
+        + Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>
+        + PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>
+        + Sync,
+    PW::Value: Eq,
+    [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
+{
+    type Node = [PW::Value; DIGEST_ELEMS];
+
+    fn proof_path(proof: &Self::Proof) -> &[Self::Node] {
+        proof
+    }
+
+    fn proof_from_path(path: Vec<Self::Node>) -> Self::Proof {
+        path
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -272,12 +602,17 @@ mod tests {
     use p3_matrix::dense::RowMajorMatrix;
     use p3_matrix::{Dimensions, Matrix};
     use p3_symmetric::{
-        CryptographicHasher, PaddingFreeSponge, PseudoCompressionFunction, TruncatedPermutation,
+        CryptographicHasher, DomainSeparatedCompression, DomainSeparatedHasher, PaddingFreeSponge,
+        PseudoCompressionFunction, TruncatedPermutation,
     };
     use rand::SeedableRng;
     use rand::rngs::SmallRng;
 
     use super::MerkleTreeMmcs;
+    use crate::MerkleTreeError::{
+        IncompatibleHeights, InvalidPadding, RootMismatch, WrongBatchSize,
+    };
+    use crate::{OpenedRow, PaddedMatrix, PaddingPolicy};
 
     type F = BabyBear;
 
@@ -625,4 +960,271 @@ mod tests {
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
             .expect("expected verification to succeed");
     }
+
+    #[test]
+    fn keccak_batched_matches_scalar() {
+        use p3_keccak::{KeccakF, VECTOR_LEN};
+        use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32To64};
+
+        type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+        type FieldHash = SerializingHasher32To64<U64Hash>;
+        type KeccakCompress = CompressionFunctionFromHasher<U64Hash, 2, 4>;
+        type ScalarMmcs = MerkleTreeMmcs<F, u64, FieldHash, KeccakCompress, 4>;
+        type BatchedMmcs =
+            MerkleTreeMmcs<[F; VECTOR_LEN], [u64; VECTOR_LEN], FieldHash, KeccakCompress, 4>;
+
+        let u64_hash = U64Hash::new(KeccakF);
+        let scalar = ScalarMmcs::new(FieldHash::new(u64_hash), KeccakCompress::new(u64_hash));
+        let batched = BatchedMmcs::new(FieldHash::new(u64_hash), KeccakCompress::new(u64_hash));
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 3),
+        ];
+
+        // The batched tree hashes `VECTOR_LEN` leaves per permutation, and must commit to the
+        // same tree as hashing them one at a time.
+        let (scalar_commit, scalar_data) = scalar.commit(mats.clone());
+        let (batched_commit, batched_data) = batched.commit(mats);
+        assert_eq!(scalar_commit, batched_commit);
+        assert_eq!(
+            scalar.open_batch(13, &scalar_data),
+            batched.open_batch(13, &batched_data)
+        );
+    }
+
+    #[test]
+    fn domain_separated_hashers() {
+        type TaggedHash = DomainSeparatedHasher<MyHash, F>;
+        type TaggedMmcs =
+            MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, TaggedHash, TaggedHash, 8>;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let mmcs = TaggedMmcs::new(
+            TaggedHash::new(hash.clone(), vec![F::ZERO]),
+            TaggedHash::new(hash.clone(), vec![F::ONE]),
+        );
+        let plain = MyMmcs::new(hash, MyCompress::new(perm));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let (opened_values, proof) = mmcs.open_batch(13, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 13, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        assert_ne!(commit, plain.commit(mats).0);
+    }
+
+    #[test]
+    fn domain_separated_compression() {
+        type WidePerm = Poseidon2BabyBear<24>;
+        type WideCompress = TruncatedPermutation<WidePerm, 2, 8, 24>;
+        type TaggedCompress = DomainSeparatedCompression<WideCompress, F>;
+        type TaggedMmcs =
+            MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, TaggedCompress, 8>;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let hash = MyHash::new(Perm::new_from_rng_128(&mut rng));
+        let compress = WideCompress::new(WidePerm::new_from_rng_128(&mut rng));
+        let mmcs = TaggedMmcs::new(
+            hash.clone(),
+            TaggedCompress::new(compress.clone(), vec![F::ONE]),
+        );
+        let other = TaggedMmcs::new(hash, TaggedCompress::new(compress, vec![F::TWO]));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        let (opened_values, proof) = mmcs.open_batch(13, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 13, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        assert_ne!(commit, other.commit(mats).0);
+        assert_eq!(
+            other.verify_batch(&commit, &dims, 13, &opened_values, &proof),
+            Err(RootMismatch)
+        );
+    }
+
+    #[test]
+    fn update_rows_matches_recommit() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        // The 5-row matrix is injected above the leaves, and the 1-row matrix at the root.
+        let mut mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 37, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 5, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 37, 2),
+            RowMajorMatrix::<F>::rand(&mut rng, 1, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (_, mut prover_data) = mmcs.commit(mats.clone());
+
+        for (matrix_index, indices) in [(0, vec![0, 36, 17, 16]), (1, vec![4, 1]), (3, vec![0])] {
+            let width = mats[matrix_index].width();
+            let new_rows = indices
+                .iter()
+                .map(|_| RowMajorMatrix::<F>::rand(&mut rng, 1, width).values)
+                .collect_vec();
+            for (&i, row) in indices.iter().zip(&new_rows) {
+                mats[matrix_index].row_mut(i).copy_from_slice(row);
+            }
+
+            let commit = mmcs.update_rows(&mut prover_data, matrix_index, &indices, &new_rows);
+            assert_eq!(commit, mmcs.commit(mats.clone()).0);
+
+            let (opened_values, proof) = mmcs.open_batch(indices[0], &prover_data);
+            mmcs.verify_batch(&commit, &dims, indices[0], &opened_values, &proof)
+                .expect("expected verification to succeed");
+        }
+    }
+
+    #[test]
+    fn padding_policies() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 12, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 5, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        assert_eq!(
+            mmcs.commit_with_policy(PaddingPolicy::Reject, mats.clone())
+                .err(),
+            Some(IncompatibleHeights)
+        );
+
+        for policy in [PaddingPolicy::RepeatLastRow, PaddingPolicy::ZeroPad] {
+            let (commit, prover_data) = mmcs.commit_with_policy(policy, mats.clone()).unwrap();
+            for index in [2, 9] {
+                let (opened_values, rows, proof, last_row_openings) =
+                    mmcs.open_batch_with_rows(index, &prover_data);
+                for ((mat, row), values) in mats.iter().zip(&rows).zip(&opened_values) {
+                    match *row {
+                        OpenedRow::Row(r) => assert_eq!(mat.row(r).collect_vec(), *values),
+                        OpenedRow::Zeros => assert!(values.iter().all(|v| *v == F::ZERO)),
+                    }
+                }
+                mmcs.verify_batch_with_policy(
+                    &commit,
+                    policy,
+                    &dims,
+                    index,
+                    &opened_values,
+                    &proof,
+                    &last_row_openings,
+                )
+                .expect("expected verification to succeed");
+            }
+        }
+
+        // Committing to non-zero padding is caught even though it matches the root.
+        let padded = vec![
+            mats[0].clone(),
+            PaddedMatrix::new(mats[1].clone(), 12, PaddingPolicy::RepeatLastRow)
+                .to_row_major_matrix(),
+        ];
+        let (commit, prover_data) = mmcs.commit(padded);
+        let (opened_values, proof) = mmcs.open_batch(9, &prover_data);
+        assert_eq!(
+            mmcs.verify_batch_with_policy(
+                &commit,
+                PaddingPolicy::ZeroPad,
+                &dims,
+                9,
+                &opened_values,
+                &proof,
+                &[],
+            ),
+            Err(InvalidPadding)
+        );
+
+        // Likewise padding which doesn't repeat the last row, once the last row is opened too.
+        let padded = vec![
+            mats[0].clone(),
+            PaddedMatrix::new(mats[1].clone(), 12, PaddingPolicy::ZeroPad).to_row_major_matrix(),
+        ];
+        let (commit, prover_data) = mmcs.commit(padded);
+        let (opened_values, proof) = mmcs.open_batch(9, &prover_data);
+        let last_row_opening = mmcs.open_batch(4, &prover_data);
+        assert_eq!(
+            mmcs.verify_batch_with_policy(
+                &commit,
+                PaddingPolicy::RepeatLastRow,
+                &dims,
+                9,
+                &opened_values,
+                &proof,
+                &[last_row_opening],
+            ),
+            Err(InvalidPadding)
+        );
+        assert_eq!(
+            mmcs.verify_batch_with_policy(
+                &commit,
+                PaddingPolicy::RepeatLastRow,
+                &dims,
+                9,
+                &opened_values,
+                &proof,
+                &[],
+            ),
+            Err(WrongBatchSize)
+        );
+    }
+
+    #[test]
+    fn open_indices_shares_nodes() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 64, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 16, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 64, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = [40, 3, 2, 41, 63, 3, 17];
+        let (opened_values, proof) = mmcs.open_indices(&indices, &prover_data);
+        for (&index, rows) in indices.iter().zip(&opened_values) {
+            assert_eq!(*rows, mmcs.open_batch(index, &prover_data).0);
+        }
+        // Six distinct paths of six siblings each, less the nodes they share or compute.
+        assert!(proof.len() < 6 * 6);
+        mmcs.verify_indices(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let mut tampered = opened_values.clone();
+        tampered[4][1][0] += F::ONE;
+        assert_eq!(
+            mmcs.verify_indices(&commit, &dims, &indices, &tampered, &proof),
+            Err(RootMismatch)
+        );
+
+        // A repeated index must open the same rows each time.
+        let mut tampered = opened_values;
+        tampered[5][0][0] += F::ONE;
+        assert_eq!(
+            mmcs.verify_indices(&commit, &dims, &indices, &tampered, &proof),
+            Err(RootMismatch)
+        );
+    }
 }
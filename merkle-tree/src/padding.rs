@@ -0,0 +1,221 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::{Either, Itertools};
+use p3_matrix::{Dimensions, Matrix};
+use p3_util::log2_ceil_usize;
+
+use crate::MerkleTreeError;
+use crate::MerkleTreeError::{EmptyMatrix, IncompatibleHeights};
+
+/// How `MerkleTreeMmcs::commit_with_policy` places matrices of different heights in one tree.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PaddingPolicy {
+    /// Hash the rows of each shorter matrix into the tree layer as tall as the matrix, as
+    /// `commit` does. Heights which round up to the same power of two must be equal.
+    #[default]
+    Inject,
+    /// Extend every matrix to the tallest height by repeating its last row. Every matrix must have
+    /// at least one row.
+    RepeatLastRow,
+    /// Extend every matrix to the tallest height with rows of zeros.
+    ZeroPad,
+    /// Only accept matrices of equal heights.
+    Reject,
+}
+
+/// The row of a matrix which an opening holds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OpenedRow {
+    /// The row at this index of the matrix as given to the commitment.
+    Row(usize),
+    /// A row of zeros which `PaddingPolicy::ZeroPad` added below the matrix.
+    Zeros,
+}
+
+impl PaddingPolicy {
+    /// The dimensions the matrices of dimensions `dims` are committed with under this policy.
+    pub fn padded_dimensions(
+        &self,
+        dims: &[Dimensions],
+    ) -> Result<Vec<Dimensions>, MerkleTreeError> {
+        let max_height = dims.iter().map(|d| d.height).max().unwrap_or(0);
+        match self {
+            Self::Inject => {
+                let compatible = dims
+                    .iter()
+                    .map(|d| d.height)
+                    .sorted()
+                    .dedup()
+                    .tuple_windows()
+                    .all(|(a, b)| a.next_power_of_two() != b.next_power_of_two());
+                if !compatible {
+                    return Err(IncompatibleHeights);
+                }
+                Ok(dims.to_vec())
+            }
+            Self::Reject => {
+                if dims.iter().any(|d| d.height != max_height) {
+                    return Err(IncompatibleHeights);
+                }
+                Ok(dims.to_vec())
+            }
+            Self::RepeatLastRow if dims.iter().any(|d| d.height == 0) => Err(EmptyMatrix),
+            Self::RepeatLastRow | Self::ZeroPad => Ok(dims
+                .iter()
+                .map(|d| Dimensions {
+                    width: d.width,
+                    height: max_height,
+                })
+                .collect()),
+        }
+    }
+
+    /// The row of each of the matrices of dimensions `dims` which an opening at `index` holds.
+    pub fn opened_rows(&self, dims: &[Dimensions], index: usize) -> Vec<OpenedRow> {
+        let max_height = dims.iter().map(|d| d.height).max().unwrap_or(0);
+        let log_max_height = log2_ceil_usize(max_height);
+        dims.iter()
+            .map(|d| match self {
+                Self::Inject | Self::Reject => {
+                    OpenedRow::Row(index >> (log_max_height - log2_ceil_usize(d.height)))
+                }
+                _ if index < d.height => OpenedRow::Row(index),
+                Self::RepeatLastRow => OpenedRow::Row(d.height - 1),
+                Self::ZeroPad => OpenedRow::Zeros,
+            })
+            .collect()
+    }
+
+    /// The indices, in increasing order, of the last rows which the rows opened at `index` of
+    /// matrices of dimensions `dims` repeat. An opening under `RepeatLastRow` holds an opening of
+    /// each of these rows too, so the verifier can check that the repeated rows equal them. Other
+    /// policies repeat no rows.
+    pub fn repeated_last_rows(&self, dims: &[Dimensions], index: usize) -> Vec<usize> {
+        if *self != Self::RepeatLastRow {
+            return Vec::new();
+        }
+        dims.iter()
+            .filter(|d| d.height > 0 && index >= d.height)
+            .map(|d| d.height - 1)
+            .sorted()
+            .dedup()
+            .collect()
+    }
+}
+
+/// A matrix extended below its last row according to a `PaddingPolicy`, without copying it.
+#[derive(Clone, Debug)]
+pub struct PaddedMatrix<M> {
+    pub inner: M,
+    height: usize,
+    pub(crate) policy: PaddingPolicy,
+}
+
+impl<M> PaddedMatrix<M> {
+    /// Extend `inner` to `height` rows according to `policy`, which must be one which pads if
+    /// `height` is larger than the height of `inner`. Under `PaddingPolicy::RepeatLastRow`,
+    /// `inner` must have at least one row; `PaddingPolicy::padded_dimensions` rejects those
+    /// which don't.
+    pub(crate) const fn new(inner: M, height: usize, policy: PaddingPolicy) -> Self {
+        Self {
+            inner,
+            height,
+            policy,
+        }
+    }
+}
+
+impl<T, M> Matrix<T> for PaddedMatrix<M>
+where
+    T: Clone + Default + Send + Sync,
+    M: Matrix<T>,
+{
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    type Row<'a>
+        = Either<M::Row<'a>, iter::RepeatN<T>>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height, "Row index out of bounds.");
+        let inner_height = self.inner.height();
+        if r < inner_height {
+            return Either::Left(self.inner.row(r));
+        }
+        match self.policy {
+            PaddingPolicy::RepeatLastRow => Either::Left(self.inner.row(inner_height - 1)),
+            _ => Either::Right(iter::repeat_n(T::default(), self.width())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_matrix::dense::RowMajorMatrix;
+
+    use super::*;
+
+    #[test]
+    fn padded_rows() {
+        let mat = RowMajorMatrix::new((1..=6).collect_vec(), 2);
+        let repeated = PaddedMatrix::new(mat.clone(), 5, PaddingPolicy::RepeatLastRow);
+        let zeros = PaddedMatrix::new(mat, 5, PaddingPolicy::ZeroPad);
+        assert_eq!(repeated.row(1).collect_vec(), [3, 4]);
+        assert_eq!(repeated.row(4).collect_vec(), [5, 6]);
+        assert_eq!(zeros.row(4).collect_vec(), [0, 0]);
+    }
+
+    #[test]
+    fn policies() {
+        let dims = [(8, 2), (4, 5), (2, 1)].map(|(height, width)| Dimensions { width, height });
+        let heights = |policy: PaddingPolicy| {
+            policy
+                .padded_dimensions(&dims)
+                .map(|dims| dims.iter().map(|d| d.height).collect_vec())
+        };
+        assert_eq!(heights(PaddingPolicy::Inject), Ok(vec![8, 4, 2]));
+        assert_eq!(heights(PaddingPolicy::ZeroPad), Ok(vec![8, 8, 8]));
+        assert_eq!(heights(PaddingPolicy::Reject), Err(IncompatibleHeights));
+
+        use OpenedRow::{Row, Zeros};
+        assert_eq!(
+            PaddingPolicy::Inject.opened_rows(&dims, 5),
+            [Row(5), Row(2), Row(1)]
+        );
+        assert_eq!(
+            PaddingPolicy::RepeatLastRow.opened_rows(&dims, 5),
+            [Row(5), Row(3), Row(1)]
+        );
+        assert_eq!(
+            PaddingPolicy::ZeroPad.opened_rows(&dims, 5),
+            [Row(5), Zeros, Zeros]
+        );
+    }
+
+    #[test]
+    fn repeated_last_rows() {
+        let dims =
+            [(8, 1), (4, 1), (2, 1), (4, 1)].map(|(height, width)| Dimensions { width, height });
+        let policy = PaddingPolicy::RepeatLastRow;
+        assert_eq!(policy.repeated_last_rows(&dims, 1), []);
+        assert_eq!(policy.repeated_last_rows(&dims, 3), [1]);
+        assert_eq!(policy.repeated_last_rows(&dims, 6), [1, 3]);
+        assert_eq!(PaddingPolicy::ZeroPad.repeated_last_rows(&dims, 6), []);
+
+        let empty = [Dimensions {
+            width: 1,
+            height: 0,
+        }];
+        assert_eq!(policy.padded_dimensions(&empty), Err(EmptyMatrix));
+    }
+}
@@ -20,7 +20,7 @@ use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::log2_strict_usize;
 
-use crate::Mersenne31;
+use crate::{Mersenne31, Mersenne31ComplexRadix2Dit};
 
 type F = Mersenne31;
 type C = Complex<Mersenne31>;
@@ -158,16 +158,116 @@ impl Mersenne31Dft {
     /// result. In particular, the type of the result elements are in
     /// the extension field, not the domain field.
     pub fn dft_batch<Dft: TwoAdicSubgroupDft<C>>(mat: RowMajorMatrix<F>) -> RowMajorMatrix<C> {
-        let dft = Dft::default();
-        dft_postprocess(dft.dft_batch(dft_preprocess(mat)).to_row_major_matrix())
+        Mersenne31ComplexDft::new(Dft::default()).dft_real_batch(mat)
     }
 
     /// Compute the inverse DFT of each column of `mat`.
     ///
     /// NB: See comment on `dft_batch()` for information on packing.
     pub fn idft_batch<Dft: TwoAdicSubgroupDft<C>>(mat: RowMajorMatrix<C>) -> RowMajorMatrix<F> {
-        let dft = Dft::default();
-        idft_postprocess(dft.idft_batch(idft_preprocess(mat)))
+        Mersenne31ComplexDft::new(Dft::default()).idft_real_batch(mat)
+    }
+}
+
+/// A DFT backend over `Mersenne31Complex`, whose multiplicative group
+/// has the two-adic subgroups that `Mersenne31` lacks.
+///
+/// Complex columns are transformed by `inner`. Columns of real
+/// `Mersenne31` values can either be lifted into the extension with
+/// `lift_real()` and transformed like any other, or transformed with
+/// `dft_real_batch()`, which packs them into a transform of half the
+/// height.
+#[derive(Debug, Default, Clone)]
+pub struct Mersenne31ComplexDft<Dft = Mersenne31ComplexRadix2Dit> {
+    inner: Dft,
+}
+
+impl<Dft> Mersenne31ComplexDft<Dft> {
+    /// Create a backend doing its complex transforms with `inner`.
+    pub const fn new(inner: Dft) -> Self {
+        Self { inner }
+    }
+
+    /// Embed a matrix of real values into the complex extension.
+    pub fn lift_real(mat: RowMajorMatrix<F>) -> RowMajorMatrix<C> {
+        let width = mat.width();
+        RowMajorMatrix::new(mat.values.into_iter().map(C::new_real).collect(), width)
+    }
+
+    /// Take the real parts of a matrix of complex values, e.g. the
+    /// inverse DFT of the DFT of a lifted real matrix.
+    pub fn real_parts(mat: &RowMajorMatrix<C>) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new(mat.values.iter().map(|x| x.real()).collect(), mat.width())
+    }
+}
+
+impl<Dft: TwoAdicSubgroupDft<C>> Mersenne31ComplexDft<Dft> {
+    /// Compute the DFT of each real column of `mat`, whose height `h`
+    /// must be even.
+    ///
+    /// The result has the first `h/2 + 1` rows of the DFT of the lifted
+    /// matrix; the remaining rows are the conjugates of these, in
+    /// reverse order.
+    pub fn dft_real_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<C> {
+        dft_postprocess(
+            self.inner
+                .dft_batch(dft_preprocess(mat))
+                .to_row_major_matrix(),
+        )
+    }
+
+    /// Compute the inverse DFT of each column of `mat`, as returned by
+    /// `dft_real_batch()`.
+    pub fn idft_real_batch(&self, mat: RowMajorMatrix<C>) -> RowMajorMatrix<F> {
+        idft_postprocess(self.inner.idft_batch(idft_preprocess(mat)))
+    }
+}
+
+impl<Dft: TwoAdicSubgroupDft<C>> TwoAdicSubgroupDft<C> for Mersenne31ComplexDft<Dft> {
+    type Evaluations = Dft::Evaluations;
+
+    fn dft_batch(&self, mat: RowMajorMatrix<C>) -> Self::Evaluations {
+        self.inner.dft_batch(mat)
+    }
+
+    fn dft_batch_colmajor(&self, cols: RowMajorMatrix<C>) -> RowMajorMatrix<C> {
+        self.inner.dft_batch_colmajor(cols)
+    }
+
+    fn coset_dft_batch(&self, mat: RowMajorMatrix<C>, shift: C) -> Self::Evaluations {
+        self.inner.coset_dft_batch(mat, shift)
+    }
+
+    fn idft_batch(&self, mat: RowMajorMatrix<C>) -> RowMajorMatrix<C> {
+        self.inner.idft_batch(mat)
+    }
+
+    fn coset_idft_batch(&self, mat: RowMajorMatrix<C>, shift: C) -> RowMajorMatrix<C> {
+        self.inner.coset_idft_batch(mat, shift)
+    }
+
+    fn lde_batch(&self, mat: RowMajorMatrix<C>, added_bits: usize) -> Self::Evaluations {
+        self.inner.lde_batch(mat, added_bits)
+    }
+
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<C>,
+        added_bits: usize,
+        shift: C,
+    ) -> Self::Evaluations {
+        self.inner.coset_lde_batch(mat, added_bits, shift)
+    }
+
+    fn coset_lde_batch_subset(
+        &self,
+        mat: RowMajorMatrix<C>,
+        added_bits: usize,
+        shift: C,
+        which_coset: usize,
+    ) -> Self::Evaluations {
+        self.inner
+            .coset_lde_batch_subset(mat, added_bits, shift, which_coset)
     }
 }
 
@@ -178,7 +278,6 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use super::*;
-    use crate::Mersenne31ComplexRadix2Dit;
 
     type Base = Mersenne31;
     type Dft = Mersenne31ComplexRadix2Dit;
@@ -238,4 +337,43 @@ mod tests {
 
         assert_eq!(c.values, conv);
     }
+
+    #[test]
+    fn real_batch_matches_lifted() {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let input = RowMajorMatrix::<Base>::rand(&mut rng, N, 3);
+        let dft = Mersenne31ComplexDft::<Dft>::default();
+
+        let lifted = Mersenne31ComplexDft::<Dft>::lift_real(input.clone());
+        let full = dft.dft_batch(lifted.clone());
+        let half = dft.dft_real_batch(input.clone());
+        assert_eq!(half.values, full.values[..(N / 2 + 1) * 3]);
+
+        assert_eq!(dft.idft_real_batch(half), input);
+        assert_eq!(
+            Mersenne31ComplexDft::<Dft>::real_parts(&dft.idft_batch(full)),
+            input
+        );
+    }
+
+    #[test]
+    fn coset_lde_matches_inner() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mat = RowMajorMatrix::<C>::rand(&mut rng, 1 << 6, 3);
+        let shift = C::new_complex(Base::new(3), Base::new(5));
+        let dft = Mersenne31ComplexDft::<Dft>::default();
+        let inner = Dft::default();
+        assert_eq!(
+            dft.coset_lde_batch(mat.clone(), 2, shift)
+                .to_row_major_matrix(),
+            inner
+                .coset_lde_batch(mat.clone(), 2, shift)
+                .to_row_major_matrix(),
+        );
+        assert_eq!(
+            dft.coset_idft_batch(mat.clone(), shift),
+            inner.coset_idft_batch(mat, shift),
+        );
+    }
 }
@@ -1,10 +1,13 @@
 use p3_field::extension::{
-    BinomiallyExtendable, Complex, HasComplexBinomialExtension, HasTwoAdicComplexBinomialExtension,
+    BinomiallyExtendable, BinomiallyExtendableAlgebra, Complex, HasComplexBinomialExtension,
+    HasTwoAdicComplexBinomialExtension,
 };
 use p3_field::{PrimeCharacteristicRing, TwoAdicField, field_to_array};
 
 use crate::Mersenne31;
 
+impl BinomiallyExtendableAlgebra<Self, 3> for Mersenne31 {}
+
 impl BinomiallyExtendable<3> for Mersenne31 {
     // ```sage
     // p = 2^31 - 1
@@ -25,6 +28,37 @@ impl BinomiallyExtendable<3> for Mersenne31 {
     const EXT_GENERATOR: [Self; 3] = [Self::new(10), Self::ONE, Self::ZERO];
 }
 
+impl BinomiallyExtendableAlgebra<Self, 7> for Mersenne31 {}
+
+impl BinomiallyExtendable<7> for Mersenne31 {
+    // As 7 divides p - 1 and 3 is not a 7th power, x^7 - 3 is irreducible. Verifiable in Sage with
+    // ```sage
+    // p = 2^31 - 1
+    // F = GF(p)
+    // R.<x> = F[]
+    // assert (x^7 - 3).is_irreducible()
+    // ```
+    const W: Self = Self::new(3);
+
+    // ```sage
+    // F(3)^((p-1)/7)
+    // ```
+    const DTH_ROOT: Self = Self::new(1752599774);
+
+    // ```sage
+    // F.extension(x^7 - 3, 'u').multiplicative_generator()
+    // ```
+    const EXT_GENERATOR: [Self; 7] = [
+        Self::TWO,
+        Self::ONE,
+        Self::ZERO,
+        Self::ZERO,
+        Self::ZERO,
+        Self::ZERO,
+        Self::ZERO,
+    ];
+}
+
 impl HasComplexBinomialExtension<2> for Mersenne31 {
     // Verifiable in Sage with
     // ```sage
@@ -105,6 +139,66 @@ impl HasTwoAdicComplexBinomialExtension<3> for Mersenne31 {
     }
 }
 
+// Together with the cubic extension above, this gives towers of degree 6 and 8 over Mersenne31.
+// As 5 does not divide p - 1, there is no quintic binomial extension.
+// TODO: Trinomial moduli, which would give extensions of these degrees over Mersenne31 directly,
+// are not supported by `p3_field` yet.
+impl HasComplexBinomialExtension<4> for Mersenne31 {
+    // As i + 2 is not a square in the complex extension and p^2 = 1 mod 4,
+    // y^4 - i - 2 is irreducible. Verifiable in Sage with
+    // ```sage
+    // p = 2**31 - 1  # Mersenne31
+    // F = GF(p)  # The base field GF(p)
+    // R.<x> = F[]  # The polynomial ring over F
+    // K.<i> = F.extension(x^2 + 1)  # The complex extension field
+    // R2.<y> = K[]
+    // f4 = y^4 - i - 2
+    // assert f4.is_irreducible()
+    // ```
+    const W: Complex<Self> = Complex::new_complex(Self::TWO, Self::ONE);
+
+    // DTH_ROOT = W^((p^2 - 1)/4).
+    const DTH_ROOT: Complex<Self> = Complex::new_imag(Self::ONE);
+
+    // Verifiable in Sage with
+    // ```sage
+    // K4.<j> = K.extension(f4)
+    //  g = j + 4
+    // for f in factor(p^8 - 1):
+    //   assert g^((p^8-1) // f) != 1
+    // ```
+    const EXT_GENERATOR: [Complex<Self>; 4] = [
+        Complex::new_real(Self::new(4)),
+        Complex::new_real(Self::ONE),
+        Complex::ZERO,
+        Complex::ZERO,
+    ];
+}
+
+impl HasTwoAdicComplexBinomialExtension<4> for Mersenne31 {
+    const COMPLEX_EXT_TWO_ADICITY: usize = 34;
+
+    fn complex_ext_two_adic_generator(bits: usize) -> [Complex<Self>; 4] {
+        assert!(bits <= 34);
+        match bits {
+            // The square of the generator for 34 bits.
+            33 => [
+                Complex::ZERO,
+                Complex::ZERO,
+                Complex::new_complex(Self::new(1437746044), Self::new(946469285)),
+                Complex::ZERO,
+            ],
+            34 => [
+                Complex::ZERO,
+                Complex::new_complex(Self::new(626196722), Self::new(1655729666)),
+                Complex::ZERO,
+                Complex::ZERO,
+            ],
+            _ => field_to_array(Complex::two_adic_generator(bits)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_cubic_extension {
     use num_bigint::BigUint;
@@ -146,6 +240,49 @@ mod test_cubic_extension {
     );
 }
 
+#[cfg(test)]
+mod test_septic_extension {
+    use num_bigint::BigUint;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field_testing::test_field;
+
+    use crate::Mersenne31;
+
+    type F = Mersenne31;
+    type EF = BinomialExtensionField<F, 7>;
+
+    // There is a redundant representation of zero but we already tested it
+    // when testing the base field.
+    const ZEROS: [EF; 1] = [EF::ZERO];
+    const ONES: [EF; 1] = [EF::ONE];
+
+    // Get the prime factorization of the order of the multiplicative group.
+    // i.e. the prime factorization of P^7 - 1.
+    fn multiplicative_group_prime_factorization() -> [(BigUint, u32); 11] {
+        [
+            (BigUint::from(2u8), 1),
+            (BigUint::from(3u8), 2),
+            (BigUint::from(7u8), 2),
+            (BigUint::from(11u8), 1),
+            (BigUint::from(29u8), 1),
+            (BigUint::from(31u8), 1),
+            (BigUint::from(151u8), 1),
+            (BigUint::from(331u16), 1),
+            (BigUint::from(4243u16), 1),
+            (BigUint::from(791948526123957812537327u128), 1),
+            (BigUint::from(143784862988552073346720159u128), 1),
+        ]
+    }
+
+    test_field!(
+        super::EF,
+        &super::ZEROS,
+        &super::ONES,
+        &super::multiplicative_group_prime_factorization()
+    );
+}
+
 #[cfg(test)]
 mod test_cubic_complex_extension {
     use num_bigint::BigUint;
@@ -239,3 +376,52 @@ mod test_quadratic_complex_extension {
 
     test_two_adic_extension_field!(super::F, super::EF);
 }
+
+#[cfg(test)]
+mod test_quartic_complex_extension {
+    use num_bigint::BigUint;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_field::extension::{BinomialExtensionField, Complex};
+    use p3_field_testing::{test_field, test_two_adic_extension_field};
+
+    use crate::Mersenne31;
+
+    type F = Complex<Mersenne31>;
+    type EF = BinomialExtensionField<F, 4>;
+
+    // There is a redundant representation of zero but we already tested it
+    // when testing the base field.
+    const ZEROS: [EF; 1] = [EF::ZERO];
+    const ONES: [EF; 1] = [EF::ONE];
+
+    // Get the prime factorization of the order of the multiplicative group.
+    // i.e. the prime factorization of P^8 - 1.
+    fn multiplicative_group_prime_factorization() -> [(BigUint, u32); 15] {
+        [
+            (BigUint::from(2u8), 34),
+            (BigUint::from(3u8), 2),
+            (BigUint::from(5u8), 1),
+            (BigUint::from(7u8), 1),
+            (BigUint::from(11u8), 1),
+            (BigUint::from(17u8), 1),
+            (BigUint::from(31u8), 1),
+            (BigUint::from(41u8), 1),
+            (BigUint::from(151u8), 1),
+            (BigUint::from(331u16), 1),
+            (BigUint::from(733u16), 1),
+            (BigUint::from(1709u16), 1),
+            (BigUint::from(21529u16), 1),
+            (BigUint::from(368140581013u64), 1),
+            (BigUint::from(708651694622727115232673724657u128), 1),
+        ]
+    }
+
+    test_field!(
+        super::EF,
+        &super::ZEROS,
+        &super::ONES,
+        &super::multiplicative_group_prime_factorization()
+    );
+
+    test_two_adic_extension_field!(super::F, super::EF);
+}
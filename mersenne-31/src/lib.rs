@@ -20,7 +20,7 @@ mod mersenne_31;
 mod poseidon2;
 mod radix_2_dit;
 
-pub use dft::Mersenne31Dft;
+pub use dft::{Mersenne31ComplexDft, Mersenne31Dft};
 pub use mds::*;
 pub use mersenne_31::*;
 pub use poseidon2::*;
@@ -403,7 +403,9 @@ impl PrimeField32 for Mersenne31 {
     fn as_canonical_u32(&self) -> u32 {
         // Since our invariant guarantees that `value` fits in 31 bits, there is only one possible
         // `value` that is not canonical, namely 2^31 - 1 = p = 0.
-        if self.value == Self::ORDER_U32 {
+        if cfg!(feature = "ct") {
+            self.value - select_u32(self.value == Self::ORDER_U32, Self::ORDER_U32)
+        } else if self.value == Self::ORDER_U32 {
             0
         } else {
             self.value
@@ -437,7 +439,11 @@ impl Add for Mersenne31 {
 
         // If self + rhs did not overflow, return it.
         // If self + rhs overflowed, sum_corr = self + rhs - (2**31 - 1).
-        Self::new(if over { sum_corr } else { sum_u32 })
+        if cfg!(feature = "ct") {
+            Self::new(sum_u32.wrapping_sub(select_u32(over, Self::ORDER_U32)))
+        } else {
+            Self::new(if over { sum_corr } else { sum_u32 })
+        }
     }
 }
 
@@ -537,6 +543,13 @@ pub(crate) fn from_u62(input: u64) -> Mersenne31 {
     Mersenne31::new(input_lo) + Mersenne31::new(input_high)
 }
 
+/// Return `x` if `cond` holds and zero otherwise, using a mask rather than a branch so that the
+/// control flow doesn't depend on `cond`.
+#[inline(always)]
+const fn select_u32(cond: bool, x: u32) -> u32 {
+    x & (cond as u32).wrapping_neg()
+}
+
 #[cfg(test)]
 mod tests {
     use num_bigint::BigUint;
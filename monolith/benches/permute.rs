@@ -1,14 +1,22 @@
 use core::array;
 
-use criterion::{Criterion, criterion_group, criterion_main};
-use p3_field::PrimeCharacteristicRing;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use p3_field::{Field, PackedValue, PrimeCharacteristicRing};
 use p3_mds::MdsPermutation;
 use p3_mersenne_31::{MdsMatrixMersenne31, Mersenne31};
-use p3_monolith::MonolithMersenne31;
+use p3_monolith::{MonolithMdsMatrixMersenne31, MonolithMersenne31};
+use p3_symmetric::Permutation;
+
+type Packed = <Mersenne31 as Field>::Packing;
 
 fn bench_monolith(c: &mut Criterion) {
     monolith::<_, 12>(c, MdsMatrixMersenne31);
     monolith::<_, 16>(c, MdsMatrixMersenne31);
+
+    monolith_packed::<12>(c);
+    monolith_packed::<16>(c);
+
+    bars_packed::<16>(c);
 }
 
 fn monolith<Mds, const WIDTH: usize>(c: &mut Criterion, mds: Mds)
@@ -25,5 +33,62 @@ where
     });
 }
 
+/// Compare the scalar and packed permutations with the same MDS layer, counting permutations so
+/// that the packed run's `Packed::WIDTH` permutations per call show up as throughput.
+fn monolith_packed<const WIDTH: usize>(c: &mut Criterion)
+where
+    MonolithMdsMatrixMersenne31<6>:
+        MdsPermutation<Mersenne31, WIDTH> + Permutation<[Packed; WIDTH]>,
+{
+    let monolith: MonolithMersenne31<_, WIDTH, 5> =
+        MonolithMersenne31::new(MonolithMdsMatrixMersenne31::<6>);
+
+    let mut group = c.benchmark_group(format!("monolith_packed::<Mersenne31, {}>", WIDTH));
+
+    let mut input = array::from_fn(Mersenne31::from_usize);
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("scalar", |b| b.iter(|| monolith.permutation(&mut input)));
+
+    let mut packed_input: [Packed; WIDTH] = array::from_fn(|i| {
+        Packed::from_fn(|lane| Mersenne31::from_usize(i * Packed::WIDTH + lane))
+    });
+    group.throughput(Throughput::Elements(Packed::WIDTH as u64));
+    group.bench_function("packed", |b| {
+        b.iter(|| monolith.permutation_packed(&mut packed_input))
+    });
+
+    group.finish();
+}
+
+/// Compare the bars through the lookup tables with the bitwise bars on packed states, counting
+/// states as in `monolith_packed`.
+fn bars_packed<const WIDTH: usize>(c: &mut Criterion)
+where
+    MonolithMdsMatrixMersenne31<6>: MdsPermutation<Mersenne31, WIDTH>,
+{
+    let monolith: MonolithMersenne31<_, WIDTH, 5> =
+        MonolithMersenne31::new(MonolithMdsMatrixMersenne31::<6>);
+
+    let mut group = c.benchmark_group(format!("monolith_bars::<Mersenne31, {}>", WIDTH));
+
+    let mut input = array::from_fn(Mersenne31::from_usize);
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("lookup", |b| b.iter(|| monolith.bars(&mut input)));
+
+    let mut packed_input: [Packed; WIDTH] = array::from_fn(|i| {
+        Packed::from_fn(|lane| Mersenne31::from_usize(i * Packed::WIDTH + lane))
+    });
+    group.throughput(Throughput::Elements(Packed::WIDTH as u64));
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            MonolithMersenne31::<MonolithMdsMatrixMersenne31<6>, WIDTH, 5>::bars_packed(
+                &mut packed_input,
+            )
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(benches, bench_monolith);
 criterion_main!(benches);
@@ -0,0 +1,72 @@
+//! Hash and compression functions built from Monolith-31, for use in a `MerkleTreeMmcs`.
+
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+
+use crate::{MonolithMdsMatrixMersenne31, MonolithMersenne31};
+
+/// Monolith-31 with its own MDS layer and the standard six rounds.
+///
+/// The standard widths are 16, where a sponge has rate 8, and 24, where it has rate 16.
+pub type MonolithMersenne31Default<const WIDTH: usize> =
+    MonolithMersenne31<MonolithMdsMatrixMersenne31<6>, WIDTH, 5>;
+
+/// A sponge hashing Mersenne31 elements to `OUT` elements with Monolith-31, e.g. to hash the
+/// leaves of a Merkle tree.
+pub type MonolithHash<const WIDTH: usize, const RATE: usize, const OUT: usize> =
+    PaddingFreeSponge<MonolithMersenne31Default<WIDTH>, WIDTH, RATE, OUT>;
+
+/// A 2-to-1 compression of `CHUNK`-element digests with Monolith-31, e.g. to hash the inner nodes
+/// of a Merkle tree.
+pub type MonolithCompress<const WIDTH: usize, const CHUNK: usize> =
+    TruncatedPermutation<MonolithMersenne31Default<WIDTH>, 2, CHUNK, WIDTH>;
+
+#[cfg(test)]
+mod tests {
+    use core::array;
+
+    use p3_field::{Field, PackedValue, PrimeCharacteristicRing};
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CryptographicHasher, PseudoCompressionFunction};
+
+    use super::*;
+
+    type F = Mersenne31;
+    type P = <F as Field>::Packing;
+
+    fn hash_and_compress<const WIDTH: usize, const RATE: usize>() {
+        let perm = MonolithMersenne31Default::<WIDTH>::new(MonolithMdsMatrixMersenne31);
+        let hash = MonolithHash::<WIDTH, RATE, 8>::new(perm.clone());
+        let compress = MonolithCompress::<WIDTH, 8>::new(perm.clone());
+
+        let input: [F; RATE] = array::from_fn(F::from_usize);
+        let mut state = [F::ZERO; WIDTH];
+        state[..RATE].copy_from_slice(&input);
+        perm.permutation(&mut state);
+        assert_eq!(hash.hash_iter(input), state[..8]);
+
+        let (left, right): ([F; 8], [F; 8]) = (array::from_fn(F::from_usize), [F::TWO; 8]);
+        let mut state = [F::ZERO; WIDTH];
+        state[..8].copy_from_slice(&left);
+        state[8..16].copy_from_slice(&right);
+        perm.permutation(&mut state);
+        assert_eq!(compress.compress([left, right]), state[..8]);
+
+        // The packed hash, which a Merkle tree uses for its leaves, agrees in every lane.
+        let packed_input: [P; RATE] = array::from_fn(|i| P::from_fn(|j| F::from_usize(i + j)));
+        let packed_digest: [P; 8] = hash.hash_iter(packed_input);
+        for lane in 0..P::WIDTH {
+            let digest: [F; 8] = hash.hash_iter(packed_input.map(|p| p.as_slice()[lane]));
+            assert_eq!(packed_digest.map(|p| p.as_slice()[lane]), digest);
+        }
+    }
+
+    #[test]
+    fn hash_and_compress_width_16() {
+        hash_and_compress::<16, 8>();
+    }
+
+    #[test]
+    fn hash_and_compress_width_24() {
+        hash_and_compress::<24, 16>();
+    }
+}
@@ -4,9 +4,12 @@
 
 extern crate alloc;
 
+mod hash;
 mod monolith;
 mod monolith_mds;
+mod packed_bar;
 mod util;
 
+pub use hash::{MonolithCompress, MonolithHash, MonolithMersenne31Default};
 pub use monolith::MonolithMersenne31;
 pub use monolith_mds::MonolithMdsMatrixMersenne31;
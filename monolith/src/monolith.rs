@@ -7,18 +7,20 @@ use alloc::borrow::ToOwned;
 use alloc::vec::Vec;
 
 use p3_field::integers::QuotientMap;
-use p3_field::{PrimeCharacteristicRing, PrimeField32};
+use p3_field::{Algebra, PackedField, PackedValue, PrimeCharacteristicRing, PrimeField32};
 use p3_mds::MdsPermutation;
 use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CryptographicPermutation, Permutation};
 use sha3::digest::{ExtendableOutput, Update};
 use sha3::{Shake128, Shake128Reader};
 
+use crate::packed_bar::{bar_bitwise, bar_slice};
 use crate::util::get_random_u32;
 
 // The Monolith-31 permutation over Mersenne31.
 // NUM_FULL_ROUNDS is the number of rounds - 1
 // (used to avoid const generics because we need an array of length NUM_FULL_ROUNDS)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MonolithMersenne31<Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize>
 where
     Mds: MdsPermutation<Mersenne31, WIDTH>,
@@ -119,24 +121,26 @@ where
     }
 
     #[inline]
-    pub fn concrete(&self, state: &mut [Mersenne31; WIDTH]) {
+    pub fn concrete<R: Clone>(&self, state: &mut [R; WIDTH])
+    where
+        Mds: Permutation<[R; WIDTH]>,
+    {
         self.mds.permute_mut(state);
     }
 
     #[inline]
-    pub fn add_round_constants(
+    pub fn add_round_constants<R: Algebra<Mersenne31>>(
         &self,
-        state: &mut [Mersenne31; WIDTH],
+        state: &mut [R; WIDTH],
         round_constants: &[Mersenne31; WIDTH],
     ) {
-        // TODO: vectorize?
         for (x, rc) in state.iter_mut().zip(round_constants) {
             *x += *rc;
         }
     }
 
     #[inline]
-    pub fn bricks(state: &mut [Mersenne31; WIDTH]) {
+    pub fn bricks<R: PrimeCharacteristicRing>(state: &mut [R; WIDTH]) {
         // Feistel Type-3
         for (x, x_mut) in state.to_owned().iter().zip(state.iter_mut().skip(1)) {
             *x_mut += x.square();
@@ -171,6 +175,23 @@ where
             .for_each(|el| *el = self.bar(*el));
     }
 
+    /// The bar of `el`, computed with bitwise operations on the whole word rather than with the
+    /// lookup tables.
+    #[inline]
+    pub fn bar_bitwise(el: Mersenne31) -> Mersenne31 {
+        bar_bitwise(el)
+    }
+
+    /// The bars of packed states, computed with the bitwise form of the S-boxes on whole AVX2 or
+    /// NEON vectors where the target has them.
+    #[inline]
+    pub fn bars_packed<P: PackedValue<Value = Mersenne31>>(state: &mut [P; WIDTH]) {
+        state
+            .iter_mut()
+            .take(Self::NUM_BARS)
+            .for_each(|packed| bar_slice(packed.as_slice_mut()));
+    }
+
     pub fn permutation(&self, state: &mut [Mersenne31; WIDTH]) {
         self.concrete(state);
         for rc in self.round_constants {
@@ -183,14 +204,59 @@ where
         Self::bricks(state);
         self.concrete(state);
     }
+
+    /// Apply the permutation to `P::WIDTH` states at once, one in each lane.
+    pub fn permutation_packed<P: PackedField<Scalar = Mersenne31>>(&self, state: &mut [P; WIDTH])
+    where
+        Mds: Permutation<[P; WIDTH]>,
+    {
+        self.concrete(state);
+        for rc in self.round_constants {
+            Self::bars_packed(state);
+            Self::bricks(state);
+            self.concrete(state);
+            self.add_round_constants(state, &rc);
+        }
+        Self::bars_packed(state);
+        Self::bricks(state);
+        self.concrete(state);
+    }
+}
+
+impl<P, Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize> Permutation<[P; WIDTH]>
+    for MonolithMersenne31<Mds, WIDTH, NUM_FULL_ROUNDS>
+where
+    P: PackedField<Scalar = Mersenne31>,
+    Mds: MdsPermutation<Mersenne31, WIDTH> + Permutation<[P; WIDTH]>,
+{
+    fn permute_mut(&self, input: &mut [P; WIDTH]) {
+        // A single state is faster through the lookup tables than through the bitwise bars.
+        if P::WIDTH == 1 {
+            let mut state = input.map(|packed| packed.as_slice()[0]);
+            self.permutation(&mut state);
+            *input = state.map(|x| P::from_fn(|_| x));
+        } else {
+            self.permutation_packed(input);
+        }
+    }
+}
+
+impl<P, Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize> CryptographicPermutation<[P; WIDTH]>
+    for MonolithMersenne31<Mds, WIDTH, NUM_FULL_ROUNDS>
+where
+    P: PackedField<Scalar = Mersenne31>,
+    Mds: MdsPermutation<Mersenne31, WIDTH> + Permutation<[P; WIDTH]>,
+{
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
     use core::array;
 
-    use p3_field::PrimeCharacteristicRing;
+    use p3_field::{Field, PackedValue, PrimeCharacteristicRing, PrimeField32};
     use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::Permutation;
 
     use crate::monolith::MonolithMersenne31;
     use crate::monolith_mds::MonolithMdsMatrixMersenne31;
@@ -213,4 +279,74 @@ mod tests {
 
         assert_eq!(input, expected);
     }
+
+    #[test]
+    fn bar_bitwise_matches_lookup() {
+        type Monolith = MonolithMersenne31<MonolithMdsMatrixMersenne31<6>, 16, 5>;
+        let monolith = Monolith::new(MonolithMdsMatrixMersenne31::<6>);
+
+        let samples = (0..1 << 16)
+            .map(|i: u32| i.wrapping_mul(0x9E37_79B9))
+            .chain([0, 1, 0x00FF_FFFF, 0x7F00_0000, Mersenne31::ORDER_U32 - 1]);
+        for x in samples.map(Mersenne31::from_u32) {
+            assert_eq!(Monolith::bar_bitwise(x), monolith.bar(x));
+        }
+    }
+
+    #[test]
+    fn bars_packed_match_lookup() {
+        type P = <Mersenne31 as Field>::Packing;
+        type Monolith = MonolithMersenne31<MonolithMdsMatrixMersenne31<6>, 16, 5>;
+        let monolith = Monolith::new(MonolithMdsMatrixMersenne31::<6>);
+
+        // Include the redundant representation of zero, which the vector bars must canonicalize.
+        let redundant_zero = Mersenne31::new_checked(Mersenne31::ORDER_U32).unwrap();
+        let mut packed: [P; 16] = array::from_fn(|i| {
+            P::from_fn(|lane| match i * P::WIDTH + lane {
+                0 => redundant_zero,
+                j => Mersenne31::from_u32((j as u32).wrapping_mul(0x9E37_79B9)),
+            })
+        });
+        let mut states: Vec<[Mersenne31; 16]> = (0..P::WIDTH)
+            .map(|lane| packed.map(|p| p.as_slice()[lane]))
+            .collect();
+
+        Monolith::bars_packed(&mut packed);
+
+        for (lane, state) in states.iter_mut().enumerate() {
+            monolith.bars(state);
+            assert_eq!(*state, packed.map(|p| p.as_slice()[lane]));
+        }
+    }
+
+    #[test]
+    fn packed_matches_scalar() {
+        type P = <Mersenne31 as Field>::Packing;
+        let mds = MonolithMdsMatrixMersenne31::<6>;
+        let monolith: MonolithMersenne31<_, 16, 5> = MonolithMersenne31::new(mds);
+
+        let mut states: Vec<[Mersenne31; 16]> = (0..P::WIDTH)
+            .map(|lane| array::from_fn(|i| Mersenne31::from_usize(i * P::WIDTH + lane)))
+            .collect();
+        let mut packed: [P; 16] = array::from_fn(|i| P::from_fn(|lane| states[lane][i]));
+
+        monolith.permutation_packed(&mut packed);
+
+        for (lane, state) in states.iter_mut().enumerate() {
+            monolith.permutation(state);
+            assert_eq!(*state, packed.map(|p| p.as_slice()[lane]));
+        }
+    }
+
+    #[test]
+    fn scalar_permute_matches_permutation() {
+        let mds = MonolithMdsMatrixMersenne31::<6>;
+        let monolith: MonolithMersenne31<_, 16, 5> = MonolithMersenne31::new(mds);
+
+        let mut expected = array::from_fn(Mersenne31::from_usize);
+        let mut input = expected;
+        monolith.permutation(&mut expected);
+        monolith.permute_mut(&mut input);
+        assert_eq!(input, expected);
+    }
 }
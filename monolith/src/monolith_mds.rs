@@ -1,7 +1,7 @@
 //! Monolith-31's default MDS permutation.
 //! With significant inspiration from https://extgit.iaik.tugraz.at/krypto/zkfriendlyhashzoo/
 
-use p3_field::PrimeField32;
+use p3_field::{Algebra, PrimeField32};
 use p3_mds::MdsPermutation;
 use p3_mds::util::apply_circulant;
 use p3_mersenne_31::Mersenne31;
@@ -19,10 +19,10 @@ const MATRIX_CIRC_MDS_16_MERSENNE31_MONOLITH: [u64; 16] = [
     33823, 28750, 1108,
 ];
 
-impl<const WIDTH: usize, const NUM_ROUNDS: usize> Permutation<[Mersenne31; WIDTH]>
-    for MonolithMdsMatrixMersenne31<NUM_ROUNDS>
+impl<A: Algebra<Mersenne31> + Copy, const WIDTH: usize, const NUM_ROUNDS: usize>
+    Permutation<[A; WIDTH]> for MonolithMdsMatrixMersenne31<NUM_ROUNDS>
 {
-    fn permute(&self, input: [Mersenne31; WIDTH]) -> [Mersenne31; WIDTH] {
+    fn permute(&self, input: [A; WIDTH]) -> [A; WIDTH] {
         if WIDTH == 16 {
             let matrix: [u64; WIDTH] = MATRIX_CIRC_MDS_16_MERSENNE31_MONOLITH[..]
                 .try_into()
@@ -36,11 +36,11 @@ impl<const WIDTH: usize, const NUM_ROUNDS: usize> Permutation<[Mersenne31; WIDTH
             shake.update(&[16, 15]);
             shake.update(b"MDS");
             let mut shake_finalized = shake.finalize_xof();
-            apply_cauchy_mds_matrix(&mut shake_finalized, input)
+            apply_cauchy_mds_matrix::<Mersenne31, _, WIDTH>(&mut shake_finalized, input)
         }
     }
 
-    fn permute_mut(&self, input: &mut [Mersenne31; WIDTH]) {
+    fn permute_mut(&self, input: &mut [A; WIDTH]) {
         *input = self.permute(*input);
     }
 }
@@ -50,11 +50,11 @@ impl<const WIDTH: usize, const NUM_ROUNDS: usize> MdsPermutation<Mersenne31, WID
 {
 }
 
-fn apply_cauchy_mds_matrix<F: PrimeField32, const WIDTH: usize>(
+fn apply_cauchy_mds_matrix<F: PrimeField32, A: Algebra<F> + Copy, const WIDTH: usize>(
     shake: &mut Shake128Reader,
-    to_multiply: [F; WIDTH],
-) -> [F; WIDTH] {
-    let mut output: [F; WIDTH] = [F::ZERO; WIDTH];
+    to_multiply: [A; WIDTH],
+) -> [A; WIDTH] {
+    let mut output: [A; WIDTH] = [A::ZERO; WIDTH];
 
     // As F is a PrimeField, it's order is equal to its characteristic.
     // Thus 2|F| > 2^bits > |F|.
@@ -75,7 +75,7 @@ fn apply_cauchy_mds_matrix<F: PrimeField32, const WIDTH: usize>(
                 // Hence x_i + y_j < |F|.
                 F::from_canonical_unchecked(x_i + y_j).inverse()
             };
-            output[i] += val * to_multiply[j];
+            output[i] += to_multiply[j] * val;
         }
     }
 
@@ -0,0 +1,180 @@
+//! The bars of Monolith-31 in the bitwise form of their S-boxes, applied a whole SIMD vector at a
+//! time on targets with AVX2 or NEON.
+
+use p3_field::PrimeField32;
+use p3_mersenne_31::Mersenne31;
+
+/// The bar of `el`, computed with bitwise operations on the whole word rather than with the lookup
+/// tables.
+#[inline]
+pub(crate) fn bar_bitwise(el: Mersenne31) -> Mersenne31 {
+    let y = el.as_canonical_u32();
+
+    // The top limb's S-box is the 7-bit chi, which is the 8-bit one without the third rotation,
+    // so that rotation is masked to ones there.
+    let r1 = rotate_limbs(y, 1);
+    let r2 = rotate_limbs(y, 2);
+    let r3 = rotate_limbs(y, 3) | 0xFF00_0000;
+    let tmp = (y ^ (!r1 & r2 & r3)) & 0x7FFF_FFFF;
+
+    unsafe {
+        // Safety: tmp < 2^31, and rotating its limbs keeps it below 2^31.
+        Mersenne31::from_canonical_unchecked(rotate_limbs(tmp, 1))
+    }
+}
+
+/// Apply the bar to every element of `els`, a vector at a time where the target has SIMD
+/// instructions, and one element at a time for the rest.
+#[inline]
+pub(crate) fn bar_slice(els: &mut [Mersenne31]) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    let els = {
+        let mut chunks = els.chunks_exact_mut(x86_64_avx2::WIDTH);
+        for chunk in &mut chunks {
+            x86_64_avx2::bar(chunk.try_into().unwrap());
+        }
+        chunks.into_remainder()
+    };
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    let els = {
+        let mut chunks = els.chunks_exact_mut(aarch64_neon::WIDTH);
+        for chunk in &mut chunks {
+            aarch64_neon::bar(chunk.try_into().unwrap());
+        }
+        chunks.into_remainder()
+    };
+
+    els.iter_mut().for_each(|el| *el = bar_bitwise(*el));
+}
+
+/// Rotate each of the low three bytes of `y` left by `k`, and its top seven bits left by `k`
+/// within those seven bits, matching the limbs the bars split an element into.
+#[inline(always)]
+const fn rotate_limbs(y: u32, k: u32) -> u32 {
+    let lo_mask = ((1 << k) - 1) * 0x01_0101;
+    let hi_mask = ((0xFF << k) & 0xFF) * 0x01_0101;
+    let low = ((y << k) & hi_mask) | ((y >> (8 - k)) & lo_mask);
+    let top = y >> 24;
+    let top = ((top << k) | (top >> (7 - k))) & 0x7F;
+    low | (top << 24)
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+mod x86_64_avx2 {
+    use core::arch::x86_64::*;
+    use core::mem::transmute;
+
+    use p3_field::PrimeField32;
+    use p3_mersenne_31::Mersenne31;
+
+    pub(super) const WIDTH: usize = 8;
+
+    /// Apply the bar to each element of `els`, as `bar_bitwise` does, in one AVX2 vector.
+    #[inline]
+    pub(super) fn bar(els: &mut [Mersenne31; WIDTH]) {
+        unsafe {
+            // Safety: If this code got compiled then AVX2 intrinsics are available.
+            // `Mersenne31` is `repr(transparent)` over a `u32`, so `[Mersenne31; 8]` can be
+            // transmuted to and from `__m256i`.
+            let y: __m256i = transmute(*els);
+
+            // P is a second representation of zero, which the bitwise S-boxes would misread.
+            let is_p = _mm256_cmpeq_epi32(y, _mm256_set1_epi32(Mersenne31::ORDER_U32 as i32));
+            let y = _mm256_andnot_si256(is_p, y);
+
+            let r1 = rotate_limbs(y, 1);
+            let r2 = rotate_limbs(y, 2);
+            let r3 = _mm256_or_si256(
+                rotate_limbs(y, 3),
+                _mm256_set1_epi32(0xFF00_0000_u32 as i32),
+            );
+            let chi = _mm256_and_si256(_mm256_andnot_si256(r1, r2), r3);
+            let tmp = _mm256_and_si256(_mm256_xor_si256(y, chi), _mm256_set1_epi32(0x7FFF_FFFF));
+
+            // Safety: every lane is below 2^31, so it is a valid `Mersenne31`.
+            *els = transmute::<__m256i, [Mersenne31; WIDTH]>(rotate_limbs(tmp, 1));
+        }
+    }
+
+    /// The vector form of `super::rotate_limbs`, for `k` in `1..=3`.
+    #[inline(always)]
+    fn rotate_limbs(y: __m256i, k: i32) -> __m256i {
+        unsafe {
+            // Safety: If this code got compiled then AVX2 intrinsics are available.
+            let shift = |n: i32| _mm_cvtsi32_si128(n);
+            let lo_mask = _mm256_set1_epi32(((1 << k) - 1) * 0x01_0101);
+            let hi_mask = _mm256_set1_epi32(((0xFF << k) & 0xFF) * 0x01_0101);
+            let low = _mm256_or_si256(
+                _mm256_and_si256(_mm256_sll_epi32(y, shift(k)), hi_mask),
+                _mm256_and_si256(_mm256_srl_epi32(y, shift(8 - k)), lo_mask),
+            );
+            let top = _mm256_srli_epi32::<24>(y);
+            let top = _mm256_and_si256(
+                _mm256_or_si256(
+                    _mm256_sll_epi32(top, shift(k)),
+                    _mm256_srl_epi32(top, shift(7 - k)),
+                ),
+                _mm256_set1_epi32(0x7F),
+            );
+            _mm256_or_si256(low, _mm256_slli_epi32::<24>(top))
+        }
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod aarch64_neon {
+    use core::arch::aarch64::*;
+    use core::mem::transmute;
+
+    use p3_field::PrimeField32;
+    use p3_mersenne_31::Mersenne31;
+
+    pub(super) const WIDTH: usize = 4;
+
+    /// Apply the bar to each element of `els`, as `bar_bitwise` does, in one NEON vector.
+    #[inline]
+    pub(super) fn bar(els: &mut [Mersenne31; WIDTH]) {
+        unsafe {
+            // Safety: If this code got compiled then NEON intrinsics are available.
+            // `Mersenne31` is `repr(transparent)` over a `u32`, so `[Mersenne31; 4]` can be
+            // transmuted to and from `uint32x4_t`.
+            let y: uint32x4_t = transmute(*els);
+
+            // P is a second representation of zero, which the bitwise S-boxes would misread.
+            let is_p = vceqq_u32(y, vdupq_n_u32(Mersenne31::ORDER_U32));
+            let y = vbicq_u32(y, is_p);
+
+            let r1 = rotate_limbs(y, 1);
+            let r2 = rotate_limbs(y, 2);
+            let r3 = vorrq_u32(rotate_limbs(y, 3), vdupq_n_u32(0xFF00_0000));
+            let chi = vandq_u32(vbicq_u32(r2, r1), r3);
+            let tmp = vandq_u32(veorq_u32(y, chi), vdupq_n_u32(0x7FFF_FFFF));
+
+            // Safety: every lane is below 2^31, so it is a valid `Mersenne31`.
+            *els = transmute::<uint32x4_t, [Mersenne31; WIDTH]>(rotate_limbs(tmp, 1));
+        }
+    }
+
+    /// The vector form of `super::rotate_limbs`, for `k` in `1..=3`.
+    #[inline(always)]
+    fn rotate_limbs(y: uint32x4_t, k: i32) -> uint32x4_t {
+        unsafe {
+            // Safety: If this code got compiled then NEON intrinsics are available.
+            // `vshlq_u32` shifts right by the negation of a negative shift.
+            let shift = |n: i32| vdupq_n_s32(n);
+            let lo_mask = vdupq_n_u32((((1 << k) - 1) * 0x01_0101) as u32);
+            let hi_mask = vdupq_n_u32((((0xFF << k) & 0xFF) * 0x01_0101) as u32);
+            let low = vorrq_u32(
+                vandq_u32(vshlq_u32(y, shift(k)), hi_mask),
+                vandq_u32(vshlq_u32(y, shift(k - 8)), lo_mask),
+            );
+            let top = vshrq_n_u32::<24>(y);
+            let top = vandq_u32(
+                vorrq_u32(vshlq_u32(top, shift(k)), vshlq_u32(top, shift(k - 7))),
+                vdupq_n_u32(0x7F),
+            );
+            vorrq_u32(low, vshlq_n_u32::<24>(top))
+        }
+    }
+}
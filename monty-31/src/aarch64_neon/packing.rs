@@ -392,6 +392,46 @@ fn sub<MPNeon: MontyParametersNeon>(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x
     }
 }
 
+/// Multiply two elements of `F[X]/(X^4 - w)` with coefficients `a` and `b`, writing the
+/// coefficients of the product to `res`.
+///
+/// Coefficient `i` of the product is the sum over `j` of `a_j c_{ij}`, where `c_{ij}` is `b_{i - j}`
+/// for `j <= i` and `w b_{4 + i - j}` otherwise. Column `j` of `c` is the window starting at
+/// position `4 - j` of `(w b_0, w b_1, w b_2, w b_3, b_0, b_1, b_2, b_3)`, so the product is the
+/// sum of the broadcast coefficients of `a` times these windows.
+#[inline]
+pub(crate) fn quartic_mul_packed<FP: FieldParameters>(
+    a: &[MontyField31<FP>; 4],
+    b: &[MontyField31<FP>; 4],
+    res: &mut [MontyField31<FP>; 4],
+    w: MontyField31<FP>,
+) {
+    unsafe {
+        // Safety: If this code got compiled then NEON intrinsics are available.
+        // `MontyField31` is `repr(transparent)` so `[MontyField31; 4]` can be transmuted to and
+        // from `uint32x4_t`.
+        let b_vec: uint32x4_t = transmute(*b);
+        let w_b = mul::<FP>(b_vec, aarch64::vdupq_n_u32(w.value));
+
+        let prod_0 = mul::<FP>(aarch64::vdupq_n_u32(a[0].value), b_vec);
+        let prod_1 = mul::<FP>(
+            aarch64::vdupq_n_u32(a[1].value),
+            aarch64::vextq_u32::<3>(w_b, b_vec),
+        );
+        let prod_2 = mul::<FP>(
+            aarch64::vdupq_n_u32(a[2].value),
+            aarch64::vextq_u32::<2>(w_b, b_vec),
+        );
+        let prod_3 = mul::<FP>(
+            aarch64::vdupq_n_u32(a[3].value),
+            aarch64::vextq_u32::<1>(w_b, b_vec),
+        );
+
+        let sum = add::<FP>(add::<FP>(prod_0, prod_1), add::<FP>(prod_2, prod_3));
+        *res = transmute::<uint32x4_t, [MontyField31<FP>; 4]>(sum);
+    }
+}
+
 impl<PMP: PackedMontyParameters> From<MontyField31<PMP>> for PackedMontyField31Neon<PMP> {
     #[inline]
     fn from(value: MontyField31<PMP>) -> Self {
@@ -20,7 +20,8 @@ use rand::distr::{Distribution, StandardUniform};
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::utils::{
-    from_monty, halve_u32, monty_reduce, to_monty, to_monty_64, to_monty_64_signed, to_monty_signed,
+    from_monty, halve_u32, monty_reduce, select_u32, to_monty, to_monty_64, to_monty_64_signed,
+    to_monty_signed,
 };
 use crate::{FieldParameters, MontyParameters, RelativelyPrimePower, TwoAdicData};
 
@@ -496,12 +497,9 @@ impl<FP: MontyParameters> Add for MontyField31<FP> {
 
     #[inline]
     fn add(self, rhs: Self) -> Self {
-        let mut sum = self.value + rhs.value;
+        let sum = self.value + rhs.value;
         let (corr_sum, over) = sum.overflowing_sub(FP::PRIME);
-        if !over {
-            sum = corr_sum;
-        }
-        Self::new_monty(sum)
+        Self::new_monty(corr_sum.wrapping_add(select_u32(over, FP::PRIME)))
     }
 }
 
@@ -530,7 +528,7 @@ impl<FP: MontyParameters> Sub for MontyField31<FP> {
     #[inline]
     fn sub(self, rhs: Self) -> Self {
         let (mut diff, over) = self.value.overflowing_sub(rhs.value);
-        let corr = if over { FP::PRIME } else { 0 };
+        let corr = select_u32(over, FP::PRIME);
         diff = diff.wrapping_add(corr);
         Self::new_monty(diff)
     }
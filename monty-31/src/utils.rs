@@ -58,8 +58,7 @@ pub(crate) const fn from_monty<MP: MontyParameters>(x: u32) -> u32 {
 pub(crate) const fn halve_u32<FP: FieldParameters>(input: u32) -> u32 {
     let shr = input >> 1;
     let lo_bit = input & 1;
-    let shr_corr = shr + FP::HALF_P_PLUS_1;
-    if lo_bit == 0 { shr } else { shr_corr }
+    shr + select_u32(lo_bit != 0, FP::HALF_P_PLUS_1)
 }
 
 /// Montgomery reduction of a value in `0..P << MONTY_BITS`.
@@ -73,6 +72,23 @@ pub(crate) const fn monty_reduce<MP: MontyParameters>(x: u64) -> u32 {
 
     let (x_sub_u, over) = x.overflowing_sub(u);
     let x_sub_u_hi = (x_sub_u >> MP::MONTY_BITS) as u32;
-    let corr = if over { MP::PRIME } else { 0 };
+    let corr = select_u32(over, MP::PRIME);
     x_sub_u_hi.wrapping_add(corr)
 }
+
+/// Return `x` if `cond` holds and zero otherwise.
+///
+/// With the `ct` feature the choice is made with a mask, so that the control flow doesn't depend
+/// on `cond`. This is what keeps addition, subtraction, multiplication and halving free of
+/// data-dependent branches.
+#[inline(always)]
+#[must_use]
+pub(crate) const fn select_u32(cond: bool, x: u32) -> u32 {
+    if cfg!(feature = "ct") {
+        x & (cond as u32).wrapping_neg()
+    } else if cond {
+        x
+    } else {
+        0
+    }
+}
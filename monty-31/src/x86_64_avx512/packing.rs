@@ -4,7 +4,7 @@
 
 use alloc::vec::Vec;
 use core::arch::asm;
-use core::arch::x86_64::{self, __m512i, __mmask8, __mmask16};
+use core::arch::x86_64::{self, __m128i, __m512i, __mmask8, __mmask16};
 use core::array;
 use core::hint::unreachable_unchecked;
 use core::iter::{Product, Sum};
@@ -990,6 +990,49 @@ fn general_dot_product<
     }
 }
 
+/// Multiply two elements of `F[X]/(X^4 - w)` with coefficients `a` and `b`, writing the
+/// coefficients of the product to `res`.
+///
+/// Coefficient `i` of the product is the sum over `j` of `a_j c_{ij}`, where `c_{ij}` is `b_{i - j}`
+/// for `j <= i` and `w b_{4 + i - j}` otherwise. Column `j` of `c` is the window starting at
+/// position `4 - j` of `(w b_0, w b_1, w b_2, w b_3, b_0, b_1, b_2, b_3)`, so the product is a
+/// single `dot_product_4` of the broadcast coefficients of `a` with these windows, whose low four
+/// lanes hold the result.
+#[inline]
+pub(crate) fn quartic_mul_packed<FP: FieldParameters>(
+    a: &[MontyField31<FP>; 4],
+    b: &[MontyField31<FP>; 4],
+    res: &mut [MontyField31<FP>; 4],
+    w: MontyField31<FP>,
+) {
+    unsafe {
+        // Safety: If this code got compiled then AVX-512F intrinsics are available.
+        // `MontyField31` is `repr(transparent)` so `[MontyField31; 4]` can be transmuted to and
+        // from `__m128i`. Every lane we build is either zero or a canonical field element.
+        let b_vec: __m128i = transmute(*b);
+        let w_b = x86_64::_mm512_castsi512_si128(mul::<FP>(
+            x86_64::_mm512_zextsi128_si512(b_vec),
+            w.as_m512i(),
+        ));
+
+        let rhs = [
+            b_vec,
+            x86_64::_mm_alignr_epi8::<12>(b_vec, w_b),
+            x86_64::_mm_alignr_epi8::<8>(b_vec, w_b),
+            x86_64::_mm_alignr_epi8::<4>(b_vec, w_b),
+        ];
+        let rhs = [
+            PackedMontyField31AVX512::<FP>::from_vector(x86_64::_mm512_zextsi128_si512(rhs[0])),
+            PackedMontyField31AVX512::<FP>::from_vector(x86_64::_mm512_zextsi128_si512(rhs[1])),
+            PackedMontyField31AVX512::<FP>::from_vector(x86_64::_mm512_zextsi128_si512(rhs[2])),
+            PackedMontyField31AVX512::<FP>::from_vector(x86_64::_mm512_zextsi128_si512(rhs[3])),
+        ];
+
+        let product = dot_product_4(*a, rhs);
+        *res = transmute::<__m128i, [MontyField31<FP>; 4]>(x86_64::_mm512_castsi512_si128(product));
+    }
+}
+
 impl<PMP: PackedMontyParameters> From<MontyField31<PMP>> for PackedMontyField31AVX512<PMP> {
     #[inline]
     fn from(value: MontyField31<PMP>) -> Self {
@@ -130,7 +130,9 @@ pub fn generate_trace_rows<
 }
 
 /// `rows` will normally consist of 24 rows, with an exception for the final row.
-fn generate_trace_rows_for_perm<
+///
+/// Returns the output of the permutation.
+pub(crate) fn generate_trace_rows_for_perm<
     F: PrimeField,
     LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>,
     const WIDTH: usize,
@@ -149,7 +151,7 @@ fn generate_trace_rows_for_perm<
     >,
     mut state: [F; WIDTH],
     constants: &RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
-) {
+) -> [F; WIDTH] {
     perm.export.write(F::ONE);
     perm.inputs
         .iter_mut()
@@ -191,6 +193,8 @@ fn generate_trace_rows_for_perm<
             &mut state, full_round, constants,
         );
     }
+
+    state
 }
 
 #[inline]
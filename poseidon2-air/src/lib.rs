@@ -8,10 +8,12 @@ mod air;
 mod columns;
 mod constants;
 mod generation;
+mod sponge;
 mod vectorized;
 
 pub use air::*;
 pub use columns::*;
 pub use constants::*;
 pub use generation::*;
+pub use sponge::*;
 pub use vectorized::*;
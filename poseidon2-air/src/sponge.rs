@@ -0,0 +1,722 @@
+use alloc::vec::Vec;
+use core::borrow::{Borrow, BorrowMut};
+use core::mem::{self, MaybeUninit, size_of};
+
+use p3_air::{Air, AirBuilder, BaseAir, InteractionAirBuilder};
+use p3_field::{Field, PrimeCharacteristicRing, PrimeField};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use p3_poseidon2::GenericPoseidon2LinearLayers;
+use tracing::instrument;
+
+use crate::air::eval;
+use crate::generation::generate_trace_rows_for_perm;
+use crate::{Poseidon2Air, Poseidon2Cols, RoundConstants};
+
+/// Columns for a Poseidon2 AIR which runs sponges, one permutation per row.
+///
+/// A sponge occupies consecutive rows. Its first row starts from the zero state, and each later
+/// row starts from the output of the row above, with the rate either overwritten by an absorbed
+/// block or left as it was to squeeze further outputs.
+#[repr(C)]
+pub struct Poseidon2SpongeCols<
+    T,
+    const WIDTH: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+> {
+    /// The permutation of this row. Its boolean `export` column says whether the row sends its
+    /// output to the output bus, which it may only do when the output is squeezed.
+    pub perm:
+        Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+
+    /// Whether this row starts a new sponge.
+    pub reset: T,
+
+    /// Whether the rate of this row's input is a block absorbed from the input bus.
+    pub absorb: T,
+
+    /// The position of the sponge in the trace, counting from zero.
+    pub sponge_id: T,
+
+    /// The position of this row within its sponge, counting from zero.
+    pub index: T,
+}
+
+pub const fn num_sponge_cols<
+    const WIDTH: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>() -> usize {
+    size_of::<
+        Poseidon2SpongeCols<
+            u8,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >,
+    >()
+}
+
+impl<
+    T,
+    const WIDTH: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>
+    Borrow<
+        Poseidon2SpongeCols<
+            T,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >,
+    > for [T]
+{
+    fn borrow(
+        &self,
+    ) -> &Poseidon2SpongeCols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>
+    {
+        let (prefix, shorts, suffix) = unsafe {
+            self.align_to::<Poseidon2SpongeCols<
+                T,
+                WIDTH,
+                SBOX_DEGREE,
+                SBOX_REGISTERS,
+                HALF_FULL_ROUNDS,
+                PARTIAL_ROUNDS,
+            >>()
+        };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(shorts.len(), 1);
+        &shorts[0]
+    }
+}
+
+impl<
+    T,
+    const WIDTH: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>
+    BorrowMut<
+        Poseidon2SpongeCols<
+            T,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >,
+    > for [T]
+{
+    fn borrow_mut(
+        &mut self,
+    ) -> &mut Poseidon2SpongeCols<
+        T,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    > {
+        let (prefix, shorts, suffix) = unsafe {
+            self.align_to_mut::<Poseidon2SpongeCols<
+                T,
+                WIDTH,
+                SBOX_DEGREE,
+                SBOX_REGISTERS,
+                HALF_FULL_ROUNDS,
+                PARTIAL_ROUNDS,
+            >>()
+        };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(shorts.len(), 1);
+        &mut shorts[0]
+    }
+}
+
+/// One permutation of a sponge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongeOp<F, const RATE: usize> {
+    /// Overwrite the rate with a block, then permute.
+    Absorb([F; RATE]),
+    /// Permute without absorbing, to squeeze more outputs.
+    Squeeze,
+}
+
+/// An AIR proving a batch of Poseidon2 sponges, in overwrite mode with a rate of `RATE`.
+///
+/// The AIR talks to the rest of the system through two buses:
+/// - each absorbing row receives `(sponge_id, index, block)` on `input_bus`, and
+/// - each row whose output is squeezed, i.e. which ends its sponge or is followed by a squeeze,
+///   may send `(sponge_id, index, output[..RATE])` on `output_bus`, once if its boolean `export`
+///   column is set.
+///
+/// `eval` only declares these interactions, which nothing checks row by row. To enforce them,
+/// wrap the AIR in `p3_uni_stark::LogUpAir` and prove it with `prove_multi_with_aux`, together
+/// with the AIRs on the other ends of the buses.
+///
+/// A sponge absorbing whole blocks agrees with `PaddingFreeSponge` over the same permutation, and
+/// a sponge of a single absorbing row is a compression of the form used for Merkle paths.
+#[derive(Debug)]
+pub struct Poseidon2SpongeAir<
+    F: Field,
+    LinearLayers,
+    const WIDTH: usize,
+    const RATE: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+> {
+    pub(crate) air: Poseidon2Air<
+        F,
+        LinearLayers,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >,
+    input_bus: usize,
+    output_bus: usize,
+}
+
+impl<
+    F: Field,
+    LinearLayers,
+    const WIDTH: usize,
+    const RATE: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>
+    Poseidon2SpongeAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        RATE,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    pub const fn new(
+        constants: RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+        input_bus: usize,
+        output_bus: usize,
+    ) -> Self {
+        assert!(
+            0 < RATE && RATE < WIDTH,
+            "The rate must leave a nonzero capacity"
+        );
+        assert!(HALF_FULL_ROUNDS > 0);
+        Self {
+            air: Poseidon2Air::new(constants),
+            input_bus,
+            output_bus,
+        }
+    }
+
+    pub fn generate_trace_rows(&self, sponges: &[Vec<SpongeOp<F, RATE>>]) -> RowMajorMatrix<F>
+    where
+        F: PrimeField,
+        LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>,
+    {
+        generate_sponge_trace_rows::<
+            _,
+            LinearLayers,
+            WIDTH,
+            RATE,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >(sponges, &self.air.constants)
+    }
+}
+
+impl<
+    F: Field,
+    LinearLayers: Sync,
+    const WIDTH: usize,
+    const RATE: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+> BaseAir<F>
+    for Poseidon2SpongeAir<
+        F,
+        LinearLayers,
+        WIDTH,
+        RATE,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    fn width(&self) -> usize {
+        num_sponge_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>()
+    }
+}
+
+impl<
+    AB: InteractionAirBuilder,
+    LinearLayers: GenericPoseidon2LinearLayers<AB::Expr, WIDTH>,
+    const WIDTH: usize,
+    const RATE: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+> Air<AB>
+    for Poseidon2SpongeAir<
+        AB::F,
+        LinearLayers,
+        WIDTH,
+        RATE,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >
+{
+    #[inline]
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &Poseidon2SpongeCols<
+            AB::Var,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = (*local).borrow();
+        let next: &Poseidon2SpongeCols<
+            AB::Var,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        > = (*next).borrow();
+
+        eval::<_, _, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>(
+            &self.air,
+            builder,
+            &local.perm,
+        );
+        let output = &local.perm.ending_full_rounds[HALF_FULL_ROUNDS - 1].post;
+
+        builder.assert_bools([local.reset, local.absorb, local.perm.export]);
+
+        // A sponge starts from the zero state and absorbs its first block.
+        builder.when_first_row().assert_one(local.reset);
+        builder.when_first_row().assert_zero(local.sponge_id);
+        let mut when_reset = builder.when(local.reset);
+        when_reset.assert_one(local.absorb);
+        when_reset.assert_zero(local.index);
+        for &capacity in &local.perm.inputs[RATE..] {
+            when_reset.assert_zero(capacity);
+        }
+
+        // The next row either starts the next sponge, or continues this one from its output.
+        let mut when_transition = builder.when_transition();
+        when_transition
+            .when(next.reset)
+            .assert_eq(next.sponge_id, local.sponge_id + AB::Expr::ONE);
+        let continues = AB::Expr::ONE - next.reset;
+        let mut when_continues = when_transition.when(continues.clone());
+        when_continues.assert_eq(next.sponge_id, local.sponge_id);
+        when_continues.assert_eq(next.index, local.index + AB::Expr::ONE);
+        for (&input, &out) in next.perm.inputs[RATE..].iter().zip(&output[RATE..]) {
+            when_continues.assert_eq(input, out);
+        }
+        let mut when_squeezes = when_continues.when(AB::Expr::ONE - next.absorb);
+        for (&input, &out) in next.perm.inputs[..RATE].iter().zip(&output[..RATE]) {
+            when_squeezes.assert_eq(input, out);
+        }
+
+        // An output which is absorbed over isn't squeezed, so it can't be exported.
+        when_transition
+            .when(continues * next.absorb)
+            .assert_zero(local.perm.export);
+
+        builder.push_receive(
+            self.input_bus,
+            [local.sponge_id, local.index]
+                .into_iter()
+                .chain(local.perm.inputs[..RATE].iter().copied()),
+            local.absorb,
+        );
+        builder.push_send(
+            self.output_bus,
+            [local.sponge_id, local.index]
+                .into_iter()
+                .chain(output[..RATE].iter().copied()),
+            local.perm.export,
+        );
+    }
+}
+
+/// Generate the trace of the nonempty list `sponges`, each of which must start by absorbing.
+///
+/// Every row whose output is squeezed exports it once. The trace is padded to a power of two by
+/// squeezing the last sponge further, with the padding rows exporting nothing.
+#[instrument(name = "generate Poseidon2 sponge trace", skip_all)]
+pub fn generate_sponge_trace_rows<
+    F: PrimeField,
+    LinearLayers: GenericPoseidon2LinearLayers<F, WIDTH>,
+    const WIDTH: usize,
+    const RATE: usize,
+    const SBOX_DEGREE: u64,
+    const SBOX_REGISTERS: usize,
+    const HALF_FULL_ROUNDS: usize,
+    const PARTIAL_ROUNDS: usize,
+>(
+    sponges: &[Vec<SpongeOp<F, RATE>>],
+    constants: &RoundConstants<F, WIDTH, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
+) -> RowMajorMatrix<F> {
+    assert!(!sponges.is_empty(), "There must be at least one sponge");
+    assert!(
+        sponges
+            .iter()
+            .all(|ops| matches!(ops.first(), Some(SpongeOp::Absorb(_)))),
+        "Every sponge must start by absorbing"
+    );
+
+    let num_ops = sponges.iter().map(Vec::len).sum::<usize>();
+    let n = num_ops.next_power_of_two();
+    let ncols =
+        num_sponge_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>();
+    let mut vec = Vec::with_capacity(n * ncols);
+    let trace = &mut vec.spare_capacity_mut()[..n * ncols];
+
+    let (prefix, mut rows, suffix) = unsafe {
+        trace.align_to_mut::<Poseidon2SpongeCols<
+            MaybeUninit<F>,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >>()
+    };
+    assert!(prefix.is_empty(), "Alignment should match");
+    assert!(suffix.is_empty(), "Alignment should match");
+    assert_eq!(rows.len(), n);
+
+    // Each sponge depends only on itself, so the sponges are generated in parallel. The padding
+    // rows go with the last sponge, which they continue.
+    let mut chunks = Vec::with_capacity(sponges.len());
+    for (i, ops) in sponges.iter().enumerate() {
+        let len = if i + 1 == sponges.len() {
+            rows.len()
+        } else {
+            ops.len()
+        };
+        let (chunk, rest) = mem::take(&mut rows).split_at_mut(len);
+        chunks.push(chunk);
+        rows = rest;
+    }
+
+    chunks
+        .into_par_iter()
+        .zip(sponges)
+        .enumerate()
+        .for_each(|(sponge_id, (rows, ops))| {
+            let mut state = [F::ZERO; WIDTH];
+            for (index, row) in rows.iter_mut().enumerate() {
+                let op = ops.get(index).copied().unwrap_or(SpongeOp::Squeeze);
+                let next_absorbs = matches!(ops.get(index + 1), Some(SpongeOp::Absorb(_)));
+                let absorb = match op {
+                    SpongeOp::Absorb(block) => {
+                        state[..RATE].copy_from_slice(&block);
+                        true
+                    }
+                    SpongeOp::Squeeze => false,
+                };
+
+                row.reset.write(F::from_bool(index == 0));
+                row.absorb.write(F::from_bool(absorb));
+                row.sponge_id.write(F::from_usize(sponge_id));
+                row.index.write(F::from_usize(index));
+                state = generate_trace_rows_for_perm::<
+                    F,
+                    LinearLayers,
+                    WIDTH,
+                    SBOX_DEGREE,
+                    SBOX_REGISTERS,
+                    HALF_FULL_ROUNDS,
+                    PARTIAL_ROUNDS,
+                >(&mut row.perm, state, constants);
+                row.perm
+                    .export
+                    .write(F::from_bool(index < ops.len() && !next_absorbs));
+            }
+        });
+
+    unsafe {
+        vec.set_len(n * ncols);
+    }
+
+    RowMajorMatrix::new(vec, ncols)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::ExtensionMmcs;
+    use p3_dft::Radix2DitParallel;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+    use p3_koala_bear::{GenericPoseidon2LinearLayersKoalaBear, KoalaBear, Poseidon2KoalaBear};
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_poseidon2::ExternalLayerConstants;
+    use p3_symmetric::{CryptographicHasher, PaddingFreeSponge, Permutation, TruncatedPermutation};
+    use p3_uni_stark::{
+        LogUpAir, StarkConfig, VerificationError, check_constraints_report, prove_multi_with_aux,
+        verify_multi_with_aux,
+    };
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    type F = KoalaBear;
+    const WIDTH: usize = 16;
+    const RATE: usize = 8;
+    type SpongeAir =
+        Poseidon2SpongeAir<F, GenericPoseidon2LinearLayersKoalaBear, WIDTH, RATE, 3, 0, 4, 20>;
+    type Cols = Poseidon2SpongeCols<F, WIDTH, 3, 0, 4, 20>;
+
+    fn setup() -> (
+        SpongeAir,
+        Poseidon2KoalaBear<WIDTH>,
+        Vec<Vec<SpongeOp<F, RATE>>>,
+    ) {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let constants = RoundConstants::<F, WIDTH, 4, 20>::from_rng(&mut rng);
+        let perm = Poseidon2KoalaBear::new(
+            ExternalLayerConstants::new(
+                constants.beginning_full_round_constants.to_vec(),
+                constants.ending_full_round_constants.to_vec(),
+            ),
+            constants.partial_round_constants.to_vec(),
+        );
+        let sponges = [1, 3, 2]
+            .into_iter()
+            .map(|num_blocks| {
+                (0..num_blocks)
+                    .map(|_| SpongeOp::Absorb(rng.random()))
+                    .chain([SpongeOp::Squeeze])
+                    .collect()
+            })
+            .collect();
+        (SpongeAir::new(constants, 0, 1), perm, sponges)
+    }
+
+    #[test]
+    fn squeezes_match_padding_free_sponge() {
+        let (air, perm, sponges) = setup();
+        let trace = air.generate_trace_rows(&sponges);
+        assert_eq!(trace.height(), 16);
+
+        let rows: Vec<&Cols> = trace.row_slices().map(Borrow::borrow).collect();
+        let output = |row: &Cols| row.perm.ending_full_rounds[3].post;
+        let exported: Vec<usize> = (0..rows.len())
+            .filter(|&i| rows[i].perm.export == F::ONE)
+            .collect();
+        assert_eq!(exported, [0, 1, 4, 5, 7, 8]);
+
+        // Each sponge exports its digest, then the output of the squeeze which follows it.
+        let hasher = PaddingFreeSponge::<_, WIDTH, RATE, RATE>::new(perm.clone());
+        for (ops, pair) in sponges.iter().zip(exported.chunks_exact(2)) {
+            let blocks = ops.iter().flat_map(|op| match op {
+                SpongeOp::Absorb(block) => block.to_vec(),
+                SpongeOp::Squeeze => vec![],
+            });
+            assert_eq!(output(rows[pair[0]])[..RATE], hasher.hash_iter(blocks));
+            assert_eq!(output(rows[pair[1]]), perm.permute(output(rows[pair[0]])));
+        }
+    }
+
+    #[test]
+    fn constraints_hold_and_bind_chaining() {
+        let (air, _, sponges) = setup();
+        let mut trace = air.generate_trace_rows(&sponges);
+        let report =
+            check_constraints_report::<F, F, _>(&air, None, &trace, None, &[], &[], &vec![]);
+        assert!(report.is_ok());
+
+        // Break the capacity carried into the second row of the second sponge.
+        let cols: &mut Cols = trace.row_mut(3).borrow_mut();
+        cols.perm.inputs[WIDTH - 1] += F::ONE;
+        let report =
+            check_constraints_report::<F, F, _>(&air, None, &trace, None, &[], &[], &vec![]);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "There must be at least one sponge")]
+    fn rejects_no_sponges() {
+        let (air, _, _) = setup();
+        air.generate_trace_rows(&[]);
+    }
+
+    /// The sponge AIR, or a caller sending `(sponge_id, index, block)` on its own `input_bus` and
+    /// receiving `(sponge_id, index, output)` on its own `output_bus`, with one multiplicity
+    /// column for each.
+    enum Chip {
+        Sponge(SpongeAir),
+        Caller { input_bus: usize, output_bus: usize },
+    }
+
+    impl BaseAir<F> for Chip {
+        fn width(&self) -> usize {
+            match self {
+                Self::Sponge(air) => BaseAir::<F>::width(air),
+                Self::Caller { .. } => 2 + RATE + 2,
+            }
+        }
+    }
+
+    impl<AB: InteractionAirBuilder<F = F>> Air<AB> for Chip
+    where
+        SpongeAir: Air<AB>,
+    {
+        fn eval(&self, builder: &mut AB) {
+            match self {
+                Self::Sponge(air) => air.eval(builder),
+                &Self::Caller {
+                    input_bus,
+                    output_bus,
+                } => {
+                    let local = builder.main().row_slice(0).to_vec();
+                    let tuple = local[..2 + RATE].to_vec();
+                    builder.push_send(input_bus, tuple.clone(), local[2 + RATE]);
+                    builder.push_receive(output_bus, tuple, local[2 + RATE + 1]);
+                }
+            }
+        }
+    }
+
+    /// A caller trace with one row per block absorbed by `sponge_trace`, and one per output
+    /// exported by it.
+    fn generate_caller_trace(sponge_trace: &RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let mut values = vec![];
+        for row in sponge_trace.row_slices() {
+            let row: &Cols = (*row).borrow();
+            let output = &row.perm.ending_full_rounds[3].post;
+            if row.absorb == F::ONE {
+                values.extend([row.sponge_id, row.index]);
+                values.extend(&row.perm.inputs[..RATE]);
+                values.extend([F::ONE, F::ZERO]);
+            }
+            if row.perm.export == F::ONE {
+                values.extend([row.sponge_id, row.index]);
+                values.extend(&output[..RATE]);
+                values.extend([F::ZERO, F::ONE]);
+            }
+        }
+        let mut trace = RowMajorMatrix::new(values, 2 + RATE + 2);
+        trace.pad_to_height(trace.height().next_power_of_two(), F::ZERO);
+        trace
+    }
+
+    type Challenge = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2KoalaBear<WIDTH>;
+    type MyHash = PaddingFreeSponge<Perm, WIDTH, RATE, RATE>;
+    type MyCompress = TruncatedPermutation<Perm, 2, RATE, WIDTH>;
+    type ValMmcs =
+        MerkleTreeMmcs<<F as Field>::Packing, <F as Field>::Packing, MyHash, MyCompress, RATE>;
+    type ChallengeMmcs = ExtensionMmcs<F, Challenge, ValMmcs>;
+    type Challenger = DuplexChallenger<F, Perm, WIDTH, RATE>;
+    type Pcs = TwoAdicFriPcs<F, Radix2DitParallel<F>, ValMmcs, ChallengeMmcs>;
+
+    /// Prove the sponges of `setup` alongside `caller`, whose trace is edited by `tamper`.
+    fn prove_and_verify_with_caller(
+        caller: Chip,
+        tamper: impl FnOnce(&mut RowMajorMatrix<F>),
+    ) -> Result<(), VerificationError<impl core::fmt::Debug>> {
+        let (air, perm, sponges) = setup();
+        let sponge_trace = air.generate_trace_rows(&sponges);
+        let mut caller_trace = generate_caller_trace(&sponge_trace);
+        tamper(&mut caller_trace);
+
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+        let fri_config = create_test_fri_config(ChallengeMmcs::new(val_mmcs.clone()), 2);
+        let pcs = Pcs::new(Radix2DitParallel::default(), val_mmcs, fri_config);
+        let config = StarkConfig::<_, Challenge, Challenger>::new(pcs);
+
+        let airs = [LogUpAir::new(Chip::Sponge(air)), LogUpAir::new(caller)];
+        let public_values = vec![vec![]; 2];
+        let proof = prove_multi_with_aux(
+            &config,
+            &airs,
+            &mut Challenger::new(perm.clone()),
+            vec![sponge_trace, caller_trace],
+            &public_values,
+        );
+        verify_multi_with_aux(
+            &config,
+            &airs,
+            &mut Challenger::new(perm),
+            &proof,
+            &public_values,
+        )
+    }
+
+    #[test]
+    fn buses_balance_against_a_caller() {
+        let caller = Chip::Caller {
+            input_bus: 0,
+            output_bus: 1,
+        };
+        prove_and_verify_with_caller(caller, |_| {}).expect("verification failed");
+    }
+
+    #[test]
+    fn buses_reject_a_wrong_output() {
+        let caller = Chip::Caller {
+            input_bus: 0,
+            output_bus: 1,
+        };
+        // The second row of the caller receives the digest of the first sponge.
+        let result = prove_and_verify_with_caller(caller, |trace| trace.row_mut(1)[2] += F::ONE);
+        assert!(matches!(result, Err(VerificationError::UnbalancedAuxSums)));
+    }
+
+    #[test]
+    fn buses_reject_a_mismatched_bus() {
+        let caller = Chip::Caller {
+            input_bus: 0,
+            output_bus: 2,
+        };
+        let result = prove_and_verify_with_caller(caller, |_| {});
+        assert!(matches!(result, Err(VerificationError::UnbalancedAuxSums)));
+    }
+}
@@ -20,9 +20,10 @@ pub use generic::*;
 pub use internal::*;
 use p3_field::{Algebra, InjectiveMonomial, PrimeField, PrimeField64};
 use p3_symmetric::{CryptographicPermutation, Permutation};
-use rand::Rng;
 use rand::distr::{Distribution, StandardUniform};
-pub use round_numbers::poseidon2_round_numbers_128;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoroshiro128Plus;
+pub use round_numbers::{poseidon2_check_round_numbers, poseidon2_round_numbers_128};
 
 const SUPPORTED_WIDTHS: [usize; 8] = [2, 3, 4, 8, 12, 16, 20, 24];
 
@@ -95,6 +96,30 @@ where
             round_numbers.unwrap_or_else(|_| panic!("{}", round_numbers.unwrap_err()));
         Self::new_from_rng(rounds_f, rounds_p, rng)
     }
+
+    /// Create a new Poseidon2 configuration with the given round numbers, after checking them
+    /// with `poseidon2_check_round_numbers` at `security_bits` bits of security.
+    ///
+    /// The round constants are drawn from a `Xoroshiro128Plus` seeded with `seed`, so the same
+    /// seed always gives the same permutation and the constants are easy to reproduce in sage.
+    /// This is intended for experimenting with fields and round numbers for which no optimal
+    /// parameters are listed. The width is still fixed by `WIDTH`, as the state is an array.
+    pub fn new_from_seed(
+        rounds_f: usize,
+        rounds_p: usize,
+        security_bits: usize,
+        seed: u64,
+    ) -> Result<Self, &'static str>
+    where
+        StandardUniform: Distribution<F> + Distribution<[F; WIDTH]>,
+    {
+        if !SUPPORTED_WIDTHS.contains(&WIDTH) {
+            return Err("Unsupported width");
+        }
+        poseidon2_check_round_numbers::<F>(WIDTH, D, rounds_f, rounds_p, security_bits)?;
+        let mut rng = Xoroshiro128Plus::seed_from_u64(seed);
+        Ok(Self::new_from_rng(rounds_f, rounds_p, &mut rng))
+    }
 }
 
 impl<F, A, ExternalPerm, InternalPerm, const WIDTH: usize, const D: u64> Permutation<[A; WIDTH]>
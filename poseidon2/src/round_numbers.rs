@@ -78,3 +78,177 @@ pub const fn poseidon2_round_numbers_128<F: PrimeField64>(
         _ => Err("The optimal parameters for that size of prime have not been computed."),
     }
 }
+
+/// Check that `rounds_f` full and `rounds_p` partial rounds give `security_bits` bits of security
+/// to Poseidon2 over `F` with the given width and S-box degree `d`.
+///
+/// This evaluates the constraints described above directly, rather than looking them up, so it
+/// applies to any 64-bit prime field and width. As in the reference scripts, the rounds must
+/// leave a security margin: the constraints are checked for `rounds_f - 2` full rounds and
+/// `rounds_p / 1.075` partial rounds. The optimal round numbers returned by
+/// [`poseidon2_round_numbers_128`] are the smallest which pass.
+///
+/// An error describing the first constraint which fails is returned.
+pub fn poseidon2_check_round_numbers<F: PrimeField64>(
+    width: usize,
+    d: u64,
+    rounds_f: usize,
+    rounds_p: usize,
+    security_bits: usize,
+) -> Result<(), &'static str> {
+    if !relatively_prime_u64(d, F::ORDER_U64 - 1) {
+        return Err("Invalid permutation: gcd(d, F::ORDER_U64 - 1) must be 1");
+    }
+    if d < 3 || width < 2 {
+        return Err("The S-box degree must be at least 3 and the width at least 2");
+    }
+    if !rounds_f.is_multiple_of(2) || rounds_f < 2 {
+        return Err("The number of full rounds must be even and at least 2");
+    }
+
+    // Strip the security margin.
+    let r_f = (rounds_f - 2) as f64;
+    let r_p = floor(rounds_p as f64 / 1.075);
+
+    let m = security_bits as f64;
+    let t = width as f64;
+    let alpha = d as f64;
+    let log2_p = log2(F::ORDER_U64 as f64);
+    let n = (F::ORDER_U64.ilog2() + 1) as f64;
+    let log2_alpha = log2(alpha);
+
+    // Statistical attacks.
+    let statistical = if m <= floor(log2_p - (alpha - 1.) / 2.) * (t + 1.) {
+        6.
+    } else {
+        10.
+    };
+    if r_f < statistical {
+        return Err("Too few full rounds to resist statistical attacks");
+    }
+
+    // Interpolation and Gröbner basis attacks, which full and partial rounds both defend against.
+    let interpolation = 1. + ceil(m.min(n) / log2_alpha) + ceil(log2(t) / log2_alpha) - r_p;
+    let groebner_1 = m.min(log2_p) / log2_alpha - r_p;
+    let groebner_2 = t - 1. + (m / (t + 1.)).min(log2_p / 2.) / log2_alpha - r_p;
+    let groebner_3 = (t - 2. + m / (2. * log2_alpha) - r_p) / (t - 1.);
+    if r_f < ceil(interpolation) {
+        return Err("Too few rounds to resist interpolation attacks");
+    }
+    if r_f < ceil(groebner_1).max(ceil(groebner_2)).max(ceil(groebner_3)) {
+        return Err("Too few rounds to resist Gröbner basis attacks");
+    }
+
+    // The attack of https://eprint.iacr.org/2023/537.pdf, whose cost is twice the log of a binomial
+    // coefficient.
+    let r = floor(t / 3.);
+    let under = r * (r_f / 2.) + r_p + alpha;
+    let over = (r_f - 1.) * t + r_p + r + under;
+    let log2_binomial: f64 = (1..=under as u64)
+        .map(|i| log2(over - under + i as f64) - log2(i as f64))
+        .sum();
+    if ceil(2. * log2_binomial) < m {
+        return Err("Too few rounds to resist the attack of eprint 2023/537");
+    }
+
+    Ok(())
+}
+
+/// The base 2 logarithm of a positive `x`, which `core` does not provide.
+fn log2(mut x: f64) -> f64 {
+    debug_assert!(x > 0.);
+    let mut res = 0.;
+    while x >= 2. {
+        x /= 2.;
+        res += 1.;
+    }
+    while x < 1. {
+        x *= 2.;
+        res -= 1.;
+    }
+    // Now 1 <= x < 2. Read off the bits of the fractional part, squaring to shift each in turn.
+    let mut bit = 0.5;
+    for _ in 0..f64::MANTISSA_DIGITS {
+        x *= x;
+        if x >= 2. {
+            x /= 2.;
+            res += bit;
+        }
+        bit /= 2.;
+    }
+    res
+}
+
+fn floor(x: f64) -> f64 {
+    let truncated = x as i64 as f64;
+    if truncated > x {
+        truncated - 1.
+    } else {
+        truncated
+    }
+}
+
+fn ceil(x: f64) -> f64 {
+    let truncated = x as i64 as f64;
+    if truncated < x {
+        truncated + 1.
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_goldilocks::Goldilocks;
+    use p3_symmetric::Permutation;
+
+    use super::*;
+
+    #[test]
+    fn check_accepts_exactly_the_optimal_round_numbers() {
+        fn check<F: PrimeField64>(widths: &[usize]) {
+            for &width in widths {
+                for d in [3, 5, 7, 9, 11] {
+                    let Ok((rounds_f, rounds_p)) = poseidon2_round_numbers_128::<F>(width, d)
+                    else {
+                        continue;
+                    };
+                    assert_eq!(
+                        poseidon2_check_round_numbers::<F>(width, d, rounds_f, rounds_p, 128),
+                        Ok(())
+                    );
+                    assert!(
+                        poseidon2_check_round_numbers::<F>(width, d, rounds_f, rounds_p - 1, 128)
+                            .is_err()
+                    );
+                    assert!(
+                        poseidon2_check_round_numbers::<F>(width, d, rounds_f - 2, rounds_p, 128)
+                            .is_err()
+                    );
+                }
+            }
+        }
+        check::<BabyBear>(&[16, 24]);
+        check::<Goldilocks>(&[8, 12, 16]);
+    }
+
+    #[test]
+    fn new_from_seed_checks_rounds() {
+        type Perm = Poseidon2BabyBear<16>;
+        assert!(Perm::new_from_seed(8, 12, 128, 1).is_err());
+        let (a, b) = (
+            Perm::new_from_seed(8, 13, 128, 1).unwrap(),
+            Perm::new_from_seed(8, 13, 128, 1).unwrap(),
+        );
+        let input = BabyBear::new_array(core::array::from_fn(|i| i as u32));
+        assert_eq!(a.permute(input), b.permute(input));
+    }
+
+    #[test]
+    fn log2_is_accurate() {
+        for (x, expected) in [(1., 0.), (8., 3.), (0.25, -2.), (10., 3.321928094887362)] {
+            assert!((log2(x) - expected).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Rescue-Prime instances over BabyBear and Goldilocks, and the hash and compression functions
+//! built from them for use in a `MerkleTreeMmcs`, behind the `baby-bear` and `goldilocks` features.
+
+#[cfg(feature = "baby-bear")]
+use p3_baby_bear::{BabyBear, MdsMatrixBabyBear};
+#[cfg(feature = "goldilocks")]
+use p3_goldilocks::{Goldilocks, MdsMatrixGoldilocks};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+
+use crate::Rescue;
+
+/// Rescue-Prime over BabyBear, whose smallest permutation monomial is `x^7`.
+///
+/// Build it with [`Rescue::new_rescue_prime`] for one of the widths 8, 12 or 16.
+#[cfg(feature = "baby-bear")]
+pub type RescueBabyBear<const WIDTH: usize> = Rescue<BabyBear, MdsMatrixBabyBear, WIDTH, 7>;
+
+/// Rescue-Prime over Goldilocks, whose smallest permutation monomial is `x^7`.
+///
+/// Build it with [`Rescue::new_rescue_prime`] for one of the widths 8, 12 or 16.
+#[cfg(feature = "goldilocks")]
+pub type RescueGoldilocks<const WIDTH: usize> = Rescue<Goldilocks, MdsMatrixGoldilocks, WIDTH, 7>;
+
+/// A sponge hashing BabyBear elements to `OUT` elements with Rescue-Prime.
+#[cfg(feature = "baby-bear")]
+pub type RescueBabyBearHash<const WIDTH: usize, const RATE: usize, const OUT: usize> =
+    PaddingFreeSponge<RescueBabyBear<WIDTH>, WIDTH, RATE, OUT>;
+
+/// A 2-to-1 compression of `CHUNK`-element BabyBear digests with Rescue-Prime.
+#[cfg(feature = "baby-bear")]
+pub type RescueBabyBearCompress<const WIDTH: usize, const CHUNK: usize> =
+    TruncatedPermutation<RescueBabyBear<WIDTH>, 2, CHUNK, WIDTH>;
+
+/// A sponge hashing Goldilocks elements to `OUT` elements with Rescue-Prime.
+#[cfg(feature = "goldilocks")]
+pub type RescueGoldilocksHash<const WIDTH: usize, const RATE: usize, const OUT: usize> =
+    PaddingFreeSponge<RescueGoldilocks<WIDTH>, WIDTH, RATE, OUT>;
+
+/// A 2-to-1 compression of `CHUNK`-element Goldilocks digests with Rescue-Prime.
+#[cfg(feature = "goldilocks")]
+pub type RescueGoldilocksCompress<const WIDTH: usize, const CHUNK: usize> =
+    TruncatedPermutation<RescueGoldilocks<WIDTH>, 2, CHUNK, WIDTH>;
+
+#[cfg(test)]
+mod tests {
+    use core::array;
+
+    use p3_field::PrimeCharacteristicRing;
+    use p3_symmetric::{CryptographicHasher, Permutation, PseudoCompressionFunction};
+
+    use super::*;
+
+    #[cfg(feature = "goldilocks")]
+    #[test]
+    fn round_constants_match_reference() {
+        // The first constants of the reference implementation, whose integers are wider than 64
+        // bits for Goldilocks.
+        let constants = RescueGoldilocks::<8>::get_round_constants_rescue_prime(1, 4, 128);
+        let expected = [
+            5250156239823432273,
+            17991370199276831394,
+            15363758995121189373,
+        ];
+        assert_eq!(constants[..3], expected.map(Goldilocks::from_u64));
+    }
+
+    fn hash_and_compress<F, Perm, const WIDTH: usize, const RATE: usize>(perm: Perm)
+    where
+        F: PrimeCharacteristicRing + Copy + PartialEq + core::fmt::Debug,
+        Perm: Permutation<[F; WIDTH]> + Clone,
+    {
+        let hash = PaddingFreeSponge::<_, WIDTH, RATE, 4>::new(perm.clone());
+        let compress = TruncatedPermutation::<_, 2, 4, WIDTH>::new(perm.clone());
+
+        let input: [F; RATE] = array::from_fn(F::from_usize);
+        let mut state = [F::ZERO; WIDTH];
+        state[..RATE].copy_from_slice(&input);
+        perm.permute_mut(&mut state);
+        assert_eq!(hash.hash_iter(input), state[..4]);
+
+        let (left, right): ([F; 4], [F; 4]) = (array::from_fn(F::from_usize), [F::TWO; 4]);
+        let mut state = [F::ZERO; WIDTH];
+        state[..4].copy_from_slice(&left);
+        state[4..8].copy_from_slice(&right);
+        perm.permute_mut(&mut state);
+        assert_eq!(compress.compress([left, right]), state[..4]);
+    }
+
+    #[cfg(feature = "baby-bear")]
+    #[test]
+    fn baby_bear_widths() {
+        let mds = MdsMatrixBabyBear::default();
+        hash_and_compress::<_, _, 8, 4>(RescueBabyBear::<8>::new_rescue_prime(4, 128, mds.clone()));
+        hash_and_compress::<_, _, 12, 8>(RescueBabyBear::<12>::new_rescue_prime(
+            4,
+            128,
+            mds.clone(),
+        ));
+        hash_and_compress::<_, _, 16, 8>(RescueBabyBear::<16>::new_rescue_prime(8, 128, mds));
+    }
+
+    #[cfg(feature = "goldilocks")]
+    #[test]
+    fn goldilocks_widths() {
+        let mds = MdsMatrixGoldilocks;
+        hash_and_compress::<_, _, 8, 4>(RescueGoldilocks::<8>::new_rescue_prime(
+            4,
+            128,
+            mds.clone(),
+        ));
+        hash_and_compress::<_, _, 12, 8>(RescueGoldilocks::<12>::new_rescue_prime(
+            4,
+            128,
+            mds.clone(),
+        ));
+        hash_and_compress::<_, _, 16, 8>(RescueGoldilocks::<16>::new_rescue_prime(8, 128, mds));
+    }
+}
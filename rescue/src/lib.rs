@@ -1,9 +1,12 @@
-#![allow(dead_code)] // TODO: remove when we settle on implementation details and publicly export
 #![no_std]
 
 extern crate alloc;
 
+#[cfg(any(feature = "baby-bear", feature = "goldilocks"))]
+mod hash;
 mod rescue;
 mod util;
 
+#[cfg(any(feature = "baby-bear", feature = "goldilocks"))]
+pub use hash::*;
 pub use rescue::*;
@@ -74,7 +74,24 @@ where
             .collect()
     }
 
-    fn get_round_constants_rescue_prime(
+    /// The Rescue-Prime permutation for a sponge of capacity `capacity` and `sec_level` bits of
+    /// security, with the number of rounds and the round constants the Rescue-Prime paper derives
+    /// from those parameters.
+    pub fn new_rescue_prime(capacity: usize, sec_level: usize, mds: Mds) -> Self
+    where
+        F: PrimeField64,
+    {
+        let num_rounds = Self::num_rounds(capacity, sec_level);
+        let round_constants =
+            Self::get_round_constants_rescue_prime(num_rounds, capacity, sec_level);
+        Self::new(num_rounds, round_constants, mds)
+    }
+
+    /// The round constants of Rescue-Prime, read from SHAKE256 seeded with the parameters.
+    ///
+    /// Each constant is a little-endian integer of one byte more than the field's bit length
+    /// rounds up to, reduced modulo the field order.
+    pub fn get_round_constants_rescue_prime(
         num_rounds: usize,
         capacity: usize,
         sec_level: usize,
@@ -104,8 +121,8 @@ where
                     .collect_vec()
                     .iter()
                     .rev()
-                    .fold(0, |acc, &byte| (acc << 8) + *byte as u64);
-                F::from_u64(integer)
+                    .fold(0, |acc, &byte| (acc << 8) + *byte as u128);
+                F::from_u128(integer)
             })
             .collect()
     }
@@ -15,7 +15,7 @@ pub trait CompressionFunction<T, const N: usize>: PseudoCompressionFunction<T, N
 
 #[derive(Clone, Debug)]
 pub struct TruncatedPermutation<InnerP, const N: usize, const CHUNK: usize, const WIDTH: usize> {
-    inner_permutation: InnerP,
+    pub(crate) inner_permutation: InnerP,
 }
 
 impl<InnerP, const N: usize, const CHUNK: usize, const WIDTH: usize>
@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+
+use crate::compression::{CompressionFunction, PseudoCompressionFunction, TruncatedPermutation};
+use crate::hasher::CryptographicHasher;
+use crate::permutation::CryptographicPermutation;
+
+/// A hasher which prepends a fixed tag to everything it hashes, so that hashes made for
+/// different purposes, e.g. Merkle leaves, Merkle nodes and transcript absorptions, cannot
+/// collide even when they share the inner hasher.
+///
+/// The tag is given as elements of `Tag`, which are converted into whatever the inner hasher
+/// consumes, so the same tag works for a field and its packing. Tags used together should have
+/// the same length, as otherwise one tag followed by some input may equal another tag followed
+/// by a different input.
+///
+/// It is also a compression function which hashes the tag followed by its inputs, so one inner
+/// hasher with two tags can serve as both the leaf hasher and the compression of a Merkle tree.
+/// To tag a compression built from a permutation, such as `TruncatedPermutation`, use
+/// `DomainSeparatedCompression`, which keeps its single permutation call.
+#[derive(Clone, Debug)]
+pub struct DomainSeparatedHasher<H, Tag> {
+    inner: H,
+    tag: Vec<Tag>,
+}
+
+impl<H, Tag> DomainSeparatedHasher<H, Tag> {
+    pub const fn new(inner: H, tag: Vec<Tag>) -> Self {
+        Self { inner, tag }
+    }
+
+    /// The tag prepended to every input.
+    pub fn tag(&self) -> &[Tag] {
+        &self.tag
+    }
+}
+
+impl<Item, Out, H, Tag> CryptographicHasher<Item, Out> for DomainSeparatedHasher<H, Tag>
+where
+    Item: Clone + From<Tag>,
+    H: CryptographicHasher<Item, Out>,
+    Tag: Copy,
+{
+    fn hash_iter<I>(&self, input: I) -> Out
+    where
+        I: IntoIterator<Item = Item>,
+    {
+        self.inner
+            .hash_iter(self.tag.iter().map(|&t| Item::from(t)).chain(input))
+    }
+}
+
+impl<T, H, Tag, const CHUNK: usize, const N: usize> PseudoCompressionFunction<[T; CHUNK], N>
+    for DomainSeparatedHasher<H, Tag>
+where
+    T: Clone + From<Tag>,
+    H: CryptographicHasher<T, [T; CHUNK]>,
+    Tag: Copy,
+{
+    fn compress(&self, input: [[T; CHUNK]; N]) -> [T; CHUNK] {
+        self.hash_iter(input.into_iter().flatten())
+    }
+}
+
+impl<T, H, Tag, const CHUNK: usize, const N: usize> CompressionFunction<[T; CHUNK], N>
+    for DomainSeparatedHasher<H, Tag>
+where
+    T: Clone + From<Tag>,
+    H: CryptographicHasher<T, [T; CHUNK]>,
+    Tag: Copy,
+{
+}
+
+/// A compression function which writes a fixed tag into the part of the state of an inner
+/// compression its inputs leave unused, e.g. the capacity of a `TruncatedPermutation`, so
+/// compressions made for different purposes cannot collide. Unlike prepending the tag with a
+/// `DomainSeparatedHasher`, this costs nothing: the compression still makes one permutation call.
+///
+/// As with `DomainSeparatedHasher`, the tag is given as elements of `Tag`, which are converted
+/// into the elements the inner permutation acts on.
+#[derive(Clone, Debug)]
+pub struct DomainSeparatedCompression<C, Tag> {
+    inner: C,
+    tag: Vec<Tag>,
+}
+
+impl<InnerP, Tag, const N: usize, const CHUNK: usize, const WIDTH: usize>
+    DomainSeparatedCompression<TruncatedPermutation<InnerP, N, CHUNK, WIDTH>, Tag>
+{
+    /// Tag `inner`, whose state must have room for `tag` after the `N` inputs.
+    pub fn new(inner: TruncatedPermutation<InnerP, N, CHUNK, WIDTH>, tag: Vec<Tag>) -> Self {
+        assert!(
+            N * CHUNK + tag.len() <= WIDTH,
+            "The tag doesn't fit in the capacity of the compression."
+        );
+        Self { inner, tag }
+    }
+}
+
+impl<C, Tag> DomainSeparatedCompression<C, Tag> {
+    /// The tag written into the state of every compression.
+    pub fn tag(&self) -> &[Tag] {
+        &self.tag
+    }
+}
+
+impl<T, InnerP, Tag, const N: usize, const CHUNK: usize, const WIDTH: usize>
+    PseudoCompressionFunction<[T; CHUNK], N>
+    for DomainSeparatedCompression<TruncatedPermutation<InnerP, N, CHUNK, WIDTH>, Tag>
+where
+    T: Copy + Default + From<Tag>,
+    InnerP: CryptographicPermutation<[T; WIDTH]>,
+    Tag: Copy,
+{
+    fn compress(&self, input: [[T; CHUNK]; N]) -> [T; CHUNK] {
+        let mut pre = [T::default(); WIDTH];
+        for i in 0..N {
+            pre[i * CHUNK..(i + 1) * CHUNK].copy_from_slice(&input[i]);
+        }
+        for (x, &t) in pre[N * CHUNK..].iter_mut().zip(&self.tag) {
+            *x = T::from(t);
+        }
+        let post = self.inner.inner_permutation.permute(pre);
+        post[..CHUNK].try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::Permutation;
+
+    /// A position-dependent sum, so that prepending a tag changes the output.
+    #[derive(Clone)]
+    struct MockHasher;
+
+    impl CryptographicHasher<u64, [u64; 2]> for MockHasher {
+        fn hash_iter<I>(&self, input: I) -> [u64; 2]
+        where
+            I: IntoIterator<Item = u64>,
+        {
+            input.into_iter().zip(1..).fold([0, 0], |[a, b], (x, i)| {
+                [a.wrapping_add(x), b.wrapping_add(x.wrapping_mul(i))]
+            })
+        }
+    }
+
+    #[test]
+    fn prepends_tag() {
+        let leaf = DomainSeparatedHasher::new(MockHasher, vec![0u32, 1]);
+        let node = DomainSeparatedHasher::new(MockHasher, vec![0u32, 2]);
+
+        let input = [5u64, 6, 7];
+        assert_eq!(
+            leaf.hash_slice(&input),
+            MockHasher.hash_slice(&[0, 1, 5, 6, 7])
+        );
+        assert_eq!(
+            node.compress([[5u64, 6], [7, 8]]),
+            MockHasher.hash_slice(&[0, 2, 5, 6, 7, 8])
+        );
+        assert_ne!(
+            leaf.hash_slice(&[5u64, 6, 7, 8]),
+            node.compress([[5, 6], [7, 8]])
+        );
+    }
+
+    /// Scales each element by its position plus one, then reverses the state.
+    #[derive(Clone)]
+    struct MockPermutation;
+
+    impl Permutation<[u64; 8]> for MockPermutation {
+        fn permute_mut(&self, input: &mut [u64; 8]) {
+            input.iter_mut().zip(1..).for_each(|(x, i)| *x *= i);
+            input.reverse();
+        }
+    }
+
+    impl CryptographicPermutation<[u64; 8]> for MockPermutation {}
+
+    #[test]
+    fn writes_tag_into_capacity() {
+        let plain = TruncatedPermutation::<MockPermutation, 2, 2, 8>::new(MockPermutation);
+        let leaf = DomainSeparatedCompression::new(plain.clone(), vec![0u32, 0, 0, 1]);
+        let node = DomainSeparatedCompression::new(plain, vec![0u32, 0, 0, 2]);
+
+        let input = [[5u64, 6], [7, 8]];
+        let expected = |tag: u64| {
+            let post = MockPermutation.permute([5, 6, 7, 8, 0, 0, 0, tag]);
+            [post[0], post[1]]
+        };
+        assert_eq!(leaf.compress(input), expected(1));
+        assert_eq!(node.compress(input), expected(2));
+        assert_ne!(leaf.compress(input), node.compress(input));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in the capacity")]
+    fn rejects_tag_longer_than_capacity() {
+        let plain = TruncatedPermutation::<MockPermutation, 2, 2, 8>::new(MockPermutation);
+        let _ = DomainSeparatedCompression::new(plain, vec![0u32; 5]);
+    }
+}
@@ -5,15 +5,19 @@
 extern crate alloc;
 
 mod compression;
+mod domain_separation;
 mod hash;
 mod hasher;
 mod permutation;
 mod serializing_hasher;
 mod sponge;
+mod sponge_state;
 
 pub use compression::*;
+pub use domain_separation::*;
 pub use hash::*;
 pub use hasher::*;
 pub use permutation::*;
 pub use serializing_hasher::*;
 pub use sponge::*;
+pub use sponge_state::*;
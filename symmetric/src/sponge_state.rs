@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+
+use crate::permutation::CryptographicPermutation;
+
+/// Whether the sponge is taking input or giving output, and how far into the rate it has got.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SpongeMode {
+    Absorbing(usize),
+    Squeezing(usize),
+}
+
+/// The state of a padding-free, overwrite-mode sponge, which is fed and read incrementally.
+///
+/// Absorbing a sequence of inputs, in any number of calls, and then squeezing at most `RATE`
+/// elements gives the same output as `PaddingFreeSponge` on the whole sequence. Absorbing after
+/// squeezing, or squeezing more than `RATE` elements at once, keeps going from where the sponge
+/// left off, so this can also back a challenger or a transcript.
+///
+/// `WIDTH` is the sponge's rate plus the sponge's capacity.
+#[derive(Clone, Debug)]
+pub struct SpongeState<T, P, const WIDTH: usize, const RATE: usize> {
+    permutation: P,
+    state: [T; WIDTH],
+    mode: SpongeMode,
+}
+
+impl<T, P, const WIDTH: usize, const RATE: usize> SpongeState<T, P, WIDTH, RATE>
+where
+    T: Default + Copy,
+    P: CryptographicPermutation<[T; WIDTH]>,
+{
+    pub fn new(permutation: P) -> Self {
+        const { assert!(RATE > 0 && RATE < WIDTH) };
+        Self {
+            permutation,
+            state: [T::default(); WIDTH],
+            mode: SpongeMode::Absorbing(0),
+        }
+    }
+
+    /// Absorb `input`, overwriting the rate and permuting each time it is full.
+    pub fn absorb(&mut self, input: &[T]) {
+        if input.is_empty() {
+            return;
+        }
+        let mut pos = match self.mode {
+            SpongeMode::Absorbing(pos) => pos,
+            // Squeezed outputs are overwritten by the new input, which the next squeeze permutes.
+            SpongeMode::Squeezing(_) => 0,
+        };
+        for &x in input {
+            self.state[pos] = x;
+            pos += 1;
+            if pos == RATE {
+                self.permutation.permute_mut(&mut self.state);
+                pos = 0;
+            }
+        }
+        self.mode = SpongeMode::Absorbing(pos);
+    }
+
+    /// Squeeze `n` elements out of the rate, permuting each time it is used up.
+    pub fn squeeze(&mut self, n: usize) -> Vec<T> {
+        let mut pos = self.start_squeezing();
+        let output = (0..n)
+            .map(|_| {
+                if pos == RATE {
+                    self.permutation.permute_mut(&mut self.state);
+                    pos = 0;
+                }
+                pos += 1;
+                self.state[pos - 1]
+            })
+            .collect();
+        self.mode = SpongeMode::Squeezing(pos);
+        output
+    }
+
+    /// Overwrite the start of the rate with `input`, permute, and return the new rate.
+    ///
+    /// Any input absorbed but not yet permuted is permuted first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is longer than `RATE`.
+    pub fn duplex(&mut self, input: &[T]) -> [T; RATE] {
+        assert!(input.len() <= RATE, "Duplex input is longer than the rate.");
+        self.start_squeezing();
+        self.state[..input.len()].copy_from_slice(input);
+        self.permutation.permute_mut(&mut self.state);
+        // The returned rate counts as squeezed.
+        self.mode = SpongeMode::Squeezing(RATE);
+        self.state[..RATE].try_into().unwrap()
+    }
+
+    /// The full state of the sponge, rate first.
+    pub const fn state(&self) -> &[T; WIDTH] {
+        &self.state
+    }
+
+    /// Permute any partial block of input, and return how much of the rate has been squeezed.
+    fn start_squeezing(&mut self) -> usize {
+        match self.mode {
+            SpongeMode::Absorbing(0) => 0,
+            SpongeMode::Absorbing(_) => {
+                self.permutation.permute_mut(&mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(pos) => pos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{CryptographicHasher, PaddingFreeSponge, Permutation};
+
+    /// Shifts the state by one and adds a distinct multiple of each element, so that the position
+    /// of every input matters.
+    #[derive(Clone)]
+    struct MockPermutation;
+
+    impl<const WIDTH: usize> Permutation<[u64; WIDTH]> for MockPermutation {
+        fn permute_mut(&self, input: &mut [u64; WIDTH]) {
+            let sum = (input.iter().zip(1u64..))
+                .fold(0u64, |acc, (x, i)| acc.wrapping_add(x.wrapping_mul(i)));
+            input.rotate_right(1);
+            input[0] = input[0].wrapping_add(sum);
+        }
+    }
+
+    impl<const WIDTH: usize> CryptographicPermutation<[u64; WIDTH]> for MockPermutation {}
+
+    type State = SpongeState<u64, MockPermutation, 6, 4>;
+
+    #[test]
+    fn matches_padding_free_sponge() {
+        let sponge = PaddingFreeSponge::<_, 6, 4, 3>::new(MockPermutation);
+        for len in 0..11 {
+            let input: Vec<u64> = (1..=len).collect();
+            for split in 0..=input.len() {
+                let mut state = State::new(MockPermutation);
+                state.absorb(&input[..split]);
+                state.absorb(&input[split..]);
+                assert_eq!(state.squeeze(3), sponge.hash_iter(input.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn squeezes_continue() {
+        let mut state = State::new(MockPermutation);
+        state.absorb(&[1, 2, 3]);
+        let mut split = State::new(MockPermutation);
+        split.absorb(&[1, 2, 3]);
+
+        // Squeezing past the rate permutes, however the output is requested.
+        let output = state.squeeze(10);
+        let mut split_output = split.squeeze(3);
+        split_output.extend(split.squeeze(7));
+        assert_eq!(output, split_output);
+        assert_ne!(output[..4], output[4..8]);
+
+        // Absorbing again gives fresh output.
+        state.absorb(&[4]);
+        assert_ne!(state.squeeze(1), output[8..9]);
+    }
+
+    #[test]
+    fn duplex_overwrites_and_permutes() {
+        let mut state = State::new(MockPermutation);
+        state.absorb(&[1, 2]);
+        let rate = state.duplex(&[5, 6, 7]);
+
+        let mut expected = [0; 6];
+        expected[..2].copy_from_slice(&[1, 2]);
+        MockPermutation.permute_mut(&mut expected);
+        expected[..3].copy_from_slice(&[5, 6, 7]);
+        MockPermutation.permute_mut(&mut expected);
+        assert_eq!(rate, expected[..4]);
+
+        // The duplexed rate has been used up, so squeezing permutes again.
+        MockPermutation.permute_mut(&mut expected);
+        assert_eq!(state.squeeze(2), vec![expected[0], expected[1]]);
+    }
+}
@@ -0,0 +1,108 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_benchmark_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, prove};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+const WIDTH: usize = 32;
+
+/// Each column holds the cube of its left neighbour, and the first column of the next row is
+/// `x^3 + x` for the first column `x` of this row, recomputing a cube the constraints already use.
+struct CubeChainAir;
+
+impl<F> BaseAir<F> for CubeChainAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for CubeChainAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        for i in 1..WIDTH {
+            let prev: AB::Expr = local[i - 1].into();
+            builder.assert_eq(local[i], prev.cube());
+        }
+        let first: AB::Expr = local[0].into();
+        builder
+            .when_transition()
+            .assert_eq(next[0], first.clone().cube() + first);
+    }
+}
+
+fn generate_trace(log_height: usize) -> RowMajorMatrix<Val> {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let height = 1 << log_height;
+    let mut values = Vec::with_capacity(height * WIDTH);
+    let mut first: Val = rng.random();
+    for _ in 0..height {
+        let row_start = values.len();
+        values.push(first);
+        for i in 1..WIDTH {
+            values.push(values[row_start + i - 1].cube());
+        }
+        first = values[row_start + 1] + values[row_start];
+    }
+    RowMajorMatrix::new(values, WIDTH)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn bench_constraint_eval(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let config = || {
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let val_mmcs = ValMmcs::new(hash, compress);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = create_benchmark_fri_config(challenge_mmcs);
+        MyConfig::new(Pcs::new(Dft::default(), val_mmcs, fri_config))
+    };
+
+    let mut group = c.benchmark_group("prove_cube_chain");
+    group.sample_size(10);
+    for log_height in [12, 14, 16] {
+        for (name, config) in [
+            ("air_eval", config()),
+            ("constraint_dag", config().with_constraint_dag()),
+        ] {
+            group.bench_function(BenchmarkId::new(name, 1 << log_height), |b| {
+                b.iter_batched(
+                    || generate_trace(log_height),
+                    |trace| {
+                        let mut challenger = Challenger::new(perm.clone());
+                        prove(&config, &CubeChainAir, &mut challenger, trace, &vec![])
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+}
+
+criterion_group!(benches, bench_constraint_eval);
+criterion_main!(benches);
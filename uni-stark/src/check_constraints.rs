@@ -1,63 +1,258 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
-use p3_field::Field;
+use itertools::Itertools;
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, ExtensionBuilder, InteractionAirBuilder,
+    PairBuilder, PeriodicAirBuilder, PermutationAirBuilder, RowAirBuilder,
+};
+use p3_field::{ExtensionField, Field};
 use p3_matrix::Matrix;
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::stack::VerticalPair;
 use tracing::instrument;
 
-#[instrument(name = "check constraints", skip_all)]
+use crate::{SymbolicAirBuilder, SymbolicExpression, get_symbolic_constraints_with_aux};
+
 pub(crate) fn check_constraints<F, A>(air: &A, main: &RowMajorMatrix<F>, public_values: &Vec<F>)
 where
     F: Field,
-    A: for<'a> Air<DebugConstraintBuilder<'a, F>>,
+    A: BaseAir<F> + for<'a> Air<DebugConstraintBuilder<'a, F>>,
+{
+    check_constraints_with_aux::<F, F, A>(air, None, main, None, &[], &[], public_values);
+}
+
+/// Like `check_constraints`, but also exposes the given preprocessed trace, auxiliary trace,
+/// challenges and sums to the AIR.
+#[instrument(name = "check constraints", skip_all)]
+pub(crate) fn check_constraints_with_aux<F, EF, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    aux: Option<&RowMajorMatrix<EF>>,
+    aux_challenges: &[EF],
+    aux_sums: &[EF],
+    public_values: &Vec<F>,
+) where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: BaseAir<F> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
 {
+    eval_constraints(
+        air,
+        preprocessed,
+        main,
+        aux,
+        aux_challenges,
+        aux_sums,
+        public_values,
+        false,
+    );
+}
+
+/// A constraint which didn't vanish on some row of the trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure<EF> {
+    pub row: usize,
+    /// The position of the constraint in the order the AIR asserts them, which is also its index
+    /// in `get_symbolic_constraints`.
+    pub constraint_index: usize,
+    pub value: EF,
+}
+
+/// Every constraint failure found by `check_constraints_report`, in row order.
+#[derive(Clone, Debug)]
+pub struct ConstraintReport<F: Field, EF> {
+    pub failures: Vec<ConstraintFailure<EF>>,
+    constraints: Vec<SymbolicExpression<F>>,
+}
+
+impl<F: Field, EF> ConstraintReport<F, EF> {
+    /// Whether every constraint vanished on every row.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The symbolic expression of the constraint with the given index.
+    pub fn expression(&self, constraint_index: usize) -> &SymbolicExpression<F> {
+        &self.constraints[constraint_index]
+    }
+}
+
+/// Evaluate every constraint of `air` on every row of the trace and report each one which didn't
+/// vanish, rather than panicking at the first.
+#[instrument(name = "check constraints", skip_all)]
+pub fn check_constraints_report<F, EF, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    aux: Option<&RowMajorMatrix<EF>>,
+    aux_challenges: &[EF],
+    aux_sums: &[EF],
+    public_values: &Vec<F>,
+) -> ConstraintReport<F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: BaseAir<F> + Air<SymbolicAirBuilder<F>> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
+{
+    let failures = eval_constraints(
+        air,
+        preprocessed,
+        main,
+        aux,
+        aux_challenges,
+        aux_sums,
+        public_values,
+        true,
+    );
+    let constraints = get_symbolic_constraints_with_aux(
+        air,
+        preprocessed.map_or(0, |preprocessed| preprocessed.width()),
+        aux.map_or(0, |aux| aux.width()),
+        aux_challenges.len(),
+        aux_sums.len(),
+        public_values.len(),
+    );
+    ConstraintReport {
+        failures,
+        constraints,
+    }
+}
+
+/// Evaluate the constraints on every row, either collecting the failures or panicking at the
+/// first.
+fn eval_constraints<F, EF, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    aux: Option<&RowMajorMatrix<EF>>,
+    aux_challenges: &[EF],
+    aux_sums: &[EF],
+    public_values: &Vec<F>,
+    collect_failures: bool,
+) -> Vec<ConstraintFailure<EF>>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: BaseAir<F> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
+{
+    let mut failures = Vec::new();
     let height = main.height();
+    let window_size = <A as BaseAir<F>>::window_size(air);
+    let periodic_columns = <A as BaseAir<F>>::periodic_columns(air);
 
-    (0..height).for_each(|i| {
+    for i in 0..height {
         let i_next = (i + 1) % height;
 
-        let local = main.row_slice(i);
-        let next = main.row_slice(i_next);
-        let main = VerticalPair::new(
-            RowMajorMatrixView::new_row(&*local),
-            RowMajorMatrixView::new_row(&*next),
+        let main_rows = (0..window_size)
+            .flat_map(|offset| main.row_slice((i + offset) % height).to_vec())
+            .collect_vec();
+        let main = RowMajorMatrixView::new(&main_rows, main.width());
+
+        let preprocessed_rows = preprocessed.map_or(vec![], |preprocessed| {
+            [i, i_next]
+                .into_iter()
+                .flat_map(|row| preprocessed.row_slice(row).to_vec())
+                .collect()
+        });
+        let preprocessed = RowMajorMatrixView::new(
+            &preprocessed_rows,
+            preprocessed.map_or(0, |preprocessed| preprocessed.width()),
+        );
+
+        let aux_rows = aux.map(|aux| (aux.row_slice(i), aux.row_slice(i_next)));
+        let (aux_local, aux_next): (&[EF], &[EF]) = aux_rows
+            .as_ref()
+            .map_or((&[], &[]), |(local, next)| (&**local, &**next));
+        let aux = VerticalPair::new(
+            RowMajorMatrixView::new_row(aux_local),
+            RowMajorMatrixView::new_row(aux_next),
         );
 
         let mut builder = DebugConstraintBuilder {
             row_index: i,
+            height,
+            window_size,
+            preprocessed,
             main,
+            aux,
+            aux_challenges,
+            aux_sums,
+            periodic_values: periodic_columns
+                .iter()
+                .map(|column| column[i % column.len()])
+                .collect(),
             public_values,
             is_first_row: F::from_bool(i == 0),
             is_last_row: F::from_bool(i == height - 1),
-            is_transition: F::from_bool(i != height - 1),
+            constraint_index: 0,
+            failures: collect_failures.then(Vec::new),
         };
 
         air.eval(&mut builder);
-    });
+        failures.extend(
+            builder
+                .failures
+                .into_iter()
+                .flatten()
+                .map(|(constraint_index, value)| ConstraintFailure {
+                    row: i,
+                    constraint_index,
+                    value,
+                }),
+        );
+    }
+    failures
 }
 
 /// An `AirBuilder` which asserts that each constraint is zero, allowing any failed constraints to
-/// be detected early.
+/// be detected early, or which records the failed constraints for `check_constraints_report`.
 #[derive(Debug)]
-pub struct DebugConstraintBuilder<'a, F: Field> {
+pub struct DebugConstraintBuilder<'a, F: Field, EF: ExtensionField<F> = F> {
     row_index: usize,
-    main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    height: usize,
+    window_size: usize,
+    preprocessed: RowMajorMatrixView<'a, F>,
+    main: RowMajorMatrixView<'a, F>,
+    aux: VerticalPair<RowMajorMatrixView<'a, EF>, RowMajorMatrixView<'a, EF>>,
+    aux_challenges: &'a [EF],
+    aux_sums: &'a [EF],
+    periodic_values: Vec<F>,
     public_values: &'a [F],
     is_first_row: F,
     is_last_row: F,
-    is_transition: F,
+    /// The index of the next constraint to be asserted.
+    constraint_index: usize,
+    /// The index and value of each nonzero constraint, if recording rather than asserting.
+    failures: Option<Vec<(usize, EF)>>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> DebugConstraintBuilder<'_, F, EF> {
+    /// Record the value of the next constraint if we're collecting failures, returning whether we
+    /// are.
+    fn record(&mut self, value: EF) -> bool {
+        let index = self.constraint_index;
+        self.constraint_index += 1;
+        let Some(failures) = &mut self.failures else {
+            return false;
+        };
+        if !value.is_zero() {
+            failures.push((index, value));
+        }
+        true
+    }
 }
 
-impl<'a, F> AirBuilder for DebugConstraintBuilder<'a, F>
+impl<'a, F, EF> AirBuilder for DebugConstraintBuilder<'a, F, EF>
 where
     F: Field,
+    EF: ExtensionField<F>,
 {
     type F = F;
     type Expr = F;
     type Var = F;
-    type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+    type M = RowMajorMatrixView<'a, F>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -72,18 +267,22 @@ where
     }
 
     /// # Panics
-    /// This function panics if `size` is not `2`.
+    /// This function panics if `size` is not between `2` and the AIR's window size.
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            self.is_transition
-        } else {
-            panic!("only supports a window size of 2")
-        }
+        assert!(
+            (2..=self.window_size).contains(&size),
+            "window size {size} is not supported by this AIR"
+        );
+        F::from_bool(self.row_index + size <= self.height)
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        let x: F = x.into();
+        if self.record(x.into()) {
+            return;
+        }
         assert_eq!(
-            x.into(),
+            x,
             F::ZERO,
             "constraints had nonzero value on row {}",
             self.row_index
@@ -93,6 +292,9 @@ where
     fn assert_eq<I1: Into<Self::Expr>, I2: Into<Self::Expr>>(&mut self, x: I1, y: I2) {
         let x = x.into();
         let y = y.into();
+        if self.record((x - y).into()) {
+            return;
+        }
         assert_eq!(
             x, y,
             "values didn't match on row {}: {} != {}",
@@ -101,10 +303,87 @@ where
     }
 }
 
-impl<F: Field> AirBuilderWithPublicValues for DebugConstraintBuilder<'_, F> {
+impl<F: Field, EF: ExtensionField<F>> AirBuilderWithPublicValues
+    for DebugConstraintBuilder<'_, F, EF>
+{
     type PublicVar = Self::F;
 
     fn public_values(&self) -> &[Self::F] {
         self.public_values
     }
 }
+
+impl<F: Field, EF: ExtensionField<F>> PairBuilder for DebugConstraintBuilder<'_, F, EF> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> RowAirBuilder for DebugConstraintBuilder<'_, F, EF> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        F::from_bool(self.row_index == row % self.height)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> PeriodicAirBuilder for DebugConstraintBuilder<'_, F, EF> {
+    type PeriodicVar = F;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> ExtensionBuilder for DebugConstraintBuilder<'_, F, EF> {
+    type EF = EF;
+    type ExprEF = EF;
+    type VarEF = EF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        let x: EF = x.into();
+        if self.record(x) {
+            return;
+        }
+        assert_eq!(
+            x,
+            EF::ZERO,
+            "constraints had nonzero value on row {}",
+            self.row_index
+        );
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> PermutationAirBuilder
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    type MP = VerticalPair<RowMajorMatrixView<'a, EF>, RowMajorMatrixView<'a, EF>>;
+    type RandomVar = EF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.aux_challenges
+    }
+
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        self.aux_sums
+    }
+}
+
+/// Interactions are only balanced across whole traces, so no row can be checked on its own.
+impl<F: Field, EF: ExtensionField<F>> InteractionAirBuilder for DebugConstraintBuilder<'_, F, EF> {
+    fn push_send<I, M>(
+        &mut self,
+        _bus_id: usize,
+        _fields: impl IntoIterator<Item = I>,
+        _multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+    }
+}
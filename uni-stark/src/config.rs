@@ -3,6 +3,7 @@ use core::marker::PhantomData;
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{ExtensionField, Field};
+use p3_util::log2_ceil_usize;
 
 pub type PcsError<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
@@ -34,11 +35,82 @@ pub trait StarkGenericConfig {
         + CanSample<Self::Challenge>;
 
     fn pcs(&self) -> &Self::Pcs;
+
+    /// The log2 of the number of chunks to split the quotient polynomial into, given the maximum
+    /// degree multiple of the AIR's constraints.
+    ///
+    /// The quotient is evaluated on a coset of this many times the trace height, which must not
+    /// exceed the size of the PCS's low-degree extension. The default is the smallest split which
+    /// fits the quotient; see `log_quotient_degree_for`.
+    fn log_quotient_degree(&self, constraint_degree: usize) -> usize {
+        log_quotient_degree_for(constraint_degree)
+    }
+
+    /// Whether proofs should be zero-knowledge.
+    ///
+    /// If so, the trace and any auxiliary segments are committed with `Pcs::commit_zk`, which
+    /// randomizes them into polynomials of twice the degree, so that the values opened outside the
+    /// trace domain are masked. This roughly doubles the degree of the quotient, which is itself
+    /// masked by a random polynomial committed alongside the trace. The PCS must
+    /// support this, and should also hide what it reveals about the committed polynomials itself,
+    /// e.g. with hiding MMCSs.
+    fn is_zk(&self) -> bool {
+        false
+    }
+
+    /// Whether the prover should evaluate the constraints from their `SymbolicDag`, rather than by
+    /// running `Air::eval` on every packed chunk of the quotient domain.
+    ///
+    /// Both give the same quotient; which is faster depends on the AIR.
+    fn evaluates_constraint_dag(&self) -> bool {
+        false
+    }
+}
+
+/// The log2 of the number of quotient chunks `config` uses for constraints of the given maximum
+/// degree multiple, accounting for the randomized trace in zero-knowledge mode.
+pub(crate) fn effective_log_quotient_degree<SC: StarkGenericConfig>(
+    config: &SC,
+    constraint_degree: usize,
+) -> usize {
+    if config.is_zk() {
+        config.log_quotient_degree(2 * constraint_degree)
+    } else {
+        config.log_quotient_degree(constraint_degree)
+    }
+}
+
+/// In zero-knowledge mode, the domain of the zero matrix which `Pcs::commit_zk` randomizes into
+/// the polynomial masking the quotient over `quotient_domain`. Randomizing doubles its degree, so
+/// the mask has degree below the size of `quotient_domain`, as the quotient does.
+pub(crate) fn quotient_mask_domain<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_domain: &Domain<SC>,
+) -> Domain<SC> {
+    config
+        .pcs()
+        .natural_domain_for_degree(quotient_domain.size() / 2)
+}
+
+/// The log2 of the smallest power-of-two number of chunks a quotient polynomial can be split into,
+/// given the maximum degree multiple of the constraints.
+pub fn log_quotient_degree_for(constraint_degree: usize) -> usize {
+    // We pad to at least degree 2, since a quotient argument doesn't make sense with smaller degrees.
+    // The quotient's actual degree is approximately (max_constraint_degree - 1) n,
+    // where subtracting 1 comes from division by the vanishing polynomial.
+    // But we pad it to a power of two so that we can efficiently decompose the quotient.
+    log2_ceil_usize(constraint_degree.max(2) - 1)
 }
 
 #[derive(Debug)]
 pub struct StarkConfig<Pcs, Challenge, Challenger> {
     pcs: Pcs,
+    /// The quotient is split into at least `2^min_log_quotient_degree` chunks.
+    min_log_quotient_degree: usize,
+    /// Whether proofs are zero-knowledge.
+    zk: bool,
+    /// Whether the prover evaluates the constraints from their `SymbolicDag`.
+    constraint_dag: bool,
     _phantom: PhantomData<(Challenge, Challenger)>,
 }
 
@@ -46,9 +118,32 @@ impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
     pub const fn new(pcs: Pcs) -> Self {
         Self {
             pcs,
+            min_log_quotient_degree: 0,
+            zk: false,
+            constraint_dag: false,
             _phantom: PhantomData,
         }
     }
+
+    /// Split the quotient into at least `2^min_log_quotient_degree` chunks, evaluating it on a
+    /// correspondingly bigger coset, whatever the degree of the constraints.
+    pub const fn with_min_log_quotient_degree(mut self, min_log_quotient_degree: usize) -> Self {
+        self.min_log_quotient_degree = min_log_quotient_degree;
+        self
+    }
+
+    /// Make proofs zero-knowledge; see `StarkGenericConfig::is_zk`.
+    pub const fn with_zk(mut self) -> Self {
+        self.zk = true;
+        self
+    }
+
+    /// Evaluate the constraints from their `SymbolicDag` when proving; see
+    /// `StarkGenericConfig::evaluates_constraint_dag`.
+    pub const fn with_constraint_dag(mut self) -> Self {
+        self.constraint_dag = true;
+        self
+    }
 }
 
 impl<Pcs, Challenge, Challenger> StarkGenericConfig for StarkConfig<Pcs, Challenge, Challenger>
@@ -66,4 +161,16 @@ where
     fn pcs(&self) -> &Self::Pcs {
         &self.pcs
     }
+
+    fn log_quotient_degree(&self, constraint_degree: usize) -> usize {
+        log_quotient_degree_for(constraint_degree).max(self.min_log_quotient_degree)
+    }
+
+    fn is_zk(&self) -> bool {
+        self.zk
+    }
+
+    fn evaluates_constraint_dag(&self) -> bool {
+        self.constraint_dag
+    }
 }
@@ -0,0 +1,108 @@
+use alloc::vec::Vec;
+
+use p3_air::{AirBuilder, RowAirBuilder};
+
+use crate::{
+    DagNode, Entry, PackedChallenge, PackedVal, ProverConstraintFolder, StarkGenericConfig,
+    SymbolicDag, SymbolicExpression, Val,
+};
+
+/// Evaluates an AIR's constraints from their `SymbolicDag`, rather than by running `Air::eval` on
+/// each packed chunk of the quotient domain.
+///
+/// Each distinct subexpression is evaluated once per chunk, and no expressions are rebuilt through
+/// the builder's traits.
+pub(crate) struct DagEvaluator<SC: StarkGenericConfig> {
+    dag: SymbolicDag<Val<SC>>,
+}
+
+/// The value of a node: base field until it involves the auxiliary trace or its challenges.
+enum Value<SC: StarkGenericConfig> {
+    Base(PackedVal<SC>),
+    Ext(PackedChallenge<SC>),
+}
+
+// Derived impls would needlessly require `SC: Copy`.
+impl<SC: StarkGenericConfig> Clone for Value<SC> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<SC: StarkGenericConfig> Copy for Value<SC> {}
+
+impl<SC: StarkGenericConfig> DagEvaluator<SC> {
+    pub(crate) fn new(constraints: &[SymbolicExpression<Val<SC>>]) -> Self {
+        Self {
+            dag: SymbolicDag::new(constraints),
+        }
+    }
+
+    /// Fold the constraints into `folder.accumulator` from the values in `folder`, as
+    /// `Air::eval` would.
+    pub(crate) fn eval(&self, folder: &mut ProverConstraintFolder<'_, SC>) {
+        let mut values: Vec<Value<SC>> = Vec::with_capacity(self.dag.nodes.len());
+        for &node in &self.dag.nodes {
+            let value = match node {
+                DagNode::Variable { entry, index } => match entry {
+                    Entry::Preprocessed { offset } => Value::Base(
+                        folder.preprocessed.values[offset * folder.preprocessed.width + index],
+                    ),
+                    Entry::Main { offset } => {
+                        Value::Base(folder.main.values[offset * folder.main.width + index])
+                    }
+                    Entry::Permutation { offset } => {
+                        Value::Ext(folder.aux.values[offset * folder.aux.width + index])
+                    }
+                    Entry::Periodic => Value::Base(folder.periodic_values[index]),
+                    Entry::Public => Value::Base(folder.public_values[index].into()),
+                    Entry::Challenge => Value::Ext(folder.aux_challenges[index].into()),
+                    Entry::AuxSum => Value::Ext(folder.aux_sums[index].into()),
+                },
+                DagNode::IsFirstRow => Value::Base(folder.is_first_row),
+                DagNode::IsLastRow => Value::Base(folder.is_last_row),
+                DagNode::IsTransition => Value::Base(folder.is_transition),
+                DagNode::IsTransitionWindow(size) => Value::Base(folder.is_transition_window(size)),
+                DagNode::IsRow(row) => Value::Base(folder.is_row(row)),
+                DagNode::Constant(i) => Value::Base(self.dag.constants[i].into()),
+                DagNode::Add(x, y) => match (values[x], values[y]) {
+                    (Value::Base(x), Value::Base(y)) => Value::Base(x + y),
+                    (Value::Base(x), Value::Ext(y)) | (Value::Ext(y), Value::Base(x)) => {
+                        Value::Ext(y + x)
+                    }
+                    (Value::Ext(x), Value::Ext(y)) => Value::Ext(x + y),
+                },
+                DagNode::Sub(x, y) => match (values[x], values[y]) {
+                    (Value::Base(x), Value::Base(y)) => Value::Base(x - y),
+                    (Value::Base(x), Value::Ext(y)) => {
+                        Value::Ext(PackedChallenge::<SC>::from(x) - y)
+                    }
+                    (Value::Ext(x), Value::Base(y)) => Value::Ext(x - y),
+                    (Value::Ext(x), Value::Ext(y)) => Value::Ext(x - y),
+                },
+                DagNode::Neg(x) => match values[x] {
+                    Value::Base(x) => Value::Base(-x),
+                    Value::Ext(x) => Value::Ext(-x),
+                },
+                DagNode::Mul(x, y) => match (values[x], values[y]) {
+                    (Value::Base(x), Value::Base(y)) => Value::Base(x * y),
+                    (Value::Base(x), Value::Ext(y)) | (Value::Ext(y), Value::Base(x)) => {
+                        Value::Ext(y * x)
+                    }
+                    (Value::Ext(x), Value::Ext(y)) => Value::Ext(x * y),
+                },
+            };
+            values.push(value);
+        }
+
+        for &node in &self.dag.constraints {
+            let alpha_power: PackedChallenge<SC> =
+                folder.alpha_powers[folder.constraint_index].into();
+            folder.accumulator += match values[node] {
+                Value::Base(x) => alpha_power * x,
+                Value::Ext(x) => alpha_power * x,
+            };
+            folder.constraint_index += 1;
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use alloc::vec::Vec;
 
-use p3_air::{AirBuilder, AirBuilderWithPublicValues};
+use p3_air::{
+    AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PairBuilder, PeriodicAirBuilder,
+    PermutationAirBuilder, RowAirBuilder,
+};
 use p3_field::{BasedVectorSpace, PackedField};
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
@@ -9,11 +12,21 @@ use crate::{PackedChallenge, PackedVal, StarkGenericConfig, Val};
 
 #[derive(Debug)]
 pub struct ProverConstraintFolder<'a, SC: StarkGenericConfig> {
+    pub preprocessed: RowMajorMatrixView<'a, PackedVal<SC>>,
     pub main: RowMajorMatrixView<'a, PackedVal<SC>>,
+    pub aux: RowMajorMatrixView<'a, PackedChallenge<SC>>,
+    pub aux_challenges: &'a [SC::Challenge],
+    /// The sums exposed by the auxiliary segments, see `MultiStageAir::aux_sums`.
+    pub aux_sums: &'a [SC::Challenge],
+    pub periodic_values: Vec<PackedVal<SC>>,
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: PackedVal<SC>,
     pub is_last_row: PackedVal<SC>,
     pub is_transition: PackedVal<SC>,
+    /// `is_transition_window(size)` for each `size` from 3 up to the AIR's window size.
+    pub is_transition_windows: Vec<PackedVal<SC>>,
+    /// `is_row(row)` for each row singled out by the AIR's constraints.
+    pub row_selectors: Vec<(usize, PackedVal<SC>)>,
     pub alpha_powers: &'a [SC::Challenge],
     pub decomposed_alpha_powers: &'a [Vec<Val<SC>>],
     pub accumulator: PackedChallenge<SC>,
@@ -24,11 +37,21 @@ type ViewPair<'a, T> = VerticalPair<RowMajorMatrixView<'a, T>, RowMajorMatrixVie
 
 #[derive(Debug)]
 pub struct VerifierConstraintFolder<'a, SC: StarkGenericConfig> {
-    pub main: ViewPair<'a, SC::Challenge>,
+    pub preprocessed: RowMajorMatrixView<'a, SC::Challenge>,
+    pub main: RowMajorMatrixView<'a, SC::Challenge>,
+    pub aux: ViewPair<'a, SC::Challenge>,
+    pub aux_challenges: &'a [SC::Challenge],
+    /// The sums exposed by the auxiliary segments, see `MultiStageAir::aux_sums`.
+    pub aux_sums: &'a [SC::Challenge],
+    pub periodic_values: Vec<SC::Challenge>,
     pub public_values: &'a Vec<Val<SC>>,
     pub is_first_row: SC::Challenge,
     pub is_last_row: SC::Challenge,
     pub is_transition: SC::Challenge,
+    /// `is_transition_window(size)` for each `size` from 3 up to the AIR's window size.
+    pub is_transition_windows: Vec<SC::Challenge>,
+    /// `is_row(row)` for each row singled out by the AIR's constraints.
+    pub row_selectors: Vec<(usize, SC::Challenge)>,
     pub alpha: SC::Challenge,
     pub accumulator: SC::Challenge,
 }
@@ -55,13 +78,15 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for ProverConstraintFolder<'a, SC> {
     }
 
     /// # Panics
-    /// This function panics if `size` is not `2`.
+    /// This function panics if `size` is not between `2` and the AIR's window size.
     #[inline]
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            self.is_transition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+        match size {
+            2 => self.is_transition,
+            3.. if size - 3 < self.is_transition_windows.len() => {
+                self.is_transition_windows[size - 3]
+            }
+            _ => panic!("window size {size} is not supported by this AIR"),
         }
     }
 
@@ -94,11 +119,74 @@ impl<SC: StarkGenericConfig> AirBuilderWithPublicValues for ProverConstraintFold
     }
 }
 
+impl<SC: StarkGenericConfig> PairBuilder for ProverConstraintFolder<'_, SC> {
+    #[inline]
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<SC: StarkGenericConfig> RowAirBuilder for ProverConstraintFolder<'_, SC> {
+    /// # Panics
+    /// This function panics if the AIR's constraints did not single out `row` when evaluated
+    /// symbolically.
+    #[inline]
+    fn is_row(&self, row: usize) -> Self::Expr {
+        row_selector(&self.row_selectors, row)
+    }
+}
+
+impl<SC: StarkGenericConfig> PeriodicAirBuilder for ProverConstraintFolder<'_, SC> {
+    type PeriodicVar = PackedVal<SC>;
+
+    #[inline]
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<SC: StarkGenericConfig> ExtensionBuilder for ProverConstraintFolder<'_, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = PackedChallenge<SC>;
+    type VarEF = PackedChallenge<SC>;
+
+    #[inline]
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        let x: PackedChallenge<SC> = x.into();
+        let alpha_power = self.alpha_powers[self.constraint_index];
+        self.accumulator += Into::<PackedChallenge<SC>>::into(alpha_power) * x;
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for ProverConstraintFolder<'a, SC> {
+    type MP = RowMajorMatrixView<'a, PackedChallenge<SC>>;
+    type RandomVar = SC::Challenge;
+
+    #[inline]
+    fn permutation(&self) -> Self::MP {
+        self.aux
+    }
+
+    #[inline]
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.aux_challenges
+    }
+
+    #[inline]
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        self.aux_sums
+    }
+}
+
 impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC> {
     type F = Val<SC>;
     type Expr = SC::Challenge;
     type Var = SC::Challenge;
-    type M = ViewPair<'a, SC::Challenge>;
+    type M = RowMajorMatrixView<'a, SC::Challenge>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -113,12 +201,14 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC>
     }
 
     /// # Panics
-    /// This function panics if `size` is not `2`.
+    /// This function panics if `size` is not between `2` and the AIR's window size.
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            self.is_transition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+        match size {
+            2 => self.is_transition,
+            3.. if size - 3 < self.is_transition_windows.len() => {
+                self.is_transition_windows[size - 3]
+            }
+            _ => panic!("window size {size} is not supported by this AIR"),
         }
     }
 
@@ -136,3 +226,65 @@ impl<SC: StarkGenericConfig> AirBuilderWithPublicValues for VerifierConstraintFo
         self.public_values
     }
 }
+
+impl<SC: StarkGenericConfig> PairBuilder for VerifierConstraintFolder<'_, SC> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<SC: StarkGenericConfig> RowAirBuilder for VerifierConstraintFolder<'_, SC> {
+    /// # Panics
+    /// This function panics if the AIR's constraints did not single out `row` when evaluated
+    /// symbolically.
+    fn is_row(&self, row: usize) -> Self::Expr {
+        row_selector(&self.row_selectors, row)
+    }
+}
+
+impl<SC: StarkGenericConfig> PeriodicAirBuilder for VerifierConstraintFolder<'_, SC> {
+    type PeriodicVar = SC::Challenge;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<SC: StarkGenericConfig> ExtensionBuilder for VerifierConstraintFolder<'_, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = SC::Challenge;
+    type VarEF = SC::Challenge;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.assert_zero(x);
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for VerifierConstraintFolder<'a, SC> {
+    type MP = ViewPair<'a, SC::Challenge>;
+    type RandomVar = SC::Challenge;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.aux_challenges
+    }
+
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        self.aux_sums
+    }
+}
+
+/// Look up the selector of `row` among those computed ahead of constraint evaluation.
+fn row_selector<T: Copy>(row_selectors: &[(usize, T)], row: usize) -> T {
+    row_selectors
+        .iter()
+        .find(|&&(r, _)| r == row)
+        .map(|&(_, selector)| selector)
+        .unwrap_or_else(|| panic!("row {row} was not singled out when evaluated symbolically"))
+}
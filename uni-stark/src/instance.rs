@@ -0,0 +1,189 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_challenger::CanObserve;
+use p3_field::Field;
+
+use crate::{Entry, SymbolicExpression};
+
+/// What the prover and verifier bind about an AIR before anything is committed, so that a proof
+/// for one AIR is rejected by another with the same trace shape.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AirShape<F> {
+    /// The number of columns in the main trace.
+    pub(crate) width: usize,
+    /// The number of public values the constraints were built with.
+    pub(crate) num_public_values: usize,
+    /// The number of constraints.
+    pub(crate) constraint_count: usize,
+    /// The largest degree multiple of any constraint.
+    pub(crate) max_constraint_degree: usize,
+    /// The constraints encoded as field elements, in the order they are folded.
+    ///
+    /// Each distinct node of the constraints' expression graph is written once, after the nodes it
+    /// refers to, as a tag followed by its contents, with children given by their position in the
+    /// list of nodes. The positions of the constraints' roots follow the nodes.
+    pub(crate) structure: Vec<F>,
+    /// The rows the constraints single out with `is_row`, in increasing order.
+    pub(crate) rows: Vec<usize>,
+}
+
+impl<F: Field> AirShape<F> {
+    /// Summarize an AIR from its symbolic constraints.
+    pub(crate) fn new(
+        width: usize,
+        num_public_values: usize,
+        constraints: &[SymbolicExpression<F>],
+    ) -> Self {
+        let mut encoder = Encoder {
+            structure: Vec::new(),
+            num_nodes: 0,
+            memo: BTreeMap::new(),
+            rows: BTreeSet::new(),
+        };
+        let roots = constraints
+            .iter()
+            .map(|constraint| encoder.encode(constraint))
+            .collect::<Vec<_>>();
+        encoder
+            .structure
+            .extend(roots.into_iter().map(F::from_usize));
+        Self {
+            width,
+            num_public_values,
+            constraint_count: constraints.len(),
+            max_constraint_degree: constraints
+                .iter()
+                .map(SymbolicExpression::degree_multiple)
+                .max()
+                .unwrap_or(0),
+            structure: encoder.structure,
+            rows: encoder.rows.into_iter().collect(),
+        }
+    }
+
+    /// Observe the width, constraint count, public value count and constraint structure.
+    pub(crate) fn observe<C: CanObserve<F>>(&self, challenger: &mut C) {
+        challenger.observe(F::from_usize(self.width));
+        challenger.observe(F::from_usize(self.constraint_count));
+        challenger.observe(F::from_usize(self.num_public_values));
+        challenger.observe(F::from_usize(self.structure.len()));
+        challenger.observe_slice(&self.structure);
+    }
+}
+
+/// Writes the nodes of symbolic expressions into `AirShape::structure`.
+struct Encoder<F> {
+    structure: Vec<F>,
+    num_nodes: usize,
+    /// The position of each node shared through an `Rc` that has already been written.
+    memo: BTreeMap<*const SymbolicExpression<F>, usize>,
+    rows: BTreeSet<usize>,
+}
+
+impl<F: Field> Encoder<F> {
+    /// Write `expr` and the nodes it refers to, reusing those shared through the same `Rc`, record
+    /// the rows it singles out, and return its position.
+    fn encode(&mut self, expr: &SymbolicExpression<F>) -> usize {
+        let mut child = |encoder: &mut Self, x: &Rc<SymbolicExpression<F>>| {
+            let ptr = Rc::as_ptr(x);
+            if let Some(&position) = encoder.memo.get(&ptr) {
+                return F::from_usize(position);
+            }
+            let position = encoder.encode(x);
+            encoder.memo.insert(ptr, position);
+            F::from_usize(position)
+        };
+
+        let node = match expr {
+            SymbolicExpression::Variable(v) => {
+                let (tag, offset) = match v.entry {
+                    Entry::Preprocessed { offset } => (0, offset),
+                    Entry::Main { offset } => (1, offset),
+                    Entry::Permutation { offset } => (2, offset),
+                    Entry::Periodic => (3, 0),
+                    Entry::Public => (4, 0),
+                    Entry::Challenge => (5, 0),
+                    Entry::AuxSum => (6, 0),
+                };
+                vec![
+                    F::ZERO,
+                    F::from_u8(tag),
+                    F::from_usize(offset),
+                    F::from_usize(v.index),
+                ]
+            }
+            SymbolicExpression::IsFirstRow => vec![F::from_u8(1)],
+            SymbolicExpression::IsLastRow => vec![F::from_u8(2)],
+            SymbolicExpression::IsTransition => vec![F::from_u8(3)],
+            SymbolicExpression::IsTransitionWindow(size) => {
+                vec![F::from_u8(4), F::from_usize(*size)]
+            }
+            SymbolicExpression::IsRow(row) => {
+                self.rows.insert(*row);
+                vec![F::from_u8(10), F::from_usize(*row)]
+            }
+            SymbolicExpression::Constant(c) => vec![F::from_u8(5), *c],
+            SymbolicExpression::Add { x, y, .. } => {
+                vec![F::from_u8(6), child(self, x), child(self, y)]
+            }
+            SymbolicExpression::Sub { x, y, .. } => {
+                vec![F::from_u8(7), child(self, x), child(self, y)]
+            }
+            SymbolicExpression::Neg { x, .. } => vec![F::from_u8(8), child(self, x)],
+            SymbolicExpression::Mul { x, y, .. } => {
+                vec![F::from_u8(9), child(self, x), child(self, y)]
+            }
+        };
+        self.structure.extend(node);
+        self.num_nodes += 1;
+        self.num_nodes - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_air::{Air, AirBuilder, BaseAir};
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::Matrix;
+
+    use super::*;
+    use crate::get_symbolic_constraints;
+
+    /// Asserts `local[0] * local[1] = next[c]` on transitions.
+    struct ProductAir {
+        c: usize,
+    }
+
+    impl<F> BaseAir<F> for ProductAir {
+        fn width(&self) -> usize {
+            2
+        }
+    }
+
+    impl<AB: AirBuilder> Air<AB> for ProductAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let (local, next) = (main.row_slice(0), main.row_slice(1));
+            builder
+                .when_transition()
+                .assert_eq(local[0] * local[1], next[self.c]);
+        }
+    }
+
+    fn shape(air: &ProductAir) -> AirShape<BabyBear> {
+        let constraints = get_symbolic_constraints::<BabyBear, _>(air, 0, 0);
+        AirShape::new(2, 0, &constraints)
+    }
+
+    #[test]
+    fn test_structure_distinguishes_same_shape() {
+        let (a, b) = (shape(&ProductAir { c: 0 }), shape(&ProductAir { c: 1 }));
+        assert_eq!(a.constraint_count, b.constraint_count);
+        assert_eq!(a.max_constraint_degree, b.max_constraint_degree);
+        assert_ne!(a.structure, b.structure);
+        assert_eq!(a, shape(&ProductAir { c: 0 }));
+    }
+}
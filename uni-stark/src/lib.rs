@@ -3,24 +3,47 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "stats")]
+extern crate std;
 
 mod config;
+mod dag_evaluator;
 mod folder;
+mod instance;
+mod lookup;
+mod metadata;
+mod multi_stage;
+mod periodic;
+mod preprocessed;
 mod proof;
 mod prover;
+mod row_selector;
+#[cfg(feature = "stats")]
+mod stats;
 mod symbolic_builder;
+mod symbolic_dag;
 mod symbolic_expression;
 mod symbolic_variable;
+mod trace_generator;
 mod verifier;
+mod window;
 
 mod check_constraints;
 
 pub use check_constraints::*;
 pub use config::*;
 pub use folder::*;
+pub use lookup::*;
+pub use metadata::*;
+pub use multi_stage::*;
+pub use preprocessed::*;
 pub use proof::*;
 pub use prover::*;
+#[cfg(feature = "stats")]
+pub use stats::*;
 pub use symbolic_builder::*;
+pub use symbolic_dag::*;
 pub use symbolic_expression::*;
 pub use symbolic_variable::*;
+pub use trace_generator::*;
 pub use verifier::*;
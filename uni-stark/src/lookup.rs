@@ -0,0 +1,362 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, ExtensionBuilder, InteractionAirBuilder,
+    PairBuilder, PeriodicAirBuilder, PermutationAirBuilder, RowAirBuilder,
+};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::Matrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+
+use crate::{AuxStage, MultiStageAir};
+
+/// A lookup declared during constraint evaluation: a tuple of values, led by its bus, and its
+/// multiplicity.
+type Interaction<Expr> = (Vec<Expr>, Expr);
+
+/// The tuple `LogUpAir` looks up for `fields` sent on `bus_id`: the bus comes first, so that
+/// tuples sent on different buses never match.
+fn bus_tuple<Expr: PrimeCharacteristicRing, I: Into<Expr>>(
+    bus_id: usize,
+    fields: impl IntoIterator<Item = I>,
+) -> Vec<Expr> {
+    iter::once(Expr::from_usize(bus_id))
+        .chain(fields.into_iter().map(Into::into))
+        .collect()
+}
+
+/// Wraps an `AirBuilder`, forwarding constraints to it and recording the lookups declared on it.
+#[derive(Debug)]
+pub struct LookupCollector<'a, AB: AirBuilder> {
+    inner: &'a mut AB,
+    interactions: Vec<Interaction<AB::Expr>>,
+}
+
+impl<'a, AB: AirBuilder> LookupCollector<'a, AB> {
+    pub const fn new(inner: &'a mut AB) -> Self {
+        Self {
+            inner,
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl<AB: AirBuilder> AirBuilder for LookupCollector<'_, AB> {
+    type F = AB::F;
+    type Expr = AB::Expr;
+    type Var = AB::Var;
+    type M = AB::M;
+
+    fn main(&self) -> Self::M {
+        self.inner.main()
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.inner.is_first_row()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.inner.is_last_row()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        self.inner.is_transition_window(size)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.inner.assert_zero(x);
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> AirBuilderWithPublicValues for LookupCollector<'_, AB> {
+    type PublicVar = AB::PublicVar;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.inner.public_values()
+    }
+}
+
+impl<AB: PairBuilder> PairBuilder for LookupCollector<'_, AB> {
+    fn preprocessed(&self) -> Self::M {
+        self.inner.preprocessed()
+    }
+}
+
+impl<AB: RowAirBuilder> RowAirBuilder for LookupCollector<'_, AB> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        self.inner.is_row(row)
+    }
+}
+
+impl<AB: PeriodicAirBuilder> PeriodicAirBuilder for LookupCollector<'_, AB> {
+    type PeriodicVar = AB::PeriodicVar;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.inner.periodic_values()
+    }
+}
+
+impl<AB: AirBuilder> InteractionAirBuilder for LookupCollector<'_, AB> {
+    fn push_send<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.interactions
+            .push((bus_tuple(bus_id, fields), multiplicity.into()));
+    }
+}
+
+/// An `AirBuilder` which evaluates the lookups of a single row of a trace, ignoring constraints.
+#[derive(Debug)]
+pub struct InteractionEvaluator<'a, F: Field> {
+    main: RowMajorMatrixView<'a, F>,
+    row: usize,
+    height: usize,
+    window_size: usize,
+    interactions: Vec<Interaction<F>>,
+}
+
+impl<'a, F: Field> AirBuilder for InteractionEvaluator<'a, F> {
+    type F = F;
+    type Expr = F;
+    type Var = F;
+    type M = RowMajorMatrixView<'a, F>;
+
+    fn main(&self) -> Self::M {
+        self.main
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        F::from_bool(self.row == 0)
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        F::from_bool(self.row == self.height - 1)
+    }
+
+    /// # Panics
+    /// This function panics if `size` is not between `2` and the AIR's window size.
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        assert!(
+            (2..=self.window_size).contains(&size),
+            "window size {size} is not supported by this AIR"
+        );
+        F::from_bool(self.row + size <= self.height)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, _x: I) {}
+}
+
+impl<F: Field> RowAirBuilder for InteractionEvaluator<'_, F> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        F::from_bool(self.row == row % self.height)
+    }
+}
+
+impl<F: Field> InteractionAirBuilder for InteractionEvaluator<'_, F> {
+    fn push_send<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.interactions
+            .push((bus_tuple(bus_id, fields), multiplicity.into()));
+    }
+}
+
+/// Evaluate the lookups `air` declares on row `row` of `main`.
+fn row_interactions<F, A>(air: &A, main: &RowMajorMatrix<F>, row: usize) -> Vec<Interaction<F>>
+where
+    F: Field,
+    A: BaseAir<F> + for<'a> Air<InteractionEvaluator<'a, F>>,
+{
+    let height = main.height();
+    let window_size = <A as BaseAir<F>>::window_size(air);
+    let rows = (0..window_size)
+        .flat_map(|offset| main.row_slice((row + offset) % height).to_vec())
+        .collect::<Vec<_>>();
+    let mut evaluator = InteractionEvaluator {
+        main: RowMajorMatrixView::new(&rows, main.width()),
+        row,
+        height,
+        window_size,
+        interactions: vec![],
+    };
+    air.eval(&mut evaluator);
+    evaluator.interactions
+}
+
+/// Adds a LogUp argument to an AIR which declares interactions through `InteractionAirBuilder`.
+///
+/// Each interaction sends the tuple `v` made of its bus followed by its fields, with multiplicity
+/// `m`. With challenges `z` and `beta`, it contributes `m / (z - sum_j beta^j v_j)` per row. A single auxiliary segment holds one column per lookup with that term, plus a running
+/// sum of the terms over all rows. Its final value is exposed as the segment's sum, and the verifier
+/// requires the sums of all AIRs in a proof to add up to zero. With high probability, this only
+/// holds if every tuple is sent and received equally often across all of them, so one AIR can look
+/// up values held by another when both are proven with `prove_multi_with_aux`.
+///
+/// The wrapped AIR's lookups are evaluated from the main trace alone, so they must not depend on
+/// public values, preprocessed columns or periodic columns.
+#[derive(Debug)]
+pub struct LogUpAir<A> {
+    pub inner: A,
+}
+
+impl<A> LogUpAir<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// The number of lookups `inner` declares per row.
+    pub fn num_lookups<F>(&self) -> usize
+    where
+        F: Field,
+        A: BaseAir<F> + for<'a> Air<InteractionEvaluator<'a, F>>,
+    {
+        // Which lookups are declared doesn't depend on the trace, so any row will do.
+        let width = <A as BaseAir<F>>::width(&self.inner);
+        let window_size = <A as BaseAir<F>>::window_size(&self.inner);
+        let main = RowMajorMatrix::new(F::zero_vec(window_size * width), width);
+        row_interactions(&self.inner, &main, 0).len()
+    }
+}
+
+impl<F, A: BaseAir<F>> BaseAir<F> for LogUpAir<A> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        self.inner.preprocessed_trace()
+    }
+
+    fn window_size(&self) -> usize {
+        self.inner.window_size()
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        self.inner.periodic_columns()
+    }
+}
+
+impl<F, A> MultiStageAir<F> for LogUpAir<A>
+where
+    F: Field,
+    A: BaseAir<F> + for<'a> Air<InteractionEvaluator<'a, F>>,
+{
+    fn aux_stages(&self) -> Vec<AuxStage> {
+        vec![AuxStage {
+            width: self.num_lookups::<F>() + 1,
+            num_challenges: 2,
+            num_sums: 1,
+        }]
+    }
+
+    fn generate_aux_trace<EF: ExtensionField<F>>(
+        &self,
+        _stage: usize,
+        main: &RowMajorMatrix<F>,
+        _prev_aux: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        let (z, beta) = (challenges[0], challenges[1]);
+        let height = main.height();
+
+        let interactions = (0..height)
+            .map(|row| row_interactions(&self.inner, main, row))
+            .collect::<Vec<_>>();
+
+        let mut inverses = interactions
+            .iter()
+            .flatten()
+            .map(|(values, _)| {
+                z - values
+                    .iter()
+                    .zip(beta.powers())
+                    .map(|(&v, beta_pow)| beta_pow * v)
+                    .sum::<EF>()
+            })
+            .collect::<Vec<_>>();
+        EF::batch_inverse_in_place(&mut inverses);
+        let mut inverses = inverses.into_iter();
+
+        let width = self.num_lookups::<F>() + 1;
+        let mut values = Vec::with_capacity(height * width);
+        let mut running_sum = EF::ZERO;
+        for row in &interactions {
+            for ((_, multiplicity), inverse) in row.iter().zip(inverses.by_ref()) {
+                let term = inverse * *multiplicity;
+                running_sum += term;
+                values.push(term);
+            }
+            values.push(running_sum);
+        }
+        RowMajorMatrix::new(values, width)
+    }
+
+    fn aux_sums<EF: ExtensionField<F>>(&self, _stage: usize, aux: &RowMajorMatrix<EF>) -> Vec<EF> {
+        // The running sum is the last column, and its value on the last row is the total.
+        aux.values.last().copied().into_iter().collect()
+    }
+}
+
+impl<AB, A> Air<AB> for LogUpAir<A>
+where
+    AB: PermutationAirBuilder,
+    A: BaseAir<AB::F> + for<'a> Air<LookupCollector<'a, AB>>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let mut collector = LookupCollector::new(builder);
+        self.inner.eval(&mut collector);
+        let interactions = collector.interactions;
+
+        let aux = builder.permutation();
+        let (aux_local, aux_next) = (aux.row_slice(0), aux.row_slice(1));
+        let num_lookups = interactions.len();
+        let challenges = builder.permutation_randomness();
+        let (z, beta): (AB::ExprEF, AB::ExprEF) = (challenges[0].into(), challenges[1].into());
+        let total: AB::ExprEF = builder.permutation_values()[0].into();
+
+        // Each term column times its denominator is the multiplicity.
+        for ((values, multiplicity), &term) in interactions.into_iter().zip(aux_local.iter()) {
+            let mut denominator = z.clone();
+            let mut beta_pow = AB::ExprEF::ONE;
+            for value in values {
+                denominator -= beta_pow.clone() * value;
+                beta_pow *= beta.clone();
+            }
+            let term: AB::ExprEF = term.into();
+            builder.assert_eq_ext(term * denominator, multiplicity);
+        }
+
+        let sum_local: AB::ExprEF = aux_local[num_lookups].into();
+        let sum_next: AB::ExprEF = aux_next[num_lookups].into();
+        let terms_local = aux_local[..num_lookups]
+            .iter()
+            .map(|&term| Into::<AB::ExprEF>::into(term))
+            .sum::<AB::ExprEF>();
+        let terms_next = aux_next[..num_lookups]
+            .iter()
+            .map(|&term| Into::<AB::ExprEF>::into(term))
+            .sum::<AB::ExprEF>();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(sum_local.clone(), terms_local);
+        builder
+            .when_transition()
+            .assert_eq_ext(sum_next, sum_local.clone() + terms_next);
+        builder.when_last_row().assert_eq_ext(sum_local, total);
+    }
+}
@@ -0,0 +1,98 @@
+use p3_air::Air;
+use p3_field::Field;
+use p3_matrix::Matrix;
+
+use crate::{SymbolicAirBuilder, get_symbolic_constraints, log_quotient_degree_for};
+
+/// The shape of an AIR's trace and constraints, as seen by the prover and verifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StarkMetadata {
+    /// The number of columns in the main trace.
+    pub width: usize,
+    /// The number of preprocessed columns.
+    pub preprocessed_width: usize,
+    /// The number of constraints, each of which is folded in with its own power of `alpha`.
+    pub constraint_count: usize,
+    /// The largest degree multiple of any constraint.
+    pub max_constraint_degree: usize,
+    /// The number of chunks the quotient polynomial is split into, by `log_quotient_degree_for`.
+    pub quotient_degree: usize,
+    /// The log2 of `quotient_degree`.
+    pub log_quotient_degree: usize,
+}
+
+impl StarkMetadata {
+    /// Evaluate the constraints of `air` symbolically and summarize them.
+    pub fn for_air<F, A>(air: &A, num_public_values: usize) -> Self
+    where
+        F: Field,
+        A: Air<SymbolicAirBuilder<F>>,
+    {
+        let preprocessed_width = air
+            .preprocessed_trace()
+            .map_or(0, |preprocessed| preprocessed.width());
+        let constraints = get_symbolic_constraints(air, preprocessed_width, num_public_values);
+        let max_constraint_degree = constraints
+            .iter()
+            .map(|c| c.degree_multiple())
+            .max()
+            .unwrap_or(0);
+        let log_quotient_degree = log_quotient_degree_for(max_constraint_degree);
+        Self {
+            width: air.width(),
+            preprocessed_width,
+            constraint_count: constraints.len(),
+            max_constraint_degree,
+            quotient_degree: 1 << log_quotient_degree,
+            log_quotient_degree,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_air::{AirBuilder, BaseAir};
+    use p3_baby_bear::BabyBear;
+
+    use super::*;
+    use crate::get_log_quotient_degree;
+
+    /// Asserts `x^5 = y` on every row and `x = 0` on the first.
+    struct QuinticAir;
+
+    impl<F> BaseAir<F> for QuinticAir {
+        fn width(&self) -> usize {
+            2
+        }
+    }
+
+    impl<AB: AirBuilder> Air<AB> for QuinticAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let local = main.row_slice(0);
+            let x: AB::Expr = local[0].into();
+            builder.assert_eq(x.exp_const_u64::<5>(), local[1]);
+            builder.when_first_row().assert_zero(local[0]);
+        }
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = StarkMetadata::for_air::<BabyBear, _>(&QuinticAir, 0);
+        assert_eq!(
+            metadata,
+            StarkMetadata {
+                width: 2,
+                preprocessed_width: 0,
+                constraint_count: 2,
+                max_constraint_degree: 5,
+                quotient_degree: 4,
+                log_quotient_degree: 2,
+            }
+        );
+        assert_eq!(
+            metadata.log_quotient_degree,
+            get_log_quotient_degree::<BabyBear, _>(&QuinticAir, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use alloc::vec::Vec;
+
+use p3_air::BaseAir;
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+
+/// The shape of one auxiliary trace segment.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuxStage {
+    /// The number of challenge field columns in the segment.
+    pub width: usize,
+    /// The number of challenges sampled right before the segment is generated.
+    pub num_challenges: usize,
+    /// The number of sums the segment exposes to the verifier, e.g. the final value of a running
+    /// sum. The verifier requires the sums exposed by all AIRs in a proof to add up to zero.
+    pub num_sums: usize,
+}
+
+/// An AIR whose trace is committed in stages.
+///
+/// The main trace is committed first. Each auxiliary segment is then generated over the challenge
+/// field from the main trace, the earlier segments and all challenges sampled so far, and committed
+/// in turn. This is the shape needed by permutation and lookup arguments.
+///
+/// When evaluating constraints, `PermutationAirBuilder::permutation` exposes the columns of every
+/// auxiliary segment, stage after stage, and `PermutationAirBuilder::permutation_randomness` the
+/// challenges of every stage in the order they were sampled. `PermutationAirBuilder::permutation_values`
+/// exposes the sums of every stage, as returned by `MultiStageAir::aux_sums`.
+pub trait MultiStageAir<F: Field>: BaseAir<F> {
+    /// The shape of each auxiliary segment, in commitment order.
+    fn aux_stages(&self) -> Vec<AuxStage>;
+
+    /// Generate the auxiliary segment of `stage`.
+    ///
+    /// `prev_aux` holds the segments of the earlier stages and `challenges` the challenges of
+    /// every stage up to and including this one.
+    fn generate_aux_trace<EF: ExtensionField<F>>(
+        &self,
+        stage: usize,
+        main: &RowMajorMatrix<F>,
+        prev_aux: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF>;
+
+    /// The sums exposed by the auxiliary segment `aux` of `stage`, `num_sums` of them.
+    ///
+    /// They are observed right after the segment is committed, and the AIR should constrain the
+    /// segment to agree with them.
+    fn aux_sums<EF: ExtensionField<F>>(&self, _stage: usize, _aux: &RowMajorMatrix<EF>) -> Vec<EF> {
+        Vec::new()
+    }
+}
+
+/// The total width of the auxiliary segments.
+pub(crate) fn aux_width(stages: &[AuxStage]) -> usize {
+    stages.iter().map(|stage| stage.width).sum()
+}
+
+/// The total number of challenges sampled for the auxiliary segments.
+pub(crate) fn num_aux_challenges(stages: &[AuxStage]) -> usize {
+    stages.iter().map(|stage| stage.num_challenges).sum()
+}
+
+/// The total number of sums exposed by the auxiliary segments.
+pub(crate) fn num_aux_sums(stages: &[AuxStage]) -> usize {
+    stages.iter().map(|stage| stage.num_sums).sum()
+}
+
+/// Place the auxiliary segments side by side, as the constraint builders see them.
+#[cfg(debug_assertions)]
+pub(crate) fn concat_aux<EF: Field>(segments: &[RowMajorMatrix<EF>]) -> RowMajorMatrix<EF> {
+    use p3_matrix::Matrix;
+
+    let width = segments.iter().map(|segment| segment.width()).sum();
+    let height = segments.first().map_or(0, |segment| segment.height());
+    let mut values = Vec::with_capacity(width * height);
+    for r in 0..height {
+        for segment in segments {
+            values.extend_from_slice(&segment.row_slice(r));
+        }
+    }
+    RowMajorMatrix::new(values, width)
+}
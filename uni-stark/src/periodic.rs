@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use p3_commit::PolynomialSpace;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, RootsOfUnityTable};
+use p3_util::log2_strict_usize;
+
+/// A periodic column of an AIR, interpolated so that it can be evaluated anywhere.
+///
+/// A column repeating every `p` rows of a trace over `s<g>` of height `n` is `Q((x / s)^{n/p})`,
+/// where `Q` interpolates one period over the subgroup of order `p`. This needs a multiplicative
+/// trace domain.
+#[derive(Clone, Debug)]
+pub(crate) struct PeriodicColumn<F> {
+    /// The coefficients of `Q`.
+    coeffs: Vec<F>,
+    /// `n / p`, the power mapping the trace domain onto the subgroup of order `p`.
+    stride: usize,
+    /// `1 / s`, mapping the trace domain onto its subgroup.
+    shift_inv: F,
+}
+
+impl<F: Field> PeriodicColumn<F> {
+    /// Interpolate each of `columns` over `trace_domain`, or return `None` if the period of one
+    /// of them is not a power of two dividing the trace height, or if there are columns and
+    /// `trace_domain` is not a multiplicative coset.
+    pub(crate) fn interpolate_all<D>(trace_domain: &D, columns: &[Vec<F>]) -> Option<Vec<Self>>
+    where
+        D: PolynomialSpace<Val = F>,
+    {
+        if columns.is_empty() {
+            return Some(Vec::new());
+        }
+        let height = trace_domain.size();
+        let generator = trace_domain.multiplicative_generator()?;
+        let shift_inv = trace_domain.first_point().inverse();
+        columns
+            .iter()
+            .map(|values| {
+                let period = values.len();
+                if !period.is_power_of_two() || period > height {
+                    return None;
+                }
+                let stride = height / period;
+                // coeffs[k] = (1/p) sum_j values[j] omega^{-jk}, an inverse DFT over the subgroup.
+                let omega_inv = generator.exp_u64(stride as u64).inverse();
+                let log_period = log2_strict_usize(period);
+                let roots = RootsOfUnityTable::new(omega_inv, log_period).roots(log_period);
+                let period_inv = F::from_usize(period).inverse();
+                let coeffs = (0..period)
+                    .map(|k| {
+                        values
+                            .iter()
+                            .enumerate()
+                            .map(|(j, &value)| roots[(j * k) % period] * value)
+                            .sum::<F>()
+                            * period_inv
+                    })
+                    .collect();
+                Some(Self {
+                    coeffs,
+                    stride,
+                    shift_inv,
+                })
+            })
+            .collect()
+    }
+
+    /// Evaluate the column at `x`.
+    pub(crate) fn eval<Ext: ExtensionField<F>>(&self, x: Ext) -> Ext {
+        let y = (x * self.shift_inv).exp_u64(self.stride as u64);
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(Ext::ZERO, |acc, &coeff| acc * y + coeff)
+    }
+
+    /// Evaluate the column on every point of `domain`, a coset at least as large as the trace
+    /// domain.
+    pub(crate) fn eval_on_coset<D>(&self, domain: &D) -> Vec<F>
+    where
+        D: PolynomialSpace<Val = F>,
+    {
+        // Raising the points of the coset to the power `stride` cycles through only
+        // `size / stride` values, so evaluate those and repeat them.
+        let size = domain.size();
+        let distinct = iter::successors(Some(domain.first_point()), |&x| domain.next_point(x))
+            .take(size / self.stride)
+            .map(|x| self.eval(x))
+            .collect::<Vec<_>>();
+        distinct.into_iter().cycle().take(size).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_circle::CircleDomain;
+    use p3_field::coset::TwoAdicMultiplicativeCoset;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    #[test]
+    fn test_periodic_column_on_trace_domain() {
+        let values = [3, 1, 4, 1].map(BabyBear::from_u8).to_vec();
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::ONE, 4).unwrap();
+        let columns = PeriodicColumn::interpolate_all(&trace_domain, &[values.clone()]).unwrap();
+        assert_eq!(
+            columns[0].eval_on_coset(&trace_domain),
+            values.iter().cycle().take(16).copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_periodic_column_on_coset() {
+        let values = [2, 7, 1, 8, 2, 8, 1, 8].map(BabyBear::from_u8).to_vec();
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::ONE, 3).unwrap();
+        let columns = PeriodicColumn::interpolate_all(&trace_domain, &[values]).unwrap();
+        let coset = trace_domain.create_disjoint_domain(32);
+        let points = iter::successors(Some(coset.first_point()), |&x| coset.next_point(x));
+        let expected = points
+            .take(32)
+            .map(|x| columns[0].eval(x))
+            .collect::<Vec<_>>();
+        assert_eq!(columns[0].eval_on_coset(&coset), expected);
+    }
+
+    #[test]
+    fn test_invalid_period() {
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::ONE, 2).unwrap();
+        let not_power_of_two = vec![vec![BabyBear::ONE; 3]];
+        let too_long = vec![vec![BabyBear::ONE; 8]];
+        assert!(PeriodicColumn::interpolate_all(&trace_domain, &not_power_of_two).is_none());
+        assert!(PeriodicColumn::interpolate_all(&trace_domain, &too_long).is_none());
+    }
+
+    #[test]
+    fn test_periodic_column_on_shifted_domain() {
+        let values = [3, 1, 4, 1].map(BabyBear::from_u8).to_vec();
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::GENERATOR, 3).unwrap();
+        let columns = PeriodicColumn::interpolate_all(&trace_domain, &[values.clone()]).unwrap();
+        assert_eq!(
+            columns[0].eval_on_coset(&trace_domain),
+            values.iter().cycle().take(8).copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_periodic_column_rejects_circle_domain() {
+        let trace_domain = CircleDomain::<Mersenne31>::standard(3);
+        let values = vec![vec![Mersenne31::ONE; 4]];
+        assert!(PeriodicColumn::interpolate_all(&trace_domain, &values).is_none());
+        assert!(PeriodicColumn::interpolate_all(&trace_domain, &[]).is_some());
+    }
+}
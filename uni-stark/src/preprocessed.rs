@@ -0,0 +1,98 @@
+use alloc::vec;
+
+use p3_air::BaseAir;
+use p3_commit::Pcs;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::proof::Com;
+use crate::{StarkGenericConfig, Val};
+
+type PcsProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
+
+/// The committed preprocessed columns of an AIR, as needed by the prover.
+pub struct PreprocessedProverData<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+    pub(crate) prover_data: PcsProverData<SC>,
+}
+
+/// The committed preprocessed columns of an AIR, as needed by the verifier.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedVerifierData<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+}
+
+/// Everything the prover needs about an AIR beyond its trace, computed once by `setup`.
+pub struct ProverKey<SC: StarkGenericConfig> {
+    pub(crate) preprocessed: Option<PreprocessedProverData<SC>>,
+}
+
+/// Everything the verifier needs about an AIR beyond the proof, computed once by `setup`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifierKey<SC: StarkGenericConfig> {
+    pub(crate) preprocessed: Option<PreprocessedVerifierData<SC>>,
+}
+
+impl<SC: StarkGenericConfig> VerifierKey<SC> {
+    /// The commitment to the preprocessed columns, if the AIR has any.
+    pub fn preprocessed_commitment(&self) -> Option<&Com<SC>> {
+        self.preprocessed
+            .as_ref()
+            .map(|preprocessed| &preprocessed.commitment)
+    }
+}
+
+/// Commit to the preprocessed columns of `air`, if it has any.
+///
+/// The preprocessed trace fixes the trace height: traces proven against the resulting keys must
+/// have the same height.
+#[instrument(skip_all)]
+pub fn setup<SC, A>(config: &SC, air: &A) -> (ProverKey<SC>, VerifierKey<SC>)
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>>,
+{
+    let Some(preprocessed) = air.preprocessed_trace() else {
+        return (
+            ProverKey { preprocessed: None },
+            VerifierKey { preprocessed: None },
+        );
+    };
+
+    let width = preprocessed.width();
+    let degree = preprocessed.height();
+    let degree_bits = log2_strict_usize(degree);
+
+    let pcs = config.pcs();
+    let domain = pcs.natural_domain_for_degree(degree);
+    let (commitment, prover_data) = info_span!("commit to preprocessed trace")
+        .in_scope(|| pcs.commit(vec![(domain, preprocessed)]));
+
+    let vk = VerifierKey {
+        preprocessed: Some(PreprocessedVerifierData {
+            width,
+            degree_bits,
+            commitment: commitment.clone(),
+        }),
+    };
+    let pk = ProverKey {
+        preprocessed: Some(PreprocessedProverData {
+            width,
+            degree_bits,
+            commitment,
+            prover_data,
+        }),
+    };
+    (pk, vk)
+}
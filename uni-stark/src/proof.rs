@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::StarkGenericConfig;
 
-type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+pub(crate) type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
 >>::Commitment;
@@ -23,15 +23,64 @@ pub struct Proof<SC: StarkGenericConfig> {
     pub(crate) degree_bits: usize,
 }
 
+/// A proof of several AIRs at once, produced by `prove_multi` or `prove_multi_with_aux`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultiProof<SC: StarkGenericConfig> {
+    pub(crate) commitments: Commitments<Com<SC>>,
+    /// The opened values of each AIR, in order.
+    pub(crate) opened_values: Vec<OpenedValues<SC::Challenge>>,
+    pub(crate) opening_proof: PcsProof<SC>,
+    /// The log height of each AIR's trace, in order.
+    pub(crate) degree_bits: Vec<usize>,
+}
+
+/// A proof of one AIR over several traces of the same height, produced by
+/// `prove_identical_batch`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BatchProof<SC: StarkGenericConfig> {
+    pub(crate) commitments: Commitments<Com<SC>>,
+    /// The opened trace values of each instance, in order. Their `quotient_chunks` and
+    /// `quotient_mask` are empty, as the instances share one quotient.
+    pub(crate) opened_values: Vec<OpenedValues<SC::Challenge>>,
+    /// The openings of the chunks of the combined quotient.
+    pub(crate) quotient_chunks: Vec<Vec<SC::Challenge>>,
+    /// The opening of the mask of the combined quotient, empty unless the proof is
+    /// zero-knowledge.
+    pub(crate) quotient_mask: Vec<SC::Challenge>,
+    pub(crate) opening_proof: PcsProof<SC>,
+    pub(crate) degree_bits: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Commitments<Com> {
     pub(crate) trace: Com,
+    /// One commitment per auxiliary trace segment, in stage order.
+    pub(crate) aux_traces: Vec<Com>,
     pub(crate) quotient_chunks: Com,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenedValues<Challenge> {
+    /// Empty unless the AIR has preprocessed columns.
+    pub(crate) preprocessed_local: Vec<Challenge>,
+    pub(crate) preprocessed_next: Vec<Challenge>,
     pub(crate) trace_local: Vec<Challenge>,
     pub(crate) trace_next: Vec<Challenge>,
+    /// The main trace at each row after the next one, for AIRs with a window of more than two
+    /// rows.
+    pub(crate) trace_after_next: Vec<Vec<Challenge>>,
+    /// The openings of each auxiliary segment, in stage order. Segments are committed flattened
+    /// to the base field, so each challenge field column contributes `DIMENSION` values here.
+    pub(crate) aux_trace_local: Vec<Vec<Challenge>>,
+    pub(crate) aux_trace_next: Vec<Vec<Challenge>>,
+    /// The sums exposed by each auxiliary segment, in stage order. These aren't openings: they are
+    /// claimed by the prover, observed right after the segment's commitment and tied to the
+    /// segment by the AIR's constraints.
+    pub(crate) aux_sums: Vec<Vec<Challenge>>,
     pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
+    /// In zero-knowledge mode, the opening of the random polynomial added to the quotient before
+    /// it is split into chunks, as `DIMENSION` base field columns. Empty otherwise.
+    pub(crate) quotient_mask: Vec<Challenge>,
 }
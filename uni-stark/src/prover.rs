@@ -1,20 +1,32 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
 use itertools::{Itertools, izip};
-use p3_air::Air;
+use p3_air::{Air, BaseAir};
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::{BasedVectorSpace, PackedValue, PrimeCharacteristicRing};
+use p3_field::{BasedVectorSpace, ExtensionField, Field, PackedValue, PrimeCharacteristicRing};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::{log2_ceil_usize, log2_strict_usize};
+use p3_util::log2_strict_usize;
 use tracing::{debug_span, info_span, instrument};
 
+use crate::config::{effective_log_quotient_degree, quotient_mask_domain};
+use crate::dag_evaluator::DagEvaluator;
+use crate::instance::AirShape;
+use crate::multi_stage::{aux_width, num_aux_challenges, num_aux_sums};
+use crate::periodic::PeriodicColumn;
+use crate::proof::Com;
+use crate::row_selector::row_selectors_on_coset;
+use crate::trace_generator::generate_trace;
+use crate::window::{transition_window_factors, window_points};
 use crate::{
-    Commitments, Domain, OpenedValues, PackedChallenge, PackedVal, Proof, ProverConstraintFolder,
-    StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val, get_symbolic_constraints,
+    AuxStage, BatchProof, ChunkedTraceGenerator, Commitments, Domain, MultiProof, MultiStageAir,
+    OpenedValues, PackedChallenge, PackedVal, PreprocessedProverData, Proof,
+    ProverConstraintFolder, ProverKey, StarkGenericConfig, SymbolicAirBuilder, Val,
+    get_symbolic_constraints, get_symbolic_constraints_with_aux,
 };
 
 #[instrument(skip_all)]
@@ -37,48 +49,980 @@ where
     #[cfg(debug_assertions)]
     crate::check_constraints::check_constraints(air, &trace, public_values);
 
-    let degree = trace.height();
-    let log_degree = log2_strict_usize(degree);
+    prove_stages(
+        config,
+        air,
+        challenger,
+        None,
+        trace,
+        &[],
+        |_, _, _, _| unreachable!("there are no auxiliary stages"),
+        public_values,
+        |_| {},
+    )
+}
+
+/// Like `prove`, but with the trace generated in chunks by `generator`.
+///
+/// The whole trace is generated before anything is committed to, so the peak memory use is the
+/// same as that of `prove`.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_generator<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+    G,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    generator: &G,
+    public_values: &Vec<Val<SC>>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    G: ChunkedTraceGenerator<Val<SC>> + ?Sized,
+{
+    assert_eq!(
+        generator.width(),
+        <A as BaseAir<Val<SC>>>::width(air),
+        "the generated trace does not match the width of the AIR"
+    );
+    let trace = generate_trace(generator);
+    prove(config, air, challenger, trace, public_values)
+}
+
+/// Prove an AIR whose trace is committed in stages.
+///
+/// The main trace is committed first, after which each auxiliary segment declared by
+/// `MultiStageAir::aux_stages` is generated from freshly sampled challenges and committed in turn.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi_stage<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let aux_stages = air.aux_stages();
+    #[cfg(debug_assertions)]
+    let mut all_sums = Vec::new();
+
+    #[cfg(debug_assertions)]
+    if aux_stages.is_empty() {
+        crate::check_constraints::check_constraints_with_aux(
+            air,
+            None,
+            &trace,
+            None,
+            &[],
+            &[],
+            public_values,
+        );
+    }
+
+    prove_stages(
+        config,
+        air,
+        challenger,
+        None,
+        trace,
+        &aux_stages,
+        |stage, main, prev_aux, challenges| {
+            let (aux, sums) =
+                generate_aux_stage(air, &aux_stages, stage, main, prev_aux, challenges);
+
+            #[cfg(debug_assertions)]
+            {
+                all_sums.extend_from_slice(&sums);
+                if stage + 1 == aux_stages.len() {
+                    let mut segments = prev_aux.to_vec();
+                    segments.push(aux.clone());
+                    crate::check_constraints::check_constraints_with_aux(
+                        air,
+                        None,
+                        main,
+                        Some(&crate::multi_stage::concat_aux(&segments)),
+                        challenges,
+                        &all_sums,
+                        public_values,
+                    );
+                }
+            }
+
+            (aux, sums)
+        },
+        public_values,
+        |_| {},
+    )
+}
+
+/// Prove an AIR with preprocessed columns, committed ahead of time by `setup`.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_key<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    pk: &ProverKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    #[cfg(debug_assertions)]
+    crate::check_constraints::check_constraints_with_aux::<Val<SC>, Val<SC>, A>(
+        air,
+        air.preprocessed_trace().as_ref(),
+        &trace,
+        None,
+        &[],
+        &[],
+        public_values,
+    );
+
+    prove_stages(
+        config,
+        air,
+        challenger,
+        pk.preprocessed.as_ref(),
+        trace,
+        &[],
+        |_, _, _, _| unreachable!("there are no auxiliary stages"),
+        public_values,
+        |_| {},
+    )
+}
+
+/// Prove several AIRs, whose traces may have different heights, in one proof.
+///
+/// All traces are committed together, then all quotients together, and everything is opened with a
+/// single opening argument. To prove different kinds of AIR at once, wrap them in an enum.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(airs.len(), traces.len(), "expected one trace per AIR");
 
-    let symbolic_constraints = get_symbolic_constraints::<Val<SC>, A>(air, 0, public_values.len());
-    let constraint_count = symbolic_constraints.len();
-    let constraint_degree = symbolic_constraints
+    #[cfg(debug_assertions)]
+    for (air, trace, public_values) in izip!(airs, &traces, public_values) {
+        crate::check_constraints::check_constraints(air, trace, public_values);
+    }
+
+    prove_airs(
+        config,
+        airs,
+        challenger,
+        traces,
+        &vec![vec![]; airs.len()],
+        |_, _, _, _, _| unreachable!("there are no auxiliary stages"),
+        public_values,
+    )
+}
+
+/// Like `prove_multi`, for AIRs whose traces are committed in stages as in `prove_multi_stage`.
+///
+/// The auxiliary segments of every AIR's `i`-th stage are committed together, from challenges
+/// shared between all AIRs, so a lookup argument can span several AIRs: the sums exposed by all of
+/// them must add up to zero.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi_with_aux<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(airs.len(), traces.len(), "expected one trace per AIR");
+    let aux_stages = airs.iter().map(|air| air.aux_stages()).collect_vec();
+    #[cfg(debug_assertions)]
+    let mut all_sums = vec![vec![]; airs.len()];
+
+    #[cfg(debug_assertions)]
+    for (air, stages, trace, public_values) in izip!(airs, &aux_stages, &traces, public_values) {
+        if stages.is_empty() {
+            crate::check_constraints::check_constraints_with_aux(
+                air,
+                None,
+                trace,
+                None,
+                &[],
+                &[],
+                public_values,
+            );
+        }
+    }
+
+    prove_airs(
+        config,
+        airs,
+        challenger,
+        traces,
+        &aux_stages,
+        |i, stage, main, prev_aux, challenges| {
+            let (aux, sums) =
+                generate_aux_stage(&airs[i], &aux_stages[i], stage, main, prev_aux, challenges);
+
+            #[cfg(debug_assertions)]
+            {
+                all_sums[i].extend_from_slice(&sums);
+                if stage + 1 == aux_stages[i].len() {
+                    let mut segments = prev_aux.to_vec();
+                    segments.push(aux.clone());
+                    crate::check_constraints::check_constraints_with_aux(
+                        &airs[i],
+                        None,
+                        main,
+                        Some(&crate::multi_stage::concat_aux(&segments)),
+                        challenges,
+                        &all_sums[i],
+                        &public_values[i],
+                    );
+                }
+            }
+
+            (aux, sums)
+        },
+        public_values,
+    )
+}
+
+/// Prove `airs` in one proof, with `aux_stages` holding the auxiliary stages of each AIR and
+/// `generate_aux` generating the segment of one AIR's stage along with the sums it exposes.
+#[allow(clippy::type_complexity)]
+fn prove_airs<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    aux_stages: &[Vec<AuxStage>],
+    mut generate_aux: impl FnMut(
+        usize,
+        usize,
+        &RowMajorMatrix<Val<SC>>,
+        &[RowMajorMatrix<SC::Challenge>],
+        &[SC::Challenge],
+    ) -> (RowMajorMatrix<SC::Challenge>, Vec<SC::Challenge>),
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(
+        airs.len(),
+        public_values.len(),
+        "expected one set of public values per AIR"
+    );
+
+    let pcs = config.pcs();
+    let degree_bits = traces
+        .iter()
+        .map(|trace| log2_strict_usize(trace.height()))
+        .collect_vec();
+    let trace_domains = traces
         .iter()
-        .map(SymbolicExpression::degree_multiple)
-        .max()
-        .unwrap_or(0);
-    let log_quotient_degree = log2_ceil_usize(constraint_degree - 1);
+        .map(|trace| pcs.natural_domain_for_degree(trace.height()))
+        .collect_vec();
+    let constraints = izip!(airs, aux_stages, public_values)
+        .map(|(air, stages, public_values)| {
+            get_symbolic_constraints_with_aux::<Val<SC>, A>(
+                air,
+                0,
+                aux_width(stages),
+                num_aux_challenges(stages),
+                num_aux_sums(stages),
+                public_values.len(),
+            )
+        })
+        .collect_vec();
+    let shapes = izip!(airs, public_values, &constraints)
+        .map(|(air, public_values, constraints)| {
+            AirShape::new(
+                <A as BaseAir<Val<SC>>>::width(air),
+                public_values.len(),
+                constraints,
+            )
+        })
+        .collect_vec();
+    let log_quotient_degrees = shapes
+        .iter()
+        .map(|shape| effective_log_quotient_degree(config, shape.max_constraint_degree))
+        .collect_vec();
+    let quotient_domains = izip!(&trace_domains, &degree_bits, &log_quotient_degrees)
+        .map(|(trace_domain, &degree_bits, &log_quotient_degree)| {
+            trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree))
+        })
+        .collect_vec();
+
+    // The auxiliary segments are generated from the main traces, so keep them around if we need
+    // them.
+    let num_aux_rounds = aux_stages.iter().map(Vec::len).max().unwrap_or(0);
+    let main_traces = if num_aux_rounds > 0 {
+        traces.clone()
+    } else {
+        vec![]
+    };
+
+    // In zero-knowledge mode, the quotient masks follow the traces, one per AIR.
+    let (trace_commit, trace_data) = info_span!("commit to trace data").in_scope(|| {
+        commit_traces(
+            config,
+            izip!(trace_domains.iter().copied(), traces)
+                .chain(
+                    quotient_domains
+                        .iter()
+                        .filter_map(|quotient_domain| quotient_mask(config, quotient_domain)),
+                )
+                .collect_vec(),
+        )
+    });
+
+    // Observe the instance.
+    for &log_degree in &degree_bits {
+        // degree < 2^255 so we can safely cast log_degree to a u8.
+        challenger.observe(Val::<SC>::from_u8(log_degree as u8));
+    }
+    for shape in &shapes {
+        shape.observe(challenger);
+    }
+    challenger.observe(trace_commit.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+
+    // The `round`-th stages of all AIRs are committed together, each AIR taking as many of the
+    // round's challenges as its stage needs.
+    let mut aux_traces = vec![Vec::new(); airs.len()];
+    let mut aux_challenges = vec![Vec::new(); airs.len()];
+    let mut aux_sums: Vec<Vec<Vec<SC::Challenge>>> = vec![Vec::new(); airs.len()];
+    let mut aux_commits = Vec::with_capacity(num_aux_rounds);
+    let mut aux_data = Vec::with_capacity(num_aux_rounds);
+    for round in 0..num_aux_rounds {
+        let num_challenges = aux_stages
+            .iter()
+            .filter_map(|stages| stages.get(round))
+            .map(|stage| stage.num_challenges)
+            .max()
+            .unwrap();
+        let challenges = (0..num_challenges)
+            .map(|_| challenger.sample_algebra_element::<SC::Challenge>())
+            .collect_vec();
+
+        let mut segments = Vec::new();
+        for (i, stages) in aux_stages.iter().enumerate() {
+            let Some(stage) = stages.get(round) else {
+                continue;
+            };
+            aux_challenges[i].extend_from_slice(&challenges[..stage.num_challenges]);
+            let (aux_trace, sums) = generate_aux(
+                i,
+                round,
+                &main_traces[i],
+                &aux_traces[i],
+                &aux_challenges[i],
+            );
+            segments.push((trace_domains[i], aux_trace.flatten_to_base()));
+            aux_traces[i].push(aux_trace);
+            aux_sums[i].push(sums);
+        }
+
+        let (aux_commit, data) = info_span!("commit to auxiliary traces", round)
+            .in_scope(|| commit_traces(config, segments));
+        challenger.observe(aux_commit.clone());
+        for &sum in aux_sums.iter().filter_map(|sums| sums.get(round)).flatten() {
+            challenger.observe_algebra_element(sum);
+        }
+        aux_commits.push(aux_commit);
+        aux_data.push(data);
+    }
+    drop(aux_traces);
+    drop(main_traces);
+
+    let alpha: SC::Challenge = challenger.sample_algebra_element();
+
+    let mut quotient_chunks = Vec::new();
+    for (i, air) in airs.iter().enumerate() {
+        let quotient_domain = quotient_domains[i];
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&trace_data, i, quotient_domain);
+        // Within each round, the segments are committed in the order of the AIRs having one.
+        let aux_on_quotient_domain = (0..aux_stages[i].len())
+            .map(|round| {
+                let idx = aux_stages[..i]
+                    .iter()
+                    .filter(|stages| stages.len() > round)
+                    .count();
+                pcs.get_evaluations_on_domain(&aux_data[round], idx, quotient_domain)
+            })
+            .collect_vec();
+        let dag = config
+            .evaluates_constraint_dag()
+            .then(|| DagEvaluator::new(&constraints[i]));
+        let mut quotient_values = quotient_values(
+            air,
+            dag.as_ref(),
+            &public_values[i],
+            trace_domains[i],
+            quotient_domain,
+            None,
+            trace_on_quotient_domain,
+            &aux_on_quotient_domain,
+            &aux_challenges[i],
+            &aux_sums[i].concat(),
+            alpha,
+            shapes[i].constraint_count,
+            &shapes[i].rows,
+        );
+        if config.is_zk() {
+            add_quotient_mask(
+                config,
+                &trace_data,
+                airs.len() + i,
+                quotient_domain,
+                &mut quotient_values,
+            );
+        }
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        let quotient_degree = 1 << log_quotient_degrees[i];
+        quotient_chunks.extend(izip!(
+            quotient_domain.split_domains(quotient_degree),
+            quotient_domain.split_evals(quotient_degree, quotient_flat)
+        ));
+    }
+
+    let (quotient_commit, quotient_data) =
+        info_span!("commit to quotient poly chunks").in_scope(|| pcs.commit(quotient_chunks));
+    challenger.observe(quotient_commit.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
+        let mut rounds = vec![(
+            &trace_data,
+            izip!(airs, &trace_domains)
+                .map(|(air, domain)| {
+                    window_points(domain, zeta, <A as BaseAir<Val<SC>>>::window_size(air))
+                })
+                .chain(
+                    (0..airs.len())
+                        .filter(|_| config.is_zk())
+                        .map(|_| vec![zeta]),
+                )
+                .collect_vec(),
+        )];
+        rounds.extend(aux_data.iter().enumerate().map(|(round, data)| {
+            (
+                data,
+                izip!(aux_stages, &trace_domains)
+                    .filter(|(stages, _)| stages.len() > round)
+                    .map(|(_, domain)| vec![zeta, domain.next_point(zeta).unwrap()])
+                    .collect_vec(),
+            )
+        }));
+        rounds.push((
+            &quotient_data,
+            // open every chunk at zeta
+            log_quotient_degrees
+                .iter()
+                .flat_map(|&log_quotient_degree| (0..1 << log_quotient_degree).map(|_| vec![zeta]))
+                .collect_vec(),
+        ));
+        pcs.open(rounds, challenger)
+    });
+    // The rounds come back in the order we opened them: traces and quotient masks if any, each
+    // round of auxiliary segments, then quotient chunks.
+    let mut opened_rounds = opened_values.into_iter();
+    let mut trace_round = opened_rounds.next().unwrap();
+    let mut quotient_masks = trace_round
+        .split_off(airs.len())
+        .into_iter()
+        .map(|mut v| v.pop().unwrap());
+    let mut aux_rounds = opened_rounds
+        .by_ref()
+        .take(num_aux_rounds)
+        .map(Vec::into_iter)
+        .collect_vec();
+    let mut quotient_round = opened_rounds.next().unwrap().into_iter();
+    let opened_values = izip!(trace_round, &log_quotient_degrees, aux_stages, aux_sums)
+        .map(|(trace_points, &log_quotient_degree, stages, aux_sums)| {
+            let mut trace_points = trace_points.into_iter();
+            let trace_local = trace_points.next().unwrap();
+            let trace_next = trace_points.next().unwrap();
+            let trace_after_next = trace_points.collect_vec();
+            let (aux_trace_local, aux_trace_next) = aux_rounds[..stages.len()]
+                .iter_mut()
+                .map(|round| {
+                    let mut points = round.next().unwrap();
+                    let next = points.pop().unwrap();
+                    let local = points.pop().unwrap();
+                    (local, next)
+                })
+                .unzip();
+            let quotient_chunks = quotient_round
+                .by_ref()
+                .take(1 << log_quotient_degree)
+                .map(|mut v| v.pop().unwrap())
+                .collect_vec();
+            OpenedValues {
+                preprocessed_local: vec![],
+                preprocessed_next: vec![],
+                trace_local,
+                trace_next,
+                trace_after_next,
+                aux_trace_local,
+                aux_trace_next,
+                aux_sums,
+                quotient_chunks,
+                quotient_mask: quotient_masks.next().unwrap_or_default(),
+            }
+        })
+        .collect_vec();
+
+    MultiProof {
+        commitments: Commitments {
+            trace: trace_commit,
+            aux_traces: aux_commits,
+            quotient_chunks: quotient_commit,
+        },
+        opened_values,
+        opening_proof,
+        degree_bits,
+    }
+}
+
+/// Prove one AIR over several independent traces of the same height in one proof.
+///
+/// All traces are committed in a single round. Their quotients are combined with the powers of a
+/// random challenge into one quotient, so the proof carries a single set of quotient chunks
+/// however many traces there are.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_identical_batch<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> BatchProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert!(!traces.is_empty(), "expected at least one trace");
+    assert_eq!(
+        traces.len(),
+        public_values.len(),
+        "expected one set of public values per trace"
+    );
+    let degree = traces[0].height();
+    assert!(
+        traces.iter().all(|trace| trace.height() == degree),
+        "all traces must have the same height"
+    );
+    let num_public_values = public_values[0].len();
+    assert!(
+        public_values
+            .iter()
+            .all(|public_values| public_values.len() == num_public_values),
+        "all instances must have the same number of public values"
+    );
+
+    #[cfg(debug_assertions)]
+    for (trace, public_values) in izip!(&traces, public_values) {
+        crate::check_constraints::check_constraints(air, trace, public_values);
+    }
+
+    let log_degree = log2_strict_usize(degree);
+    let constraints = get_symbolic_constraints::<Val<SC>, A>(air, 0, num_public_values);
+    let shape = AirShape::new(
+        <A as BaseAir<Val<SC>>>::width(air),
+        num_public_values,
+        &constraints,
+    );
+    let log_quotient_degree = effective_log_quotient_degree(config, shape.max_constraint_degree);
     let quotient_degree = 1 << log_quotient_degree;
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
+    let quotient_domain =
+        trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
+    let num_instances = traces.len();
 
-    let (trace_commit, trace_data) =
-        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]));
+    // In zero-knowledge mode, the mask of the combined quotient follows the traces.
+    let (trace_commit, trace_data) = info_span!("commit to trace data").in_scope(|| {
+        commit_traces(
+            config,
+            traces
+                .into_iter()
+                .map(|trace| (trace_domain, trace))
+                .chain(quotient_mask(config, &quotient_domain))
+                .collect_vec(),
+        )
+    });
 
     // Observe the instance.
     // degree < 2^255 so we can safely cast log_degree to a u8.
     challenger.observe(Val::<SC>::from_u8(log_degree as u8));
-    // TODO: Might be best practice to include other instance data here; see verifier comment.
-
+    challenger.observe(Val::<SC>::from_usize(num_instances));
+    shape.observe(challenger);
     challenger.observe(trace_commit.clone());
-    challenger.observe_slice(public_values);
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
     let alpha: SC::Challenge = challenger.sample_algebra_element();
+    // Combines the quotients of the instances.
+    let beta: SC::Challenge = challenger.sample_algebra_element();
+
+    let dag = config
+        .evaluates_constraint_dag()
+        .then(|| DagEvaluator::new(&constraints));
+    let mut combined_quotient = vec![SC::Challenge::ZERO; quotient_domain.size()];
+    for (i, (public_values, beta_power)) in izip!(public_values, beta.powers()).enumerate() {
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&trace_data, i, quotient_domain);
+        let quotient_values = quotient_values(
+            air,
+            dag.as_ref(),
+            public_values,
+            trace_domain,
+            quotient_domain,
+            None,
+            trace_on_quotient_domain,
+            &[],
+            &[],
+            &[],
+            alpha,
+            shape.constraint_count,
+            &shape.rows,
+        );
+        combined_quotient
+            .par_iter_mut()
+            .zip(quotient_values.par_iter())
+            .for_each(|(acc, &value)| *acc += beta_power * value);
+    }
+    if config.is_zk() {
+        add_quotient_mask(
+            config,
+            &trace_data,
+            num_instances,
+            quotient_domain,
+            &mut combined_quotient,
+        );
+    }
+    let quotient_flat = RowMajorMatrix::new_col(combined_quotient).flatten_to_base();
+    let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
+    let qc_domains = quotient_domain.split_domains(quotient_degree);
+
+    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
+        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
+    challenger.observe(quotient_commit.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
+        let points = window_points(
+            &trace_domain,
+            zeta,
+            <A as BaseAir<Val<SC>>>::window_size(air),
+        );
+        let mut trace_points = vec![points; num_instances];
+        if config.is_zk() {
+            trace_points.push(vec![zeta]);
+        }
+        pcs.open(
+            vec![
+                (&trace_data, trace_points),
+                (
+                    &quotient_data,
+                    // open every chunk at zeta
+                    (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
+                ),
+            ],
+            challenger,
+        )
+    });
+    let mut opened_rounds = opened_values.into_iter();
+    let mut trace_round = opened_rounds.next().unwrap();
+    let quotient_mask = trace_round
+        .split_off(num_instances)
+        .pop()
+        .map_or_else(Vec::new, |mut v| v.pop().unwrap());
+    let quotient_chunks = opened_rounds
+        .next()
+        .unwrap()
+        .into_iter()
+        .map(|mut v| v.pop().unwrap())
+        .collect_vec();
+    let opened_values = trace_round
+        .into_iter()
+        .map(|trace_points| {
+            let mut trace_points = trace_points.into_iter();
+            let trace_local = trace_points.next().unwrap();
+            let trace_next = trace_points.next().unwrap();
+            let trace_after_next = trace_points.collect_vec();
+            OpenedValues {
+                preprocessed_local: vec![],
+                preprocessed_next: vec![],
+                trace_local,
+                trace_next,
+                trace_after_next,
+                aux_trace_local: vec![],
+                aux_trace_next: vec![],
+                aux_sums: vec![],
+                quotient_chunks: vec![],
+                quotient_mask: vec![],
+            }
+        })
+        .collect_vec();
+
+    BatchProof {
+        commitments: Commitments {
+            trace: trace_commit,
+            aux_traces: vec![],
+            quotient_chunks: quotient_commit,
+        },
+        opened_values,
+        quotient_chunks,
+        quotient_mask,
+        opening_proof,
+        degree_bits: log_degree,
+    }
+}
+
+/// The phases of proving, each reported by `prove_stages` as it ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ProvingPhase {
+    /// Committing to the main trace and any auxiliary segments.
+    Commit,
+    /// Computing and committing to the quotient polynomial.
+    Quotient,
+    /// Opening all commitments.
+    Open,
+}
+
+/// Generate the auxiliary segment of `stage` and the sums it exposes, checking both against the
+/// shape `aux_stages` declares.
+fn generate_aux_stage<F, EF, A>(
+    air: &A,
+    aux_stages: &[AuxStage],
+    stage: usize,
+    main: &RowMajorMatrix<F>,
+    prev_aux: &[RowMajorMatrix<EF>],
+    challenges: &[EF],
+) -> (RowMajorMatrix<EF>, Vec<EF>)
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiStageAir<F>,
+{
+    let aux = air.generate_aux_trace(stage, main, prev_aux, challenges);
+    assert_eq!(
+        aux.width(),
+        aux_stages[stage].width,
+        "auxiliary segment {stage} has the wrong width"
+    );
+    let sums = air.aux_sums(stage, &aux);
+    assert_eq!(
+        sums.len(),
+        aux_stages[stage].num_sums,
+        "auxiliary segment {stage} exposes the wrong number of sums"
+    );
+    (aux, sums)
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn prove_stages<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+    trace: RowMajorMatrix<Val<SC>>,
+    aux_stages: &[AuxStage],
+    mut generate_aux: impl FnMut(
+        usize,
+        &RowMajorMatrix<Val<SC>>,
+        &[RowMajorMatrix<SC::Challenge>],
+        &[SC::Challenge],
+    ) -> (RowMajorMatrix<SC::Challenge>, Vec<SC::Challenge>),
+    public_values: &Vec<Val<SC>>,
+    mut end_phase: impl FnMut(ProvingPhase),
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let degree = trace.height();
+    let log_degree = log2_strict_usize(degree);
+
+    let preprocessed_width = preprocessed.map_or(0, |preprocessed| preprocessed.width);
+    if let Some(preprocessed) = preprocessed {
+        assert_eq!(
+            preprocessed.degree_bits, log_degree,
+            "trace height does not match the preprocessed trace"
+        );
+    }
+
+    let symbolic_constraints = get_symbolic_constraints_with_aux::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        aux_width(aux_stages),
+        num_aux_challenges(aux_stages),
+        num_aux_sums(aux_stages),
+        public_values.len(),
+    );
+    let shape = AirShape::new(
+        <A as BaseAir<Val<SC>>>::width(air),
+        public_values.len(),
+        &symbolic_constraints,
+    );
+    let log_quotient_degree = effective_log_quotient_degree(config, shape.max_constraint_degree);
+    let quotient_degree = 1 << log_quotient_degree;
 
+    let pcs = config.pcs();
+    let trace_domain = pcs.natural_domain_for_degree(degree);
     let quotient_domain =
         trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
 
+    // The auxiliary segments are generated from the main trace, so keep it around if we need it.
+    let main_trace = (!aux_stages.is_empty()).then(|| trace.clone());
+
+    // In zero-knowledge mode, the quotient mask follows the trace.
+    let (trace_commit, trace_data) = info_span!("commit to trace data").in_scope(|| {
+        commit_traces(
+            config,
+            iter::once((trace_domain, trace))
+                .chain(quotient_mask(config, &quotient_domain))
+                .collect_vec(),
+        )
+    });
+
+    // Observe the instance.
+    // degree < 2^255 so we can safely cast log_degree to a u8.
+    challenger.observe(Val::<SC>::from_u8(log_degree as u8));
+    shape.observe(challenger);
+    if let Some(preprocessed) = preprocessed {
+        challenger.observe(preprocessed.commitment.clone());
+    }
+
+    challenger.observe(trace_commit.clone());
+    challenger.observe_slice(public_values);
+
+    let mut aux_traces = Vec::with_capacity(aux_stages.len());
+    let mut aux_challenges = Vec::with_capacity(num_aux_challenges(aux_stages));
+    let mut aux_sums: Vec<Vec<SC::Challenge>> = Vec::with_capacity(aux_stages.len());
+    let mut aux_commits = Vec::with_capacity(aux_stages.len());
+    let mut aux_data = Vec::with_capacity(aux_stages.len());
+    for (stage, aux_stage) in aux_stages.iter().enumerate() {
+        aux_challenges.extend(
+            (0..aux_stage.num_challenges)
+                .map(|_| challenger.sample_algebra_element::<SC::Challenge>()),
+        );
+        let (aux_trace, sums) = generate_aux(
+            stage,
+            main_trace.as_ref().unwrap(),
+            &aux_traces,
+            &aux_challenges,
+        );
+        let (aux_commit, data) = info_span!("commit to auxiliary trace", stage)
+            .in_scope(|| commit_traces(config, vec![(trace_domain, aux_trace.flatten_to_base())]));
+        challenger.observe(aux_commit.clone());
+        for &sum in &sums {
+            challenger.observe_algebra_element(sum);
+        }
+        aux_traces.push(aux_trace);
+        aux_sums.push(sums);
+        aux_commits.push(aux_commit);
+        aux_data.push(data);
+    }
+    drop(aux_traces);
+    end_phase(ProvingPhase::Commit);
+
+    let alpha: SC::Challenge = challenger.sample_algebra_element();
+
+    let preprocessed_on_quotient_domain = preprocessed.map(|preprocessed| {
+        pcs.get_evaluations_on_domain(&preprocessed.prover_data, 0, quotient_domain)
+    });
     let trace_on_quotient_domain = pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+    let aux_on_quotient_domain = aux_data
+        .iter()
+        .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain))
+        .collect_vec();
 
-    let quotient_values = quotient_values(
+    let dag = config
+        .evaluates_constraint_dag()
+        .then(|| DagEvaluator::new(&symbolic_constraints));
+    let mut quotient_values = quotient_values(
         air,
+        dag.as_ref(),
         public_values,
         trace_domain,
         quotient_domain,
+        preprocessed_on_quotient_domain,
         trace_on_quotient_domain,
+        &aux_on_quotient_domain,
+        &aux_challenges,
+        &aux_sums.concat(),
         alpha,
-        constraint_count,
+        shape.constraint_count,
+        &shape.rows,
     );
+    if config.is_zk() {
+        add_quotient_mask(
+            config,
+            &trace_data,
+            1,
+            quotient_domain,
+            &mut quotient_values,
+        );
+    }
     let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
     let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
     let qc_domains = quotient_domain.split_domains(quotient_degree);
@@ -86,9 +1030,11 @@ where
     let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
         .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
     challenger.observe(quotient_commit.clone());
+    end_phase(ProvingPhase::Quotient);
 
     let commitments = Commitments {
         trace: trace_commit,
+        aux_traces: aux_commits,
         quotient_chunks: quotient_commit,
     };
 
@@ -96,25 +1042,78 @@ where
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
     let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
-        pcs.open(
-            vec![
-                (&trace_data, vec![vec![zeta, zeta_next]]),
-                (
-                    &quotient_data,
-                    // open every chunk at zeta
-                    (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
-                ),
-            ],
-            challenger,
-        )
+        let mut trace_points = vec![window_points(
+            &trace_domain,
+            zeta,
+            <A as BaseAir<Val<SC>>>::window_size(air),
+        )];
+        if config.is_zk() {
+            trace_points.push(vec![zeta]);
+        }
+        let mut rounds = vec![(&trace_data, trace_points)];
+        rounds.extend(
+            aux_data
+                .iter()
+                .map(|data| (data, vec![vec![zeta, zeta_next]])),
+        );
+        rounds.extend(
+            preprocessed
+                .map(|preprocessed| (&preprocessed.prover_data, vec![vec![zeta, zeta_next]])),
+        );
+        rounds.push((
+            &quotient_data,
+            // open every chunk at zeta
+            (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
+        ));
+        pcs.open(rounds, challenger)
     });
-    let trace_local = opened_values[0][0][0].clone();
-    let trace_next = opened_values[0][0][1].clone();
-    let quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+    end_phase(ProvingPhase::Open);
+    // The rounds come back in the order we opened them: trace and quotient mask if any, auxiliary
+    // segments, preprocessed columns if any, then quotient chunks.
+    let mut opened_rounds = opened_values.into_iter();
+    let mut trace_round = opened_rounds.next().unwrap().into_iter();
+    let mut trace_points = trace_round.next().unwrap().into_iter();
+    let quotient_mask = trace_round
+        .next()
+        .map_or_else(Vec::new, |mut v| v.pop().unwrap());
+    let trace_local = trace_points.next().unwrap();
+    let trace_next = trace_points.next().unwrap();
+    let trace_after_next = trace_points.collect_vec();
+    let (aux_trace_local, aux_trace_next) = opened_rounds
+        .by_ref()
+        .take(aux_stages.len())
+        .map(|mut round| {
+            let next = round[0].pop().unwrap();
+            let local = round[0].pop().unwrap();
+            (local, next)
+        })
+        .unzip();
+    let (preprocessed_local, preprocessed_next) = match preprocessed {
+        Some(_) => {
+            let mut round = opened_rounds.next().unwrap();
+            let next = round[0].pop().unwrap();
+            let local = round[0].pop().unwrap();
+            (local, next)
+        }
+        None => (vec![], vec![]),
+    };
+    let quotient_chunks = opened_rounds
+        .next()
+        .unwrap()
+        .into_iter()
+        .map(|mut v| v.pop().unwrap())
+        .collect_vec();
     let opened_values = OpenedValues {
+        preprocessed_local,
+        preprocessed_next,
         trace_local,
         trace_next,
+        trace_after_next,
+        aux_trace_local,
+        aux_trace_next,
+        aux_sums,
         quotient_chunks,
+        quotient_mask,
     };
     Proof {
         commitments,
@@ -124,29 +1123,133 @@ where
     }
 }
 
+/// Commit to traces over their domains, randomized by `Pcs::commit_zk` in zero-knowledge mode.
+#[allow(clippy::type_complexity)]
+fn commit_traces<SC: StarkGenericConfig>(
+    config: &SC,
+    evaluations: Vec<(Domain<SC>, RowMajorMatrix<Val<SC>>)>,
+) -> (
+    Com<SC>,
+    <SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::ProverData,
+) {
+    if config.is_zk() {
+        assert!(
+            <SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::ZK,
+            "zero-knowledge mode needs a PCS which supports it"
+        );
+        config.pcs().commit_zk(evaluations)
+    } else {
+        config.pcs().commit(evaluations)
+    }
+}
+
+/// In zero-knowledge mode, the zero matrix which `commit_traces` randomizes into the polynomial
+/// masking the quotient over `quotient_domain`.
+///
+/// The mask is committed before the constraints are folded, so the prover can't choose it to
+/// cancel them out.
+fn quotient_mask<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_domain: &Domain<SC>,
+) -> Option<(Domain<SC>, RowMajorMatrix<Val<SC>>)> {
+    config.is_zk().then(|| {
+        let domain = quotient_mask_domain(config, quotient_domain);
+        let ext_degree = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
+        let zeros = Val::<SC>::zero_vec(domain.size() * ext_degree);
+        (domain, RowMajorMatrix::new(zeros, ext_degree))
+    })
+}
+
+/// Add the quotient mask committed as matrix `idx` of `trace_data` to the quotient's values over
+/// `quotient_domain`.
+fn add_quotient_mask<SC: StarkGenericConfig>(
+    config: &SC,
+    trace_data: &<SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::ProverData,
+    idx: usize,
+    quotient_domain: Domain<SC>,
+    quotient_values: &mut [SC::Challenge],
+) {
+    let mask = config
+        .pcs()
+        .get_evaluations_on_domain(trace_data, idx, quotient_domain);
+    quotient_values
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, value)| {
+            *value += SC::Challenge::from_basis_coefficients_slice(&mask.row_slice(i)).unwrap();
+        });
+}
+
 #[instrument(name = "compute quotient polynomial", skip_all)]
+#[allow(clippy::too_many_arguments)]
 fn quotient_values<SC, A, Mat>(
     air: &A,
+    dag: Option<&DagEvaluator<SC>>,
     public_values: &Vec<Val<SC>>,
     trace_domain: Domain<SC>,
     quotient_domain: Domain<SC>,
+    preprocessed_on_quotient_domain: Option<Mat>,
     trace_on_quotient_domain: Mat,
+    aux_on_quotient_domain: &[Mat],
+    aux_challenges: &[SC::Challenge],
+    aux_sums: &[SC::Challenge],
     alpha: SC::Challenge,
     constraint_count: usize,
+    rows: &[usize],
 ) -> Vec<SC::Challenge>
 where
     SC: StarkGenericConfig,
-    A: for<'a> Air<ProverConstraintFolder<'a, SC>>,
+    A: BaseAir<Val<SC>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
     Mat: Matrix<Val<SC>> + Sync,
 {
     let quotient_size = quotient_domain.size();
+    let preprocessed_width = preprocessed_on_quotient_domain
+        .as_ref()
+        .map_or(0, |preprocessed| preprocessed.width());
     let width = trace_on_quotient_domain.width();
+    let aux_width = aux_on_quotient_domain
+        .iter()
+        .map(|aux| aux.width() / SC::Challenge::DIMENSION)
+        .sum();
     let mut sels = debug_span!("Compute Selectors")
         .in_scope(|| trace_domain.selectors_on_coset(quotient_domain));
 
     let qdb = log2_strict_usize(quotient_domain.size()) - log2_strict_usize(trace_domain.size());
     let next_step = 1 << qdb;
 
+    let periodic_columns = PeriodicColumn::interpolate_all(
+        &trace_domain,
+        &<A as BaseAir<Val<SC>>>::periodic_columns(air),
+    )
+    .expect(
+        "periodic columns need a multiplicative trace domain, and periods which are powers of two \
+         dividing the trace height",
+    );
+    let mut periodic_on_quotient_domain = periodic_columns
+        .iter()
+        .map(|column| column.eval_on_coset(&quotient_domain))
+        .collect_vec();
+
+    let mut row_selectors_on_quotient_domain =
+        row_selectors_on_coset(&trace_domain, &quotient_domain, rows);
+
+    // For windows of more than two rows, the factors turning `is_transition` into each larger
+    // `is_transition_window`, one column per window size.
+    let window_size = <A as BaseAir<Val<SC>>>::window_size(air);
+    let mut window_factors = vec![Vec::with_capacity(quotient_size); window_size.saturating_sub(2)];
+    if window_size > 2 {
+        let points = iter::successors(Some(quotient_domain.first_point()), |&x| {
+            quotient_domain.next_point(x)
+        });
+        for x in points.take(quotient_size) {
+            let factors = transition_window_factors(&trace_domain, x, window_size)
+                .expect("windows of more than two rows need a multiplicative trace domain");
+            for (column, factor) in izip!(&mut window_factors, factors) {
+                column.push(factor);
+            }
+        }
+    }
+
     // We take PackedVal::<SC>::WIDTH worth of values at a time from a quotient_size slice, so we need to
     // pad with default values in the case where quotient_size is smaller than PackedVal::<SC>::WIDTH.
     for _ in quotient_size..PackedVal::<SC>::WIDTH {
@@ -154,6 +1257,13 @@ where
         sels.is_last_row.push(Val::<SC>::default());
         sels.is_transition.push(Val::<SC>::default());
         sels.inv_vanishing.push(Val::<SC>::default());
+        for column in window_factors
+            .iter_mut()
+            .chain(&mut periodic_on_quotient_domain)
+            .chain(&mut row_selectors_on_quotient_domain)
+        {
+            column.push(Val::<SC>::default());
+        }
     }
 
     let mut alpha_powers = alpha.powers().take(constraint_count).collect_vec();
@@ -169,6 +1279,8 @@ where
         })
         .collect();
 
+    // Each value is computed independently and collected in order, so the quotient doesn't depend
+    // on how the work is scheduled across threads.
     (0..quotient_size)
         .into_par_iter()
         .step_by(PackedVal::<SC>::WIDTH)
@@ -178,26 +1290,89 @@ where
             let is_first_row = *PackedVal::<SC>::from_slice(&sels.is_first_row[i_range.clone()]);
             let is_last_row = *PackedVal::<SC>::from_slice(&sels.is_last_row[i_range.clone()]);
             let is_transition = *PackedVal::<SC>::from_slice(&sels.is_transition[i_range.clone()]);
+            let is_transition_windows = window_factors
+                .iter()
+                .map(|column| {
+                    is_transition * *PackedVal::<SC>::from_slice(&column[i_range.clone()])
+                })
+                .collect_vec();
+            let row_selectors = izip!(rows, &row_selectors_on_quotient_domain)
+                .map(|(&row, column)| (row, *PackedVal::<SC>::from_slice(&column[i_range.clone()])))
+                .collect_vec();
+            let periodic_values = periodic_on_quotient_domain
+                .iter()
+                .map(|column| *PackedVal::<SC>::from_slice(&column[i_range.clone()]))
+                .collect_vec();
             let inv_vanishing = *PackedVal::<SC>::from_slice(&sels.inv_vanishing[i_range]);
 
+            let preprocessed = RowMajorMatrix::new(
+                preprocessed_on_quotient_domain
+                    .as_ref()
+                    .map_or(vec![], |preprocessed| {
+                        preprocessed.vertically_packed_row_pair::<PackedVal<SC>>(i_start, next_step)
+                    }),
+                preprocessed_width,
+            );
             let main = RowMajorMatrix::new(
-                trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
+                if window_size == 2 {
+                    trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step)
+                } else {
+                    (0..window_size)
+                        .flat_map(|offset| {
+                            trace_on_quotient_domain
+                                .vertically_packed_row(i_start + offset * next_step)
+                        })
+                        .collect_vec()
+                },
                 width,
             );
 
+            // The auxiliary segments are committed flattened to the base field, so regroup each
+            // run of DIMENSION packed base columns into one packed challenge column.
+            let aux_packed = aux_on_quotient_domain
+                .iter()
+                .map(|aux| aux.vertically_packed_row_pair::<PackedVal<SC>>(i_start, next_step))
+                .collect_vec();
+            let aux = RowMajorMatrix::new(
+                [0, 1]
+                    .into_iter()
+                    .flat_map(|offset| {
+                        aux_packed.iter().flat_map(move |packed| {
+                            let row_len = packed.len() / 2;
+                            packed[offset * row_len..(offset + 1) * row_len]
+                                .chunks_exact(SC::Challenge::DIMENSION)
+                                .map(|coeffs| {
+                                    PackedChallenge::<SC>::from_basis_coefficients_fn(|i| coeffs[i])
+                                })
+                        })
+                    })
+                    .collect_vec(),
+                aux_width,
+            );
+
             let accumulator = PackedChallenge::<SC>::ZERO;
             let mut folder = ProverConstraintFolder {
+                preprocessed: preprocessed.as_view(),
                 main: main.as_view(),
+                aux: aux.as_view(),
+                aux_challenges,
+                aux_sums,
+                periodic_values,
                 public_values,
                 is_first_row,
                 is_last_row,
                 is_transition,
+                is_transition_windows,
+                row_selectors,
                 alpha_powers: &alpha_powers,
                 decomposed_alpha_powers: &decomposed_alpha_powers,
                 accumulator,
                 constraint_index: 0,
             };
-            air.eval(&mut folder);
+            match dag {
+                Some(dag) => dag.eval(&mut folder),
+                None => air.eval(&mut folder),
+            }
 
             // quotient(x) = constraints(x) / Z_H(x)
             let quotient = folder.accumulator * inv_vanishing;
@@ -211,3 +1386,182 @@ where
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_air::AirBuilder;
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::ExtensionMmcs;
+    use p3_dft::Radix2DitParallel;
+    use p3_field::Field;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::{HidingFriPcs, create_test_fri_config};
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use super::*;
+    use crate::{StarkConfig, verify, verify_identical_batch, verify_multi};
+
+    type Val = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type ValMmcs = MerkleTreeHidingMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        MyHash,
+        MyCompress,
+        SmallRng,
+        8,
+        4,
+    >;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type Dft = Radix2DitParallel<Val>;
+    type MyPcs = HidingFriPcs<Val, Dft, ValMmcs, ChallengeMmcs, SmallRng>;
+    type MyConfig = StarkConfig<MyPcs, Challenge, Challenger>;
+
+    /// Asserts `next = local^2` on transitions.
+    struct SquaringAir;
+
+    impl<F> BaseAir<F> for SquaringAir {
+        fn width(&self) -> usize {
+            1
+        }
+    }
+
+    impl<AB: AirBuilder> Air<AB> for SquaringAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let (local, next) = (main.row_slice(0), main.row_slice(1));
+            let x: AB::Expr = local[0].into();
+            builder.when_transition().assert_eq(x.square(), next[0]);
+        }
+    }
+
+    fn trace(start: u32) -> RowMajorMatrix<Val> {
+        let values = iter::successors(Some(Val::from_u32(start)), |x| Some(x.square()))
+            .take(1 << 4)
+            .collect();
+        RowMajorMatrix::new_col(values)
+    }
+
+    // WARNING: a seeded SmallRng is only fit for tests; real provers need a cryptographic RNG.
+    fn setup(zk: bool) -> (MyConfig, Perm) {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let val_mmcs = ValMmcs::new(hash, compress, SmallRng::seed_from_u64(2));
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = create_test_fri_config(challenge_mmcs, 2);
+        let pcs = MyPcs::new(
+            Dft::default(),
+            val_mmcs,
+            fri_config,
+            4,
+            SmallRng::seed_from_u64(3),
+        );
+        let config = MyConfig::new(pcs);
+        (if zk { config.with_zk() } else { config }, perm)
+    }
+
+    /// The opened mask recombined into a challenge, which the verifier removes from the
+    /// recombined quotient chunks.
+    fn mask_value(quotient_mask: &[Challenge]) -> Challenge {
+        assert_eq!(
+            quotient_mask.len(),
+            <Challenge as BasedVectorSpace<Val>>::DIMENSION
+        );
+        quotient_mask
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| Challenge::ith_basis_element(i).unwrap() * c)
+            .sum()
+    }
+
+    #[test]
+    fn test_zk_quotient_is_masked() {
+        let (config, perm) = setup(true);
+        let proof = prove(
+            &config,
+            &SquaringAir,
+            &mut Challenger::new(perm.clone()),
+            trace(3),
+            &vec![],
+        );
+        // The verifier only accepts chunks which recombine to the quotient plus the mask, so a
+        // nonzero mask means the opened chunks are not those of the quotient itself.
+        assert_ne!(
+            mask_value(&proof.opened_values.quotient_mask),
+            Challenge::ZERO
+        );
+        verify(
+            &config,
+            &SquaringAir,
+            &mut Challenger::new(perm),
+            &proof,
+            &vec![],
+        )
+        .expect("verification failed");
+    }
+
+    #[test]
+    fn test_zk_quotient_is_masked_with_several_traces() {
+        let (config, perm) = setup(true);
+        let public_values = vec![vec![]; 2];
+
+        let proof = prove_multi(
+            &config,
+            &[SquaringAir, SquaringAir],
+            &mut Challenger::new(perm.clone()),
+            vec![trace(3), trace(5)],
+            &public_values,
+        );
+        for opened_values in &proof.opened_values {
+            assert_ne!(mask_value(&opened_values.quotient_mask), Challenge::ZERO);
+        }
+        verify_multi(
+            &config,
+            &[SquaringAir, SquaringAir],
+            &mut Challenger::new(perm.clone()),
+            &proof,
+            &public_values,
+        )
+        .expect("verification failed");
+
+        let proof = prove_identical_batch(
+            &config,
+            &SquaringAir,
+            &mut Challenger::new(perm.clone()),
+            vec![trace(3), trace(5)],
+            &public_values,
+        );
+        assert_ne!(mask_value(&proof.quotient_mask), Challenge::ZERO);
+        verify_identical_batch(
+            &config,
+            &SquaringAir,
+            &mut Challenger::new(perm),
+            &proof,
+            &public_values,
+        )
+        .expect("verification failed");
+    }
+
+    #[test]
+    fn test_quotient_is_unmasked_without_zk() {
+        let (config, perm) = setup(false);
+        let proof = prove(
+            &config,
+            &SquaringAir,
+            &mut Challenger::new(perm),
+            trace(3),
+            &vec![],
+        );
+        assert!(proof.opened_values.quotient_mask.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::Itertools;
+use p3_commit::PolynomialSpace;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+
+/// Evaluate the selector of row `row` at `x`, the Lagrange basis polynomial of the point of
+/// `trace_domain` for that row, taken modulo its size.
+pub(crate) fn row_selector_at_point<D, Ext>(trace_domain: &D, x: Ext, row: usize) -> Ext
+where
+    D: PolynomialSpace,
+    Ext: ExtensionField<D::Val>,
+{
+    trace_domain.lagrange_basis_at_point(row % trace_domain.size(), x)
+}
+
+/// Evaluate the selector of each of `rows` at every point of `coset`, which must be disjoint from
+/// `trace_domain`.
+///
+/// The selectors are normalized, and agree with `row_selector_at_point` at every point.
+pub(crate) fn row_selectors_on_coset<D: PolynomialSpace>(
+    trace_domain: &D,
+    coset: &D,
+    rows: &[usize],
+) -> Vec<Vec<D::Val>> {
+    let xs = iter::successors(Some(coset.first_point()), |&x| coset.next_point(x))
+        .take(coset.size())
+        .collect_vec();
+
+    let Some(generator) = trace_domain.multiplicative_generator() else {
+        return rows
+            .iter()
+            .map(|&row| {
+                xs.iter()
+                    .map(|&x| row_selector_at_point(trace_domain, x, row))
+                    .collect()
+            })
+            .collect();
+    };
+
+    // Over a coset of a multiplicative subgroup, the selector of the point `x_i` is
+    // `c_i Z(X) / (X - x_i)` for the vanishing polynomial `Z` and some constant `c_i`. So evaluate
+    // `Z` once per point, invert the denominators of each row in a batch, and take `c_i` from the
+    // selector at the first point of the coset.
+    let vanishing = xs
+        .iter()
+        .map(|&x| trace_domain.vanishing_poly_at_point(x))
+        .collect_vec();
+    rows.iter()
+        .map(|&row| {
+            let x_i =
+                trace_domain.first_point() * generator.exp_u64((row % trace_domain.size()) as u64);
+            let mut inv_denoms = xs.iter().map(|&x| x - x_i).collect_vec();
+            D::Val::batch_inverse_in_place(&mut inv_denoms);
+            let c_i =
+                row_selector_at_point(trace_domain, xs[0], row) * (xs[0] - x_i) / vanishing[0];
+            vanishing
+                .iter()
+                .zip(inv_denoms)
+                .map(|(&z, inv_denom)| c_i * z * inv_denom)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::coset::TwoAdicMultiplicativeCoset;
+
+    use super::*;
+
+    #[test]
+    fn selectors_on_coset_match_selectors_at_point() {
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::ONE, 3).unwrap();
+        let coset = trace_domain.create_disjoint_domain(32);
+        let rows = [0, 3, 7, 9];
+
+        let selectors = row_selectors_on_coset(&trace_domain, &coset, &rows);
+        let points = iter::successors(Some(coset.first_point()), |&x| coset.next_point(x))
+            .take(coset.size())
+            .collect_vec();
+        for (&row, selector) in rows.iter().zip(&selectors) {
+            for (&x, &value) in points.iter().zip(selector) {
+                assert_eq!(value, row_selector_at_point(&trace_domain, x, row));
+            }
+        }
+
+        // The selectors of all rows of the trace are normalized, so they sum to one everywhere.
+        let all_rows = (0..trace_domain.size()).collect_vec();
+        let all_selectors = row_selectors_on_coset(&trace_domain, &coset, &all_rows);
+        for i in 0..coset.size() {
+            let sum: BabyBear = all_selectors.iter().map(|selector| selector[i]).sum();
+            assert_eq!(sum, BabyBear::ONE);
+        }
+    }
+}
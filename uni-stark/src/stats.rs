@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+use core::iter;
+use core::time::Duration;
+use std::time::Instant;
+
+use p3_air::Air;
+use p3_commit::Pcs;
+use p3_matrix::dense::RowMajorMatrix;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::prover::{ProvingPhase, prove_stages};
+use crate::{Proof, ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, Val};
+
+/// Measurements of a single run of the prover, returned by `prove_with_stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStats {
+    /// Time spent committing to the main trace and any auxiliary segments.
+    pub commit_time: Duration,
+    /// Time spent computing and committing to the quotient polynomial.
+    pub quotient_time: Duration,
+    /// Time spent opening the commitments.
+    pub open_time: Duration,
+    /// The serialized size in bytes of each commitment: the trace, any auxiliary segments, then
+    /// the quotient chunks.
+    pub commitment_sizes: Vec<usize>,
+    /// The serialized size of the whole proof in bytes.
+    pub proof_size: usize,
+    /// The number of queries made by the opening argument, if it is query-based.
+    pub num_queries: Option<usize>,
+}
+
+/// Like `prove`, but also measures how long each phase of proving took and how large the
+/// resulting proof is.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_stats<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> (Proof<SC>, ProofStats)
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    #[cfg(debug_assertions)]
+    crate::check_constraints::check_constraints(air, &trace, public_values);
+
+    let mut stats = ProofStats::default();
+    let mut phase_start = Instant::now();
+    let proof = prove_stages(
+        config,
+        air,
+        challenger,
+        None,
+        trace,
+        &[],
+        |_, _, _, _| unreachable!("there are no auxiliary stages"),
+        public_values,
+        |phase| {
+            let elapsed = phase_start.elapsed();
+            match phase {
+                ProvingPhase::Commit => stats.commit_time = elapsed,
+                ProvingPhase::Quotient => stats.quotient_time = elapsed,
+                ProvingPhase::Open => stats.open_time = elapsed,
+            }
+            phase_start = Instant::now();
+        },
+    );
+
+    let commitments = &proof.commitments;
+    stats.commitment_sizes = iter::once(&commitments.trace)
+        .chain(&commitments.aux_traces)
+        .chain(iter::once(&commitments.quotient_chunks))
+        .map(serialized_size)
+        .collect();
+    stats.proof_size = serialized_size(&proof);
+    stats.num_queries = config.pcs().num_queries();
+
+    (proof, stats)
+}
+
+fn serialized_size<T: Serialize>(value: &T) -> usize {
+    postcard::to_allocvec(value)
+        .expect("proof data is serializable")
+        .len()
+}
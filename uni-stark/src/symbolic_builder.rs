@@ -1,17 +1,19 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, PairBuilder};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, InteractionAirBuilder,
+    PairBuilder, PeriodicAirBuilder, PermutationAirBuilder, RowAirBuilder,
+};
 use p3_field::Field;
+use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
-use p3_util::log2_ceil_usize;
 use tracing::instrument;
 
-use crate::Entry;
 use crate::symbolic_expression::SymbolicExpression;
 use crate::symbolic_variable::SymbolicVariable;
+use crate::{Entry, log_quotient_degree_for};
 
-#[instrument(name = "infer log of constraint degree", skip_all)]
 pub fn get_log_quotient_degree<F, A>(
     air: &A,
     preprocessed_width: usize,
@@ -21,17 +23,34 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    // We pad to at least degree 2, since a quotient argument doesn't make sense with smaller degrees.
-    let constraint_degree =
-        get_max_constraint_degree(air, preprocessed_width, num_public_values).max(2);
-
-    // The quotient's actual degree is approximately (max_constraint_degree - 1) n,
-    // where subtracting 1 comes from division by the vanishing polynomial.
-    // But we pad it to a power of two so that we can efficiently decompose the quotient.
-    log2_ceil_usize(constraint_degree - 1)
+    get_log_quotient_degree_with_aux(air, preprocessed_width, 0, 0, 0, num_public_values)
+}
+
+/// Like `get_log_quotient_degree`, for an AIR with auxiliary trace segments of the given total
+/// width, number of challenges and number of exposed sums.
+#[instrument(name = "infer log of constraint degree", skip_all)]
+pub fn get_log_quotient_degree_with_aux<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    aux_width: usize,
+    num_aux_challenges: usize,
+    num_aux_sums: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    log_quotient_degree_for(get_max_constraint_degree_with_aux(
+        air,
+        preprocessed_width,
+        aux_width,
+        num_aux_challenges,
+        num_aux_sums,
+        num_public_values,
+    ))
 }
 
-#[instrument(name = "infer constraint degree", skip_all, level = "debug")]
 pub fn get_max_constraint_degree<F, A>(
     air: &A,
     preprocessed_width: usize,
@@ -41,14 +60,38 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    get_symbolic_constraints(air, preprocessed_width, num_public_values)
-        .iter()
-        .map(|c| c.degree_multiple())
-        .max()
-        .unwrap_or(0)
+    get_max_constraint_degree_with_aux(air, preprocessed_width, 0, 0, 0, num_public_values)
+}
+
+/// Like `get_max_constraint_degree`, for an AIR with auxiliary trace segments of the given total
+/// width, number of challenges and number of exposed sums.
+#[instrument(name = "infer constraint degree", skip_all, level = "debug")]
+pub fn get_max_constraint_degree_with_aux<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    aux_width: usize,
+    num_aux_challenges: usize,
+    num_aux_sums: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    get_symbolic_constraints_with_aux(
+        air,
+        preprocessed_width,
+        aux_width,
+        num_aux_challenges,
+        num_aux_sums,
+        num_public_values,
+    )
+    .iter()
+    .map(|c| c.degree_multiple())
+    .max()
+    .unwrap_or(0)
 }
 
-#[instrument(name = "evaluate constraints symbolically", skip_all, level = "debug")]
 pub fn get_symbolic_constraints<F, A>(
     air: &A,
     preprocessed_width: usize,
@@ -58,18 +101,91 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values);
+    get_symbolic_constraints_with_aux(air, preprocessed_width, 0, 0, 0, num_public_values)
+}
+
+/// Like `get_symbolic_constraints`, for an AIR with auxiliary trace segments of the given total
+/// width, number of challenges and number of exposed sums.
+///
+/// # Panics
+/// Panics if `air` declares a `max_constraint_degree` other than that of its constraints.
+#[instrument(name = "evaluate constraints symbolically", skip_all, level = "debug")]
+pub fn get_symbolic_constraints_with_aux<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    aux_width: usize,
+    num_aux_challenges: usize,
+    num_aux_sums: usize,
+    num_public_values: usize,
+) -> Vec<SymbolicExpression<F>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values)
+        .with_window_size(air.window_size())
+        .with_periodic(air.periodic_columns().len())
+        .with_aux(aux_width, num_aux_challenges, num_aux_sums);
     air.eval(&mut builder);
-    builder.constraints()
+    let constraints = builder.constraints();
+
+    if let Some(declared) = air.max_constraint_degree() {
+        let degree = constraints
+            .iter()
+            .map(|c| c.degree_multiple())
+            .max()
+            .unwrap_or(0);
+        assert_eq!(
+            declared, degree,
+            "The AIR declares a max constraint degree of {declared}, but its constraints have degree {degree}."
+        );
+    }
+    constraints
+}
+
+/// The interactions `air` declares through `InteractionAirBuilder`, in the order it declares them.
+pub fn get_symbolic_interactions<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Vec<SymbolicInteraction<F>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values)
+        .with_window_size(air.window_size())
+        .with_periodic(air.periodic_columns().len());
+    air.eval(&mut builder);
+    builder.interactions
+}
+
+/// An interaction with a bus, recorded symbolically.
+#[derive(Clone, Debug)]
+pub struct SymbolicInteraction<F> {
+    pub bus_id: usize,
+    pub fields: Vec<SymbolicExpression<F>>,
+    /// The number of times `fields` is sent; receives have their multiplicity negated.
+    pub multiplicity: SymbolicExpression<F>,
 }
 
 /// An `AirBuilder` for evaluating constraints symbolically, and recording them for later use.
+///
+/// Only the degree and number of constraints matter here, so extension field expressions are
+/// modelled over `F` itself: auxiliary columns and challenges are ordinary symbolic variables, and
+/// each extension constraint is recorded as a single constraint.
 #[derive(Debug)]
 pub struct SymbolicAirBuilder<F: Field> {
     preprocessed: RowMajorMatrix<SymbolicVariable<F>>,
     main: RowMajorMatrix<SymbolicVariable<F>>,
+    aux: RowMajorMatrix<SymbolicVariable<F>>,
+    aux_challenges: Vec<SymbolicVariable<F>>,
+    aux_sums: Vec<SymbolicVariable<F>>,
+    periodic_values: Vec<SymbolicVariable<F>>,
     public_values: Vec<SymbolicVariable<F>>,
+    window_size: usize,
     constraints: Vec<SymbolicExpression<F>>,
+    interactions: Vec<SymbolicInteraction<F>>,
 }
 
 impl<F: Field> SymbolicAirBuilder<F> {
@@ -93,11 +209,59 @@ impl<F: Field> SymbolicAirBuilder<F> {
         Self {
             preprocessed: RowMajorMatrix::new(prep_values, preprocessed_width),
             main: RowMajorMatrix::new(main_values, width),
+            aux: RowMajorMatrix::new(vec![], 0),
+            aux_challenges: vec![],
+            aux_sums: vec![],
+            periodic_values: vec![],
             public_values,
+            window_size: 2,
             constraints: vec![],
+            interactions: vec![],
         }
     }
 
+    pub(crate) fn with_window_size(mut self, window_size: usize) -> Self {
+        let width = self.main.width();
+        let main_values = (0..window_size)
+            .flat_map(|offset| {
+                (0..width).map(move |index| SymbolicVariable::new(Entry::Main { offset }, index))
+            })
+            .collect();
+        self.main = RowMajorMatrix::new(main_values, width);
+        self.window_size = window_size;
+        self
+    }
+
+    pub(crate) fn with_aux(
+        mut self,
+        aux_width: usize,
+        num_aux_challenges: usize,
+        num_aux_sums: usize,
+    ) -> Self {
+        let aux_values = [0, 1]
+            .into_iter()
+            .flat_map(|offset| {
+                (0..aux_width)
+                    .map(move |index| SymbolicVariable::new(Entry::Permutation { offset }, index))
+            })
+            .collect();
+        self.aux = RowMajorMatrix::new(aux_values, aux_width);
+        self.aux_challenges = (0..num_aux_challenges)
+            .map(|index| SymbolicVariable::new(Entry::Challenge, index))
+            .collect();
+        self.aux_sums = (0..num_aux_sums)
+            .map(|index| SymbolicVariable::new(Entry::AuxSum, index))
+            .collect();
+        self
+    }
+
+    pub(crate) fn with_periodic(mut self, num_periodic_columns: usize) -> Self {
+        self.periodic_values = (0..num_periodic_columns)
+            .map(|index| SymbolicVariable::new(Entry::Periodic, index))
+            .collect();
+        self
+    }
+
     pub(crate) fn constraints(self) -> Vec<SymbolicExpression<F>> {
         self.constraints
     }
@@ -122,12 +286,12 @@ impl<F: Field> AirBuilder for SymbolicAirBuilder<F> {
     }
 
     /// # Panics
-    /// This function panics if `size` is not `2`.
+    /// This function panics if `size` is not between `2` and the AIR's window size.
     fn is_transition_window(&self, size: usize) -> Self::Expr {
-        if size == 2 {
-            SymbolicExpression::IsTransition
-        } else {
-            panic!("uni-stark only supports a window size of 2")
+        match size {
+            2 => SymbolicExpression::IsTransition,
+            3.. if size <= self.window_size => SymbolicExpression::IsTransitionWindow(size),
+            _ => panic!("window size {size} is not supported by this AIR"),
         }
     }
 
@@ -149,6 +313,68 @@ impl<F: Field> PairBuilder for SymbolicAirBuilder<F> {
     }
 }
 
+impl<F: Field> RowAirBuilder for SymbolicAirBuilder<F> {
+    fn is_row(&self, row: usize) -> Self::Expr {
+        SymbolicExpression::IsRow(row)
+    }
+}
+
+impl<F: Field> PeriodicAirBuilder for SymbolicAirBuilder<F> {
+    type PeriodicVar = SymbolicVariable<F>;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<F: Field> ExtensionBuilder for SymbolicAirBuilder<F> {
+    type EF = F;
+    type ExprEF = SymbolicExpression<F>;
+    type VarEF = SymbolicVariable<F>;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.constraints.push(x.into());
+    }
+}
+
+impl<F: Field> PermutationAirBuilder for SymbolicAirBuilder<F> {
+    type MP = RowMajorMatrix<Self::VarEF>;
+    type RandomVar = SymbolicVariable<F>;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux.clone()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        &self.aux_challenges
+    }
+
+    fn permutation_values(&self) -> &[Self::RandomVar] {
+        &self.aux_sums
+    }
+}
+
+impl<F: Field> InteractionAirBuilder for SymbolicAirBuilder<F> {
+    fn push_send<I, M>(
+        &mut self,
+        bus_id: usize,
+        fields: impl IntoIterator<Item = I>,
+        multiplicity: M,
+    ) where
+        I: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.interactions.push(SymbolicInteraction {
+            bus_id,
+            fields: fields.into_iter().map(Into::into).collect(),
+            multiplicity: multiplicity.into(),
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -156,6 +382,8 @@ mod tests {
 
     use p3_air::BaseAir;
     use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_util::log2_ceil_usize;
 
     use super::*;
 
@@ -327,4 +555,39 @@ mod tests {
             "Constraint should match the asserted one"
         );
     }
+
+    /// Sends `(local[0], local[1])` on bus 0 and receives `local[0]` on bus 1 when `local[2]` is
+    /// set.
+    struct BusAir;
+
+    impl BaseAir<BabyBear> for BusAir {
+        fn width(&self) -> usize {
+            3
+        }
+    }
+
+    impl<AB: InteractionAirBuilder> Air<AB> for BusAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let local = main.row_slice(0);
+            builder.push_send(0, [local[0], local[1]], AB::Expr::ONE);
+            builder
+                .when(local[2])
+                .push_receive(1, [local[0]], AB::Expr::TWO);
+        }
+    }
+
+    #[test]
+    fn test_get_symbolic_interactions() {
+        let interactions = get_symbolic_interactions::<BabyBear, _>(&BusAir, 0, 0);
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(interactions[0].bus_id, 0);
+        assert_eq!(interactions[0].fields.len(), 2);
+        assert_eq!(interactions[0].multiplicity.degree_multiple(), 0);
+        assert_eq!(interactions[1].bus_id, 1);
+        assert_eq!(interactions[1].fields.len(), 1);
+        // The receive is filtered by a column, and adds no constraints.
+        assert_eq!(interactions[1].multiplicity.degree_multiple(), 1);
+        assert!(get_symbolic_constraints::<BabyBear, _>(&BusAir, 0, 0).is_empty());
+    }
 }
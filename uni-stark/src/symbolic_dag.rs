@@ -0,0 +1,239 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::Field;
+
+use crate::{Entry, SymbolicExpression};
+
+/// A node of a `SymbolicDag`. Operands are the indices of earlier nodes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DagNode {
+    Variable {
+        entry: Entry,
+        index: usize,
+    },
+    IsFirstRow,
+    IsLastRow,
+    IsTransition,
+    IsTransitionWindow(usize),
+    IsRow(usize),
+    /// The constant at this index of `SymbolicDag::constants`.
+    Constant(usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+}
+
+/// A set of constraints with their common subexpressions merged.
+///
+/// Subexpressions are merged when they are structurally equal, whether or not they were shared
+/// through the same `Rc`, and the operands of additions and multiplications are put in a canonical
+/// order so that `x + y` and `y + x` are merged too.
+#[derive(Clone, Debug)]
+pub struct SymbolicDag<F> {
+    /// The distinct subexpressions, each after its operands.
+    pub nodes: Vec<DagNode>,
+    /// The distinct constants.
+    pub constants: Vec<F>,
+    /// The node computing each constraint, in the order the constraints were given.
+    pub constraints: Vec<usize>,
+    /// The degree multiple of each node.
+    degrees: Vec<usize>,
+    /// The total size of the constraints' expression trees, counting a shared subexpression once
+    /// per use.
+    tree_size: usize,
+}
+
+impl<F: Field> SymbolicDag<F> {
+    pub fn new(constraints: &[SymbolicExpression<F>]) -> Self {
+        let mut builder = DagBuilder {
+            dag: Self {
+                nodes: vec![],
+                constants: vec![],
+                constraints: Vec::with_capacity(constraints.len()),
+                degrees: vec![],
+                tree_size: 0,
+            },
+            node_indices: BTreeMap::new(),
+            memo: BTreeMap::new(),
+        };
+        for constraint in constraints {
+            let (node, size) = builder.add(constraint);
+            builder.dag.constraints.push(node);
+            builder.dag.tree_size = builder.dag.tree_size.saturating_add(size);
+        }
+        builder.dag
+    }
+
+    /// The number of distinct subexpressions.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of subexpressions before merging, saturating at `usize::MAX`.
+    pub const fn tree_size(&self) -> usize {
+        self.tree_size
+    }
+
+    /// The degree multiple of the given node.
+    pub fn degree(&self, node: usize) -> usize {
+        self.degrees[node]
+    }
+
+    /// The degree multiple of each constraint.
+    pub fn constraint_degrees(&self) -> Vec<usize> {
+        self.constraints
+            .iter()
+            .map(|&node| self.degrees[node])
+            .collect()
+    }
+
+    /// The trace and periodic columns the given constraint reads, as entries and column indices.
+    pub fn constraint_columns(&self, constraint: usize) -> BTreeSet<(Entry, usize)> {
+        let mut columns = BTreeSet::new();
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack = vec![self.constraints[constraint]];
+        while let Some(node) = stack.pop() {
+            if core::mem::replace(&mut visited[node], true) {
+                continue;
+            }
+            match self.nodes[node] {
+                DagNode::Variable { entry, index } => {
+                    if !matches!(entry, Entry::Public | Entry::Challenge | Entry::AuxSum) {
+                        columns.insert((entry, index));
+                    }
+                }
+                DagNode::Add(x, y) | DagNode::Sub(x, y) | DagNode::Mul(x, y) => {
+                    stack.extend([x, y]);
+                }
+                DagNode::Neg(x) => stack.push(x),
+                DagNode::IsFirstRow
+                | DagNode::IsLastRow
+                | DagNode::IsTransition
+                | DagNode::IsTransitionWindow(_)
+                | DagNode::IsRow(_)
+                | DagNode::Constant(_) => {}
+            }
+        }
+        columns
+    }
+}
+
+struct DagBuilder<F> {
+    dag: SymbolicDag<F>,
+    node_indices: BTreeMap<DagNode, usize>,
+    /// The node and tree size of each subexpression shared through an `Rc`, by pointer.
+    memo: BTreeMap<*const SymbolicExpression<F>, (usize, usize)>,
+}
+
+impl<F: Field> DagBuilder<F> {
+    /// Add `expr`, returning its node and tree size.
+    fn add(&mut self, expr: &SymbolicExpression<F>) -> (usize, usize) {
+        let (node, size) = match expr {
+            SymbolicExpression::Variable(v) => (
+                DagNode::Variable {
+                    entry: v.entry,
+                    index: v.index,
+                },
+                1,
+            ),
+            SymbolicExpression::IsFirstRow => (DagNode::IsFirstRow, 1),
+            SymbolicExpression::IsLastRow => (DagNode::IsLastRow, 1),
+            SymbolicExpression::IsTransition => (DagNode::IsTransition, 1),
+            SymbolicExpression::IsTransitionWindow(size) => (DagNode::IsTransitionWindow(*size), 1),
+            SymbolicExpression::IsRow(row) => (DagNode::IsRow(*row), 1),
+            SymbolicExpression::Constant(c) => {
+                let index = self
+                    .dag
+                    .constants
+                    .iter()
+                    .position(|constant| constant == c)
+                    .unwrap_or_else(|| {
+                        self.dag.constants.push(*c);
+                        self.dag.constants.len() - 1
+                    });
+                (DagNode::Constant(index), 1)
+            }
+            SymbolicExpression::Add { x, y, .. } => {
+                let ((x, x_size), (y, y_size)) = (self.add_shared(x), self.add_shared(y));
+                (DagNode::Add(x.min(y), x.max(y)), 1 + x_size + y_size)
+            }
+            SymbolicExpression::Sub { x, y, .. } => {
+                let ((x, x_size), (y, y_size)) = (self.add_shared(x), self.add_shared(y));
+                (DagNode::Sub(x, y), 1 + x_size + y_size)
+            }
+            SymbolicExpression::Neg { x, .. } => {
+                let (x, x_size) = self.add_shared(x);
+                (DagNode::Neg(x), 1 + x_size)
+            }
+            SymbolicExpression::Mul { x, y, .. } => {
+                let ((x, x_size), (y, y_size)) = (self.add_shared(x), self.add_shared(y));
+                (DagNode::Mul(x.min(y), x.max(y)), 1 + x_size + y_size)
+            }
+        };
+        let index = *self.node_indices.entry(node).or_insert_with(|| {
+            self.dag.nodes.push(node);
+            self.dag.degrees.push(expr.degree_multiple());
+            self.dag.nodes.len() - 1
+        });
+        (index, size)
+    }
+
+    fn add_shared(&mut self, expr: &Rc<SymbolicExpression<F>>) -> (usize, usize) {
+        let ptr = Rc::as_ptr(expr);
+        if let Some(&result) = self.memo.get(&ptr) {
+            return result;
+        }
+        let result = self.add(expr);
+        self.memo.insert(ptr, result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_air::{Air, AirBuilder, BaseAir};
+    use p3_baby_bear::BabyBear;
+    use p3_matrix::Matrix;
+
+    use super::*;
+    use crate::get_symbolic_constraints;
+
+    /// Asserts `next[0] = local[0] * local[1]` and `next[1] = local[1] * local[0] + local[0]`.
+    struct ProductAir;
+
+    impl<F> BaseAir<F> for ProductAir {
+        fn width(&self) -> usize {
+            2
+        }
+    }
+
+    impl<AB: AirBuilder> Air<AB> for ProductAir {
+        fn eval(&self, builder: &mut AB) {
+            let main = builder.main();
+            let (local, next) = (main.row_slice(0), main.row_slice(1));
+            builder.assert_eq(local[0] * local[1], next[0]);
+            builder.assert_eq(local[1] * local[0] + local[0], next[1]);
+        }
+    }
+
+    #[test]
+    fn test_merges_common_subexpressions() {
+        let constraints = get_symbolic_constraints::<BabyBear, _>(&ProductAir, 0, 0);
+        let dag = SymbolicDag::new(&constraints);
+
+        // local[0], local[1], their product, next[0], next[1], the sum and two differences.
+        assert_eq!(dag.num_nodes(), 8);
+        assert_eq!(dag.tree_size(), 5 + 7);
+        assert_eq!(dag.constraint_degrees(), vec![2, 2]);
+
+        let main = |offset, index| (Entry::Main { offset }, index);
+        assert_eq!(
+            dag.constraint_columns(1),
+            BTreeSet::from([main(0, 0), main(0, 1), main(1, 1)])
+        );
+    }
+}
@@ -14,6 +14,11 @@ pub enum SymbolicExpression<F> {
     IsFirstRow,
     IsLastRow,
     IsTransition,
+    /// `is_transition_window(size)` for a window of more than two rows, which vanishes on the
+    /// last `size - 1` rows.
+    IsTransitionWindow(usize),
+    /// `is_row(row)`, which vanishes on every row but `row`.
+    IsRow(usize),
     Constant(F),
     Add {
         x: Rc<Self>,
@@ -41,7 +46,7 @@ impl<F> SymbolicExpression<F> {
     pub const fn degree_multiple(&self) -> usize {
         match self {
             Self::Variable(v) => v.degree_multiple(),
-            Self::IsFirstRow | Self::IsLastRow => 1,
+            Self::IsFirstRow | Self::IsLastRow | Self::IsTransitionWindow(_) | Self::IsRow(_) => 1,
             Self::IsTransition | Self::Constant(_) => 0,
             Self::Add {
                 degree_multiple, ..
@@ -284,6 +289,16 @@ mod tests {
             "IsTransition should have degree 0"
         );
 
+        let is_transition_window = SymbolicExpression::<BabyBear>::IsTransitionWindow(3);
+        assert_eq!(
+            is_transition_window.degree_multiple(),
+            1,
+            "IsTransitionWindow should have degree 1"
+        );
+
+        let is_row = SymbolicExpression::<BabyBear>::IsRow(5);
+        assert_eq!(is_row.degree_multiple(), 1, "IsRow should have degree 1");
+
         let add_expr = SymbolicExpression::<BabyBear>::Add {
             x: Rc::new(variable_expr.clone()),
             y: Rc::new(preprocessed_var.clone()),
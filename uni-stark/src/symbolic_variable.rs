@@ -5,16 +5,18 @@ use p3_field::Field;
 
 use crate::symbolic_expression::SymbolicExpression;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Entry {
     Preprocessed { offset: usize },
     Main { offset: usize },
     Permutation { offset: usize },
+    Periodic,
     Public,
     Challenge,
+    AuxSum,
 }
 
-/// A variable within the evaluation window, i.e. a column in either the local or next row.
+/// A variable within the evaluation window, i.e. a column in one of the rows it covers.
 #[derive(Copy, Clone, Debug)]
 pub struct SymbolicVariable<F> {
     pub entry: Entry,
@@ -33,8 +35,11 @@ impl<F> SymbolicVariable<F> {
 
     pub const fn degree_multiple(&self) -> usize {
         match self.entry {
-            Entry::Preprocessed { .. } | Entry::Main { .. } | Entry::Permutation { .. } => 1,
-            Entry::Public | Entry::Challenge => 0,
+            Entry::Preprocessed { .. }
+            | Entry::Main { .. }
+            | Entry::Permutation { .. }
+            | Entry::Periodic => 1,
+            Entry::Public | Entry::Challenge | Entry::AuxSum => 0,
         }
     }
 }
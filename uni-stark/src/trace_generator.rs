@@ -0,0 +1,50 @@
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+/// A trace the prover generates in chunks of rows, in parallel when the `parallel` feature is
+/// enabled, rather than one the caller materializes.
+///
+/// The chunks are written into one buffer holding the whole trace, which is committed to once it
+/// is complete. Generation is not pipelined with the low-degree extension or the commitment, since
+/// each column's extension needs all of its rows. This saves the caller from allocating a trace
+/// of its own and from parallelizing its generation, not the prover from holding the trace.
+pub trait ChunkedTraceGenerator<F>: Sync {
+    /// The number of columns in the trace.
+    fn width(&self) -> usize;
+
+    /// The number of rows in the trace, which must be a power of two.
+    fn height(&self) -> usize;
+
+    /// The number of rows generated by each call to `generate_chunk`. The last chunk may be
+    /// shorter if this doesn't divide the height.
+    fn chunk_height(&self) -> usize {
+        1 << 10
+    }
+
+    /// Write the rows starting at `start_row` into `rows`, in row-major order. `rows` holds a
+    /// whole number of rows.
+    fn generate_chunk(&self, start_row: usize, rows: &mut [F]);
+}
+
+/// Generate every chunk of `generator` into a single matrix.
+#[instrument(name = "generate trace", skip_all)]
+pub(crate) fn generate_trace<F, G>(generator: &G) -> RowMajorMatrix<F>
+where
+    F: Field,
+    G: ChunkedTraceGenerator<F> + ?Sized,
+{
+    let width = generator.width();
+    let chunk_height = generator.chunk_height();
+    assert!(chunk_height > 0, "chunks must hold at least one row");
+
+    let mut values = F::zero_vec(generator.height() * width);
+    if width > 0 {
+        values
+            .par_chunks_mut(chunk_height * width)
+            .enumerate()
+            .for_each(|(i, rows)| generator.generate_chunk(i * chunk_height, rows));
+    }
+    RowMajorMatrix::new(values, width)
+}
@@ -1,7 +1,9 @@
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
-use itertools::Itertools;
+use itertools::{Itertools, izip};
 use p3_air::{Air, BaseAir};
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
@@ -11,8 +13,18 @@ use p3_matrix::stack::VerticalPair;
 use p3_util::zip_eq::zip_eq;
 use tracing::instrument;
 
-use crate::symbolic_builder::{SymbolicAirBuilder, get_log_quotient_degree};
-use crate::{PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
+use crate::config::{effective_log_quotient_degree, quotient_mask_domain};
+use crate::instance::AirShape;
+use crate::multi_stage::{aux_width, num_aux_challenges, num_aux_sums};
+use crate::periodic::PeriodicColumn;
+use crate::row_selector::row_selector_at_point;
+use crate::symbolic_builder::{SymbolicAirBuilder, get_symbolic_constraints_with_aux};
+use crate::window::{transition_window_factors, window_points};
+use crate::{
+    AuxStage, BatchProof, Domain, MultiProof, MultiStageAir, OpenedValues, PcsError,
+    PreprocessedVerifierData, Proof, StarkGenericConfig, Val, VerifierConstraintFolder,
+    VerifierKey,
+};
 
 #[instrument(skip_all)]
 pub fn verify<SC, A>(
@@ -25,6 +37,577 @@ pub fn verify<SC, A>(
 where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_stages(config, air, challenger, None, proof, &[], public_values)
+}
+
+/// Verify a proof produced by `prove_multi_stage`.
+#[instrument(skip_all)]
+pub fn verify_multi_stage<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_stages(
+        config,
+        air,
+        challenger,
+        None,
+        proof,
+        &air.aux_stages(),
+        public_values,
+    )
+}
+
+/// Verify a proof produced by `prove_with_key`.
+#[instrument(skip_all)]
+pub fn verify_with_key<SC, A>(
+    config: &SC,
+    vk: &VerifierKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_stages(
+        config,
+        air,
+        challenger,
+        vk.preprocessed.as_ref(),
+        proof,
+        &[],
+        public_values,
+    )
+}
+
+/// Verify a proof produced by `prove_multi`.
+#[instrument(skip_all)]
+pub fn verify_multi<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    verify_airs(
+        config,
+        airs,
+        challenger,
+        proof,
+        &vec![vec![]; airs.len()],
+        public_values,
+    )
+}
+
+/// Verify a proof produced by `prove_multi_with_aux`.
+#[instrument(skip_all)]
+pub fn verify_multi_with_aux<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: MultiStageAir<Val<SC>>
+        + Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let aux_stages = airs.iter().map(|air| air.aux_stages()).collect_vec();
+    verify_airs(config, airs, challenger, proof, &aux_stages, public_values)
+}
+
+/// Verify a proof of `airs`, with `aux_stages` holding the auxiliary stages of each AIR.
+fn verify_airs<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    aux_stages: &[Vec<AuxStage>],
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let MultiProof {
+        commitments,
+        opened_values,
+        opening_proof,
+        degree_bits,
+    } = proof;
+
+    let num_aux_rounds = aux_stages.iter().map(Vec::len).max().unwrap_or(0);
+    if opened_values.len() != airs.len()
+        || degree_bits.len() != airs.len()
+        || public_values.len() != airs.len()
+        || commitments.aux_traces.len() != num_aux_rounds
+    {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    let pcs = config.pcs();
+    let ext_degree = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let mask_len = if config.is_zk() { ext_degree } else { 0 };
+    let mut shapes = Vec::with_capacity(airs.len());
+    let mut trace_domains = Vec::with_capacity(airs.len());
+    let mut quotient_domains = Vec::with_capacity(airs.len());
+    let mut quotient_chunks_domains = Vec::with_capacity(airs.len());
+    for (air, stages, opened_values, &degree_bits, public_values) in
+        izip!(airs, aux_stages, opened_values, degree_bits, public_values)
+    {
+        let shape = air_shape::<SC, A>(air, 0, stages, public_values.len());
+        let log_quotient_degree =
+            effective_log_quotient_degree(config, shape.max_constraint_degree);
+        let quotient_degree = 1 << log_quotient_degree;
+        let trace_domain = pcs.natural_domain_for_degree(1 << degree_bits);
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree));
+
+        let air_width = <A as BaseAir<Val<SC>>>::width(air);
+        let valid_shape = opened_values.preprocessed_local.is_empty()
+            && opened_values.preprocessed_next.is_empty()
+            && valid_aux_shape(stages, opened_values, ext_degree)
+            && opened_values.trace_local.len() == air_width
+            && opened_values.trace_next.len() == air_width
+            && opened_values.trace_after_next.len()
+                == <A as BaseAir<Val<SC>>>::window_size(air) - 2
+            && opened_values
+                .trace_after_next
+                .iter()
+                .all(|row| row.len() == air_width)
+            && opened_values.quotient_chunks.len() == quotient_degree
+            && opened_values
+                .quotient_chunks
+                .iter()
+                .all(|qc| qc.len() == ext_degree)
+            && opened_values.quotient_mask.len() == mask_len;
+        if !valid_shape {
+            return Err(VerificationError::InvalidProofShape);
+        }
+
+        shapes.push(shape);
+        trace_domains.push(trace_domain);
+        quotient_domains.push(quotient_domain);
+        quotient_chunks_domains.push(quotient_domain.split_domains(quotient_degree));
+    }
+
+    // Observe the instance.
+    for &degree_bits in degree_bits {
+        challenger.observe(Val::<SC>::from_usize(degree_bits));
+    }
+    for shape in &shapes {
+        shape.observe(challenger);
+    }
+    challenger.observe(commitments.trace.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+
+    // The `round`-th stages of all AIRs were committed together, each AIR taking as many of the
+    // round's challenges as its stage needs.
+    let mut aux_challenges = vec![Vec::new(); airs.len()];
+    for (round, aux_commit) in commitments.aux_traces.iter().enumerate() {
+        let num_challenges = aux_stages
+            .iter()
+            .filter_map(|stages| stages.get(round))
+            .map(|stage| stage.num_challenges)
+            .max()
+            .unwrap();
+        let challenges = (0..num_challenges)
+            .map(|_| challenger.sample_algebra_element::<SC::Challenge>())
+            .collect_vec();
+        for (challenges_i, stages) in izip!(&mut aux_challenges, aux_stages) {
+            if let Some(stage) = stages.get(round) {
+                challenges_i.extend_from_slice(&challenges[..stage.num_challenges]);
+            }
+        }
+        challenger.observe(aux_commit.clone());
+        for &sum in opened_values
+            .iter()
+            .filter_map(|opened_values| opened_values.aux_sums.get(round))
+            .flatten()
+        {
+            challenger.observe_algebra_element(sum);
+        }
+    }
+
+    let alpha: SC::Challenge = challenger.sample_algebra_element();
+    challenger.observe(commitments.quotient_chunks.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let mut rounds = vec![(
+        commitments.trace.clone(),
+        izip!(airs, &trace_domains, opened_values)
+            .map(|(air, domain, opened_values)| {
+                let points = window_points(domain, zeta, <A as BaseAir<Val<SC>>>::window_size(air));
+                (
+                    committed_trace_domain(config, domain),
+                    izip!(points, trace_rows(opened_values)).collect_vec(),
+                )
+            })
+            .chain(izip!(&quotient_domains, opened_values).filter_map(
+                |(quotient_domain, opened_values)| {
+                    quotient_mask_claim(config, quotient_domain, zeta, &opened_values.quotient_mask)
+                },
+            ))
+            .collect_vec(),
+    )];
+    rounds.extend(
+        commitments
+            .aux_traces
+            .iter()
+            .enumerate()
+            .map(|(round, aux_commit)| {
+                (
+                    aux_commit.clone(),
+                    izip!(&trace_domains, opened_values)
+                        .filter(|(_, opened_values)| opened_values.aux_trace_local.len() > round)
+                        .map(|(domain, opened_values)| {
+                            (
+                                committed_trace_domain(config, domain),
+                                vec![
+                                    (zeta, opened_values.aux_trace_local[round].clone()),
+                                    (
+                                        domain.next_point(zeta).unwrap(),
+                                        opened_values.aux_trace_next[round].clone(),
+                                    ),
+                                ],
+                            )
+                        })
+                        .collect_vec(),
+                )
+            }),
+    );
+    rounds.push((
+        commitments.quotient_chunks.clone(),
+        izip!(&quotient_chunks_domains, opened_values)
+            .flat_map(|(domains, opened_values)| {
+                izip!(domains, &opened_values.quotient_chunks)
+                    .map(move |(domain, values)| (*domain, vec![(zeta, values.clone())]))
+            })
+            .collect_vec(),
+    ));
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    for (
+        i,
+        (
+            air,
+            shape,
+            opened_values,
+            trace_domain,
+            quotient_chunks_domains,
+            aux_challenges,
+            public_values,
+        ),
+    ) in izip!(
+        airs,
+        &shapes,
+        opened_values,
+        trace_domains,
+        &quotient_chunks_domains,
+        &aux_challenges,
+        public_values
+    )
+    .enumerate()
+    {
+        verify_ood_evaluation::<SC, A>(
+            air,
+            opened_values,
+            trace_domain,
+            quotient_chunks_domains,
+            zeta,
+            alpha,
+            aux_challenges,
+            public_values,
+            &shape.rows,
+        )
+        .map_err(|err| match err {
+            VerificationError::OodEvaluationMismatch { .. } => {
+                VerificationError::OodEvaluationMismatch { air: Some(i) }
+            }
+            err => err,
+        })?;
+    }
+
+    check_aux_sums(opened_values)
+}
+
+/// Verify a proof produced by `prove_identical_batch`.
+#[instrument(skip_all)]
+pub fn verify_identical_batch<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &BatchProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let BatchProof {
+        commitments,
+        opened_values,
+        quotient_chunks,
+        quotient_mask,
+        opening_proof,
+        degree_bits,
+    } = proof;
+
+    let num_public_values = public_values.first().map_or(0, Vec::len);
+    if opened_values.is_empty()
+        || opened_values.len() != public_values.len()
+        || public_values
+            .iter()
+            .any(|public_values| public_values.len() != num_public_values)
+        || !commitments.aux_traces.is_empty()
+    {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    let shape = air_shape::<SC, A>(air, 0, &[], num_public_values);
+    let log_quotient_degree = effective_log_quotient_degree(config, shape.max_constraint_degree);
+    let quotient_degree = 1 << log_quotient_degree;
+
+    let pcs = config.pcs();
+    let trace_domain = pcs.natural_domain_for_degree(1 << degree_bits);
+    let quotient_domain =
+        trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree));
+    let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
+
+    let air_width = <A as BaseAir<Val<SC>>>::width(air);
+    let window_size = <A as BaseAir<Val<SC>>>::window_size(air);
+    let ext_degree = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let valid_shape = opened_values.iter().all(|opened_values| {
+        opened_values.preprocessed_local.is_empty()
+            && opened_values.preprocessed_next.is_empty()
+            && valid_aux_shape(&[], opened_values, ext_degree)
+            && opened_values.quotient_chunks.is_empty()
+            && opened_values.quotient_mask.is_empty()
+            && opened_values.trace_local.len() == air_width
+            && opened_values.trace_next.len() == air_width
+            && opened_values.trace_after_next.len() == window_size - 2
+            && opened_values
+                .trace_after_next
+                .iter()
+                .all(|row| row.len() == air_width)
+    }) && quotient_chunks.len() == quotient_degree
+        && quotient_chunks.iter().all(|qc| qc.len() == ext_degree)
+        && quotient_mask.len() == if config.is_zk() { ext_degree } else { 0 };
+    if !valid_shape {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    // Observe the instance.
+    challenger.observe(Val::<SC>::from_usize(*degree_bits));
+    challenger.observe(Val::<SC>::from_usize(opened_values.len()));
+    shape.observe(challenger);
+    challenger.observe(commitments.trace.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+    let alpha: SC::Challenge = challenger.sample_algebra_element();
+    let beta: SC::Challenge = challenger.sample_algebra_element();
+    challenger.observe(commitments.quotient_chunks.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let trace_points = window_points(&trace_domain, zeta, window_size);
+    let committed_domain = committed_trace_domain(config, &trace_domain);
+    pcs.verify(
+        vec![
+            (
+                commitments.trace.clone(),
+                opened_values
+                    .iter()
+                    .map(|opened_values| {
+                        (
+                            committed_domain,
+                            izip!(trace_points.iter().copied(), trace_rows(opened_values))
+                                .collect_vec(),
+                        )
+                    })
+                    .chain(quotient_mask_claim(
+                        config,
+                        &quotient_domain,
+                        zeta,
+                        quotient_mask,
+                    ))
+                    .collect_vec(),
+            ),
+            (
+                commitments.quotient_chunks.clone(),
+                izip!(&quotient_chunks_domains, quotient_chunks)
+                    .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                    .collect_vec(),
+            ),
+        ],
+        opening_proof,
+        challenger,
+    )
+    .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    let mut combined_constraints = SC::Challenge::ZERO;
+    for (opened_values, public_values, beta_power) in
+        izip!(opened_values, public_values, beta.powers())
+    {
+        combined_constraints += beta_power
+            * constraints_over_vanishing::<SC, A>(
+                air,
+                opened_values,
+                trace_domain,
+                zeta,
+                alpha,
+                &[],
+                public_values,
+                &shape.rows,
+            )?;
+    }
+    if combined_constraints
+        != quotient_at_point::<SC>(
+            quotient_chunks,
+            quotient_mask,
+            &quotient_chunks_domains,
+            zeta,
+        )
+    {
+        return Err(VerificationError::OodEvaluationMismatch { air: None });
+    }
+
+    Ok(())
+}
+
+/// Verify each of `proofs` of the same AIR as `verify` would, returning one result per proof.
+///
+/// This is a loop over `verify`, each proof checked against its own public values with a fresh
+/// copy of `challenger`. It saves only the symbolic evaluation of the AIR's constraints, done once
+/// per number of public values. Independently generated proofs share no commitments, so every
+/// opening proof is still verified in full.
+#[instrument(skip_all)]
+pub fn verify_each<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &SC::Challenger,
+    proofs: &[Proof<SC>],
+    public_values: &[Vec<Val<SC>>],
+) -> Vec<Result<(), VerificationError<PcsError<SC>>>>
+where
+    SC: StarkGenericConfig,
+    SC::Challenger: Clone,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    assert_eq!(
+        proofs.len(),
+        public_values.len(),
+        "expected one set of public values per proof"
+    );
+
+    // The constraints depend on the number of public values, so infer them once per count.
+    let mut shapes = BTreeMap::new();
+    izip!(proofs, public_values)
+        .map(|(proof, public_values)| {
+            let shape = shapes
+                .entry(public_values.len())
+                .or_insert_with(|| air_shape::<SC, A>(air, 0, &[], public_values.len()));
+            verify_instance(
+                config,
+                air,
+                shape,
+                &mut challenger.clone(),
+                None,
+                proof,
+                &[],
+                public_values,
+            )
+        })
+        .collect()
+}
+
+fn verify_stages<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    preprocessed: Option<&PreprocessedVerifierData<SC>>,
+    proof: &Proof<SC>,
+    aux_stages: &[AuxStage],
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let preprocessed_width = preprocessed.map_or(0, |preprocessed| preprocessed.width);
+    let shape = air_shape::<SC, A>(air, preprocessed_width, aux_stages, public_values.len());
+    verify_instance(
+        config,
+        air,
+        &shape,
+        challenger,
+        preprocessed,
+        proof,
+        aux_stages,
+        public_values,
+    )
+}
+
+/// Evaluate the constraints of `air` symbolically and summarize them.
+fn air_shape<SC, A>(
+    air: &A,
+    preprocessed_width: usize,
+    aux_stages: &[AuxStage],
+    num_public_values: usize,
+) -> AirShape<Val<SC>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let constraints = get_symbolic_constraints_with_aux::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        aux_width(aux_stages),
+        num_aux_challenges(aux_stages),
+        num_aux_sums(aux_stages),
+        num_public_values,
+    );
+    AirShape::new(air.width(), num_public_values, &constraints)
+}
+
+/// Verify a single proof of an AIR with the given shape.
+#[allow(clippy::too_many_arguments)]
+fn verify_instance<SC, A>(
+    config: &SC,
+    air: &A,
+    shape: &AirShape<Val<SC>>,
+    challenger: &mut SC::Challenger,
+    preprocessed: Option<&PreprocessedVerifierData<SC>>,
+    proof: &Proof<SC>,
+    aux_stages: &[AuxStage],
+    public_values: &Vec<Val<SC>>,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
 {
     let Proof {
         commitments,
@@ -34,7 +617,8 @@ where
     } = proof;
 
     let degree = 1 << degree_bits;
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
+    let preprocessed_width = preprocessed.map_or(0, |preprocessed| preprocessed.width);
+    let log_quotient_degree = effective_log_quotient_degree(config, shape.max_constraint_degree);
     let quotient_degree = 1 << log_quotient_degree;
 
     let pcs = config.pcs();
@@ -44,61 +628,197 @@ where
     let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
 
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
-    let valid_shape = opened_values.trace_local.len() == air_width
+    let ext_degree = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let valid_shape = preprocessed
+        .is_none_or(|preprocessed| preprocessed.degree_bits == *degree_bits)
+        && opened_values.preprocessed_local.len() == preprocessed_width
+        && opened_values.preprocessed_next.len() == preprocessed_width
+        && opened_values.trace_local.len() == air_width
         && opened_values.trace_next.len() == air_width
+        && opened_values.trace_after_next.len() == <A as BaseAir<Val<SC>>>::window_size(air) - 2
+        && opened_values
+            .trace_after_next
+            .iter()
+            .all(|row| row.len() == air_width)
+        && commitments.aux_traces.len() == aux_stages.len()
+        && valid_aux_shape(aux_stages, opened_values, ext_degree)
         && opened_values.quotient_chunks.len() == quotient_degree
         && opened_values
             .quotient_chunks
             .iter()
-            .all(|qc| qc.len() == <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION);
+            .all(|qc| qc.len() == ext_degree)
+        && opened_values.quotient_mask.len() == if config.is_zk() { ext_degree } else { 0 };
     if !valid_shape {
         return Err(VerificationError::InvalidProofShape);
     }
 
     // Observe the instance.
     challenger.observe(Val::<SC>::from_usize(proof.degree_bits));
-    // TODO: Might be best practice to include other instance data here in the transcript, like some
-    // encoding of the AIR. This protects against transcript collisions between distinct instances.
-    // Practically speaking though, the only related known attack is from failing to include public
-    // values. It's not clear if failing to include other instance data could enable a transcript
-    // collision, since most such changes would completely change the set of satisfying witnesses.
+    // Bind the AIR itself, so that a proof can't be replayed against a different AIR of the same
+    // trace shape.
+    shape.observe(challenger);
+    if let Some(preprocessed) = preprocessed {
+        challenger.observe(preprocessed.commitment.clone());
+    }
 
     challenger.observe(commitments.trace.clone());
     challenger.observe_slice(public_values);
+
+    let mut aux_challenges = Vec::with_capacity(num_aux_challenges(aux_stages));
+    for (aux_stage, (aux_commit, sums)) in zip_eq(
+        aux_stages,
+        izip!(&commitments.aux_traces, &opened_values.aux_sums),
+        VerificationError::InvalidProofShape,
+    )? {
+        aux_challenges.extend(
+            (0..aux_stage.num_challenges)
+                .map(|_| challenger.sample_algebra_element::<SC::Challenge>()),
+        );
+        challenger.observe(aux_commit.clone());
+        for &sum in sums {
+            challenger.observe_algebra_element(sum);
+        }
+    }
+
     let alpha: SC::Challenge = challenger.sample_algebra_element();
     challenger.observe(commitments.quotient_chunks.clone());
 
     let zeta: SC::Challenge = challenger.sample();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
-    pcs.verify(
-        vec![
+    let trace_points = window_points(
+        &trace_domain,
+        zeta,
+        <A as BaseAir<Val<SC>>>::window_size(air),
+    );
+    let committed_domain = committed_trace_domain(config, &trace_domain);
+    let mut rounds = vec![(
+        commitments.trace.clone(),
+        iter::once((
+            committed_domain,
+            izip!(trace_points, trace_rows(opened_values)).collect_vec(),
+        ))
+        .chain(quotient_mask_claim(
+            config,
+            &quotient_domain,
+            zeta,
+            &opened_values.quotient_mask,
+        ))
+        .collect_vec(),
+    )];
+    rounds.extend(
+        izip!(
+            &commitments.aux_traces,
+            &opened_values.aux_trace_local,
+            &opened_values.aux_trace_next
+        )
+        .map(|(aux_commit, local, next)| {
             (
-                commitments.trace.clone(),
+                aux_commit.clone(),
                 vec![(
-                    trace_domain,
-                    vec![
-                        (zeta, opened_values.trace_local.clone()),
-                        (zeta_next, opened_values.trace_next.clone()),
-                    ],
+                    committed_domain,
+                    vec![(zeta, local.clone()), (zeta_next, next.clone())],
                 )],
-            ),
-            (
-                commitments.quotient_chunks.clone(),
-                zip_eq(
-                    quotient_chunks_domains.iter(),
-                    &opened_values.quotient_chunks,
-                    VerificationError::InvalidProofShape,
-                )?
-                .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
-                .collect_vec(),
-            ),
-        ],
-        opening_proof,
-        challenger,
-    )
-    .map_err(VerificationError::InvalidOpeningArgument)?;
+            )
+        }),
+    );
+    rounds.extend(preprocessed.map(|preprocessed| {
+        (
+            preprocessed.commitment.clone(),
+            vec![(
+                trace_domain,
+                vec![
+                    (zeta, opened_values.preprocessed_local.clone()),
+                    (zeta_next, opened_values.preprocessed_next.clone()),
+                ],
+            )],
+        )
+    }));
+    rounds.push((
+        commitments.quotient_chunks.clone(),
+        zip_eq(
+            quotient_chunks_domains.iter(),
+            &opened_values.quotient_chunks,
+            VerificationError::InvalidProofShape,
+        )?
+        .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+        .collect_vec(),
+    ));
+
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    verify_ood_evaluation::<SC, A>(
+        air,
+        opened_values,
+        trace_domain,
+        &quotient_chunks_domains,
+        zeta,
+        alpha,
+        &aux_challenges,
+        public_values,
+        &shape.rows,
+    )?;
+
+    check_aux_sums(core::slice::from_ref(opened_values))
+}
+
+/// Check that the constraints of `air`, folded with `alpha` and evaluated on the values opened at
+/// `zeta`, agree with the opened quotient.
+///
+/// The shape of `opened_values` must already have been checked.
+#[allow(clippy::too_many_arguments)]
+fn verify_ood_evaluation<SC, A>(
+    air: &A,
+    opened_values: &OpenedValues<SC::Challenge>,
+    trace_domain: Domain<SC>,
+    quotient_chunks_domains: &[Domain<SC>],
+    zeta: SC::Challenge,
+    alpha: SC::Challenge,
+    aux_challenges: &[SC::Challenge],
+    public_values: &Vec<Val<SC>>,
+    rows: &[usize],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let quotient = quotient_at_point::<SC>(
+        &opened_values.quotient_chunks,
+        &opened_values.quotient_mask,
+        quotient_chunks_domains,
+        zeta,
+    );
+    let constraints = constraints_over_vanishing::<SC, A>(
+        air,
+        opened_values,
+        trace_domain,
+        zeta,
+        alpha,
+        aux_challenges,
+        public_values,
+        rows,
+    )?;
 
+    // Finally, check that
+    //     folded_constraints(zeta) / Z_H(zeta) = quotient(zeta)
+    if constraints != quotient {
+        return Err(VerificationError::OodEvaluationMismatch { air: None });
+    }
+
+    Ok(())
+}
+
+/// Recombine the opened quotient chunks into the quotient's value at `zeta`, removing the opened
+/// mask if there is one.
+///
+/// Each chunk, and the mask unless it's empty, must hold `DIMENSION` values.
+fn quotient_at_point<SC: StarkGenericConfig>(
+    quotient_chunks: &[Vec<SC::Challenge>],
+    quotient_mask: &[SC::Challenge],
+    quotient_chunks_domains: &[Domain<SC>],
+    zeta: SC::Challenge,
+) -> SC::Challenge {
     let zps = quotient_chunks_domains
         .iter()
         .enumerate()
@@ -117,56 +837,226 @@ where
         })
         .collect_vec();
 
-    let quotient = opened_values
-        .quotient_chunks
+    quotient_chunks
         .iter()
         .enumerate()
         .map(|(ch_i, ch)| {
             // We checked in valid_shape the length of "ch" is equal to
-            // <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION. Hence
-            // the unwrap() will never panic.
-            zps[ch_i]
-                * ch.iter()
-                    .enumerate()
-                    .map(|(e_i, &c)| SC::Challenge::ith_basis_element(e_i).unwrap() * c)
-                    .sum::<SC::Challenge>()
+            // <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION.
+            zps[ch_i] * recompose::<SC>(ch)
         })
-        .sum::<SC::Challenge>();
+        .sum::<SC::Challenge>()
+        - if quotient_mask.is_empty() {
+            SC::Challenge::ZERO
+        } else {
+            recompose::<SC>(quotient_mask)
+        }
+}
+
+/// Evaluate the constraints of `air` on the values opened at `zeta`, fold them with `alpha` and
+/// divide by the vanishing polynomial of the trace domain at `zeta`.
+#[allow(clippy::too_many_arguments)]
+fn constraints_over_vanishing<SC, A>(
+    air: &A,
+    opened_values: &OpenedValues<SC::Challenge>,
+    trace_domain: Domain<SC>,
+    zeta: SC::Challenge,
+    alpha: SC::Challenge,
+    aux_challenges: &[SC::Challenge],
+    public_values: &Vec<Val<SC>>,
+    rows: &[usize],
+) -> Result<SC::Challenge, VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let ext_degree = <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION;
 
     let sels = trace_domain.selectors_at_point(zeta);
 
-    let main = VerticalPair::new(
-        RowMajorMatrixView::new_row(&opened_values.trace_local),
-        RowMajorMatrixView::new_row(&opened_values.trace_next),
+    let periodic_columns = <A as BaseAir<Val<SC>>>::periodic_columns(air);
+    if !periodic_columns.is_empty() && trace_domain.multiplicative_generator().is_none() {
+        return Err(VerificationError::UnsupportedTraceDomain);
+    }
+    let periodic_values = PeriodicColumn::interpolate_all(&trace_domain, &periodic_columns)
+        .ok_or(VerificationError::InvalidProofShape)?
+        .iter()
+        .map(|column| column.eval(zeta))
+        .collect();
+
+    let main_rows = trace_rows(opened_values).concat();
+    let main = RowMajorMatrixView::new(&main_rows, opened_values.trace_local.len());
+
+    // We checked in valid_shape that each auxiliary opening holds whole challenge field columns.
+    let aux_local = opened_values
+        .aux_trace_local
+        .iter()
+        .flat_map(|values| values.chunks_exact(ext_degree).map(recompose::<SC>))
+        .collect_vec();
+    let aux_next = opened_values
+        .aux_trace_next
+        .iter()
+        .flat_map(|values| values.chunks_exact(ext_degree).map(recompose::<SC>))
+        .collect_vec();
+    let aux = VerticalPair::new(
+        RowMajorMatrixView::new_row(&aux_local),
+        RowMajorMatrixView::new_row(&aux_next),
     );
 
+    let preprocessed_rows = [
+        opened_values.preprocessed_local.as_slice(),
+        &opened_values.preprocessed_next,
+    ]
+    .concat();
+    let preprocessed =
+        RowMajorMatrixView::new(&preprocessed_rows, opened_values.preprocessed_local.len());
+
+    let aux_sums = opened_values.aux_sums.concat();
+
     let mut folder = VerifierConstraintFolder {
+        preprocessed,
         main,
+        aux,
+        aux_challenges,
+        aux_sums: &aux_sums,
+        periodic_values,
         public_values,
         is_first_row: sels.is_first_row,
         is_last_row: sels.is_last_row,
         is_transition: sels.is_transition,
+        is_transition_windows: transition_window_factors(
+            &trace_domain,
+            zeta,
+            <A as BaseAir<Val<SC>>>::window_size(air),
+        )
+        .ok_or(VerificationError::UnsupportedTraceDomain)?
+        .into_iter()
+        .map(|factor| sels.is_transition * factor)
+        .collect(),
+        row_selectors: rows
+            .iter()
+            .map(|&row| (row, row_selector_at_point(&trace_domain, zeta, row)))
+            .collect(),
         alpha,
         accumulator: SC::Challenge::ZERO,
     };
     air.eval(&mut folder);
-    let folded_constraints = folder.accumulator;
+    Ok(folder.accumulator * sels.inv_vanishing)
+}
 
-    // Finally, check that
-    //     folded_constraints(zeta) / Z_H(zeta) = quotient(zeta)
-    if folded_constraints * sels.inv_vanishing != quotient {
-        return Err(VerificationError::OodEvaluationMismatch);
+/// The domain a trace over `trace_domain` is committed over: in zero-knowledge mode, the trace is
+/// randomized into a polynomial of twice the degree.
+fn committed_trace_domain<SC: StarkGenericConfig>(
+    config: &SC,
+    trace_domain: &Domain<SC>,
+) -> Domain<SC> {
+    if config.is_zk() {
+        config
+            .pcs()
+            .natural_domain_for_degree(2 * trace_domain.size())
+    } else {
+        *trace_domain
     }
+}
+
+/// In zero-knowledge mode, the claimed opening at `zeta` of the mask of the quotient over
+/// `quotient_domain`, which is committed with the traces.
+#[allow(clippy::type_complexity)]
+fn quotient_mask_claim<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_domain: &Domain<SC>,
+    zeta: SC::Challenge,
+    quotient_mask: &[SC::Challenge],
+) -> Option<(Domain<SC>, Vec<(SC::Challenge, Vec<SC::Challenge>)>)> {
+    config.is_zk().then(|| {
+        let mask_domain = quotient_mask_domain(config, quotient_domain);
+        (
+            committed_trace_domain(config, &mask_domain),
+            vec![(zeta, quotient_mask.to_vec())],
+        )
+    })
+}
+
+/// Whether the auxiliary openings and sums in `opened_values` have the shape `aux_stages` calls for.
+fn valid_aux_shape<Challenge>(
+    aux_stages: &[AuxStage],
+    opened_values: &OpenedValues<Challenge>,
+    ext_degree: usize,
+) -> bool {
+    opened_values.aux_trace_local.len() == aux_stages.len()
+        && opened_values.aux_trace_next.len() == aux_stages.len()
+        && opened_values.aux_sums.len() == aux_stages.len()
+        && izip!(
+            aux_stages,
+            &opened_values.aux_trace_local,
+            &opened_values.aux_trace_next,
+            &opened_values.aux_sums
+        )
+        .all(|(stage, local, next, sums)| {
+            local.len() == stage.width * ext_degree
+                && next.len() == stage.width * ext_degree
+                && sums.len() == stage.num_sums
+        })
+}
 
+/// Check that the sums exposed by the auxiliary segments of all AIRs in a proof add up to zero.
+fn check_aux_sums<Challenge: Field, PcsErr>(
+    opened_values: &[OpenedValues<Challenge>],
+) -> Result<(), VerificationError<PcsErr>> {
+    let total = opened_values
+        .iter()
+        .flat_map(|opened_values| opened_values.aux_sums.iter().flatten())
+        .copied()
+        .sum::<Challenge>();
+    if !total.is_zero() {
+        return Err(VerificationError::UnbalancedAuxSums);
+    }
     Ok(())
 }
 
-#[derive(Debug)]
+/// The opened rows of the main trace, in window order.
+fn trace_rows<Challenge: Clone>(opened_values: &OpenedValues<Challenge>) -> Vec<Vec<Challenge>> {
+    [
+        opened_values.trace_local.clone(),
+        opened_values.trace_next.clone(),
+    ]
+    .into_iter()
+    .chain(opened_values.trace_after_next.iter().cloned())
+    .collect()
+}
+
+/// Recombine the openings of the base field columns making up one challenge field column.
+fn recompose<SC: StarkGenericConfig>(coeffs: &[SC::Challenge]) -> SC::Challenge {
+    // `coeffs` has length DIMENSION, hence the unwrap() will never panic.
+    coeffs
+        .iter()
+        .enumerate()
+        .map(|(e_i, &c)| SC::Challenge::ith_basis_element(e_i).unwrap() * c)
+        .sum()
+}
+
+/// Why a proof was rejected.
+///
+/// Failures inside the opening argument are reported by the PCS, e.g. as a `FriError` naming the
+/// FRI layer whose commitment an opening didn't match, or an invalid proof of work witness.
+#[derive(Debug, PartialEq, Eq)]
 pub enum VerificationError<PcsErr> {
+    /// The proof doesn't have the dimensions the AIR calls for, e.g. the wrong number of opened
+    /// columns, auxiliary segments or quotient chunks.
     InvalidProofShape,
     /// An error occurred while verifying the claimed openings.
     InvalidOpeningArgument(PcsErr),
     /// Out-of-domain evaluation mismatch, i.e. `constraints(zeta)` did not match
     /// `quotient(zeta) Z_H(zeta)`.
-    OodEvaluationMismatch,
+    OodEvaluationMismatch {
+        /// For proofs of several AIRs from `prove_multi`, the index of the AIR whose constraints
+        /// didn't match its quotient.
+        air: Option<usize>,
+    },
+    /// The sums exposed by the auxiliary segments don't add up to zero, e.g. because a lookup
+    /// argument received a tuple which no AIR sent.
+    UnbalancedAuxSums,
+    /// The AIR relies on structure the trace domain of the PCS lacks, e.g. a window of more than
+    /// two rows or a periodic column over a domain which is not a multiplicative coset.
+    UnsupportedTraceDomain,
 }
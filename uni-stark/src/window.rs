@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use p3_commit::PolynomialSpace;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+
+/// The points `x, g x, g^2 x, ...` of the rows exposed to constraints at `x`, where `g` generates
+/// `trace_domain`.
+pub(crate) fn window_points<D, Ext>(trace_domain: &D, x: Ext, window_size: usize) -> Vec<Ext>
+where
+    D: PolynomialSpace,
+    Ext: ExtensionField<D::Val>,
+{
+    iter::successors(Some(x), |&point| trace_domain.next_point(point))
+        .take(window_size)
+        .collect()
+}
+
+/// Evaluate `is_transition_window(size) / is_transition` at `x`, for each `size` from 3 up to
+/// `window_size`.
+///
+/// `is_transition` vanishes on the last row of `trace_domain`, and each further factor
+/// `x - s g^{-j}` on the row before. These factors only exist for a multiplicative trace domain
+/// `s<g>`, so this returns `None` for any other domain when `window_size` is above 2.
+pub(crate) fn transition_window_factors<D, Ext>(
+    trace_domain: &D,
+    x: Ext,
+    window_size: usize,
+) -> Option<Vec<Ext>>
+where
+    D: PolynomialSpace,
+    Ext: ExtensionField<D::Val>,
+{
+    if window_size <= 2 {
+        return Some(Vec::new());
+    }
+    let generator_inv = trace_domain.multiplicative_generator()?.inverse();
+    let shift = trace_domain.first_point();
+    let mut factor = Ext::ONE;
+    let factors = generator_inv
+        .powers()
+        .skip(2)
+        .take(window_size - 2)
+        .map(|point| {
+            factor *= x - shift * point;
+            factor
+        })
+        .collect();
+    Some(factors)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_circle::CircleDomain;
+    use p3_field::coset::TwoAdicMultiplicativeCoset;
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+
+    #[test]
+    fn test_transition_window_factors_vanish_on_last_rows() {
+        let trace_domain = TwoAdicMultiplicativeCoset::new(BabyBear::ONE, 3).unwrap();
+        let rows = window_points(&trace_domain, trace_domain.first_point(), 8);
+        let factors = |x| transition_window_factors(&trace_domain, x, 4).unwrap();
+        // The factor of the window of size `k` vanishes on rows `n - k + 1` to `n - 2`.
+        assert_eq!(factors(rows[6]), [BabyBear::ZERO, BabyBear::ZERO]);
+        assert_ne!(factors(rows[5])[0], BabyBear::ZERO);
+        assert_eq!(factors(rows[5])[1], BabyBear::ZERO);
+        assert!(factors(rows[4]).iter().all(|&f| f != BabyBear::ZERO));
+    }
+
+    #[test]
+    fn test_transition_window_factors_reject_circle_domain() {
+        let trace_domain = CircleDomain::<Mersenne31>::standard(3);
+        let x = Mersenne31::from_u8(7);
+        assert_eq!(transition_window_factors(&trace_domain, x, 2), Some(vec![]));
+        assert_eq!(transition_window_factors(&trace_domain, x, 3), None);
+    }
+}
@@ -7,12 +7,14 @@ use p3_commit::ExtensionMmcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, PrimeCharacteristicRing, PrimeField64};
-use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_fri::{FriConfig, TwoAdicFriPcs, create_test_fri_config, presets};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark::{StarkConfig, prove, verify};
+use p3_uni_stark::{
+    ConstraintFailure, StarkConfig, check_constraints_report, prove, verify, verify_each,
+};
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
 
@@ -55,6 +57,41 @@ impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
     }
 }
 
+/// `FibonacciAir` with its transition constraints declared in the opposite order: same width,
+/// constraint count and degree, and satisfied by the same traces.
+pub struct ReorderedFibonacciAir {}
+
+impl<F> BaseAir<F> for ReorderedFibonacciAir {
+    fn width(&self) -> usize {
+        NUM_FIBONACCI_COLS
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for ReorderedFibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &FibonacciRow<AB::Var> = (*local).borrow();
+        let next: &FibonacciRow<AB::Var> = (*next).borrow();
+
+        let mut when_first_row = builder.when_first_row();
+
+        when_first_row.assert_eq(local.left, a);
+        when_first_row.assert_eq(local.right, b);
+
+        let mut when_transition = builder.when_transition();
+
+        when_transition.assert_eq(local.left + local.right, next.right);
+        when_transition.assert_eq(local.right, next.left);
+
+        builder.when_last_row().assert_eq(local.right, x);
+    }
+}
+
 pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
     assert!(n.is_power_of_two());
 
@@ -166,3 +203,246 @@ fn test_incorrect_public_value() {
     ];
     prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
 }
+
+#[test]
+fn test_verify_each() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let challenger = Challenger::new(perm);
+
+    let instances = [(1 << 3, 21), (1 << 4, 987)];
+    let mut proofs = Vec::new();
+    let mut public_values = Vec::new();
+    for (n, x) in instances {
+        let trace = generate_trace_rows::<Val>(0, 1, n);
+        let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(x)];
+        proofs.push(prove(
+            &config,
+            &FibonacciAir {},
+            &mut challenger.clone(),
+            trace,
+            &pis,
+        ));
+        public_values.push(pis);
+    }
+    // Prove the first instance again, but check it against the wrong claimed result.
+    proofs.push(prove(
+        &config,
+        &FibonacciAir {},
+        &mut challenger.clone(),
+        generate_trace_rows::<Val>(0, 1, 1 << 3),
+        &public_values[0],
+    ));
+    public_values.push(vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(22)]);
+
+    let results = verify_each(
+        &config,
+        &FibonacciAir {},
+        &challenger,
+        &proofs,
+        &public_values,
+    );
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn test_proof_rejected_by_air_of_same_shape() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(21)];
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+
+    let mut challenger = Challenger::new(perm);
+    assert!(
+        verify(
+            &config,
+            &ReorderedFibonacciAir {},
+            &mut challenger,
+            &proof,
+            &pis
+        )
+        .is_err()
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_proof_stats() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(21)];
+    let mut challenger = Challenger::new(perm.clone());
+    let (proof, stats) =
+        p3_uni_stark::prove_with_stats(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+
+    // A trace and a quotient commitment, and the two queries of the test FRI config.
+    assert_eq!(stats.commitment_sizes.len(), 2);
+    assert!(stats.commitment_sizes.iter().all(|&size| size > 0));
+    assert_eq!(
+        stats.proof_size,
+        postcard::to_allocvec(&proof).unwrap().len()
+    );
+    assert_eq!(stats.num_queries, Some(2));
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
+#[test]
+fn test_deterministic_proofs() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        log_final_poly_len: 2,
+        log_max_final_poly_len: 2,
+        log_folding_arities: vec![],
+        deep_sampling: false,
+        num_queries: 2,
+        // Enough bits that a parallel search would usually find different witnesses.
+        proof_of_work_bits: 12,
+        deterministic_grinding: true,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(21)];
+
+    let proofs = (0..2)
+        .map(|_| {
+            let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+            let mut challenger = Challenger::new(perm.clone());
+            let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+            postcard::to_allocvec(&proof).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(proofs[0], proofs[1]);
+}
+
+#[test]
+fn test_fri_presets() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    let n = 1 << 10;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, trace.row_slice(n - 1)[1]];
+
+    let configs: [fn(ChallengeMmcs, usize) -> FriConfig<ChallengeMmcs>; 3] = [
+        presets::fast_prover,
+        presets::balanced,
+        presets::small_proof,
+    ];
+    let proof_sizes = configs.map(|preset| {
+        let fri_config = preset(challenge_mmcs.clone(), 40);
+        let pcs = Pcs::new(Dft::default(), val_mmcs.clone(), fri_config);
+        let config = MyConfig::new(pcs);
+
+        let mut challenger = Challenger::new(perm.clone());
+        let proof = prove(
+            &config,
+            &FibonacciAir {},
+            &mut challenger,
+            trace.clone(),
+            &pis,
+        );
+        let mut challenger = Challenger::new(perm.clone());
+        verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis)
+            .expect("verification failed");
+        postcard::to_allocvec(&proof).unwrap().len()
+    });
+
+    // Each preset trades prover time for a smaller proof than the one before it.
+    assert!(proof_sizes.is_sorted_by(|a, b| a > b), "{proof_sizes:?}");
+}
+
+#[test]
+fn test_constraint_report() {
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(21)];
+    let report = check_constraints_report::<Val, Val, _>(
+        &FibonacciAir {},
+        None,
+        &trace,
+        None,
+        &[],
+        &[],
+        &pis,
+    );
+    assert!(report.is_ok());
+
+    // Claim the wrong result and bump the left value of row 2, breaking both transitions it's in.
+    let mut trace = trace;
+    trace.values[2 * 2] += BabyBear::ONE;
+    let pis = vec![BabyBear::ZERO, BabyBear::ONE, BabyBear::from_u64(22)];
+    let report = check_constraints_report::<Val, Val, _>(
+        &FibonacciAir {},
+        None,
+        &trace,
+        None,
+        &[],
+        &[],
+        &pis,
+    );
+    assert_eq!(
+        report.failures,
+        vec![
+            // a' <- b, from row 1 to row 2.
+            ConstraintFailure {
+                row: 1,
+                constraint_index: 2,
+                value: -BabyBear::ONE,
+            },
+            // b' <- a + b, from row 2 to row 3.
+            ConstraintFailure {
+                row: 2,
+                constraint_index: 3,
+                value: BabyBear::ONE,
+            },
+            // The result on the last row.
+            ConstraintFailure {
+                row: 7,
+                constraint_index: 4,
+                value: -BabyBear::ONE,
+            },
+        ]
+    );
+    // The last row check is `is_last_row * (right - x)`.
+    assert_eq!(report.expression(4).degree_multiple(), 2);
+}
@@ -0,0 +1,153 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    ChunkedTraceGenerator, StarkConfig, VerificationError, prove, prove_with_generator, verify,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Asserts `x^degree = y` on every row.
+pub struct PowerAir {
+    degree: u64,
+}
+
+impl<F> BaseAir<F> for PowerAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for PowerAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let x: AB::Expr = local[0].into();
+        builder.assert_eq(x.exp_u64(self.degree), local[1]);
+    }
+}
+
+fn generate_trace(degree: u64, n: usize) -> RowMajorMatrix<Val> {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let values = (0..n)
+        .flat_map(|_| {
+            let x: Val = rng.random();
+            [x, x.exp_u64(degree)]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+/// Generates the rows of a `PowerAir` trace independently, with `x` counting up from one.
+struct PowerTraceGenerator {
+    degree: u64,
+    height: usize,
+}
+
+impl ChunkedTraceGenerator<Val> for PowerTraceGenerator {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn chunk_height(&self) -> usize {
+        8
+    }
+
+    fn generate_chunk(&self, start_row: usize, rows: &mut [Val]) {
+        for (i, row) in rows.chunks_exact_mut(2).enumerate() {
+            let x = Val::from_usize(start_row + i + 1);
+            row[0] = x;
+            row[1] = x.exp_u64(self.degree);
+        }
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    // A blowup of 4 leaves room for 4 quotient chunks, enough for degree 5 constraints.
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_degree_5_constraints() {
+    let (config, perm) = setup();
+    let air = PowerAir { degree: 5 };
+    let trace = generate_trace(5, 1 << 6);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[test]
+fn test_min_log_quotient_degree() {
+    let (config, perm) = setup();
+    let config = config.with_min_log_quotient_degree(2);
+    let air = PowerAir { degree: 3 };
+    let trace = generate_trace(3, 1 << 6);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+
+    // By default, degree 3 constraints only need 2 quotient chunks rather than 4.
+    let (default_config, _) = setup();
+    let mut challenger = Challenger::new(perm);
+    assert!(matches!(
+        verify(&default_config, &air, &mut challenger, &proof, &vec![]),
+        Err(VerificationError::InvalidProofShape)
+    ));
+}
+
+#[test]
+fn test_trace_generator() {
+    let (config, perm) = setup();
+    let air = PowerAir { degree: 5 };
+    let generator = PowerTraceGenerator {
+        degree: 5,
+        height: 1 << 6,
+    };
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_generator(&config, &air, &mut challenger, &generator, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
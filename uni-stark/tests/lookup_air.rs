@@ -0,0 +1,257 @@
+use p3_air::{Air, AirBuilder, BaseAir, InteractionAirBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    LogUpAir, StarkConfig, VerificationError, prove_multi_stage, prove_multi_with_aux,
+    verify_multi_stage, verify_multi_with_aux,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Range checks the `value` column against a `table` column holding `0, 1, ..., n - 1`.
+///
+/// Each row sends its value once and receives its table entry `multiplicity` times.
+pub struct RangeCheckAir;
+
+impl<F> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: InteractionAirBuilder> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let (value, table, multiplicity) = (local[0], local[1], local[2]);
+
+        builder.when_first_row().assert_zero(table);
+        builder
+            .when_transition()
+            .assert_eq(next[1], table + AB::Expr::ONE);
+
+        builder.push_send(0, [value], AB::Expr::ONE);
+        builder.push_receive(0, [table], multiplicity);
+    }
+}
+
+/// Sends each row's value on bus 0 and receives it back on bus 1, which only balances if buses are
+/// ignored.
+pub struct CrossBusAir;
+
+impl<F> BaseAir<F> for CrossBusAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: InteractionAirBuilder> Air<AB> for CrossBusAir {
+    fn eval(&self, builder: &mut AB) {
+        let value = builder.main().row_slice(0)[0];
+        builder.push_send(0, [value], AB::Expr::ONE);
+        builder.push_receive(1, [value], AB::Expr::ONE);
+    }
+}
+
+/// Splits `RangeCheckAir` into two tables: one sending values, the other receiving them from a
+/// table of `0, 1, ..., n - 1` with multiplicities.
+pub enum RangeCheckChip {
+    /// A single `value` column, each sent once.
+    Values,
+    /// A `table` column and a `multiplicity` column.
+    Table,
+}
+
+impl<F> BaseAir<F> for RangeCheckChip {
+    fn width(&self) -> usize {
+        match self {
+            Self::Values => 1,
+            Self::Table => 2,
+        }
+    }
+}
+
+impl<AB: InteractionAirBuilder> Air<AB> for RangeCheckChip {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        match self {
+            Self::Values => builder.push_send(0, [local[0]], AB::Expr::ONE),
+            Self::Table => {
+                let (table, multiplicity) = (local[0], local[1]);
+                builder.when_first_row().assert_zero(table);
+                builder
+                    .when_transition()
+                    .assert_eq(next[0], table + AB::Expr::ONE);
+                builder.push_receive(0, [table], multiplicity);
+            }
+        }
+    }
+}
+
+/// Traces for the chips of `RangeCheckChip`, with `num_values` values in a table of size `n`.
+fn generate_chip_traces(
+    num_values: usize,
+    n: usize,
+    valid: bool,
+) -> (RowMajorMatrix<Val>, RowMajorMatrix<Val>) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let mut values: Vec<usize> = (0..num_values).map(|_| rng.random_range(0..n)).collect();
+    if !valid {
+        values[0] = n;
+    }
+    let mut multiplicities = vec![0u32; n];
+    for &v in values.iter().filter(|&&v| v < n) {
+        multiplicities[v] += 1;
+    }
+    let values = RowMajorMatrix::new_col(values.into_iter().map(Val::from_usize).collect());
+    let table = (0..n).flat_map(|i| [Val::from_usize(i), Val::from_u32(multiplicities[i])]);
+    (values, RowMajorMatrix::new(table.collect(), 2))
+}
+
+fn generate_trace(n: usize, valid: bool) -> RowMajorMatrix<Val> {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let mut values: Vec<usize> = (0..n).map(|_| rng.random_range(0..n)).collect();
+    if !valid {
+        values[0] = n;
+    }
+    let mut multiplicities = vec![0u32; n];
+    for &v in values.iter().filter(|&&v| v < n) {
+        multiplicities[v] += 1;
+    }
+    let rows = (0..n).flat_map(|i| {
+        [
+            Val::from_usize(values[i]),
+            Val::from_usize(i),
+            Val::from_u32(multiplicities[i]),
+        ]
+    });
+    RowMajorMatrix::new(rows.collect(), 3)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_logup_range_check() {
+    let (config, perm) = setup();
+    let air = LogUpAir::new(RangeCheckAir);
+    assert_eq!(air.num_lookups::<Val>(), 2);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_stage(
+        &config,
+        &air,
+        &mut challenger,
+        generate_trace(1 << 6, true),
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify_multi_stage(&config, &air, &mut challenger, &proof, &vec![])
+        .expect("verification failed");
+}
+
+#[test]
+fn test_logup_unbalanced() {
+    let (config, perm) = setup();
+    let air = LogUpAir::new(RangeCheckAir);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_stage(
+        &config,
+        &air,
+        &mut challenger,
+        generate_trace(1 << 6, false),
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm);
+    assert_eq!(
+        verify_multi_stage(&config, &air, &mut challenger, &proof, &vec![]),
+        Err(VerificationError::UnbalancedAuxSums)
+    );
+}
+
+#[test]
+fn test_logup_buses_are_separate() {
+    let (config, perm) = setup();
+    let air = LogUpAir::new(CrossBusAir);
+    let trace = RowMajorMatrix::new_col((0..1 << 4).map(Val::from_u32).collect());
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_stage(&config, &air, &mut challenger, trace, &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    assert_eq!(
+        verify_multi_stage(&config, &air, &mut challenger, &proof, &vec![]),
+        Err(VerificationError::UnbalancedAuxSums)
+    );
+}
+
+/// Prove both chips of `RangeCheckChip` in one proof, sharing their lookup challenges.
+fn prove_and_verify_chips(valid: bool) -> Result<(), VerificationError<impl core::fmt::Debug>> {
+    let (config, perm) = setup();
+    let airs = [
+        LogUpAir::new(RangeCheckChip::Values),
+        LogUpAir::new(RangeCheckChip::Table),
+    ];
+    let (values, table) = generate_chip_traces(1 << 7, 1 << 5, valid);
+    let public_values = vec![vec![]; 2];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_with_aux(
+        &config,
+        &airs,
+        &mut challenger,
+        vec![values, table],
+        &public_values,
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify_multi_with_aux(&config, &airs, &mut challenger, &proof, &public_values)
+}
+
+#[test]
+fn test_logup_across_airs() {
+    prove_and_verify_chips(true).expect("verification failed");
+}
+
+#[test]
+fn test_logup_across_airs_unbalanced() {
+    assert!(matches!(
+        prove_and_verify_chips(false),
+        Err(VerificationError::UnbalancedAuxSums)
+    ));
+}
@@ -11,7 +11,7 @@ use p3_commit::testing::TrivialPcs;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, PrimeCharacteristicRing};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, SecurityAssumption, TwoAdicFriPcs};
 use p3_keccak::Keccak256Hash;
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
@@ -217,11 +217,14 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 
     let fri_config = FriConfig {
-        log_blowup,
         log_final_poly_len: 5,
-        num_queries: 40,
-        proof_of_work_bits: 8,
-        mmcs: challenge_mmcs,
+        log_max_final_poly_len: 5,
+        ..FriConfig::with_security_bits(
+            challenge_mmcs,
+            20,
+            log_blowup,
+            SecurityAssumption::CapacityBound,
+        )
     };
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
@@ -277,13 +280,12 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
     type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
 
-    let fri_config = FriConfig {
+    let fri_config = FriConfig::with_security_bits(
+        challenge_mmcs,
+        20,
         log_blowup,
-        log_final_poly_len: 0,
-        num_queries: 40,
-        proof_of_work_bits: 8,
-        mmcs: challenge_mmcs,
-    };
+        SecurityAssumption::CapacityBound,
+    );
 
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs {
@@ -0,0 +1,178 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    StarkConfig, prove_identical_batch, prove_multi, verify_identical_batch, verify_multi,
+};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// Two unrelated chips, proven together.
+pub enum Chip {
+    /// Rows `(a, b)` step to `(b, a + b)`, starting at the first two public values and ending with
+    /// the third in the second column.
+    Fibonacci,
+    /// Rows `(x, x^2)`, with `x` counting up from zero.
+    Square,
+}
+
+impl<F> BaseAir<F> for Chip {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for Chip {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        match self {
+            Self::Fibonacci => {
+                let pis = builder.public_values();
+                let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+                let mut when_first_row = builder.when_first_row();
+                when_first_row.assert_eq(local[0], a);
+                when_first_row.assert_eq(local[1], b);
+
+                let mut when_transition = builder.when_transition();
+                when_transition.assert_eq(local[1], next[0]);
+                when_transition.assert_eq(local[0] + local[1], next[1]);
+
+                builder.when_last_row().assert_eq(local[1], x);
+            }
+            Self::Square => {
+                builder.when_first_row().assert_zero(local[0]);
+                builder
+                    .when_transition()
+                    .assert_eq(next[0], local[0] + AB::Expr::ONE);
+                builder.assert_eq(local[1], local[0] * local[0]);
+            }
+        }
+    }
+}
+
+fn fibonacci_trace(n: usize) -> RowMajorMatrix<Val> {
+    fibonacci_trace_from(Val::ZERO, Val::ONE, n)
+}
+
+fn fibonacci_trace_from(mut a: Val, mut b: Val, n: usize) -> RowMajorMatrix<Val> {
+    let mut values = Vec::with_capacity(2 * n);
+    for _ in 0..n {
+        values.extend([a, b]);
+        (a, b) = (b, a + b);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+fn square_trace(n: usize) -> RowMajorMatrix<Val> {
+    let values = (0..n)
+        .flat_map(|i| {
+            let x = Val::from_usize(i);
+            [x, x.square()]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 1);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_multi_air_different_heights() {
+    let (config, perm) = setup();
+    let airs = [Chip::Fibonacci, Chip::Square];
+    let traces = vec![fibonacci_trace(1 << 3), square_trace(1 << 5)];
+    let public_values = vec![vec![Val::ZERO, Val::ONE, Val::from_u8(21)], vec![]];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi(&config, &airs, &mut challenger, traces, &public_values);
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_multi(&config, &airs, &mut challenger, &proof, &public_values)
+        .expect("verification failed");
+
+    // The public values are bound to the proof.
+    let wrong_public_values = vec![vec![Val::ZERO, Val::ONE, Val::from_u8(22)], vec![]];
+    let mut challenger = Challenger::new(perm);
+    assert!(
+        verify_multi(
+            &config,
+            &airs,
+            &mut challenger,
+            &proof,
+            &wrong_public_values
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_identical_batch() {
+    let (config, perm) = setup();
+    let air = Chip::Fibonacci;
+    // Fibonacci sequences of 8 rows starting at (0, 1), (1, 1) and (2, 3).
+    let starts = [(0, 1, 21), (1, 1, 34), (2, 3, 89)];
+    let traces = starts
+        .iter()
+        .map(|&(a, b, _)| fibonacci_trace_from(Val::from_u8(a), Val::from_u8(b), 1 << 3))
+        .collect();
+    let public_values = starts
+        .iter()
+        .map(|&(a, b, x)| vec![Val::from_u8(a), Val::from_u8(b), Val::from_u8(x)])
+        .collect::<Vec<_>>();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_identical_batch(&config, &air, &mut challenger, traces, &public_values);
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_identical_batch(&config, &air, &mut challenger, &proof, &public_values)
+        .expect("verification failed");
+
+    // Every instance's public values are bound to the proof.
+    let mut wrong_public_values = public_values.clone();
+    wrong_public_values[2][2] = Val::from_u8(90);
+    let mut challenger = Challenger::new(perm.clone());
+    assert!(
+        verify_identical_batch(&config, &air, &mut challenger, &proof, &wrong_public_values)
+            .is_err()
+    );
+
+    // So is the number of instances.
+    let mut challenger = Challenger::new(perm);
+    assert!(
+        verify_identical_batch(&config, &air, &mut challenger, &proof, &public_values[..2])
+            .is_err()
+    );
+}
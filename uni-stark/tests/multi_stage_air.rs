@@ -0,0 +1,197 @@
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder, PermutationAirBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    AuxStage, MultiStageAir, StarkConfig, VerificationError, prove_multi_stage, verify,
+    verify_multi_stage,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Checks that the second main column is a permutation of the first, using a running product
+/// `z` over a single challenge `gamma`, committed as an auxiliary segment:
+/// `z[i] = prod_{j <= i} (gamma - a[j]) / (gamma - b[j])`, which must end at one.
+pub struct PermutationCheckAir;
+
+impl<F> BaseAir<F> for PermutationCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field> MultiStageAir<F> for PermutationCheckAir {
+    fn aux_stages(&self) -> Vec<AuxStage> {
+        vec![AuxStage {
+            width: 1,
+            num_challenges: 1,
+            num_sums: 0,
+        }]
+    }
+
+    fn generate_aux_trace<EF: ExtensionField<F>>(
+        &self,
+        _stage: usize,
+        main: &RowMajorMatrix<F>,
+        _prev_aux: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        let gamma = challenges[0];
+        let mut z = EF::ONE;
+        let values = main
+            .row_slices()
+            .map(|row| {
+                z *= (gamma - row[0]) * (gamma - row[1]).inverse();
+                z
+            })
+            .collect();
+        RowMajorMatrix::new_col(values)
+    }
+}
+
+impl<AB: PermutationAirBuilder> Air<AB> for PermutationCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let aux = builder.permutation();
+        let (aux_local, aux_next) = (aux.row_slice(0), aux.row_slice(1));
+        let gamma: AB::ExprEF = builder.permutation_randomness()[0].into();
+
+        let (a_local, b_local): (AB::Expr, AB::Expr) = (local[0].into(), local[1].into());
+        let (a_next, b_next): (AB::Expr, AB::Expr) = (next[0].into(), next[1].into());
+        let z_local: AB::ExprEF = aux_local[0].into();
+        let z_next: AB::ExprEF = aux_next[0].into();
+
+        builder.when_first_row().assert_eq_ext(
+            z_local.clone() * (gamma.clone() - b_local),
+            gamma.clone() - a_local,
+        );
+        builder.when_transition().assert_eq_ext(
+            z_next * (gamma.clone() - b_next),
+            z_local.clone() * (gamma - a_next),
+        );
+        builder.when_last_row().assert_one_ext(z_local);
+    }
+}
+
+/// A trace whose second column is the first one reversed, or not a permutation if `!valid`.
+fn generate_trace(n: usize, valid: bool) -> RowMajorMatrix<Val> {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let a: Vec<Val> = (0..n).map(|_| rng.random()).collect();
+    let mut b: Vec<Val> = a.iter().rev().copied().collect();
+    if !valid {
+        b[0] += Val::ONE;
+    }
+    let values = a.into_iter().zip(b).flat_map(|(a, b)| [a, b]).collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let mut fri_config = create_test_fri_config(challenge_mmcs, 2);
+    // So that proofs can be compared byte for byte.
+    fri_config.deterministic_grinding = true;
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_multi_stage_permutation_check() {
+    let (config, perm) = setup();
+    let trace = generate_trace(1 << 6, true);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_multi_stage(
+        &config,
+        &PermutationCheckAir,
+        &mut challenger,
+        trace,
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_multi_stage(
+        &config,
+        &PermutationCheckAir,
+        &mut challenger,
+        &proof,
+        &vec![],
+    )
+    .expect("verification failed");
+
+    // The single-stage verifier does not expect the auxiliary segment.
+    let mut challenger = Challenger::new(perm);
+    assert!(matches!(
+        verify(
+            &config,
+            &PermutationCheckAir,
+            &mut challenger,
+            &proof,
+            &vec![]
+        ),
+        Err(VerificationError::InvalidProofShape)
+    ));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "constraints had nonzero value")]
+fn test_multi_stage_not_a_permutation() {
+    let (config, perm) = setup();
+    let trace = generate_trace(1 << 6, false);
+    let mut challenger = Challenger::new(perm);
+    prove_multi_stage(
+        &config,
+        &PermutationCheckAir,
+        &mut challenger,
+        trace,
+        &vec![],
+    );
+}
+
+#[test]
+fn test_constraint_dag_gives_same_proof() {
+    let prove_with = |config: &MyConfig, perm: &Perm| {
+        let mut challenger = Challenger::new(perm.clone());
+        let proof = prove_multi_stage(
+            config,
+            &PermutationCheckAir,
+            &mut challenger,
+            generate_trace(1 << 6, true),
+            &vec![],
+        );
+        postcard::to_allocvec(&proof).unwrap()
+    };
+
+    let (config, perm) = setup();
+    let folded = prove_with(&config, &perm);
+    let config = config.with_constraint_dag();
+    let from_dag = prove_with(&config, &perm);
+    assert_eq!(folded, from_dag);
+}
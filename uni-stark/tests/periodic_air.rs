@@ -0,0 +1,108 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PeriodicAirBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+const ROUND_CONSTANTS: [u32; 8] = [3, 1, 4, 1, 5, 9, 2, 6];
+
+/// Accumulates round constants which repeat every 8 rows, supplied as an uncommitted periodic
+/// column: each row is the previous one plus that row's constant.
+pub struct RoundConstantAir;
+
+impl<F: PrimeCharacteristicRing> BaseAir<F> for RoundConstantAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![ROUND_CONSTANTS.map(F::from_u32).to_vec()]
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + PeriodicAirBuilder> Air<AB> for RoundConstantAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let round_constant: AB::Expr = builder.periodic_values()[0].into();
+        let x = builder.public_values()[0];
+
+        builder.when_first_row().assert_zero(local[0]);
+        builder
+            .when_transition()
+            .assert_eq(next[0], round_constant + local[0]);
+        builder.when_last_row().assert_eq(local[0], x);
+    }
+}
+
+fn generate_trace(n: usize) -> RowMajorMatrix<Val> {
+    let mut values = vec![Val::ZERO];
+    for i in 1..n {
+        values.push(values[i - 1] + Val::from_u32(ROUND_CONSTANTS[(i - 1) % 8]));
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_periodic_column() {
+    let (config, perm) = setup();
+    let trace = generate_trace(1 << 6);
+    let pis = vec![trace.values[trace.height() - 1]];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &RoundConstantAir, &mut challenger, trace, &pis);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &RoundConstantAir, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
+fn test_periodic_column_incorrect_public_value() {
+    let (config, perm) = setup();
+    let trace = generate_trace(1 << 6);
+    let mut challenger = Challenger::new(perm);
+    prove(
+        &config,
+        &RoundConstantAir,
+        &mut challenger,
+        trace,
+        &vec![Val::ONE],
+    );
+}
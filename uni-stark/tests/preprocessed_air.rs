@@ -0,0 +1,135 @@
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, prove_with_key, setup, verify_with_key};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// Asserts that each main value is the cube of the preprocessed value in the same row, where the
+/// preprocessed column holds `offset, offset + 1, ...`.
+pub struct CubeAir {
+    log_height: usize,
+    offset: u64,
+}
+
+impl CubeAir {
+    fn preprocessed_values(&self) -> impl Iterator<Item = Val> {
+        (0..1u64 << self.log_height).map(|i| Val::from_u64(self.offset + i))
+    }
+
+    fn generate_trace(&self) -> RowMajorMatrix<Val> {
+        RowMajorMatrix::new_col(self.preprocessed_values().map(|x| x.cube()).collect())
+    }
+}
+
+impl BaseAir<Val> for CubeAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<Val>> {
+        Some(RowMajorMatrix::new_col(
+            self.preprocessed_values().collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder<F = Val>> Air<AB> for CubeAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let preprocessed = builder.preprocessed();
+        let local = main.row_slice(0);
+        let fixed = preprocessed.row_slice(0);
+
+        let x: AB::Expr = fixed[0].into();
+        builder.assert_eq(local[0], x.cube());
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup_config() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_preprocessed_columns() {
+    let (config, perm) = setup_config();
+    let air = CubeAir {
+        log_height: 5,
+        offset: 3,
+    };
+    let (pk, vk) = setup(&config, &air);
+    assert!(vk.preprocessed_commitment().is_some());
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_key(
+        &config,
+        &pk,
+        &air,
+        &mut challenger,
+        air.generate_trace(),
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify_with_key(&config, &vk, &air, &mut challenger, &proof, &vec![])
+        .expect("verification failed");
+}
+
+#[test]
+fn test_preprocessed_columns_wrong_key() {
+    let (config, perm) = setup_config();
+    let air = CubeAir {
+        log_height: 5,
+        offset: 3,
+    };
+    let other_air = CubeAir {
+        log_height: 5,
+        offset: 4,
+    };
+    let (pk, _) = setup(&config, &air);
+    let (_, other_vk) = setup(&config, &other_air);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_key(
+        &config,
+        &pk,
+        &air,
+        &mut challenger,
+        air.generate_trace(),
+        &vec![],
+    );
+
+    // The proof was made against different preprocessed columns.
+    let mut challenger = Challenger::new(perm);
+    assert!(verify_with_key(&config, &other_vk, &air, &mut challenger, &proof, &vec![]).is_err());
+}
@@ -0,0 +1,128 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, RowAirBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// The row whose left column is exposed as the last public value.
+const MIDDLE_ROW: usize = 5;
+
+/// A Fibonacci sequence in two columns, exposing its start, its end and the value on
+/// `MIDDLE_ROW` as public values `[a, b, middle, x]`.
+pub struct FibonacciIoAir;
+
+impl<F> BaseAir<F> for FibonacciIoAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues + RowAirBuilder> Air<AB> for FibonacciIoAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+
+        builder.assert_first_row_public(0, 0);
+        builder.assert_first_row_public(1, 1);
+        builder.assert_row_public(MIDDLE_ROW, 0, 2);
+        builder.assert_last_row_public(1, 3);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1], next[0]);
+        when_transition.assert_eq(local[0] + local[1], next[1]);
+    }
+}
+
+fn generate_trace(n: usize) -> RowMajorMatrix<Val> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut a, mut b) = (Val::ZERO, Val::ONE);
+    for _ in 0..n {
+        values.extend([a, b]);
+        (a, b) = (b, a + b);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_public_value_at_row() {
+    let (config, perm) = setup();
+    // F_5 = 5 sits on row 5, and F_16 = 987 ends the trace.
+    let pis = vec![Val::ZERO, Val::ONE, Val::from_u8(5), Val::from_u16(987)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(
+        &config,
+        &FibonacciIoAir,
+        &mut challenger,
+        generate_trace(1 << 4),
+        &pis,
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &FibonacciIoAir, &mut challenger, &proof, &pis).expect("verification failed");
+
+    // The same proof doesn't attest to a different value on the middle row.
+    let wrong_pis = vec![Val::ZERO, Val::ONE, Val::from_u8(8), Val::from_u16(987)];
+    let mut challenger = Challenger::new(perm);
+    assert!(
+        verify(
+            &config,
+            &FibonacciIoAir,
+            &mut challenger,
+            &proof,
+            &wrong_pis
+        )
+        .is_err()
+    );
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "constraints had nonzero value on row 5")]
+fn test_incorrect_public_value_at_row() {
+    let (config, perm) = setup();
+    let pis = vec![Val::ZERO, Val::ONE, Val::from_u8(8), Val::from_u16(987)];
+    let mut challenger = Challenger::new(perm);
+    prove(
+        &config,
+        &FibonacciIoAir,
+        &mut challenger,
+        generate_trace(1 << 4),
+        &pis,
+    );
+}
@@ -0,0 +1,154 @@
+use p3_air::{AirBuilder, BaseSubAir, ComposedAir, OffsetAir, PairAir, SubAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, get_max_constraint_degree, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// A Fibonacci sequence in two columns, starting at `0, 1`.
+struct Fibonacci;
+
+impl BaseSubAir for Fibonacci {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn max_constraint_degree(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilder> SubAir<AB> for Fibonacci {
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]) {
+        builder.when_first_row().assert_zero(local[0]);
+        builder.when_first_row().assert_one(local[1]);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1], next[0]);
+        when_transition.assert_eq(local[0] + local[1], next[1]);
+    }
+}
+
+/// A single column which squares itself on every row, starting at `2`.
+struct Square;
+
+impl BaseSubAir for Square {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn max_constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: AirBuilder> SubAir<AB> for Square {
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]) {
+        builder.when_first_row().assert_eq(local[0], AB::Expr::TWO);
+        builder
+            .when_transition()
+            .assert_eq(local[0] * local[0], next[0]);
+    }
+}
+
+/// `Square`, declaring its constraints one degree lower than they are.
+struct UnderdeclaredSquare;
+
+impl BaseSubAir for UnderdeclaredSquare {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn max_constraint_degree(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilder> SubAir<AB> for UnderdeclaredSquare {
+    fn eval(&self, builder: &mut AB, local: &[AB::Var], next: &[AB::Var]) {
+        Square.eval(builder, local, next);
+    }
+}
+
+/// A Fibonacci sequence, then a free column, then a column of repeated squares.
+fn composed_air() -> ComposedAir<PairAir<Fibonacci, OffsetAir<Square>>> {
+    ComposedAir::new(PairAir::new(Fibonacci, OffsetAir::new(Square, 1)))
+}
+
+fn generate_trace(n: usize) -> RowMajorMatrix<Val> {
+    let mut values = Vec::with_capacity(4 * n);
+    let (mut a, mut b, mut x) = (Val::ZERO, Val::ONE, Val::TWO);
+    for row in 0..n {
+        values.extend([a, b, Val::from_usize(row), x]);
+        (a, b, x) = (b, a + b, x.square());
+    }
+    RowMajorMatrix::new(values, 4)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_composed_metadata() {
+    let air = composed_air();
+    assert_eq!(air.inner.width(), 4);
+    assert_eq!(air.inner.max_constraint_degree(), 3);
+    assert_eq!(
+        get_max_constraint_degree::<Val, _>(&air, 0, 0),
+        air.inner.max_constraint_degree()
+    );
+}
+
+#[test]
+#[should_panic(expected = "declares a max constraint degree of 2")]
+fn test_underdeclared_degree() {
+    get_max_constraint_degree::<Val, _>(&ComposedAir::new(UnderdeclaredSquare), 0, 0);
+}
+
+#[test]
+fn test_prove_composed() {
+    let (config, perm) = setup();
+    let air = composed_air();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(
+        &config,
+        &air,
+        &mut challenger,
+        generate_trace(1 << 4),
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
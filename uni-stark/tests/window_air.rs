@@ -0,0 +1,153 @@
+use core::marker::PhantomData;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32};
+use p3_circle::CirclePcs;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{TwoAdicFriPcs, create_test_fri_config};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{
+    CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
+};
+use p3_uni_stark::{StarkConfig, StarkMetadata, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// A Fibonacci sequence in a single column, where each row is the sum of the two rows before it.
+pub struct FibonacciColumnAir;
+
+impl<F> BaseAir<F> for FibonacciColumnAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn window_size(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciColumnAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next, next_next) = (main.row_slice(0), main.row_slice(1), main.row_slice(2));
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local[0], a);
+        when_first_row.assert_eq(next[0], b);
+
+        builder
+            .when_transition_window(3)
+            .assert_eq(local[0] + next[0], next_next[0]);
+
+        builder.when_last_row().assert_eq(local[0], x);
+    }
+}
+
+fn generate_trace<F: Field>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
+    let mut values = vec![F::from_u64(a), F::from_u64(b)];
+    for i in 2..n {
+        values.push(values[i - 2] + values[i - 1]);
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn test_window_of_three_rows() {
+    let (config, perm) = setup();
+    let trace = generate_trace::<Val>(0, 1, 1 << 6);
+    let x = trace.values[trace.height() - 1];
+    let pis = vec![Val::ZERO, Val::ONE, x];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &FibonacciColumnAir, &mut challenger, trace, &pis);
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciColumnAir, &mut challenger, &proof, &pis)
+        .expect("verification failed");
+}
+
+#[test]
+fn test_window_metadata() {
+    let metadata = StarkMetadata::for_air::<Val, _>(&FibonacciColumnAir, 3);
+    assert_eq!(metadata.constraint_count, 4);
+    // The larger transition selector counts towards the constraint degree.
+    assert_eq!(metadata.max_constraint_degree, 2);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
+fn test_window_incorrect_public_value() {
+    let (config, perm) = setup();
+    let trace = generate_trace::<Val>(0, 1, 1 << 6);
+    let pis = vec![Val::ZERO, Val::ONE, Val::from_u32(123_123)];
+    let mut challenger = Challenger::new(perm);
+    prove(&config, &FibonacciColumnAir, &mut challenger, trace, &pis);
+}
+
+/// Windows of more than two rows need a multiplicative trace domain, which twin cosets are not.
+#[test]
+#[should_panic(expected = "windows of more than two rows need a multiplicative trace domain")]
+fn test_window_rejects_circle_domain() {
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Val, 3>;
+    type FieldHash = SerializingHasher32<Keccak256Hash>;
+    type MyCompress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, Keccak256Hash, 32>>;
+    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+    let val_mmcs = ValMmcs::new(
+        FieldHash::new(Keccak256Hash {}),
+        MyCompress::new(Keccak256Hash {}),
+    );
+    let pcs = Pcs {
+        mmcs: val_mmcs.clone(),
+        fri_config: create_test_fri_config(ChallengeMmcs::new(val_mmcs), 2),
+        _phantom: PhantomData,
+    };
+    let config = StarkConfig::<_, Challenge, Challenger>::new(pcs);
+
+    let trace = generate_trace::<Val>(0, 1, 1 << 6);
+    let x = trace.values[trace.height() - 1];
+    let pis = vec![Val::ZERO, Val::ONE, x];
+    let mut challenger = Challenger::from_hasher(vec![], Keccak256Hash {});
+    prove(&config, &FibonacciColumnAir, &mut challenger, trace, &pis);
+}
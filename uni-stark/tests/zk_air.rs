@@ -0,0 +1,140 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_fri::{HidingFriPcs, create_test_fri_config};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeHidingMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{StarkConfig, VerificationError, prove, verify};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+/// A Fibonacci sequence in two columns, starting at `0, 1` and ending at public value `x`.
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let x = builder.public_values()[0];
+
+        builder.when_first_row().assert_zero(local[0]);
+        builder.when_first_row().assert_one(local[1]);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1], next[0]);
+        when_transition.assert_eq(local[0] + local[1], next[1]);
+
+        builder.when_last_row().assert_eq(local[1], x);
+    }
+}
+
+fn generate_trace(n: usize) -> RowMajorMatrix<Val> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut a, mut b) = (Val::ZERO, Val::ONE);
+    for _ in 0..n {
+        values.extend([a, b]);
+        (a, b) = (b, a + b);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs = MerkleTreeHidingMmcs<
+    <Val as Field>::Packing,
+    <Val as Field>::Packing,
+    MyHash,
+    MyCompress,
+    SmallRng,
+    8,
+    4,
+>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = HidingFriPcs<Val, Dft, ValMmcs, ChallengeMmcs, SmallRng>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+// WARNING: a seeded SmallRng is only fit for tests; real provers need a cryptographic RNG.
+fn setup(seed: u64) -> (Pcs, Perm) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress, SmallRng::seed_from_u64(seed));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = create_test_fri_config(challenge_mmcs, 2);
+    let pcs = Pcs::new(
+        Dft::default(),
+        val_mmcs,
+        fri_config,
+        4,
+        SmallRng::seed_from_u64(seed),
+    );
+    (pcs, perm)
+}
+
+#[test]
+fn test_zk_proof() {
+    let (pcs, perm) = setup(1);
+    let config = MyConfig::new(pcs).with_zk();
+    let pis = vec![Val::from_u16(987)];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        &mut challenger,
+        generate_trace(1 << 4),
+        &pis,
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &FibonacciAir, &mut challenger, &proof, &pis).expect("verification failed");
+
+    // The wrong claimed result is still rejected.
+    let mut challenger = Challenger::new(perm);
+    assert!(verify(&config, &FibonacciAir, &mut challenger, &proof, &[Val::ONE]).is_err());
+}
+
+#[test]
+fn test_zk_and_non_zk_proofs_are_distinct() {
+    let pis = vec![Val::from_u16(987)];
+
+    let (pcs, perm) = setup(2);
+    let config = MyConfig::new(pcs);
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        &mut challenger,
+        generate_trace(1 << 4),
+        &pis,
+    );
+    let mut challenger = Challenger::new(perm.clone());
+    verify(&config, &FibonacciAir, &mut challenger, &proof, &pis).expect("verification failed");
+
+    // A zero-knowledge verifier expects the quotient split for the randomized trace.
+    let (pcs, _) = setup(2);
+    let zk_config = MyConfig::new(pcs).with_zk();
+    let mut challenger = Challenger::new(perm);
+    assert!(matches!(
+        verify(&zk_config, &FibonacciAir, &mut challenger, &proof, &pis),
+        Err(VerificationError::InvalidProofShape)
+    ));
+}